@@ -1,3 +1,4 @@
 pub mod bundle;
+pub mod proxy;
 pub mod server;
 pub mod validate;
@@ -0,0 +1,245 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::Colorize;
+use mcpkit_rs::{
+    service::{Proxy, ProxyQuitReason},
+    transport::{
+        SessionService, StderrLog, StreamableHttpClientTransport, StreamableHttpService,
+        TokioChildProcess, stdio, streamable_http_server::session::local::LocalSessionManager,
+    },
+};
+use tokio::process::Command;
+
+#[derive(Args)]
+pub struct ProxyArgs {
+    /// Server to connect to: `stdio:<command> [args...]` or an `http(s)://` URL.
+    /// The command is split like a shell would, so arguments may be quoted
+    #[arg(long)]
+    from: Upstream,
+
+    /// Where to expose it: `stdio` or `http://<host>:<port>/<path>`, where the host
+    /// may be a name such as `localhost`
+    #[arg(long, default_value = "stdio")]
+    to: Downstream,
+}
+
+#[derive(Clone, Debug)]
+enum Upstream {
+    Command(Vec<String>),
+    Http(String),
+}
+
+impl FromStr for Upstream {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(command) = s.strip_prefix("stdio:") {
+            let argv = shell_words::split(command)
+                .map_err(|e| format!("invalid command after `stdio:`: {e}"))?;
+            if argv.is_empty() {
+                return Err("missing command after `stdio:`".into());
+            }
+            Ok(Upstream::Command(argv))
+        } else if s.starts_with("http://") || s.starts_with("https://") {
+            Ok(Upstream::Http(s.to_owned()))
+        } else {
+            Err(format!(
+                "unsupported server `{s}`, expected `stdio:<command>` or an http(s) URL"
+            ))
+        }
+    }
+}
+
+impl Upstream {
+    /// Spawn the server, logging its stderr under its program name.
+    fn spawn(argv: &[String]) -> std::io::Result<TokioChildProcess> {
+        let mut command = Command::new(&argv[0]);
+        command.args(&argv[1..]);
+        let (child, _) = TokioChildProcess::builder(command)
+            .log_stderr(StderrLog::new(argv[0].as_str()))
+            .spawn()?;
+        Ok(child)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Downstream {
+    Stdio,
+    /// `bind` is a `host:port` pair; the host is resolved when binding
+    Http {
+        bind: String,
+        path: String,
+    },
+}
+
+impl FromStr for Downstream {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "stdio" {
+            return Ok(Downstream::Stdio);
+        }
+        let Some(rest) = s.strip_prefix("http://") else {
+            return Err(format!(
+                "unsupported target `{s}`, expected `stdio` or http://<host>:<port>/<path>"
+            ));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/mcp"),
+        };
+        let port = authority
+            .rsplit_once(':')
+            .filter(|(host, _)| !host.is_empty())
+            .map(|(_, port)| port)
+            .ok_or_else(|| format!("invalid bind address `{authority}`: expected <host>:<port>"))?;
+        port.parse::<u16>()
+            .map_err(|e| format!("invalid port in bind address `{authority}`: {e}"))?;
+        Ok(Downstream::Http {
+            bind: authority.to_owned(),
+            path: path.to_owned(),
+        })
+    }
+}
+
+pub async fn execute(args: ProxyArgs) -> Result<()> {
+    // stdout may carry the MCP stream, so progress goes to stderr
+    eprintln!("{}", "🔀 Starting MCP proxy...".blue().bold());
+    eprintln!("  From: {}", describe_upstream(&args.from).yellow());
+
+    let reason = match (args.from, args.to) {
+        (Upstream::Command(argv), Downstream::Stdio) => {
+            eprintln!("  To: {}", "stdio".green());
+            let child = Upstream::spawn(&argv)
+                .with_context(|| format!("Failed to spawn `{}`", argv.join(" ")))?;
+            Proxy::new(child).serve(stdio()).await?
+        }
+        (Upstream::Http(url), Downstream::Stdio) => {
+            eprintln!("  To: {}", "stdio".green());
+            Proxy::new(StreamableHttpClientTransport::from_uri(url))
+                .serve(stdio())
+                .await?
+        }
+        (Upstream::Command(argv), Downstream::Http { bind, path }) => {
+            serve_http(bind, &path, move || Ok(Proxy::new(Upstream::spawn(&argv)?))).await?
+        }
+        (Upstream::Http(url), Downstream::Http { bind, path }) => {
+            serve_http(bind, &path, move || {
+                Ok(Proxy::new(StreamableHttpClientTransport::from_uri(
+                    url.clone(),
+                )))
+            })
+            .await?
+        }
+    };
+
+    let reason = match reason {
+        ProxyQuitReason::Cancelled => "stopped",
+        ProxyQuitReason::DownstreamClosed => "client disconnected",
+        ProxyQuitReason::UpstreamClosed => "server disconnected",
+    };
+    eprintln!("\n{} ({reason})", "👋 Proxy stopped".yellow());
+    Ok(())
+}
+
+fn describe_upstream(upstream: &Upstream) -> String {
+    match upstream {
+        Upstream::Command(argv) => format!("stdio: {}", argv.join(" ")),
+        Upstream::Http(url) => url.clone(),
+    }
+}
+
+/// Serve a Streamable HTTP endpoint, with one upstream connection per session.
+async fn serve_http<S: SessionService>(
+    bind: String,
+    path: &str,
+    new_session: impl Fn() -> std::io::Result<S> + Send + Sync + 'static,
+) -> Result<ProxyQuitReason> {
+    if !path.starts_with('/') || path == "/" {
+        bail!("HTTP path must be non-empty, e.g. http://{bind}/mcp");
+    }
+    let service = StreamableHttpService::new(
+        new_session,
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+    let router = axum::Router::new().nest_service(path, service);
+    let listener = tokio::net::TcpListener::bind(&bind)
+        .await
+        .with_context(|| format!("Failed to bind {bind}"))?;
+    eprintln!(
+        "  To: {}",
+        format!("http://{}{path}", listener.local_addr()?).green()
+    );
+    eprintln!("\n{}", "Proxy is running. Press Ctrl+C to stop.".green());
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(ProxyQuitReason::Cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(s: &str) -> Vec<String> {
+        match s.parse() {
+            Ok(Upstream::Command(argv)) => argv,
+            other => panic!("{s:?} parsed as {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_stdio_command_is_split_like_a_shell() {
+        assert_eq!(argv("stdio:uvx mcp-server-git"), ["uvx", "mcp-server-git"]);
+        assert_eq!(
+            argv(r#"stdio:server --root "/tmp/my dir" 'a b'"#),
+            ["server", "--root", "/tmp/my dir", "a b"]
+        );
+        assert_eq!(argv(r"stdio:server a\ b"), ["server", "a b"]);
+    }
+
+    #[test]
+    fn test_invalid_upstreams_are_rejected() {
+        assert!("stdio:".parse::<Upstream>().is_err());
+        assert!("stdio:   ".parse::<Upstream>().is_err());
+        assert!(r#"stdio:server "unterminated"#.parse::<Upstream>().is_err());
+        assert!("ftp://example.com".parse::<Upstream>().is_err());
+        assert!(matches!(
+            "https://example.com/mcp".parse(),
+            Ok(Upstream::Http(url)) if url == "https://example.com/mcp"
+        ));
+    }
+
+    #[test]
+    fn test_downstream_parsing() {
+        assert!(matches!("stdio".parse(), Ok(Downstream::Stdio)));
+        match "http://127.0.0.1:8080".parse() {
+            Ok(Downstream::Http { bind, path }) => {
+                assert_eq!(bind, "127.0.0.1:8080");
+                assert_eq!(path, "/mcp");
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(
+            "http://localhost:8080/mcp".parse(),
+            Ok(Downstream::Http { bind, .. }) if bind == "localhost:8080"
+        ));
+        assert!(matches!(
+            "http://[::1]:8080".parse(),
+            Ok(Downstream::Http { bind, .. }) if bind == "[::1]:8080"
+        ));
+        assert!(matches!(
+            "http://127.0.0.1:8080/gateway".parse(),
+            Ok(Downstream::Http { path, .. }) if path == "/gateway"
+        ));
+        assert!("http://localhost".parse::<Downstream>().is_err());
+        assert!("http://localhost:http".parse::<Downstream>().is_err());
+        assert!("http://:8080".parse::<Downstream>().is_err());
+        assert!("tcp://127.0.0.1:1".parse::<Downstream>().is_err());
+    }
+}
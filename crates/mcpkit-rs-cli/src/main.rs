@@ -4,7 +4,7 @@ use tracing_subscriber::EnvFilter;
 
 mod commands;
 
-use commands::{bundle, server, validate};
+use commands::{bundle, proxy, server, validate};
 
 #[derive(Parser)]
 #[command(name = "mcpk")]
@@ -33,6 +33,9 @@ enum Commands {
     /// Run an MCP server
     Server(server::ServerArgs),
 
+    /// Bridge an MCP server to another transport (stdio <-> Streamable HTTP)
+    Proxy(proxy::ProxyArgs),
+
     /// Validate configuration files
     Validate(validate::ValidateArgs),
 }
@@ -47,6 +50,7 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_target(false)
+        .with_writer(std::io::stderr)
         .init();
 
     colored::control::set_override(!cli.no_color);
@@ -54,6 +58,7 @@ async fn main() -> Result<()> {
     match cli.command {
         Commands::Bundle(cmd) => bundle::execute(cmd).await,
         Commands::Server(args) => server::execute(args).await,
+        Commands::Proxy(args) => proxy::execute(args).await,
         Commands::Validate(args) => validate::execute(args).await,
     }
 }
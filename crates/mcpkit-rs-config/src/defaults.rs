@@ -38,6 +38,7 @@ pub fn default_transport_config() -> TransportConfig {
         transport_type: TransportType::Stdio,
         settings: TransportSettings::Stdio(StdioSettings {
             buffer_size: Some(65536),
+            max_message_size: None,
         }),
     }
 }
@@ -127,6 +127,9 @@ pub enum TransportSettings {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StdioSettings {
     pub buffer_size: Option<usize>,
+    /// Largest message accepted on stdin, in bytes. Longer lines are
+    /// discarded; unset uses the transport's default limit.
+    pub max_message_size: Option<usize>,
 }
 
 /// HTTP transport settings
@@ -380,6 +383,7 @@ pub struct RegistryAuth {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
 
 // Custom deserializer for TransportConfig to properly match settings with transport type
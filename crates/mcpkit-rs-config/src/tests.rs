@@ -295,6 +295,43 @@ policy:
         assert_eq!(config.version, "1.0");
         assert_eq!(config.server.name, "mcpkit-rs-server");
         assert_eq!(config.transport.transport_type, TransportType::Stdio);
+        assert!(matches!(
+            config.transport.settings,
+            TransportSettings::Stdio(StdioSettings {
+                buffer_size: Some(65536),
+                max_message_size: None,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_stdio_max_message_size() {
+        let yaml = r#"
+version: "1.0"
+server:
+  name: test-server
+  version: 0.1.0
+  bind: 127.0.0.1
+  port: 3000
+transport:
+  type: stdio
+  settings:
+    buffer_size: 65536
+    max_message_size: 1048576
+runtime:
+  type: native
+mcp:
+  protocol_version: "2024-11-05"
+"#;
+
+        let config = Config::from_yaml(yaml).unwrap();
+        assert!(matches!(
+            config.transport.settings,
+            TransportSettings::Stdio(StdioSettings {
+                buffer_size: Some(65536),
+                max_message_size: Some(1048576),
+            })
+        ));
     }
 
     #[test]
@@ -328,11 +365,15 @@ mcp:
 
     #[test]
     fn test_version_mismatch() {
-        let mut config1 = Config::default();
-        config1.version = "1.0".to_string();
-
-        let mut config2 = Config::default();
-        config2.version = "2.0".to_string();
+        let mut config1 = Config {
+            version: "1.0".to_string(),
+            ..Default::default()
+        };
+
+        let config2 = Config {
+            version: "2.0".to_string(),
+            ..Default::default()
+        };
 
         let result = config1.merge(config2);
         assert!(result.is_err());
@@ -106,6 +106,13 @@ fn validate_transport_config(transport: &crate::TransportConfig) -> Result<()> {
                     ));
                 }
             }
+            if let Some(max_message_size) = stdio.max_message_size {
+                if max_message_size == 0 {
+                    return Err(ConfigError::ValidationError(
+                        "Stdio max message size cannot be 0".to_string(),
+                    ));
+                }
+            }
         }
         crate::TransportSettings::Http(http) => {
             if let Some(max_body_size) = http.max_body_size {
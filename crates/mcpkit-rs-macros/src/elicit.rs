@@ -0,0 +1,425 @@
+use darling::{FromMeta, ast::NestedMeta};
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{Attribute, Data, DeriveInput, Expr, Fields, LitStr, Token, spanned::Spanned};
+
+use crate::common::extract_doc_line;
+
+#[derive(FromMeta, Default, Debug)]
+#[darling(default)]
+struct ContainerAttribute {
+    title: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(FromMeta, Default, Debug)]
+#[darling(default)]
+struct FieldAttribute {
+    title: Option<String>,
+    description: Option<String>,
+    format: Option<String>,
+    min_length: Option<Expr>,
+    max_length: Option<Expr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+    min_items: Option<Expr>,
+    max_items: Option<Expr>,
+    default: Option<DefaultValue>,
+}
+
+/// A default value taken verbatim: darling would parse a string literal as
+/// an expression, which turns `default = "free"` into a variable.
+#[derive(Debug)]
+struct DefaultValue(Expr);
+
+impl FromMeta for DefaultValue {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        Ok(Self(expr.clone()))
+    }
+}
+
+#[derive(FromMeta, Default, Debug)]
+#[darling(default)]
+struct VariantAttribute {
+    title: Option<String>,
+}
+
+/// The `serde` attributes that change what is deserialized.
+#[derive(Default)]
+struct SerdeAttribute {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    default: bool,
+    skip: bool,
+}
+
+impl SerdeAttribute {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut serde = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                let name = meta.path.get_ident().map(ToString::to_string);
+                match name.as_deref() {
+                    Some("rename") => serde.rename = deserialize_name(&meta)?,
+                    Some("rename_all") => serde.rename_all = deserialize_name(&meta)?,
+                    Some("default") => {
+                        serde.default = true;
+                        skip_value(&meta)?;
+                    }
+                    Some("skip" | "skip_deserializing") => serde.skip = true,
+                    Some("flatten") => {
+                        return Err(meta.error(
+                            "`#[serde(flatten)]` is not supported by `Elicit`: \
+                             elicitation schemas are flat, list the fields directly",
+                        ));
+                    }
+                    _ => skip_value(&meta)?,
+                }
+                Ok(())
+            })?;
+        }
+        Ok(serde)
+    }
+}
+
+/// The name in `rename = "..."` or `rename(deserialize = "...")`.
+fn deserialize_name(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("deserialize") {
+            name = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else {
+            skip_value(&meta)?;
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+fn skip_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream>()?;
+    }
+    Ok(())
+}
+
+/// Apply a serde `rename_all` rule to a field or variant name, the way
+/// serde does: fields are taken to be snake_case and variants PascalCase.
+fn rename(name: &str, rule: Option<&str>, variant: bool) -> syn::Result<String> {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    let Some(rule) = rule else {
+        return Ok(name.to_owned());
+    };
+    let lower_first = |name: &str| {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+            .unwrap_or_default()
+    };
+    let renamed = if variant {
+        let snake = || {
+            let mut snake = String::new();
+            for (index, c) in name.char_indices() {
+                if index > 0 && c.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(c.to_ascii_lowercase());
+            }
+            snake
+        };
+        match rule {
+            "lowercase" => Some(name.to_ascii_lowercase()),
+            "UPPERCASE" => Some(name.to_ascii_uppercase()),
+            "PascalCase" => Some(name.to_owned()),
+            "camelCase" => Some(lower_first(name)),
+            "snake_case" => Some(snake()),
+            "SCREAMING_SNAKE_CASE" => Some(snake().to_ascii_uppercase()),
+            "kebab-case" => Some(snake().replace('_', "-")),
+            "SCREAMING-KEBAB-CASE" => Some(snake().to_ascii_uppercase().replace('_', "-")),
+            _ => None,
+        }
+    } else {
+        let pascal = || {
+            let mut pascal = String::new();
+            let mut capitalize = true;
+            for c in name.chars() {
+                if c == '_' {
+                    capitalize = true;
+                } else if capitalize {
+                    pascal.push(c.to_ascii_uppercase());
+                    capitalize = false;
+                } else {
+                    pascal.push(c);
+                }
+            }
+            pascal
+        };
+        match rule {
+            "lowercase" | "snake_case" => Some(name.to_owned()),
+            "UPPERCASE" | "SCREAMING_SNAKE_CASE" => Some(name.to_ascii_uppercase()),
+            "PascalCase" => Some(pascal()),
+            "camelCase" => Some(lower_first(&pascal())),
+            "kebab-case" => Some(name.replace('_', "-")),
+            "SCREAMING-KEBAB-CASE" => Some(name.to_ascii_uppercase().replace('_', "-")),
+            _ => None,
+        }
+    };
+    renamed.ok_or_else(|| {
+        syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!("unsupported serde rename_all rule `{rule}`"),
+        )
+    })
+}
+
+fn parse_attribute<T: FromMeta + Default>(attrs: &[Attribute]) -> syn::Result<T> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("elicit")) {
+        let list = attr.meta.require_list()?;
+        metas.extend(NestedMeta::parse_meta_list(list.tokens.clone())?);
+    }
+    if metas.is_empty() {
+        return Ok(T::default());
+    }
+    Ok(T::from_list(&metas)?)
+}
+
+/// The doc comment, ignoring the `elicit` and `serde` attributes around it.
+fn doc(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .try_fold(None, extract_doc_line)
+}
+
+pub fn derive_elicit(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<DeriveInput>(input)?;
+    match &input.data {
+        Data::Struct(data) => derive_struct(&input, &data.fields),
+        Data::Enum(data) => derive_enum(&input, data),
+        Data::Union(_) => Err(syn::Error::new(
+            input.ident.span(),
+            "`Elicit` can only be derived for structs and enums",
+        )),
+    }
+}
+
+fn derive_struct(input: &DeriveInput, fields: &Fields) -> syn::Result<TokenStream> {
+    let Fields::Named(fields) = fields else {
+        return Err(syn::Error::new(
+            fields.span(),
+            "`Elicit` can only be derived for structs with named fields",
+        ));
+    };
+    let container: ContainerAttribute = parse_attribute(&input.attrs)?;
+    let container_serde = SerdeAttribute::parse(&input.attrs)?;
+
+    let mut properties = Vec::new();
+    for field in &fields.named {
+        let serde = SerdeAttribute::parse(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        let attribute: FieldAttribute = parse_attribute(&field.attrs)?;
+        let ident = field.ident.as_ref().expect("named field");
+        let name = match serde.rename {
+            Some(name) => name,
+            None => rename(
+                &ident.to_string(),
+                container_serde.rename_all.as_deref(),
+                false,
+            )?,
+        };
+        let ty = &field.ty;
+
+        let mut calls = Vec::new();
+        if let Some(title) = &attribute.title {
+            calls.push(quote! { .title(#title) });
+        }
+        match (&attribute.description, doc(&field.attrs)?) {
+            (Some(description), _) => calls.push(quote! { .description(#description) }),
+            (None, Some(doc)) => calls.push(quote! { .description(#doc) }),
+            (None, None) => {}
+        }
+        if let Some(format) = &attribute.format {
+            let variant = match format.as_str() {
+                "email" => quote! { Email },
+                "uri" => quote! { Uri },
+                "date" => quote! { Date },
+                "date-time" => quote! { DateTime },
+                _ => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        "format must be one of \"email\", \"uri\", \"date\" or \"date-time\"",
+                    ));
+                }
+            };
+            calls.push(quote! { .format(mcpkit_rs::model::StringFormat::#variant) });
+        }
+        for (value, method) in [
+            (&attribute.min_length, quote! { min_length }),
+            (&attribute.max_length, quote! { max_length }),
+            (&attribute.min, quote! { minimum }),
+            (&attribute.max, quote! { maximum }),
+            (&attribute.min_items, quote! { min_items }),
+            (&attribute.max_items, quote! { max_items }),
+        ] {
+            if let Some(value) = value {
+                calls.push(quote_spanned! { value.span()=> .#method(#value as _) });
+            }
+        }
+        if let Some(DefaultValue(default)) = &attribute.default {
+            let default = match default {
+                Expr::Array(array) => {
+                    let elems = &array.elems;
+                    quote! { ::std::vec![#elems] }
+                }
+                default => quote! { #default },
+            };
+            calls.push(quote_spanned! { default.span()=> .with_default(#default) });
+        }
+
+        let serde_default = serde.default || container_serde.default;
+        properties.push(quote_spanned! { ty.span()=>
+            properties.insert(
+                ::std::string::String::from(#name),
+                ::core::convert::Into::<mcpkit_rs::model::PrimitiveSchema>::into(
+                    <#ty as mcpkit_rs::model::ElicitField>::field_schema() #(#calls)*
+                ),
+            );
+            if <#ty as mcpkit_rs::model::ElicitField>::REQUIRED && !#serde_default {
+                required.push(::std::string::String::from(#name));
+            }
+        });
+    }
+
+    let title = container.title.map(|title| quote! { .with_title(#title) });
+    let description = match (container.description, doc(&input.attrs)?) {
+        (Some(description), _) => Some(quote! { .with_description(#description) }),
+        (None, Some(doc)) => Some(quote! { .with_description(#doc) }),
+        (None, None) => None,
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics mcpkit_rs::model::Elicit for #ident #ty_generics #where_clause {
+            fn elicitation_schema() -> mcpkit_rs::model::ElicitationSchema {
+                let mut properties = ::std::collections::BTreeMap::new();
+                let mut required = ::std::vec::Vec::<::std::string::String>::new();
+                #(#properties)*
+                let mut schema = mcpkit_rs::model::ElicitationSchema::new(properties);
+                if !required.is_empty() {
+                    schema = schema.with_required(required);
+                }
+                schema #title #description
+            }
+        }
+    })
+}
+
+fn derive_enum(input: &DeriveInput, data: &syn::DataEnum) -> syn::Result<TokenStream> {
+    let container_serde = SerdeAttribute::parse(&input.attrs)?;
+    let mut options = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.span(),
+                "`Elicit` can only be derived for enums whose variants have no fields",
+            ));
+        }
+        let serde = SerdeAttribute::parse(&variant.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        let attribute: VariantAttribute = parse_attribute(&variant.attrs)?;
+        let value = match serde.rename {
+            Some(name) => name,
+            None => rename(
+                &variant.ident.to_string(),
+                container_serde.rename_all.as_deref(),
+                true,
+            )?,
+        };
+        let title = match attribute.title {
+            Some(title) => quote! { ::core::option::Option::Some(#title) },
+            None => quote! { ::core::option::Option::None },
+        };
+        options.push(quote! { (#value, #title) });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics mcpkit_rs::model::ElicitOptions for #ident #ty_generics #where_clause {
+            fn options() -> ::std::vec::Vec<(&'static str, ::core::option::Option<&'static str>)> {
+                ::std::vec![#(#options),*]
+            }
+        }
+
+        impl #impl_generics mcpkit_rs::model::ElicitField for #ident #ty_generics #where_clause {
+            type Schema = mcpkit_rs::model::EnumSchema;
+
+            fn field_schema() -> mcpkit_rs::model::EnumSchema {
+                mcpkit_rs::model::single_select_schema::<Self>()
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename() -> syn::Result<()> {
+        assert_eq!(rename("r#type", None, false)?, "type");
+        assert_eq!(rename("first_name", Some("camelCase"), false)?, "firstName");
+        assert_eq!(
+            rename("first_name", Some("PascalCase"), false)?,
+            "FirstName"
+        );
+        assert_eq!(
+            rename("first_name", Some("kebab-case"), false)?,
+            "first-name"
+        );
+        assert_eq!(rename("DarkBlue", Some("snake_case"), true)?, "dark_blue");
+        assert_eq!(rename("DarkBlue", Some("camelCase"), true)?, "darkBlue");
+        assert_eq!(
+            rename("DarkBlue", Some("SCREAMING-KEBAB-CASE"), true)?,
+            "DARK-BLUE"
+        );
+        assert_eq!(rename("DarkBlue", Some("lowercase"), true)?, "darkblue");
+        assert_eq!(
+            rename("first_name", Some("lowercase"), false)?,
+            "first_name"
+        );
+        assert_eq!(
+            rename("first_name", Some("UPPERCASE"), false)?,
+            "FIRST_NAME"
+        );
+        assert_eq!(rename("DarkBlue", Some("UPPERCASE"), true)?, "DARKBLUE");
+        assert!(rename("DarkBlue", Some("Title Case"), true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flatten_is_rejected() {
+        let input = quote! {
+            struct Profile {
+                name: String,
+                #[serde(flatten)]
+                address: Address,
+            }
+        };
+        let error = derive_elicit(input).unwrap_err();
+        assert!(error.to_string().contains("flatten"), "{error}");
+    }
+}
@@ -4,6 +4,7 @@
 use proc_macro::TokenStream;
 
 mod common;
+mod elicit;
 mod prompt;
 mod prompt_handler;
 mod prompt_router;
@@ -181,6 +182,7 @@ pub fn tool_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
 /// | `name`            | `String` | The name of the prompt. If not provided, it defaults to the function name. |
 /// | `description`     | `String` | A description of the prompt. The document of this function will be used if not provided. |
 /// | `arguments`       | `Expr`   | An expression that evaluates to `Option<Vec<PromptArgument>>` defining the prompt's arguments. If not provided, it will automatically generate arguments from the `Parameters<T>` type found in the function signature. |
+/// | `complete`        | `CompleteAttribute` | A completion provider for one argument, repeated for each argument. `complete(arg = "name", values = [...])` matches the input against a static list with `fuzzy_match`, `complete(arg = "name", with = Self::method)` calls an async method taking `&CompletionContext<'_, Self>`. Routes built by `#[prompt_router]` answer `completion/complete` with them. |
 ///
 /// ## Example
 ///
@@ -190,6 +192,25 @@ pub fn tool_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
 ///     // Generate prompt messages based on arguments
 /// }
 /// ```
+///
+/// With completions:
+///
+/// ```rust,ignore
+/// #[prompt(
+///     complete(arg = "language", values = ["Rust", "Python"]),
+///     complete(arg = "framework", with = Self::complete_framework),
+/// )]
+/// pub async fn code_review_prompt(&self, Parameters(args): Parameters<CodeReviewArgs>) -> Vec<PromptMessage> {
+///     // Generate prompt messages based on arguments
+/// }
+///
+/// async fn complete_framework(&self, context: &CompletionContext<'_, Self>) -> Vec<String> {
+///     match context.get_argument("language") {
+///         Some("Rust") => fuzzy_match(context.value(), ["axum", "actix-web"]),
+///         _ => vec![],
+///     }
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn prompt(attr: TokenStream, input: TokenStream) -> TokenStream {
     prompt::prompt(attr.into(), input.into())
@@ -239,11 +260,15 @@ pub fn prompt_router(attr: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// This macro generates handler methods for `get_prompt` and `list_prompts` in the implementation block, using an existing `PromptRouter` instance.
 ///
+/// It also generates `complete` from the completion providers of the prompts, unless the implementation block already has one.
+/// Completions of resource templates go to `resource_router` when it is given, and fail with method not found otherwise.
+///
 /// ## Usage
 ///
-/// | field     | type   | usage |
-/// | :-        | :-     | :-    |
-/// | `router`  | `Expr` | The expression to access the `PromptRouter` instance. Defaults to `self.prompt_router`. |
+/// | field             | type   | usage |
+/// | :-                | :-     | :-    |
+/// | `router`          | `Expr` | The expression to access the `PromptRouter` instance. Defaults to `self.prompt_router`. |
+/// | `resource_router` | `Expr` | The expression to access a `ResourceTemplateRouter` completing resource templates. Defaults to none. |
 ///
 /// ## Example
 /// ```rust,ignore
@@ -274,9 +299,62 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
 /// `self.processor` field holding an `Arc<Mutex<OperationProcessor>>`, but it can be customized
 /// via `#[task_handler(processor = ...)]`. Because the macro captures `self` inside spawned
 /// futures, the handler type must implement [`Clone`].
+///
+/// `list_tasks`, `get_task_info`, `get_task_result` and `cancel_task` answer from the
+/// processor's task records, so they keep working after results are collected, and across
+/// restarts when the processor is built with a durable store through
+/// `OperationProcessor::with_store`.
 #[proc_macro_attribute]
 pub fn task_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
     task_handler::task_handler(attr.into(), input.into())
         .unwrap_or_else(|err| err.to_compile_error())
         .into()
 }
+
+/// # Elicit
+///
+/// Derives `mcpkit_rs::model::Elicit` for a struct with named fields, building the form a server
+/// sends with `Peer::elicit_form`, or `ElicitField` for an enum of unit variants, which becomes a
+/// single-select property. Property names and optional properties follow the type's `serde`
+/// attributes (`rename`, `rename_all`, `default`, `skip`), and each field type must implement
+/// `ElicitField`.
+///
+/// ## Usage
+///
+/// | attribute                  | on               | usage |
+/// | :-                         | :-               | :-    |
+/// | `title`                    | struct, field, variant | A human readable title. |
+/// | `description`              | struct, field    | A description. Defaults to the doc comment. |
+/// | `format`                   | `String` field   | One of `"email"`, `"uri"`, `"date"` or `"date-time"`. |
+/// | `min_length`, `max_length` | `String` field   | Bounds on the length. |
+/// | `min`, `max`               | number field     | Bounds on the value. |
+/// | `min_items`, `max_items`   | `Vec` field      | Bounds on how many options are selected. |
+/// | `default`                  | field            | The value the client suggests. |
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// #[derive(Deserialize, Elicit)]
+/// enum Plan {
+///     #[elicit(title = "Free tier")]
+///     Free,
+///     Pro,
+/// }
+///
+/// /// Tell us about yourself
+/// #[derive(Deserialize, Elicit)]
+/// struct Profile {
+///     #[elicit(format = "email")]
+///     email: String,
+///     #[elicit(min = 0, max = 150)]
+///     age: Option<u8>,
+///     #[elicit(default = "Free")]
+///     plan: Plan,
+/// }
+/// ```
+#[proc_macro_derive(Elicit, attributes(elicit))]
+pub fn derive_elicit(input: TokenStream) -> TokenStream {
+    elicit::derive_elicit(input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
@@ -20,6 +20,61 @@ pub struct PromptAttribute {
     pub icons: Option<Expr>,
     /// Optional metadata for the prompt
     pub meta: Option<Expr>,
+    /// Completion providers for the arguments of the prompt
+    #[darling(multiple)]
+    pub complete: Vec<CompleteAttribute>,
+}
+
+#[derive(FromMeta, Debug)]
+pub struct CompleteAttribute {
+    /// The name of the argument to complete
+    pub arg: String,
+    /// An async method completing the argument
+    #[darling(default)]
+    pub with: Option<Expr>,
+    /// Static values matched against the input
+    #[darling(default)]
+    pub values: Option<Expr>,
+}
+
+impl CompleteAttribute {
+    fn into_call(self) -> syn::Result<TokenStream> {
+        let arg = self.arg;
+        match (self.with, self.values) {
+            (Some(with), None) => Ok(quote! {
+                .with_provider(#arg, |context| {
+                    ::std::boxed::Box::pin(async move {
+                        let result = (#with)(context.server, &context).await;
+                        mcpkit_rs::handler::server::completion::IntoCompletionResult::into_completion_result(result)
+                    })
+                })
+            }),
+            (None, Some(values)) => Ok(quote! {
+                .with_values(#arg, #values)
+            }),
+            _ => Err(syn::Error::new(
+                Span::call_site(),
+                format!("completion of `{arg}` needs exactly one of `with` or `values`"),
+            )),
+        }
+    }
+}
+
+/// Generate the function returning the completion providers of a prompt
+pub fn completions_fn(
+    fn_ident: Ident,
+    complete: Vec<CompleteAttribute>,
+) -> syn::Result<ImplItemFn> {
+    let calls = complete
+        .into_iter()
+        .map(CompleteAttribute::into_call)
+        .collect::<syn::Result<Vec<_>>>()?;
+    syn::parse2::<ImplItemFn>(quote! {
+        pub fn #fn_ident() -> mcpkit_rs::handler::server::completion::ArgumentCompletions<Self> {
+            mcpkit_rs::handler::server::completion::ArgumentCompletions::new()
+                #(#calls)*
+        }
+    })
 }
 
 pub struct ResolvedPromptAttribute {
@@ -119,6 +174,12 @@ pub fn prompt(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream>
         meta: attribute.meta,
     };
     let prompt_attr_fn = resolved_prompt_attr.into_fn(prompt_attr_fn_ident.clone())?;
+    let completions_fn = if attribute.complete.is_empty() {
+        None
+    } else {
+        let completions_fn_ident = format_ident!("{}_prompt_completions", fn_ident);
+        Some(completions_fn(completions_fn_ident, attribute.complete)?)
+    };
 
     // Modify the input function for async support (same as tool macro)
     if fn_item.sig.asyncness.is_some() {
@@ -156,6 +217,7 @@ pub fn prompt(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream>
 
     Ok(quote! {
         #prompt_attr_fn
+        #completions_fn
         #fn_item
     })
 }
@@ -189,6 +251,35 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_prompt_macro_completions() -> syn::Result<()> {
+        let attr = quote! {
+            complete(arg = "language", values = ["Rust", "Python"]),
+            complete(arg = "framework", with = Self::complete_framework)
+        };
+        let input = quote! {
+            async fn review(&self, Parameters(args): Parameters<ReviewArgs>) -> Vec<PromptMessage> {
+                vec![]
+            }
+        };
+        let result_str = prompt(attr, input)?.to_string();
+
+        assert!(result_str.contains("fn review_prompt_completions"));
+        assert!(result_str.contains("with_values (\"language\""));
+        assert!(result_str.contains("with_provider (\"framework\""));
+        assert!(result_str.contains("complete_framework"));
+
+        let attr = quote! { complete(arg = "language") };
+        let input = quote! {
+            fn review(&self) -> Vec<PromptMessage> {
+                vec![]
+            }
+        };
+        assert!(prompt(attr, input).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_doc_comment_description() -> syn::Result<()> {
         let attr = quote! {}; // No explicit description
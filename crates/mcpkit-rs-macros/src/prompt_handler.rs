@@ -7,6 +7,7 @@ use syn::{Expr, ImplItem, ItemImpl, parse_quote};
 #[darling(default)]
 pub struct PromptHandlerAttribute {
     pub router: Option<Expr>,
+    pub resource_router: Option<Expr>,
     pub meta: Option<Expr>,
 }
 
@@ -63,9 +64,41 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> syn::Result<Toke
         }
     };
 
+    // Add complete implementation, answered from the completion providers of the prompts
+    // and, when a resource template router is given, of the resource templates
+    let complete_resource = match &attribute.resource_router {
+        Some(resource_router) => quote! { #resource_router.complete(completion_context).await },
+        None => quote! {
+            Err(mcpkit_rs::ErrorData::method_not_found::<
+                mcpkit_rs::model::CompleteRequestMethod,
+            >())
+        },
+    };
+    let complete_impl: ImplItem = parse_quote! {
+        async fn complete(
+            &self,
+            request: mcpkit_rs::model::CompleteRequestParams,
+            context: mcpkit_rs::service::RequestContext<mcpkit_rs::RoleServer>,
+        ) -> Result<mcpkit_rs::model::CompleteResult, mcpkit_rs::ErrorData> {
+            let completion_context = mcpkit_rs::handler::server::completion::CompletionContext::new(
+                self,
+                request,
+                context,
+            );
+            match completion_context.r#ref {
+                mcpkit_rs::model::Reference::Prompt(_) => {
+                    #router_expr.complete(completion_context).await
+                }
+                mcpkit_rs::model::Reference::Resource(_) => #complete_resource,
+            }
+        }
+    };
+
     // Check if methods already exist and replace them if they do
     let mut has_get_prompt = false;
     let mut has_list_prompts = false;
+    // A hand-written complete is kept, it may also complete resource templates
+    let mut has_complete = false;
 
     for item in &mut impl_block.items {
         if let ImplItem::Fn(fn_item) = item {
@@ -78,6 +111,9 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> syn::Result<Toke
                     *item = list_prompts_impl.clone();
                     has_list_prompts = true;
                 }
+                "complete" => {
+                    has_complete = true;
+                }
                 _ => {}
             }
         }
@@ -90,6 +126,9 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> syn::Result<Toke
     if !has_list_prompts {
         impl_block.items.push(list_prompts_impl);
     }
+    if !has_complete {
+        impl_block.items.push(complete_impl);
+    }
 
     Ok(quote! {
         #impl_block
@@ -116,6 +155,47 @@ mod test {
         assert!(result_str.contains("PromptContext") && result_str.contains("new"));
         assert!(result_str.contains("async fn list_prompts"));
         assert!(result_str.contains("ListPromptsResult"));
+        assert!(result_str.contains("async fn complete"));
+        assert!(result_str.contains("CompletionContext"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_handler_keeps_custom_complete() -> syn::Result<()> {
+        let input = quote! {
+            impl ServerHandler for MyPromptHandler {
+                async fn complete(
+                    &self,
+                    request: CompleteRequestParams,
+                    context: RequestContext<RoleServer>,
+                ) -> Result<CompleteResult, ErrorData> {
+                    Ok(CompleteResult::default())
+                }
+            }
+        };
+
+        let result_str = prompt_handler(TokenStream::new(), input)?.to_string();
+
+        assert_eq!(result_str.matches("async fn complete").count(), 1);
+        assert!(!result_str.contains("CompletionContext"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_handler_routes_resource_completions() -> syn::Result<()> {
+        let input = quote! {
+            impl ServerHandler for MyPromptHandler {}
+        };
+
+        let result_str = prompt_handler(TokenStream::new(), input.clone())?.to_string();
+        assert!(result_str.contains("method_not_found"));
+
+        let attr = quote! { resource_router = self.resource_templates };
+        let result_str = prompt_handler(attr, input)?.to_string();
+        assert!(result_str.contains("resource_templates . complete"));
+        assert!(!result_str.contains("method_not_found"));
 
         Ok(())
     }
@@ -3,6 +3,8 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::{ImplItem, ItemImpl, Visibility, parse_quote};
 
+use crate::prompt::PromptAttribute;
+
 #[derive(FromMeta, Debug, Default)]
 #[darling(default)]
 pub struct PromptRouterAttribute {
@@ -31,7 +33,7 @@ pub fn prompt_router(attr: TokenStream, input: TokenStream) -> syn::Result<Token
 
     for item in &mut impl_block.items {
         if let ImplItem::Fn(fn_item) = item {
-            let has_prompt_attr = fn_item.attrs.iter().any(|attr| {
+            let prompt_attr = fn_item.attrs.iter().find(|attr| {
                 attr.path()
                     .segments
                     .last()
@@ -39,7 +41,15 @@ pub fn prompt_router(attr: TokenStream, input: TokenStream) -> syn::Result<Token
                     .unwrap_or(false)
             });
 
-            if has_prompt_attr {
+            if let Some(prompt_attr) = prompt_attr {
+                let has_completions = match &prompt_attr.meta {
+                    syn::Meta::List(list) => {
+                        let attr_args =
+                            darling::ast::NestedMeta::parse_meta_list(list.tokens.clone())?;
+                        !PromptAttribute::from_list(&attr_args)?.complete.is_empty()
+                    }
+                    _ => false,
+                };
                 let fn_ident = &fn_item.sig.ident;
                 let attr_fn_ident = format_ident!("{}_prompt_attr", fn_ident);
 
@@ -55,10 +65,23 @@ pub fn prompt_router(attr: TokenStream, input: TokenStream) -> syn::Result<Token
                     }
                 }
 
-                // Use the exact same pattern as tool_router
-                prompt_route_fn_calls.push(quote! {
-                    .with_route((Self::#attr_fn_ident(), Self::#fn_ident))
-                });
+                if has_completions {
+                    let completions_fn_ident = format_ident!("{}_prompt_completions", fn_ident);
+                    prompt_route_fn_calls.push(quote! {
+                        .with_route(
+                            mcpkit_rs::handler::server::router::prompt::PromptRoute::new(
+                                Self::#attr_fn_ident(),
+                                Self::#fn_ident,
+                            )
+                            .with_completions(Self::#completions_fn_ident()),
+                        )
+                    });
+                } else {
+                    // Use the exact same pattern as tool_router
+                    prompt_route_fn_calls.push(quote! {
+                        .with_route((Self::#attr_fn_ident(), Self::#fn_ident))
+                    });
+                }
             }
         }
     }
@@ -108,4 +131,23 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_prompt_router_macro_with_completions() -> syn::Result<()> {
+        let input = quote! {
+            impl MyPromptHandler {
+                #[prompt(complete(arg = "language", values = ["Rust"]))]
+                async fn review(&self, Parameters(args): Parameters<ReviewArgs>) -> Vec<PromptMessage> {
+                    vec![]
+                }
+            }
+        };
+
+        let result_str = prompt_router(TokenStream::new(), input)?.to_string();
+
+        assert!(result_str.contains("PromptRoute :: new"));
+        assert!(result_str.contains("with_completions (Self :: review_prompt_completions ())"));
+
+        Ok(())
+    }
 }
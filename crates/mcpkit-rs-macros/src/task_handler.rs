@@ -33,22 +33,21 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
         let list_fn = quote! {
             async fn list_tasks(
                 &self,
-                _request: Option<mcpkit_rs::model::PaginatedRequestParam>,
+                _request: Option<mcpkit_rs::model::PaginatedRequestParams>,
                 _: mcpkit_rs::service::RequestContext<mcpkit_rs::RoleServer>,
             ) -> Result<mcpkit_rs::model::ListTasksResult, McpError> {
-                let running_ids = (#processor).lock().await.list_running();
-                let total = running_ids.len() as u64;
-                let tasks = running_ids
+                let records = (#processor)
+                    .lock()
+                    .await
+                    .task_records()
+                    .await
+                    .map_err(|err| McpError::internal_error(
+                        format!("failed to list tasks: {err}"),
+                        None,
+                    ))?;
+                let tasks = records
                     .into_iter()
-                    .map(|task_id| {
-                        let timestamp = mcpkit_rs::task_manager::current_timestamp();
-                        mcpkit_rs::model::Task::new(
-                            task_id,
-                            mcpkit_rs::model::TaskStatus::Working,
-                            timestamp.clone(),
-                            timestamp,
-                        )
-                    })
+                    .map(|record| record.task)
                     .collect::<Vec<_>>();
 
                 Ok(mcpkit_rs::model::ListTasksResult::new(tasks))
@@ -61,24 +60,34 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
         let enqueue_fn = quote! {
             async fn enqueue_task(
                 &self,
-                request: mcpkit_rs::model::CallToolRequestParam,
+                request: mcpkit_rs::model::CallToolRequestParams,
                 context: mcpkit_rs::service::RequestContext<mcpkit_rs::RoleServer>,
             ) -> Result<mcpkit_rs::model::CreateTaskResult, McpError> {
                 use mcpkit_rs::task_manager::{
-                    current_timestamp, OperationDescriptor, OperationMessage, OperationResultTransport,
+                    OperationDescriptor, OperationMessage, OperationResultTransport,
                     ToolCallTaskResult,
                 };
-                let task_id = context.id.to_string();
+                // the request id stays with the context, the task outlives it
+                let task_id = mcpkit_rs::task_manager::new_task_id();
                 let operation_name = request.name.to_string();
+                // how long the client wants the task kept, in milliseconds
+                let requested_ttl = request
+                    .task
+                    .as_ref()
+                    .and_then(|task| task.get("ttl"))
+                    .and_then(|ttl| ttl.as_u64());
                 let future_request = request.clone();
                 let future_context = context.clone();
                 let server = self.clone();
 
-                let descriptor = OperationDescriptor::new(task_id.clone(), operation_name)
+                let mut descriptor = OperationDescriptor::new(task_id.clone(), operation_name)
                     .with_context(context)
                     .with_client_request(mcpkit_rs::model::ClientRequest::CallToolRequest(
                         mcpkit_rs::model::Request::new(request),
                     ));
+                if let Some(ttl) = requested_ttl {
+                    descriptor = descriptor.with_retention_ttl(ttl);
+                }
 
                 let task_result_id = task_id.clone();
                 let future = Box::pin(async move {
@@ -89,22 +98,21 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                     )
                 });
 
-                (#processor)
-                    .lock()
-                    .await
+                let mut processor = (#processor).lock().await;
+                processor
                     .submit_operation(OperationMessage::new(descriptor, future))
                     .map_err(|err| mcpkit_rs::ErrorData::internal_error(
                         format!("failed to enqueue task: {err}"),
                         None,
                     ))?;
-
-                let timestamp = current_timestamp();
-                let task = mcpkit_rs::model::Task::new(
-                    task_id,
-                    mcpkit_rs::model::TaskStatus::Working,
-                    timestamp.clone(),
-                    timestamp,
-                ).with_status_message("Task accepted");
+                let task = processor
+                    .task_record(&task_id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|record| record.task)
+                    .ok_or_else(|| McpError::internal_error("task was not recorded", None))?
+                    .with_status_message("Task accepted");
 
                 Ok(mcpkit_rs::model::CreateTaskResult::new(task))
             }
@@ -116,57 +124,23 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
         let get_info_fn = quote! {
             async fn get_task_info(
                 &self,
-                request: mcpkit_rs::model::GetTaskInfoParam,
+                request: mcpkit_rs::model::GetTaskInfoParams,
                 _context: mcpkit_rs::service::RequestContext<mcpkit_rs::RoleServer>,
             ) -> Result<mcpkit_rs::model::GetTaskResult, McpError> {
-                use mcpkit_rs::task_manager::current_timestamp;
-                let task_id = request.task_id.clone();
-                let mut processor = (#processor).lock().await;
-
-                // Check completed results first
-                let completed = processor.peek_completed().iter().rev().find(|r| r.descriptor.operation_id == task_id);
-                if let Some(completed_result) = completed {
-                    // Determine Finished vs Failed
-                    let status = match &completed_result.result {
-                        Ok(boxed) => {
-                            if let Some(tool) = boxed.as_any().downcast_ref::<mcpkit_rs::task_manager::ToolCallTaskResult>() {
-                                match &tool.result {
-                                    Ok(_) => mcpkit_rs::model::TaskStatus::Completed,
-                                    Err(_) => mcpkit_rs::model::TaskStatus::Failed,
-                                }
-                            } else {
-                                mcpkit_rs::model::TaskStatus::Completed
-                            }
-                        }
-                        Err(_) => mcpkit_rs::model::TaskStatus::Failed,
-                    };
-                    let timestamp = current_timestamp();
-                    let mut task = mcpkit_rs::model::Task::new(
-                        task_id,
-                        status,
-                        timestamp.clone(),
-                        timestamp,
-                    );
-                    if let Some(ttl) = completed_result.descriptor.ttl {
-                        task = task.with_ttl(ttl);
-                    }
-                    return Ok(mcpkit_rs::model::GetTaskResult { meta: None, task });
-                }
-
-                // If not completed, check running
-                let running = processor.list_running();
-                if running.into_iter().any(|id| id == task_id) {
-                    let timestamp = current_timestamp();
-                    let task = mcpkit_rs::model::Task::new(
-                        task_id,
-                        mcpkit_rs::model::TaskStatus::Working,
-                        timestamp.clone(),
-                        timestamp,
-                    );
-                    return Ok(mcpkit_rs::model::GetTaskResult { meta: None, task });
+                let task_id = request.task_id;
+                let record = (#processor)
+                    .lock()
+                    .await
+                    .task_record(&task_id)
+                    .await
+                    .map_err(|err| McpError::internal_error(
+                        format!("failed to load task: {err}"),
+                        None,
+                    ))?;
+                match record {
+                    Some(record) => Ok(mcpkit_rs::model::GetTaskResult { meta: None, task: record.task }),
+                    None => Err(McpError::resource_not_found(format!("task not found: {}", task_id), None)),
                 }
-
-                Err(McpError::resource_not_found(format!("task not found: {}", task_id), None))
             }
         };
         item_impl.items.push(syn::parse2::<ImplItem>(get_info_fn)?);
@@ -176,7 +150,7 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
         let get_result_fn = quote! {
             async fn get_task_result(
                 &self,
-                request: mcpkit_rs::model::GetTaskResultParam,
+                request: mcpkit_rs::model::GetTaskResultParams,
                 _context: mcpkit_rs::service::RequestContext<mcpkit_rs::RoleServer>,
             ) -> Result<mcpkit_rs::model::GetTaskPayloadResult, McpError> {
                 use std::time::Duration;
@@ -186,36 +160,40 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                     // Scope the lock so we can await outside if needed
                     {
                         let mut processor = (#processor).lock().await;
-
-                        if let Some(task_result) = processor.take_completed_result(&task_id) {
-                            match task_result.result {
-                                Ok(boxed) => {
-                                    if let Some(tool) = boxed.as_any().downcast_ref::<mcpkit_rs::task_manager::ToolCallTaskResult>() {
-                                        match &tool.result {
-                                            Ok(call_tool) => {
-                                                let value = ::serde_json::to_value(call_tool).unwrap_or(::serde_json::Value::Null);
-                                                return Ok(mcpkit_rs::model::GetTaskPayloadResult::new(value));
-                                            }
-                                            Err(err) => return Err(McpError::internal_error(
-                                                format!("task failed: {}", err),
-                                                None,
-                                            )),
-                                        }
-                                    } else {
-                                        return Err(McpError::internal_error("unsupported task result transport", None));
-                                    }
+                        let record = processor
+                            .task_record(&task_id)
+                            .await
+                            .map_err(|err| McpError::internal_error(
+                                format!("failed to load task: {err}"),
+                                None,
+                            ))?
+                            .ok_or_else(|| McpError::resource_not_found(
+                                format!("task not found: {}", task_id),
+                                None,
+                            ))?;
+
+                        if let Some(outcome) = record.outcome {
+                            // the store keeps the outcome, drop the in-memory copy
+                            processor.take_completed_result(&task_id);
+                            match outcome {
+                                mcpkit_rs::task_manager::TaskOutcome::Result(call_tool) => {
+                                    let value = ::serde_json::to_value(call_tool).unwrap_or(::serde_json::Value::Null);
+                                    return Ok(mcpkit_rs::model::GetTaskPayloadResult::new(value));
                                 }
-                                Err(err) => return Err(McpError::internal_error(
-                                    format!("task execution error: {}", err),
+                                mcpkit_rs::task_manager::TaskOutcome::Error(err) => return Err(McpError::internal_error(
+                                    format!("task failed: {}", err),
                                     None,
                                 )),
                             }
                         }
-
-                        // Not completed yet: if not running, return not found
-                        let running = processor.list_running();
-                        if !running.iter().any(|id| id == &task_id) {
-                            return Err(McpError::resource_not_found(format!("task not found: {}", task_id), None));
+                        if record.is_terminal() {
+                            return Err(McpError::internal_error(
+                                format!(
+                                    "task execution error: {}",
+                                    record.task.status_message.unwrap_or_default()
+                                ),
+                                None,
+                            ));
                         }
                     }
 
@@ -232,31 +210,28 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
         let cancel_fn = quote! {
             async fn cancel_task(
                 &self,
-                request: mcpkit_rs::model::CancelTaskParam,
+                request: mcpkit_rs::model::CancelTaskParams,
                 _context: mcpkit_rs::service::RequestContext<mcpkit_rs::RoleServer>,
             ) -> Result<mcpkit_rs::model::CancelTaskResult, McpError> {
-                use mcpkit_rs::task_manager::current_timestamp;
                 let task_id = request.task_id;
                 let mut processor = (#processor).lock().await;
+                let cancelled = processor.cancel_task(&task_id);
+                let record = processor
+                    .task_record(&task_id)
+                    .await
+                    .map_err(|err| McpError::internal_error(
+                        format!("failed to load task: {err}"),
+                        None,
+                    ))?;
 
-                if processor.cancel_task(&task_id) {
-                    let timestamp = current_timestamp();
-                    let task = mcpkit_rs::model::Task::new(
-                        task_id,
-                        mcpkit_rs::model::TaskStatus::Cancelled,
-                        timestamp.clone(),
-                        timestamp,
-                    );
-                    return Ok(mcpkit_rs::model::CancelTaskResult { meta: None, task });
-                }
-
-                // If already completed, signal it's not cancellable
-                let exists_completed = processor.peek_completed().iter().any(|r| r.descriptor.operation_id == task_id);
-                if exists_completed {
-                    return Err(McpError::invalid_request(format!("task already completed: {}", task_id), None));
+                match record {
+                    Some(record) if cancelled => {
+                        Ok(mcpkit_rs::model::CancelTaskResult { meta: None, task: record.task })
+                    }
+                    // If already completed, signal it's not cancellable
+                    Some(_) => Err(McpError::invalid_request(format!("task already completed: {}", task_id), None)),
+                    None => Err(McpError::resource_not_found(format!("task not found: {}", task_id), None)),
                 }
-
-                Err(McpError::resource_not_found(format!("task not found: {}", task_id), None))
             }
         };
         item_impl.items.push(syn::parse2::<ImplItem>(cancel_fn)?);
@@ -85,12 +85,13 @@ fn create_exhaustive_policy() -> Policy {
                 deny: env_deny_rules,
             }),
             resources: Some(ResourceLimits {
-                limits: ResourceLimitValues {
-                    cpu: Some("2000m".to_string()),
-                    memory: Some("4Gi".to_string()),
-                    execution_time: Some("5m".to_string()),
-                    fuel: Some(100_000_000),
-                    memory_limit: None,
+                limits: {
+                    let mut limits = ResourceLimitValues::default();
+                    limits.cpu = Some("2000m".to_string());
+                    limits.memory = Some("4Gi".to_string());
+                    limits.execution_time = Some("5m".to_string());
+                    limits.fuel = Some(100_000_000);
+                    limits
                 },
             }),
         },
@@ -119,7 +120,10 @@ fn bench_exhaustive_glob_patterns(c: &mut Criterion) {
 
     for (path, op) in test_paths {
         c.bench_function(
-            &format!("exhaustive_storage_{}", path.split('/').last().unwrap()),
+            &format!(
+                "exhaustive_storage_{}",
+                path.split('/').next_back().unwrap()
+            ),
             |b| b.iter(|| black_box(compiled.is_storage_allowed(path, op))),
         );
     }
@@ -102,12 +102,13 @@ fn create_practical_policy() -> Policy {
                 ],
             }),
             resources: Some(ResourceLimits {
-                limits: ResourceLimitValues {
-                    cpu: Some("500m".to_string()),
-                    memory: Some("256Mi".to_string()),
-                    execution_time: Some("30s".to_string()),
-                    fuel: Some(1_000_000),
-                    memory_limit: None,
+                limits: {
+                    let mut limits = ResourceLimitValues::default();
+                    limits.cpu = Some("500m".to_string());
+                    limits.memory = Some("256Mi".to_string());
+                    limits.execution_time = Some("30s".to_string());
+                    limits.fuel = Some(1_000_000);
+                    limits
                 },
             }),
         },
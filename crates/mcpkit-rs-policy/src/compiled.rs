@@ -52,7 +52,8 @@ pub struct CompiledPolicy {
 }
 
 /// Compiled resource limits
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
 pub struct ResourceLimits {
     /// CPU limit in millicores (1000 = 1 CPU core)
     pub cpu_millicores: Option<u64>,
@@ -60,8 +61,12 @@ pub struct ResourceLimits {
     pub memory_bytes: Option<u64>,
     /// Execution time limit in milliseconds
     pub execution_time_ms: Option<u64>,
+    /// Lifetime CPU time limit of a native server process in milliseconds
+    pub cpu_time_ms: Option<u64>,
     /// WebAssembly fuel limit
     pub fuel: Option<u64>,
+    /// Open file descriptor limit
+    pub open_files: Option<u64>,
 }
 
 /// Path trie for efficient path matching
@@ -198,12 +203,7 @@ impl CompiledPolicy {
             env_blacklist: FxHashSet::default(),
             resource_trie: PathTrie::new(),
             capabilities: CapabilityFlags::default(),
-            resource_limits: ResourceLimits {
-                cpu_millicores: None,
-                memory_bytes: None,
-                execution_time_ms: None,
-                fuel: None,
-            },
+            resource_limits: ResourceLimits::default(),
         };
 
         // Compile network permissions
@@ -391,10 +391,18 @@ impl CompiledPolicy {
             self.resource_limits.execution_time_ms = Some(parse_time_limit(time)?);
         }
 
+        if let Some(time) = &limits.cpu_time {
+            self.resource_limits.cpu_time_ms = Some(parse_time_limit(time)?);
+        }
+
         if let Some(fuel) = limits.fuel {
             self.resource_limits.fuel = Some(fuel);
         }
 
+        if let Some(open_files) = limits.open_files {
+            self.resource_limits.open_files = Some(open_files);
+        }
+
         Ok(())
     }
 
@@ -239,4 +239,9 @@ impl PolicyState {
             .total_violations
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     }
+
+    /// Take all recorded violations, leaving the buffer empty
+    pub async fn drain_violations(&self) -> Vec<Violation> {
+        std::mem::take(&mut *self.violations.lock().await)
+    }
 }
@@ -43,4 +43,5 @@ pub use runtime::wasmtime::WasmtimeBackend;
 pub const POLICY_VERSION: &str = "1.0";
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
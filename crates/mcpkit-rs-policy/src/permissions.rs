@@ -110,7 +110,11 @@ pub struct ResourceLimits {
 }
 
 /// Actual resource limit values
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// New limits may be added in minor releases; start from
+/// [`Default::default`] and set the fields you need.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ResourceLimitValues {
     /// CPU limit (e.g., "100m" for 100 millicores, "0.5" for half a core)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -124,10 +128,19 @@ pub struct ResourceLimitValues {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub execution_time: Option<String>,
 
+    /// Total CPU time a native server process may use over its lifetime
+    /// (e.g., "60s", "10m")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_time: Option<String>,
+
     /// WebAssembly fuel limit for instruction counting
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fuel: Option<u64>,
 
+    /// Maximum number of open file descriptors for native servers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+
     /// Legacy memory limit field (deprecated, use 'memory' instead)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memory_limit: Option<String>,
@@ -48,6 +48,12 @@ impl WasmEdgeBackend {
         }
     }
 
+    /// State the host functions record violations into, for draining into an
+    /// audit log
+    pub fn policy_state(&self) -> Arc<PolicyState> {
+        self.policy_state.clone()
+    }
+
     #[cfg(feature = "wasmedge-backend")]
     fn create_mcp_module(&self, _policy: Arc<crate::compiled::CompiledPolicy>) -> Result<()> {
         // Module creation would happen here - simplified for now
@@ -49,6 +49,12 @@ impl WasmtimeBackend {
         }
     }
 
+    /// State the host functions record violations into, for draining into an
+    /// audit log
+    pub fn policy_state(&self) -> Arc<PolicyState> {
+        self.policy_state.clone()
+    }
+
     #[cfg(feature = "wasmtime-backend")]
     fn add_mcp_host_functions(
         &self,
@@ -1,9 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::{
         compiled::CompiledPolicy,
-        extensions::mcp::{McpPermissions, ToolPermissions, ToolRule},
+        extensions::mcp::{ToolPermissions, ToolRule},
         permissions::{NetworkRule, Policy, StorageRule},
     };
 
@@ -224,7 +223,7 @@ tools:
     #[test]
     fn test_mcp_extension() {
         use crate::{
-            core::{Action, Permission, PolicyExtension},
+            core::PolicyExtension,
             extensions::mcp::{McpAction, McpActionType, McpExtension, McpPermissions},
         };
 
@@ -277,6 +276,8 @@ core:
       cpu: "500m"
       memory: "512Mi"
       execution_time: "30s"
+      cpu_time: "2m"
+      open_files: 256
 "#;
 
         let policy = Policy::from_yaml(yaml).unwrap();
@@ -288,6 +289,8 @@ core:
             Some(512 * 1024 * 1024)
         );
         assert_eq!(compiled.resource_limits.execution_time_ms, Some(30000));
+        assert_eq!(compiled.resource_limits.cpu_time_ms, Some(120_000));
+        assert_eq!(compiled.resource_limits.open_files, Some(256));
     }
 
     #[test]
@@ -330,7 +333,7 @@ core:
 
     #[test]
     fn test_cache_functionality() {
-        use crate::cache::{AccessMode, ActionHash, PermissionCache};
+        use crate::cache::{ActionHash, PermissionCache};
 
         let mut cache = PermissionCache::new(10);
 
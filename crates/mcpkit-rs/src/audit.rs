@@ -0,0 +1,581 @@
+//! Structured audit logging for tool calls, resource reads and policy decisions
+//!
+//! An [`AuditLog`] fans each [`AuditRecord`] out to one or more [`AuditSink`]s.
+//! Two sinks ship with the crate: [`JsonLinesSink`] appends one JSON object per
+//! line to a file, and [`TracingSink`] emits records as `tracing` events under
+//! the `mcpkit_rs::audit` target. Anything else can implement [`AuditSink`].
+//!
+//! Audit logs are attached to `PolicyEnabledServer` and `WasmToolExecutor`
+//! through their `with_audit` builder methods. Violations that a runtime
+//! enforcer records in its `PolicyState` are drained into the same log after
+//! each audited request once the state is handed to
+//! `PolicyEnabledServer::with_policy_state`.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use mcpkit_rs::audit::{AuditLog, TracingSink};
+//!
+//! let audit = AuditLog::new()
+//!     .with_sink(Arc::new(TracingSink))
+//!     .with_redacted_fields(["password", "api_key"]);
+//! ```
+
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::model::{ErrorData, Implementation, JsonObject};
+
+/// Placeholder substituted for redacted argument values before hashing
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Authenticated subject attached to a request
+///
+/// Authentication layers insert this into the HTTP request extensions (or the
+/// request context extensions) so audit records can name the caller.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuditSubject(pub String);
+
+/// Outcome of a policy check for an audited operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyDecision {
+    /// The policy allowed the operation
+    Allowed,
+    /// The policy denied the operation
+    Denied,
+    /// No policy was configured for the operation
+    NotEvaluated,
+}
+
+/// Result of an audited operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The operation completed successfully
+    Success,
+    /// The tool ran but reported an error result (`is_error: true`)
+    ToolError,
+    /// The operation failed with a protocol error
+    Error {
+        /// JSON-RPC error code
+        code: i32,
+        /// Error message
+        message: String,
+    },
+}
+
+impl From<&ErrorData> for AuditOutcome {
+    fn from(error: &ErrorData) -> Self {
+        AuditOutcome::Error {
+            code: error.code.0,
+            message: error.message.to_string(),
+        }
+    }
+}
+
+/// A single audit log entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the operation started
+    pub timestamp: DateTime<Utc>,
+    /// Transport session the request arrived on, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Client implementation info from the `initialize` handshake
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<Implementation>,
+    /// Authenticated subject, see [`AuditSubject`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// JSON-RPC method, e.g. `tools/call`
+    pub method: String,
+    /// Tool name or resource URI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// SHA-256 of the arguments after redaction, hex encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments_digest: Option<String>,
+    /// Policy decision for the operation
+    pub decision: PolicyDecision,
+    /// Wall-clock duration of the operation
+    #[serde(rename = "duration_ms", with = "duration_ms")]
+    pub duration: Duration,
+    /// Compute units consumed by a WASM tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuel_used: Option<u64>,
+    /// How the operation ended
+    pub outcome: AuditOutcome,
+}
+
+impl AuditRecord {
+    /// Create a record for `method`, stamped with the current time
+    pub fn new(method: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            session_id: None,
+            client: None,
+            subject: None,
+            method: method.into(),
+            target: None,
+            arguments_digest: None,
+            decision: PolicyDecision::NotEvaluated,
+            duration: Duration::ZERO,
+            fuel_used: None,
+            outcome: AuditOutcome::Success,
+        }
+    }
+
+    /// Transport session the request arrived on
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Client implementation info from the `initialize` handshake
+    pub fn with_client(mut self, client: Implementation) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Authenticated subject, see [`AuditSubject`]
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Tool name or resource URI
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Digest from [`AuditLog::digest_arguments`]
+    pub fn with_arguments_digest(mut self, digest: impl Into<String>) -> Self {
+        self.arguments_digest = Some(digest.into());
+        self
+    }
+
+    /// Policy decision for the operation
+    pub fn with_decision(mut self, decision: PolicyDecision) -> Self {
+        self.decision = decision;
+        self
+    }
+
+    /// Wall-clock duration of the operation
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Compute units consumed by a WASM tool
+    pub fn with_fuel_used(mut self, fuel_used: u64) -> Self {
+        self.fuel_used = Some(fuel_used);
+        self
+    }
+
+    /// How the operation ended
+    pub fn with_outcome(mut self, outcome: AuditOutcome) -> Self {
+        self.outcome = outcome;
+        self
+    }
+}
+
+#[cfg(feature = "policy")]
+impl AuditRecord {
+    /// A denied record for a violation raised by a runtime enforcer
+    pub fn from_violation(violation: &mcpkit_rs_policy::core::Violation) -> Self {
+        use mcpkit_rs_policy::core::Violation;
+
+        let (method, target, message, timestamp) = match violation {
+            Violation::ToolDenied { tool, timestamp } => (
+                "tools/call".to_string(),
+                tool.clone(),
+                "tool denied by policy".to_string(),
+                Some(*timestamp),
+            ),
+            Violation::NetworkDenied { host, timestamp } => (
+                "network/connect".to_string(),
+                host.clone(),
+                "network access denied by policy".to_string(),
+                Some(*timestamp),
+            ),
+            Violation::FileDenied {
+                path,
+                operation,
+                timestamp,
+            } => (
+                format!("storage/{operation}"),
+                path.clone(),
+                "file access denied by policy".to_string(),
+                Some(*timestamp),
+            ),
+            Violation::ResourceLimitExceeded {
+                resource,
+                limit,
+                requested,
+            } => (
+                "limits/check".to_string(),
+                resource.clone(),
+                format!("requested {requested} exceeds the limit of {limit}"),
+                None,
+            ),
+            Violation::Custom {
+                extension,
+                message,
+                timestamp,
+            } => (
+                format!("{extension}/violation"),
+                extension.clone(),
+                message.clone(),
+                Some(*timestamp),
+            ),
+        };
+        let mut record = AuditRecord::new(method)
+            .with_target(target)
+            .with_decision(PolicyDecision::Denied)
+            .with_outcome(AuditOutcome::Error {
+                code: crate::model::ErrorCode::INVALID_PARAMS.0,
+                message,
+            });
+        if let Some(timestamp) =
+            timestamp.and_then(|secs| DateTime::from_timestamp(i64::try_from(secs).ok()?, 0))
+        {
+            record.timestamp = timestamp;
+        }
+        record
+    }
+}
+
+mod duration_ms {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let ms = f64::deserialize(deserializer)?;
+        Ok(Duration::from_nanos(
+            (ms.max(0.0) * 1_000_000.0).round() as u64
+        ))
+    }
+}
+
+/// Destination for audit records
+///
+/// `record` is called inline on the request path, so implementations should
+/// be cheap or hand off to a background task. Sinks must not panic; I/O
+/// failures should be reported through `tracing` and otherwise swallowed.
+pub trait AuditSink: Send + Sync + 'static {
+    /// Write one record; called once per audited operation, from whichever
+    /// task handled it, so implementations must tolerate concurrent calls
+    fn record(&self, record: &AuditRecord);
+
+    /// Flush any buffered records
+    fn flush(&self) {}
+}
+
+/// Appends records to a file as JSON lines
+///
+/// Records go through a buffered writer and reach the file when the buffer
+/// fills, on [`flush`](AuditSink::flush) (or [`AuditLog::flush`]) and when the
+/// sink is dropped. Call `flush` before reading the file or at shutdown if
+/// every record has to be on disk.
+pub struct JsonLinesSink {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl JsonLinesSink {
+    /// Open `path` for appending, creating it if necessary
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+}
+
+impl AuditSink for JsonLinesSink {
+    fn record(&self, record: &AuditRecord) {
+        let Ok(mut writer) = self.writer.lock() else {
+            tracing::error!("audit log writer poisoned, dropping record");
+            return;
+        };
+        let result = serde_json::to_writer(&mut *writer, record)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writer.write_all(b"\n"));
+        if let Err(error) = result {
+            tracing::error!(%error, "failed to write audit record");
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut writer) = self.writer.lock() {
+            if let Err(error) = writer.flush() {
+                tracing::error!(%error, "failed to flush audit log");
+            }
+        }
+    }
+}
+
+/// Emits records as `tracing` events with target `mcpkit_rs::audit`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingSink;
+
+impl AuditSink for TracingSink {
+    fn record(&self, record: &AuditRecord) {
+        let client = record.client.as_ref().map(|c| c.name.as_str());
+        let outcome = match &record.outcome {
+            AuditOutcome::Success => "success",
+            AuditOutcome::ToolError => "tool_error",
+            AuditOutcome::Error { .. } => "error",
+        };
+        tracing::info!(
+            target: "mcpkit_rs::audit",
+            timestamp = %record.timestamp.to_rfc3339(),
+            session_id = record.session_id.as_deref(),
+            client,
+            subject = record.subject.as_deref(),
+            method = %record.method,
+            target_name = record.target.as_deref(),
+            arguments_digest = record.arguments_digest.as_deref(),
+            decision = ?record.decision,
+            duration_ms = record.duration.as_secs_f64() * 1000.0,
+            fuel_used = record.fuel_used,
+            outcome,
+        );
+    }
+}
+
+/// Fans audit records out to a set of sinks
+///
+/// Cloning is cheap; clones share the same sinks.
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    sinks: Vec<Arc<dyn AuditSink>>,
+    redacted_fields: Arc<HashSet<String>>,
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("sinks", &self.sinks.len())
+            .field("redacted_fields", &self.redacted_fields)
+            .finish()
+    }
+}
+
+impl AuditLog {
+    /// Create an audit log with no sinks and no redacted fields
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sink; every record is sent to all sinks in the order they were added
+    pub fn with_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Argument keys whose values are replaced with [`REDACTED`] before the
+    /// digest is computed, at any nesting depth
+    pub fn with_redacted_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut redacted = (*self.redacted_fields).clone();
+        redacted.extend(fields.into_iter().map(Into::into));
+        self.redacted_fields = Arc::new(redacted);
+        self
+    }
+
+    /// Send a record to every sink
+    pub fn record(&self, record: &AuditRecord) {
+        for sink in &self.sinks {
+            sink.record(record);
+        }
+    }
+
+    /// Flush every sink
+    pub fn flush(&self) {
+        for sink in &self.sinks {
+            sink.flush();
+        }
+    }
+
+    /// Take the violations recorded by a runtime enforcer and record each one
+    /// as a denied operation
+    #[cfg(feature = "policy")]
+    pub async fn record_violations(&self, state: &mcpkit_rs_policy::core::PolicyState) {
+        for violation in state.drain_violations().await {
+            self.record(&AuditRecord::from_violation(&violation));
+        }
+    }
+
+    /// Compute the redacted SHA-256 digest of tool arguments
+    pub fn digest_arguments(&self, arguments: &JsonObject) -> String {
+        let mut value = serde_json::Value::Object(arguments.clone());
+        if !self.redacted_fields.is_empty() {
+            redact(&mut value, &self.redacted_fields);
+        }
+        // serde_json maps are ordered, so the serialization is canonical
+        let bytes = serde_json::to_vec(&value).unwrap_or_default();
+        format!("sha256:{:x}", Sha256::digest(&bytes))
+    }
+}
+
+/// Start a record for a server-side request, filling in the session, client
+/// and subject from the request context
+#[cfg(all(feature = "server", feature = "policy"))]
+pub(crate) fn server_record(
+    method: &str,
+    context: &crate::service::RequestContext<crate::service::RoleServer>,
+) -> AuditRecord {
+    let mut record = AuditRecord::new(method);
+    if let Some(info) = context.peer.peer_info() {
+        record = record.with_client(info.client_info.clone());
+    }
+    if let Some(subject) = context.extensions.get::<AuditSubject>() {
+        record = record.with_subject(subject.0.clone());
+    }
+    #[cfg(feature = "server-side-http")]
+    if let Some(parts) = context.extensions.get::<http::request::Parts>() {
+        if let Some(session_id) = parts
+            .headers
+            .get(crate::transport::common::http_header::HEADER_SESSION_ID)
+            .and_then(|v| v.to_str().ok())
+        {
+            record = record.with_session_id(session_id);
+        }
+        if record.subject.is_none() {
+            if let Some(subject) = parts.extensions.get::<AuditSubject>() {
+                record = record.with_subject(subject.0.clone());
+            }
+        }
+    }
+    record
+}
+
+fn redact(value: &mut serde_json::Value, fields: &HashSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if fields.contains(key) {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact(value, fields);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                redact(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MemorySink(Mutex<Vec<AuditRecord>>);
+
+    impl AuditSink for MemorySink {
+        fn record(&self, record: &AuditRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    fn args(value: serde_json::Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_digest_ignores_redacted_values() {
+        let audit = AuditLog::new().with_redacted_fields(["token"]);
+        let a = audit.digest_arguments(&args(serde_json::json!({
+            "query": "x",
+            "auth": { "token": "secret-1" }
+        })));
+        let b = audit.digest_arguments(&args(serde_json::json!({
+            "query": "x",
+            "auth": { "token": "secret-2" }
+        })));
+        let c = audit.digest_arguments(&args(serde_json::json!({
+            "query": "y",
+            "auth": { "token": "secret-1" }
+        })));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.starts_with("sha256:"));
+    }
+
+    #[test]
+    fn test_record_fans_out_to_sinks() {
+        let first = Arc::new(MemorySink::default());
+        let second = Arc::new(MemorySink::default());
+        let audit = AuditLog::new()
+            .with_sink(first.clone())
+            .with_sink(second.clone());
+
+        audit.record(
+            &AuditRecord::new("tools/call")
+                .with_target("echo")
+                .with_decision(PolicyDecision::Denied),
+        );
+
+        assert_eq!(first.0.lock().unwrap().len(), 1);
+        assert_eq!(second.0.lock().unwrap()[0].decision, PolicyDecision::Denied);
+    }
+
+    #[test]
+    fn test_json_lines_sink_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let sink = JsonLinesSink::open(&path).unwrap();
+
+        let record = AuditRecord::new("resources/read")
+            .with_session_id("session-1")
+            .with_target("file:///tmp/a")
+            .with_decision(PolicyDecision::Allowed)
+            .with_duration(Duration::from_millis(12))
+            .with_fuel_used(42)
+            .with_outcome(AuditOutcome::Error {
+                code: -32602,
+                message: "nope".into(),
+            });
+        sink.record(&record);
+        sink.record(&AuditRecord::new("tools/call"));
+        sink.flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let parsed: AuditRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed.session_id.as_deref(), Some("session-1"));
+        assert_eq!(parsed.fuel_used, Some(42));
+        assert_eq!(parsed.duration, Duration::from_millis(12));
+        assert_eq!(parsed.outcome, record.outcome);
+
+        let raw: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(raw["decision"], "allowed");
+        assert_eq!(raw["outcome"]["status"], "error");
+    }
+}
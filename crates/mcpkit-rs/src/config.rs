@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 #[cfg(feature = "config")]
-use mcpkit_rs_config::{Config, RuntimeType, TransportType};
+use mcpkit_rs_config::{Config, RuntimeType, StdioSettings, TransportSettings, TransportType};
 #[cfg(feature = "config")]
 use mcpkit_rs_policy::{CompiledPolicy, PolicyEngine};
 
@@ -64,6 +64,27 @@ impl ServerConfig {
         &self.config.transport.transport_type
     }
 
+    /// Largest incoming message for the stdio transport, from
+    /// `transport.settings.max_message_size`
+    pub fn max_message_size(&self) -> usize {
+        match &self.config.transport.settings {
+            TransportSettings::Stdio(StdioSettings {
+                max_message_size: Some(size),
+                ..
+            }) => *size,
+            _ => crate::transport::DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Stdio transport limited to [`max_message_size`](Self::max_message_size)
+    #[cfg(feature = "transport-io")]
+    pub fn stdio_transport<Role: crate::service::ServiceRole>(
+        &self,
+    ) -> crate::transport::async_rw::AsyncRwTransport<Role, tokio::io::Stdin, tokio::io::Stdout>
+    {
+        crate::transport::stdio_with_max_message_size(self.max_message_size())
+    }
+
     /// Get runtime type
     pub fn runtime_type(&self) -> &RuntimeType {
         &self.config.runtime.runtime_type
@@ -1,4 +1,10 @@
+pub mod cache;
+#[cfg(feature = "elicitation-terminal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "elicitation-terminal")))]
+pub mod elicitation;
+pub mod pool;
 pub mod progress;
+pub mod sampling;
 use std::sync::Arc;
 
 use crate::{
@@ -66,6 +72,9 @@ impl<H: ClientHandler> Service<RoleClient> for H {
                 self.on_url_elicitation_notification_complete(notification.params, context)
                     .await
             }
+            ServerNotification::TaskStatusNotification(notification) => {
+                self.on_task_status(notification.params, context).await
+            }
             ServerNotification::CustomNotification(notification) => {
                 self.on_custom_notification(notification, context).await
             }
@@ -239,6 +248,14 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
     ) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    /// Called when a server reports that one of its tasks changed status.
+    fn on_task_status(
+        &self,
+        params: TaskStatusNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        std::future::ready(())
+    }
     fn on_custom_notification(
         &self,
         notification: CustomNotification,
@@ -263,6 +280,157 @@ impl ClientHandler for ClientInfo {
     }
 }
 
+/// Forwards the listed [`ClientHandler`] methods to the handler in the field
+/// `$inner`, for handlers that wrap another one and only override a few.
+///
+/// ```rust,ignore
+/// impl<H: ClientHandler> ClientHandler for Wrapper<H> {
+///     forward_client_handler!(inner; ping, list_roots, get_info);
+/// }
+/// ```
+macro_rules! forward_client_handler {
+    ($inner:ident; $($method:ident),* $(,)?) => {
+        $(forward_client_handler!(@method $inner $method);)*
+    };
+    (@method $inner:ident ping) => {
+        fn ping(
+            &self,
+            context: RequestContext<RoleClient>,
+        ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+            self.$inner.ping(context)
+        }
+    };
+    (@method $inner:ident create_message) => {
+        fn create_message(
+            &self,
+            params: CreateMessageRequestParams,
+            context: RequestContext<RoleClient>,
+        ) -> impl Future<Output = Result<CreateMessageResult, McpError>> + Send + '_ {
+            self.$inner.create_message(params, context)
+        }
+    };
+    (@method $inner:ident list_roots) => {
+        fn list_roots(
+            &self,
+            context: RequestContext<RoleClient>,
+        ) -> impl Future<Output = Result<ListRootsResult, McpError>> + Send + '_ {
+            self.$inner.list_roots(context)
+        }
+    };
+    (@method $inner:ident create_elicitation) => {
+        fn create_elicitation(
+            &self,
+            request: CreateElicitationRequestParams,
+            context: RequestContext<RoleClient>,
+        ) -> impl Future<Output = Result<CreateElicitationResult, McpError>> + Send + '_ {
+            self.$inner.create_elicitation(request, context)
+        }
+    };
+    (@method $inner:ident on_custom_request) => {
+        fn on_custom_request(
+            &self,
+            request: CustomRequest,
+            context: RequestContext<RoleClient>,
+        ) -> impl Future<Output = Result<CustomResult, McpError>> + Send + '_ {
+            self.$inner.on_custom_request(request, context)
+        }
+    };
+    (@method $inner:ident on_cancelled) => {
+        fn on_cancelled(
+            &self,
+            params: CancelledNotificationParam,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_cancelled(params, context)
+        }
+    };
+    (@method $inner:ident on_progress) => {
+        fn on_progress(
+            &self,
+            params: ProgressNotificationParam,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_progress(params, context)
+        }
+    };
+    (@method $inner:ident on_logging_message) => {
+        fn on_logging_message(
+            &self,
+            params: LoggingMessageNotificationParam,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_logging_message(params, context)
+        }
+    };
+    (@method $inner:ident on_resource_updated) => {
+        fn on_resource_updated(
+            &self,
+            params: ResourceUpdatedNotificationParam,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_resource_updated(params, context)
+        }
+    };
+    (@method $inner:ident on_resource_list_changed) => {
+        fn on_resource_list_changed(
+            &self,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_resource_list_changed(context)
+        }
+    };
+    (@method $inner:ident on_tool_list_changed) => {
+        fn on_tool_list_changed(
+            &self,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_tool_list_changed(context)
+        }
+    };
+    (@method $inner:ident on_prompt_list_changed) => {
+        fn on_prompt_list_changed(
+            &self,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_prompt_list_changed(context)
+        }
+    };
+    (@method $inner:ident on_url_elicitation_notification_complete) => {
+        fn on_url_elicitation_notification_complete(
+            &self,
+            params: ElicitationResponseNotificationParam,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner
+                .on_url_elicitation_notification_complete(params, context)
+        }
+    };
+    (@method $inner:ident on_task_status) => {
+        fn on_task_status(
+            &self,
+            params: TaskStatusNotificationParam,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_task_status(params, context)
+        }
+    };
+    (@method $inner:ident on_custom_notification) => {
+        fn on_custom_notification(
+            &self,
+            notification: CustomNotification,
+            context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            self.$inner.on_custom_notification(notification, context)
+        }
+    };
+    (@method $inner:ident get_info) => {
+        fn get_info(&self) -> ClientInfo {
+            self.$inner.get_info()
+        }
+    };
+}
+pub(crate) use forward_client_handler;
+
 macro_rules! impl_client_handler_for_wrapper {
     ($wrapper:ident) => {
         impl<T: ClientHandler> ClientHandler for $wrapper<T> {
@@ -357,6 +525,14 @@ macro_rules! impl_client_handler_for_wrapper {
                 (**self).on_prompt_list_changed(context)
             }
 
+            fn on_task_status(
+                &self,
+                params: TaskStatusNotificationParam,
+                context: NotificationContext<RoleClient>,
+            ) -> impl Future<Output = ()> + Send + '_ {
+                (**self).on_task_status(params, context)
+            }
+
             fn on_custom_notification(
                 &self,
                 notification: CustomNotification,
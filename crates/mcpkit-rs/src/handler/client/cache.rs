@@ -0,0 +1,433 @@
+//! Client-side caching of list and read results.
+//!
+//! A [`ResponseCache`] keeps the last `tools/list`, `prompts/list`,
+//! `resources/list` and `resources/templates/list` results (after pagination)
+//! and the contents of every resource read through it. Wrap the client handler
+//! in a [`CachingHandler`] so the server's `list_changed` and
+//! `resources/updated` notifications invalidate the matching entries, and
+//! issue requests through a [`CachedPeer`]:
+//!
+//! ```rust,ignore
+//! let cache = ResponseCache::new().with_ttl(Duration::from_secs(300));
+//! let client = CachingHandler::new(ClientInfo::default(), cache.clone())
+//!     .serve(transport)
+//!     .await?;
+//! let peer = CachedPeer::new(client.peer().clone(), cache);
+//! let tools = peer.list_all_tools().await?; // round trip
+//! let tools = peer.list_all_tools().await?; // served from the cache
+//! ```
+//!
+//! A result fetched while an invalidation for the same entry arrives is
+//! returned to the caller but not stored, so a notification is never lost to
+//! an in-flight request.
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::forward_client_handler;
+use crate::{
+    ClientHandler, Peer, ServiceError,
+    error::ErrorData as McpError,
+    model::*,
+    service::{NotificationContext, RequestContext, RoleClient},
+};
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    value: T,
+    stored_at: Instant,
+}
+
+/// One cached value plus the number of times it was invalidated.
+#[derive(Debug)]
+struct Slot<T> {
+    entry: Option<Entry<T>>,
+    epoch: u64,
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self {
+            entry: None,
+            epoch: 0,
+        }
+    }
+}
+
+impl<T: Clone> Slot<T> {
+    fn get(&self, ttl: Option<Duration>) -> Option<T> {
+        let entry = self.entry.as_ref()?;
+        match ttl {
+            Some(ttl) if entry.stored_at.elapsed() >= ttl => None,
+            _ => Some(entry.value.clone()),
+        }
+    }
+
+    fn store(&mut self, epoch: u64, value: T) {
+        if self.epoch == epoch {
+            self.entry = Some(Entry {
+                value,
+                stored_at: Instant::now(),
+            });
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.entry = None;
+        self.epoch += 1;
+    }
+}
+
+/// Cached resource contents, keyed by URI and bounded in size.
+#[derive(Debug, Default)]
+struct Contents {
+    /// Each slot with the tick it was last used at.
+    slots: HashMap<String, (Slot<ReadResourceResult>, u64)>,
+    tick: u64,
+    /// The epoch new slots start at. It is kept above the epoch of every
+    /// evicted slot, so a fetch that outlives its slot never stores into
+    /// the slot that replaces it.
+    next_epoch: u64,
+}
+
+impl Contents {
+    /// The slot for `uri`, evicting the least recently used one if `uri` is
+    /// new and the cache already holds `capacity` resources.
+    fn slot(&mut self, uri: &str, capacity: usize) -> &mut Slot<ReadResourceResult> {
+        self.tick += 1;
+        if !self.slots.contains_key(uri) && self.slots.len() >= capacity {
+            let oldest = self
+                .slots
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(uri, _)| uri.clone());
+            if let Some((evicted, _)) = oldest.and_then(|uri| self.slots.remove(&uri)) {
+                self.next_epoch = self.next_epoch.max(evicted.epoch + 1);
+            }
+        }
+        let next_epoch = self.next_epoch;
+        let (slot, used) = self.slots.entry(uri.to_owned()).or_insert_with(|| {
+            (
+                Slot {
+                    entry: None,
+                    epoch: next_epoch,
+                },
+                0,
+            )
+        });
+        *used = self.tick;
+        slot
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    tools: Slot<Vec<Tool>>,
+    prompts: Slot<Vec<Prompt>>,
+    resources: Slot<Vec<Resource>>,
+    resource_templates: Slot<Vec<ResourceTemplate>>,
+    contents: Contents,
+}
+
+/// How many resources' contents a [`ResponseCache`] keeps by default.
+pub const DEFAULT_MAX_RESOURCES: usize = 1024;
+
+/// Shared storage for cached list and read results.
+///
+/// Cloning is cheap; clones share the same entries.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    state: Arc<Mutex<CacheState>>,
+    ttl: Option<Duration>,
+    max_resources: usize,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self {
+            state: Default::default(),
+            ttl: None,
+            max_resources: DEFAULT_MAX_RESOURCES,
+        }
+    }
+}
+
+impl ResponseCache {
+    /// A cache whose entries live until they are invalidated.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expire entries once they are older than `ttl`, even without a
+    /// notification from the server.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Keep the contents of at most `max_resources` resources, dropping the
+    /// least recently used ones first.
+    pub fn with_max_resources(mut self, max_resources: usize) -> Self {
+        self.max_resources = max_resources.max(1);
+        self
+    }
+
+    pub fn max_resources(&self) -> usize {
+        self.max_resources
+    }
+
+    /// Drop the cached tool list.
+    pub fn invalidate_tools(&self) {
+        self.state().tools.invalidate();
+    }
+
+    /// Drop the cached prompt list.
+    pub fn invalidate_prompts(&self) {
+        self.state().prompts.invalidate();
+    }
+
+    /// Drop the cached resource and resource template lists.
+    pub fn invalidate_resources(&self) {
+        let mut state = self.state();
+        state.resources.invalidate();
+        state.resource_templates.invalidate();
+    }
+
+    /// Drop the cached contents of one resource, if any. A read in flight
+    /// already has its entry, so resources never read are not tracked.
+    pub fn invalidate_resource(&self, uri: &str) {
+        if let Some((slot, _)) = self.state().contents.slots.get_mut(uri) {
+            slot.invalidate();
+        }
+    }
+
+    /// Drop every cached entry.
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.tools.invalidate();
+        state.prompts.invalidate();
+        state.resources.invalidate();
+        state.resource_templates.invalidate();
+        state
+            .contents
+            .slots
+            .values_mut()
+            .for_each(|(slot, _)| slot.invalidate());
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn get_or_fetch<T, Fut>(
+        &self,
+        slot: impl Fn(&mut CacheState) -> &mut Slot<T>,
+        fetch: impl FnOnce() -> Fut,
+    ) -> Result<T, ServiceError>
+    where
+        T: Clone,
+        Fut: Future<Output = Result<T, ServiceError>>,
+    {
+        let epoch = {
+            let mut state = self.state();
+            let slot = slot(&mut state);
+            if let Some(value) = slot.get(self.ttl) {
+                return Ok(value);
+            }
+            slot.epoch
+        };
+        let value = fetch().await?;
+        slot(&mut self.state()).store(epoch, value.clone());
+        Ok(value)
+    }
+}
+
+/// A [`Peer<RoleClient>`] that answers list and read requests from a
+/// [`ResponseCache`] when it can.
+///
+/// Requests that are not cached are available through [`CachedPeer::peer`].
+#[derive(Debug, Clone)]
+pub struct CachedPeer {
+    peer: Peer<RoleClient>,
+    cache: ResponseCache,
+}
+
+impl CachedPeer {
+    pub fn new(peer: Peer<RoleClient>, cache: ResponseCache) -> Self {
+        Self { peer, cache }
+    }
+
+    pub fn peer(&self) -> &Peer<RoleClient> {
+        &self.peer
+    }
+
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    pub async fn list_all_tools(&self) -> Result<Vec<Tool>, ServiceError> {
+        self.cache
+            .get_or_fetch(|s| &mut s.tools, || self.peer.list_all_tools())
+            .await
+    }
+
+    pub async fn list_all_prompts(&self) -> Result<Vec<Prompt>, ServiceError> {
+        self.cache
+            .get_or_fetch(|s| &mut s.prompts, || self.peer.list_all_prompts())
+            .await
+    }
+
+    pub async fn list_all_resources(&self) -> Result<Vec<Resource>, ServiceError> {
+        self.cache
+            .get_or_fetch(|s| &mut s.resources, || self.peer.list_all_resources())
+            .await
+    }
+
+    pub async fn list_all_resource_templates(&self) -> Result<Vec<ResourceTemplate>, ServiceError> {
+        self.cache
+            .get_or_fetch(
+                |s| &mut s.resource_templates,
+                || self.peer.list_all_resource_templates(),
+            )
+            .await
+    }
+
+    pub async fn read_resource(
+        &self,
+        params: ReadResourceRequestParams,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        let uri = params.uri.clone();
+        let max_resources = self.cache.max_resources;
+        self.cache
+            .get_or_fetch(
+                |s| s.contents.slot(&uri, max_resources),
+                || self.peer.read_resource(params),
+            )
+            .await
+    }
+}
+
+/// A [`ClientHandler`] that invalidates a [`ResponseCache`] on the server's
+/// change notifications, then forwards everything to the inner handler.
+#[derive(Debug, Clone)]
+pub struct CachingHandler<H> {
+    inner: H,
+    cache: ResponseCache,
+}
+
+impl<H: ClientHandler> CachingHandler<H> {
+    pub fn new(inner: H, cache: ResponseCache) -> Self {
+        Self { inner, cache }
+    }
+
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+}
+
+impl<H: ClientHandler> ClientHandler for CachingHandler<H> {
+    forward_client_handler!(
+        inner;
+        ping,
+        create_message,
+        list_roots,
+        create_elicitation,
+        on_custom_request,
+        on_cancelled,
+        on_progress,
+        on_logging_message,
+        on_url_elicitation_notification_complete,
+        on_task_status,
+        on_custom_notification,
+        get_info,
+    );
+
+    fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        self.cache.invalidate_resource(&params.uri);
+        self.inner.on_resource_updated(params, context)
+    }
+
+    fn on_resource_list_changed(
+        &self,
+        context: NotificationContext<RoleClient>,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        self.cache.invalidate_resources();
+        self.inner.on_resource_list_changed(context)
+    }
+
+    fn on_tool_list_changed(
+        &self,
+        context: NotificationContext<RoleClient>,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        self.cache.invalidate_tools();
+        self.inner.on_tool_list_changed(context)
+    }
+
+    fn on_prompt_list_changed(
+        &self,
+        context: NotificationContext<RoleClient>,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        self.cache.invalidate_prompts();
+        self.inner.on_prompt_list_changed(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_respects_ttl_and_epoch() {
+        let mut slot = Slot::<u32>::default();
+        slot.store(0, 1);
+        assert_eq!(slot.get(None), Some(1));
+        assert_eq!(slot.get(Some(Duration::ZERO)), None);
+
+        // an invalidation while a fetch is in flight discards its result
+        let epoch = slot.epoch;
+        slot.invalidate();
+        slot.store(epoch, 2);
+        assert_eq!(slot.get(None), None);
+        slot.store(slot.epoch, 3);
+        assert_eq!(slot.get(None), Some(3));
+    }
+
+    #[test]
+    fn test_contents_evict_least_recently_used() {
+        let mut contents = Contents::default();
+        contents.slot("a", 2);
+        let epoch = contents.slot("b", 2).epoch;
+        contents.slot("a", 2);
+        contents.slot("c", 2);
+        assert!(contents.slots.contains_key("a"));
+        assert!(!contents.slots.contains_key("b"));
+
+        // a fetch that outlived its evicted slot can't fill the new one
+        contents
+            .slot("b", 2)
+            .store(epoch, ReadResourceResult::new(vec![]));
+        assert!(contents.slot("b", 2).get(None).is_none());
+    }
+
+    #[test]
+    fn test_invalidating_unknown_resource_is_a_no_op() {
+        let cache = ResponseCache::new();
+        cache.invalidate_resource("memo://never-read");
+        assert!(cache.state().contents.slots.is_empty());
+    }
+}
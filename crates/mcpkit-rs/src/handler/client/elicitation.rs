@@ -0,0 +1,753 @@
+//! Answering elicitation requests in the terminal.
+//!
+//! [`TerminalElicitationHandler`] wraps a client handler and answers
+//! `elicitation/create` by prompting the user line by line:
+//!
+//! - form requests ask for each property of the requested schema in turn,
+//!   showing its title, description, constraints and default. Input is
+//!   checked against the property's [`PrimitiveSchema`] and asked for again
+//!   until it is valid. Enum options are listed with numbers and can be
+//!   picked by number, value or title; multi-select enums take a comma
+//!   separated list. An empty line keeps the default or skips an optional
+//!   property.
+//! - URL requests print the URL and ask the user whether to proceed. The
+//!   request is answered with the user's choice right away: the server only
+//!   reports that the user finished at the URL with a completion notification
+//!   afterwards. Use
+//!   [`wait_for_url_completion`](TerminalElicitationHandler::wait_for_url_completion)
+//!   to wait for it. Elicitations are forgotten once they complete or their
+//!   connection closes.
+//!
+//! At any prompt, `:decline` declines the request and `:cancel` cancels it.
+//! The end of input cancels it too.
+//!
+//! ```rust,ignore
+//! let client = TerminalElicitationHandler::new(ClientInfo::default())
+//!     .serve(transport)
+//!     .await?;
+//! ```
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::{Map, Value};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::watch,
+};
+
+use super::forward_client_handler;
+use crate::{
+    ClientHandler,
+    error::ErrorData as McpError,
+    model::*,
+    service::{NotificationContext, Peer, RequestContext, RoleClient},
+};
+
+const DECLINE: &str = ":decline";
+const CANCEL: &str = ":cancel";
+
+type Input = Box<dyn AsyncBufRead + Send + Unpin>;
+type Output = Box<dyn AsyncWrite + Send + Unpin>;
+
+struct Terminal {
+    input: Input,
+    output: Output,
+}
+
+/// A line read from the user.
+enum Answer {
+    Line(String),
+    Decline,
+    Cancel,
+}
+
+impl Terminal {
+    async fn write(&mut self, text: &str) -> io::Result<()> {
+        self.output.write_all(text.as_bytes()).await?;
+        self.output.flush().await
+    }
+
+    async fn ask(&mut self, prompt: &str) -> io::Result<Answer> {
+        self.write(prompt).await?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line).await? == 0 {
+            return Ok(Answer::Cancel);
+        }
+        Ok(match line.trim() {
+            DECLINE => Answer::Decline,
+            CANCEL => Answer::Cancel,
+            line => Answer::Line(line.to_owned()),
+        })
+    }
+}
+
+/// A [`ClientHandler`] answering elicitation requests with terminal prompts
+/// and forwarding everything else to the inner handler, see the
+/// [module docs](self).
+#[derive(Clone)]
+pub struct TerminalElicitationHandler<H> {
+    inner: H,
+    terminal: Arc<tokio::sync::Mutex<Terminal>>,
+    url_elicitations: Arc<Mutex<HashMap<String, UrlElicitation>>>,
+}
+
+/// An accepted URL elicitation awaiting its completion notification.
+struct UrlElicitation {
+    connection_id: u64,
+    completed: watch::Sender<bool>,
+}
+
+impl<H> std::fmt::Debug for TerminalElicitationHandler<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalElicitationHandler")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H: ClientHandler> TerminalElicitationHandler<H> {
+    /// Prompt on stdin and stdout.
+    pub fn new(inner: H) -> Self {
+        Self::with_io(
+            inner,
+            BufReader::new(tokio::io::stdin()),
+            tokio::io::stdout(),
+        )
+    }
+
+    /// Prompt on `output` and read the answers from `input`.
+    pub fn with_io(
+        inner: H,
+        input: impl AsyncBufRead + Send + Unpin + 'static,
+        output: impl AsyncWrite + Send + Unpin + 'static,
+    ) -> Self {
+        Self {
+            inner,
+            terminal: Arc::new(tokio::sync::Mutex::new(Terminal {
+                input: Box::new(input),
+                output: Box::new(output),
+            })),
+            url_elicitations: Default::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    /// Wait until the server reports that the URL elicitation
+    /// `elicitation_id` is complete.
+    ///
+    /// The elicitation is looked up when this is called, not when the future
+    /// is first polled. Returns `false` if no such elicitation is pending,
+    /// because the user did not accept it or it already completed, or if the
+    /// connection closes first.
+    pub fn wait_for_url_completion(
+        &self,
+        elicitation_id: &str,
+    ) -> impl Future<Output = bool> + Send + 'static {
+        let receiver = self
+            .url_elicitations
+            .lock()
+            .expect("url elicitations poisoned")
+            .get(elicitation_id)
+            .map(|elicitation| elicitation.completed.subscribe());
+        async move {
+            match receiver {
+                Some(mut receiver) => receiver.wait_for(|completed| *completed).await.is_ok(),
+                None => false,
+            }
+        }
+    }
+
+    /// Remember an accepted URL elicitation until it completes or `peer`
+    /// closes, dropping its sender then so waiters return `false`.
+    fn track_url_elicitation(&self, elicitation_id: String, peer: &Peer<RoleClient>) {
+        let connection_id = peer.connection_id();
+        let (completed, mut receiver) = watch::channel(false);
+        self.url_elicitations
+            .lock()
+            .expect("url elicitations poisoned")
+            .insert(
+                elicitation_id.clone(),
+                UrlElicitation {
+                    connection_id,
+                    completed,
+                },
+            );
+        let elicitations = self.url_elicitations.clone();
+        let peer = peer.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = peer.closed() => {
+                    let mut elicitations = elicitations.lock().expect("url elicitations poisoned");
+                    let current = elicitations
+                        .get(&elicitation_id)
+                        .is_some_and(|elicitation| elicitation.connection_id == connection_id);
+                    if current {
+                        elicitations.remove(&elicitation_id);
+                    }
+                }
+                // completed, or replaced by another elicitation with the same id
+                _ = receiver.wait_for(|completed| *completed) => {}
+            }
+        });
+    }
+
+    async fn elicit(
+        &self,
+        request: CreateElicitationRequestParams,
+        peer: &Peer<RoleClient>,
+    ) -> io::Result<CreateElicitationResult> {
+        let mut terminal = self.terminal.lock().await;
+        match request {
+            CreateElicitationRequestParams::FormElicitationParams {
+                message,
+                requested_schema,
+                ..
+            } => form(&mut terminal, &message, &requested_schema).await,
+            CreateElicitationRequestParams::UrlElicitationParams {
+                message,
+                url,
+                elicitation_id,
+                ..
+            } => {
+                terminal
+                    .write(&format!("\n{message}\nOpen this URL to continue: {url}\n"))
+                    .await?;
+                let action = loop {
+                    match terminal.ask("Proceed? [y/n] ").await? {
+                        Answer::Line(line) => match parse_bool(&line) {
+                            Some(true) => break ElicitationAction::Accept,
+                            Some(false) => break ElicitationAction::Decline,
+                            None => terminal.write("  Answer y or n\n").await?,
+                        },
+                        Answer::Decline => break ElicitationAction::Decline,
+                        Answer::Cancel => break ElicitationAction::Cancel,
+                    }
+                };
+                if action == ElicitationAction::Accept {
+                    self.track_url_elicitation(elicitation_id, peer);
+                }
+                Ok(CreateElicitationResult::new(action))
+            }
+        }
+    }
+}
+
+async fn form(
+    terminal: &mut Terminal,
+    message: &str,
+    schema: &ElicitationSchema,
+) -> io::Result<CreateElicitationResult> {
+    let mut header = format!("\n{message}\n");
+    if let Some(title) = &schema.title {
+        header.push_str(&format!("{title}\n"));
+    }
+    if let Some(description) = &schema.description {
+        header.push_str(&format!("{description}\n"));
+    }
+    header.push_str(&format!(
+        "(Enter {DECLINE} to decline or {CANCEL} to cancel.)\n"
+    ));
+    terminal.write(&header).await?;
+
+    let required = schema.required.as_deref().unwrap_or_default();
+    let mut content = Map::new();
+    for (name, property) in &schema.properties {
+        let field = Field::new(name, property, required.contains(name));
+        terminal.write(&field.describe()).await?;
+        let value = loop {
+            let line = match terminal.ask(&field.prompt()).await? {
+                Answer::Line(line) => line,
+                Answer::Decline => {
+                    return Ok(CreateElicitationResult::new(ElicitationAction::Decline));
+                }
+                Answer::Cancel => {
+                    return Ok(CreateElicitationResult::new(ElicitationAction::Cancel));
+                }
+            };
+            let value = if line.is_empty() {
+                match (field.default(), field.required) {
+                    (Some(default), _) => Ok(Some(default)),
+                    (None, false) => Ok(None),
+                    (None, true) => Err("A value is required".to_owned()),
+                }
+            } else {
+                parse_value(property, &line).map(Some)
+            };
+            match value {
+                Ok(value) => break value,
+                Err(error) => terminal.write(&format!("  {error}\n")).await?,
+            }
+        };
+        if let Some(value) = value {
+            content.insert(name.clone(), value);
+        }
+    }
+    Ok(
+        CreateElicitationResult::new(ElicitationAction::Accept)
+            .with_content(Value::Object(content)),
+    )
+}
+
+/// How a property is shown to the user.
+struct Field<'a> {
+    name: &'a str,
+    schema: &'a PrimitiveSchema,
+    required: bool,
+}
+
+impl<'a> Field<'a> {
+    fn new(name: &'a str, schema: &'a PrimitiveSchema, required: bool) -> Self {
+        Self {
+            name,
+            schema,
+            required,
+        }
+    }
+
+    /// The description, constraints and options shown before the prompt.
+    fn describe(&self) -> String {
+        let mut lines = Vec::new();
+        let (description, constraints) = match self.schema {
+            PrimitiveSchema::String(schema) => {
+                let mut constraints = Vec::new();
+                if let Some(format) = schema.format {
+                    constraints.push(
+                        match format {
+                            StringFormat::Email => "an email address",
+                            StringFormat::Uri => "a URI",
+                            StringFormat::Date => "a date, YYYY-MM-DD",
+                            StringFormat::DateTime => "a date and time, RFC 3339",
+                        }
+                        .to_owned(),
+                    );
+                }
+                match (schema.min_length, schema.max_length) {
+                    (Some(min), Some(max)) => {
+                        constraints.push(format!("{min} to {max} characters"))
+                    }
+                    (Some(min), None) => constraints.push(format!("at least {min} characters")),
+                    (None, Some(max)) => constraints.push(format!("at most {max} characters")),
+                    (None, None) => {}
+                }
+                (&schema.description, constraints)
+            }
+            PrimitiveSchema::Number(schema) => (
+                &schema.description,
+                vec![range("a number", schema.minimum, schema.maximum)],
+            ),
+            PrimitiveSchema::Integer(schema) => (
+                &schema.description,
+                vec![range("a whole number", schema.minimum, schema.maximum)],
+            ),
+            PrimitiveSchema::Boolean(schema) => (&schema.description, vec!["y or n".to_owned()]),
+            PrimitiveSchema::Enum(schema) => {
+                let options = Options::of(schema);
+                let mut constraints = Vec::new();
+                if options.multiple {
+                    constraints.push("one or more, separated by commas".to_owned());
+                    if let Some(min) = options.min_items {
+                        constraints.push(format!("at least {min}"));
+                    }
+                    if let Some(max) = options.max_items {
+                        constraints.push(format!("at most {max}"));
+                    }
+                }
+                (options.description, constraints)
+            }
+        };
+        if let Some(description) = description {
+            lines.push(format!("  {description}"));
+        }
+        if !constraints.is_empty() {
+            lines.push(format!("  ({})", constraints.join(", ")));
+        }
+        if let PrimitiveSchema::Enum(schema) = self.schema {
+            for (index, (value, title)) in Options::of(schema).values.iter().enumerate() {
+                if value == title {
+                    lines.push(format!("  {}) {value}", index + 1));
+                } else {
+                    lines.push(format!("  {}) {title} [{value}]", index + 1));
+                }
+            }
+        }
+        lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+
+    fn prompt(&self) -> String {
+        let title = match self.schema {
+            PrimitiveSchema::String(schema) => &schema.title,
+            PrimitiveSchema::Number(schema) => &schema.title,
+            PrimitiveSchema::Integer(schema) => &schema.title,
+            PrimitiveSchema::Boolean(schema) => &schema.title,
+            PrimitiveSchema::Enum(schema) => Options::of(schema).title,
+        };
+        let mut prompt = title.as_deref().unwrap_or(self.name).to_owned();
+        if self.required {
+            prompt.push_str(" (required)");
+        }
+        if let Some(default) = self.default() {
+            let default = match default {
+                Value::String(default) => default,
+                Value::Array(defaults) => defaults
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                default => default.to_string(),
+            };
+            prompt.push_str(&format!(" [{default}]"));
+        }
+        prompt.push_str(": ");
+        prompt
+    }
+
+    fn default(&self) -> Option<Value> {
+        match self.schema {
+            PrimitiveSchema::String(schema) => schema.default.clone().map(Value::from),
+            PrimitiveSchema::Number(schema) => schema.default.map(Value::from),
+            PrimitiveSchema::Integer(schema) => schema.default.map(Value::from),
+            PrimitiveSchema::Boolean(schema) => schema.default.map(Value::from),
+            PrimitiveSchema::Enum(schema) => Options::of(schema).default,
+        }
+    }
+}
+
+fn range<T: std::fmt::Display>(kind: &str, min: Option<T>, max: Option<T>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("{kind} from {min} to {max}"),
+        (Some(min), None) => format!("{kind}, at least {min}"),
+        (None, Some(max)) => format!("{kind}, at most {max}"),
+        (None, None) => kind.to_owned(),
+    }
+}
+
+/// The options of an enum property, whatever its flavour.
+struct Options<'a> {
+    title: &'a Option<std::borrow::Cow<'static, str>>,
+    description: &'a Option<std::borrow::Cow<'static, str>>,
+    /// `(value, title)` pairs.
+    values: Vec<(&'a str, &'a str)>,
+    multiple: bool,
+    min_items: Option<u64>,
+    max_items: Option<u64>,
+    default: Option<Value>,
+}
+
+impl<'a> Options<'a> {
+    fn of(schema: &'a EnumSchema) -> Self {
+        let untitled = |values: &'a [String]| {
+            values
+                .iter()
+                .map(|value| (value.as_str(), value.as_str()))
+                .collect()
+        };
+        let titled = |values: &'a [ConstTitle]| {
+            values
+                .iter()
+                .map(|value| (value.const_.as_str(), value.title.as_str()))
+                .collect()
+        };
+        match schema {
+            EnumSchema::Single(SingleSelectEnumSchema::Untitled(schema)) => Self {
+                title: &schema.title,
+                description: &schema.description,
+                values: untitled(&schema.enum_),
+                multiple: false,
+                min_items: None,
+                max_items: None,
+                default: schema.default.clone().map(Value::from),
+            },
+            EnumSchema::Single(SingleSelectEnumSchema::Titled(schema)) => Self {
+                title: &schema.title,
+                description: &schema.description,
+                values: titled(&schema.one_of),
+                multiple: false,
+                min_items: None,
+                max_items: None,
+                default: schema.default.clone().map(Value::from),
+            },
+            EnumSchema::Multi(MultiSelectEnumSchema::Untitled(schema)) => Self {
+                title: &schema.title,
+                description: &schema.description,
+                values: untitled(&schema.items.enum_),
+                multiple: true,
+                min_items: schema.min_items,
+                max_items: schema.max_items,
+                default: schema.default.clone().map(Value::from),
+            },
+            EnumSchema::Multi(MultiSelectEnumSchema::Titled(schema)) => Self {
+                title: &schema.title,
+                description: &schema.description,
+                values: titled(&schema.items.any_of),
+                multiple: true,
+                min_items: schema.min_items,
+                max_items: schema.max_items,
+                default: schema.default.clone().map(Value::from),
+            },
+            EnumSchema::Legacy(schema) => Self {
+                title: &schema.title,
+                description: &schema.description,
+                values: match &schema.enum_names {
+                    Some(names) if names.len() == schema.enum_.len() => schema
+                        .enum_
+                        .iter()
+                        .zip(names)
+                        .map(|(value, name)| (value.as_str(), name.as_str()))
+                        .collect(),
+                    _ => untitled(&schema.enum_),
+                },
+                multiple: false,
+                min_items: None,
+                max_items: None,
+                default: None,
+            },
+        }
+    }
+
+    /// The value picked by `input`: its number in the list, the value itself
+    /// or its title, ignoring case.
+    fn pick(&self, input: &str) -> Result<&'a str, String> {
+        if let Ok(index) = input.parse::<usize>() {
+            if let Some((value, _)) = index
+                .checked_sub(1)
+                .and_then(|index| self.values.get(index))
+            {
+                return Ok(value);
+            }
+        }
+        self.values
+            .iter()
+            .find(|(value, _)| *value == input)
+            .or_else(|| {
+                self.values.iter().find(|(value, title)| {
+                    value.eq_ignore_ascii_case(input) || title.eq_ignore_ascii_case(input)
+                })
+            })
+            .map(|(value, _)| *value)
+            .ok_or_else(|| format!("{input:?} is not one of the options"))
+    }
+}
+
+fn parse_bool(input: &str) -> Option<bool> {
+    match input.to_ascii_lowercase().as_str() {
+        "y" | "yes" | "true" => Some(true),
+        "n" | "no" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse and validate the user's `input` for a property of type `schema`.
+///
+/// Returns the JSON value to send, or a message explaining why the input is
+/// invalid.
+pub fn parse_value(schema: &PrimitiveSchema, input: &str) -> Result<Value, String> {
+    let input = input.trim();
+    match schema {
+        PrimitiveSchema::String(schema) => {
+            let length = input.chars().count() as u32;
+            if let Some(min) = schema.min_length.filter(|min| length < *min) {
+                return Err(format!("Enter at least {min} characters"));
+            }
+            if let Some(max) = schema.max_length.filter(|max| length > *max) {
+                return Err(format!("Enter at most {max} characters"));
+            }
+            if let Some(format) = schema.format {
+                check_format(format, input)?;
+            }
+            Ok(Value::from(input))
+        }
+        PrimitiveSchema::Number(schema) => {
+            let number = input
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or("Enter a number")?;
+            if schema.minimum.is_some_and(|min| number < min)
+                || schema.maximum.is_some_and(|max| number > max)
+            {
+                return Err(format!(
+                    "Enter {}",
+                    range("a number", schema.minimum, schema.maximum)
+                ));
+            }
+            Ok(Value::from(number))
+        }
+        PrimitiveSchema::Integer(schema) => {
+            let number = input.parse::<i64>().map_err(|_| "Enter a whole number")?;
+            if schema.minimum.is_some_and(|min| number < min)
+                || schema.maximum.is_some_and(|max| number > max)
+            {
+                return Err(format!(
+                    "Enter {}",
+                    range("a whole number", schema.minimum, schema.maximum)
+                ));
+            }
+            Ok(Value::from(number))
+        }
+        PrimitiveSchema::Boolean(_) => parse_bool(input)
+            .map(Value::from)
+            .ok_or_else(|| "Answer y or n".to_owned()),
+        PrimitiveSchema::Enum(schema) => {
+            let options = Options::of(schema);
+            if !options.multiple {
+                return options.pick(input).map(Value::from);
+            }
+            let mut picked = Vec::new();
+            for part in input
+                .split(',')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+            {
+                let value = options.pick(part)?;
+                if !picked.contains(&value) {
+                    picked.push(value);
+                }
+            }
+            let count = picked.len() as u64;
+            if let Some(min) = options.min_items.filter(|min| count < *min) {
+                return Err(format!("Pick at least {min}"));
+            }
+            if let Some(max) = options.max_items.filter(|max| count > *max) {
+                return Err(format!("Pick at most {max}"));
+            }
+            Ok(Value::from(picked))
+        }
+    }
+}
+
+fn check_format(format: StringFormat, input: &str) -> Result<(), String> {
+    if format.matches(input) {
+        return Ok(());
+    }
+    Err(match format {
+        StringFormat::Email => "Enter an email address",
+        StringFormat::Uri => "Enter a URI, such as https://example.com",
+        StringFormat::Date => "Enter a date as YYYY-MM-DD",
+        StringFormat::DateTime => "Enter a date and time, such as 2025-01-31T09:30:00Z",
+    }
+    .to_owned())
+}
+
+impl<H: ClientHandler> ClientHandler for TerminalElicitationHandler<H> {
+    forward_client_handler!(
+        inner;
+        ping,
+        create_message,
+        list_roots,
+        on_custom_request,
+        on_cancelled,
+        on_progress,
+        on_logging_message,
+        on_resource_updated,
+        on_resource_list_changed,
+        on_tool_list_changed,
+        on_prompt_list_changed,
+        on_task_status,
+        on_custom_notification,
+    );
+
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        self.elicit(request, &context.peer).await.map_err(|error| {
+            McpError::internal_error(format!("failed to prompt the user: {error}"), None)
+        })
+    }
+
+    async fn on_url_elicitation_notification_complete(
+        &self,
+        params: ElicitationResponseNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        let completed = self
+            .url_elicitations
+            .lock()
+            .expect("url elicitations poisoned")
+            .remove(&params.elicitation_id)
+            .map(|elicitation| elicitation.completed.send_replace(true));
+        if completed == Some(false) {
+            let mut terminal = self.terminal.lock().await;
+            if let Err(error) = terminal
+                .write("The request in the browser is complete.\n")
+                .await
+            {
+                tracing::debug!(%error, "failed to write to the terminal");
+            }
+        }
+        self.inner
+            .on_url_elicitation_notification_complete(params, context)
+            .await
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = self.inner.get_info();
+        let elicitation = info
+            .capabilities
+            .elicitation
+            .get_or_insert_with(Default::default);
+        elicitation.form.get_or_insert(FormElicitationCapability {
+            schema_validation: Some(true),
+        });
+        elicitation.url.get_or_insert_with(Default::default);
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        let email = PrimitiveSchema::String(StringSchema::email());
+        assert_eq!(parse_value(&email, " a@b.io ").unwrap(), "a@b.io");
+        assert!(parse_value(&email, "a@b").is_err());
+
+        let name = PrimitiveSchema::String(StringSchema::new().length(2, 3));
+        assert!(parse_value(&name, "a").is_err());
+        assert!(parse_value(&name, "abcd").is_err());
+
+        let date = PrimitiveSchema::String(StringSchema::date());
+        assert!(parse_value(&date, "2025-02-28").is_ok());
+        assert!(parse_value(&date, "2025-02-30").is_err());
+
+        let age = PrimitiveSchema::Integer(IntegerSchema::new().range(0, 150));
+        assert_eq!(parse_value(&age, "42").unwrap(), 42);
+        assert!(parse_value(&age, "151").is_err());
+        assert!(parse_value(&age, "4.2").is_err());
+
+        let yes = PrimitiveSchema::Boolean(BooleanSchema::new());
+        assert_eq!(parse_value(&yes, "Y").unwrap(), true);
+
+        let colors = PrimitiveSchema::Enum(
+            EnumSchema::builder(vec!["red".into(), "green".into(), "blue".into()])
+                .enum_titles(vec!["Red".into(), "Green".into(), "Blue".into()])
+                .unwrap()
+                .build(),
+        );
+        assert_eq!(parse_value(&colors, "2").unwrap(), "green");
+        assert_eq!(parse_value(&colors, "BLUE").unwrap(), "blue");
+        assert!(parse_value(&colors, "4").is_err());
+
+        let many = PrimitiveSchema::Enum(
+            EnumSchema::builder(vec!["a".into(), "b".into(), "c".into()])
+                .multiselect()
+                .max_items(2u64)
+                .unwrap()
+                .build(),
+        );
+        assert_eq!(
+            parse_value(&many, "1, c, a").unwrap(),
+            serde_json::json!(["a", "c"])
+        );
+        assert!(parse_value(&many, "a,b,c").is_err());
+    }
+}
@@ -0,0 +1,874 @@
+//! One facade over many MCP servers.
+//!
+//! A [`ClientPool`] connects to any number of servers and presents their
+//! tools, prompts and resources as a single catalog. Tool and prompt names are
+//! namespaced with the server name (`github.create_issue`), and calls are
+//! routed back to the server that owns them. Resources and resource templates
+//! keep their URI and get a namespaced `name`; reads are routed to the server
+//! that lists the URI, or else to the one with a template matching it. A URI
+//! that more than one server claims is refused with
+//! [`ClientPoolError::AmbiguousResource`] rather than guessed at; read it
+//! through that server's [`peer`](ClientPool::peer) instead.
+//!
+//! Catalogs are cached per server and refreshed whenever the server sends a
+//! `list_changed` notification. When the pool is served as a gateway, each
+//! refresh is passed on to its clients as a `list_changed` notification of
+//! its own.
+//!
+//! With the `server` feature the pool is also a [`ServerHandler`](crate::ServerHandler),
+//! so it can be served as an MCP gateway in front of its members:
+//!
+//! ```rust,ignore
+//! let pool = ClientPool::new();
+//! pool.connect("git", TokioChildProcess::new(Command::new("uvx").arg("mcp-server-git"))?)
+//!     .await?;
+//! pool.connect("fs", TokioChildProcess::new(Command::new("mcp-server-filesystem"))?)
+//!     .await?;
+//! pool.clone().serve(stdio()).await?.waiting().await?;
+//! ```
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+use crate::{
+    ClientHandler, Peer, ServiceError,
+    model::*,
+    service::{
+        ClientInitializeError, NotificationContext, RoleClient, RunningService, serve_client,
+    },
+    transport::IntoTransport,
+};
+
+/// The separator between server name and item name used by default.
+pub const DEFAULT_SEPARATOR: &str = ".";
+
+/// How many list changes may queue up for a slow downstream client before it
+/// is told that every list changed.
+const CHANGES_CAPACITY: usize = 16;
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ClientPoolError {
+    #[error("invalid server name {0:?}: must be non-empty and not contain the separator")]
+    InvalidName(String),
+    #[error("server {0:?} is already connected")]
+    DuplicateServer(String),
+    #[error("failed to initialize server {name:?}: {source}")]
+    Initialize {
+        name: String,
+        #[source]
+        source: Box<ClientInitializeError>,
+    },
+    #[error("unknown server {0:?}")]
+    UnknownServer(String),
+    #[error("{0:?} is not prefixed with a server name")]
+    NotNamespaced(String),
+    #[error("no server exposes resource {0:?}")]
+    UnknownResource(String),
+    #[error("resource {uri:?} is exposed by more than one server: {servers:?}")]
+    AmbiguousResource { uri: String, servers: Vec<String> },
+    #[error("server {name:?} failed: {source}")]
+    Service {
+        name: String,
+        #[source]
+        source: ServiceError,
+    },
+}
+
+/// The result of probing one pool member with a ping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerHealth {
+    pub name: String,
+    /// Whether the transport to the server is still open.
+    pub connected: bool,
+    /// Round trip time of the ping, if it succeeded.
+    pub latency: Option<Duration>,
+    /// The most recent error seen while talking to the server.
+    pub last_error: Option<String>,
+}
+
+/// One cached list. `version` is bumped whenever the list is invalidated, so
+/// a fetch that started before the invalidation can't store stale data.
+#[derive(Debug)]
+struct Slot<T> {
+    items: Option<Vec<T>>,
+    version: u64,
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self {
+            items: None,
+            version: 0,
+        }
+    }
+}
+
+impl<T> Slot<T> {
+    /// Stores `items` fetched at `version`, unless the slot was invalidated
+    /// since. Returns whether they were stored.
+    fn store(&mut self, version: u64, items: Vec<T>) -> bool {
+        if self.version != version {
+            return false;
+        }
+        self.items = Some(items);
+        true
+    }
+}
+
+#[derive(Debug, Default)]
+struct Catalog {
+    tools: Slot<Tool>,
+    prompts: Slot<Prompt>,
+    resources: Slot<Resource>,
+    resource_templates: Slot<ResourceTemplate>,
+}
+
+/// Which list of a member changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListChange {
+    Tools,
+    Prompts,
+    Resources,
+}
+
+/// The item types a [`Catalog`] caches.
+trait CatalogItem: Clone + Send + Sync + 'static {
+    const CHANGE: ListChange;
+    fn slot(catalog: &Catalog) -> &Slot<Self>;
+    fn slot_mut(catalog: &mut Catalog) -> &mut Slot<Self>;
+    fn fetch(
+        peer: Peer<RoleClient>,
+    ) -> impl Future<Output = Result<Vec<Self>, ServiceError>> + Send;
+}
+
+impl CatalogItem for Tool {
+    const CHANGE: ListChange = ListChange::Tools;
+    fn slot(catalog: &Catalog) -> &Slot<Self> {
+        &catalog.tools
+    }
+    fn slot_mut(catalog: &mut Catalog) -> &mut Slot<Self> {
+        &mut catalog.tools
+    }
+    async fn fetch(peer: Peer<RoleClient>) -> Result<Vec<Self>, ServiceError> {
+        peer.list_all_tools().await
+    }
+}
+
+impl CatalogItem for Prompt {
+    const CHANGE: ListChange = ListChange::Prompts;
+    fn slot(catalog: &Catalog) -> &Slot<Self> {
+        &catalog.prompts
+    }
+    fn slot_mut(catalog: &mut Catalog) -> &mut Slot<Self> {
+        &mut catalog.prompts
+    }
+    async fn fetch(peer: Peer<RoleClient>) -> Result<Vec<Self>, ServiceError> {
+        peer.list_all_prompts().await
+    }
+}
+
+impl CatalogItem for Resource {
+    const CHANGE: ListChange = ListChange::Resources;
+    fn slot(catalog: &Catalog) -> &Slot<Self> {
+        &catalog.resources
+    }
+    fn slot_mut(catalog: &mut Catalog) -> &mut Slot<Self> {
+        &mut catalog.resources
+    }
+    async fn fetch(peer: Peer<RoleClient>) -> Result<Vec<Self>, ServiceError> {
+        peer.list_all_resources().await
+    }
+}
+
+impl CatalogItem for ResourceTemplate {
+    const CHANGE: ListChange = ListChange::Resources;
+    fn slot(catalog: &Catalog) -> &Slot<Self> {
+        &catalog.resource_templates
+    }
+    fn slot_mut(catalog: &mut Catalog) -> &mut Slot<Self> {
+        &mut catalog.resource_templates
+    }
+    async fn fetch(peer: Peer<RoleClient>) -> Result<Vec<Self>, ServiceError> {
+        peer.list_all_resource_templates().await
+    }
+}
+
+/// Whether `uri` is an expansion of the RFC 6570 `template`.
+///
+/// Literal parts must match exactly. A `{var}` expression matches a non-empty
+/// run of characters without `/`, `?` or `#`; reserved (`{+var}`) and
+/// fragment (`{#var}`) expansions match any non-empty run, and the other
+/// operators match their prefix followed by any run.
+fn template_matches(template: &str, uri: &str) -> bool {
+    let Some(start) = template.find('{') else {
+        return template == uri;
+    };
+    let Some(uri_rest) = uri.strip_prefix(&template[..start]) else {
+        return false;
+    };
+    let Some(len) = template[start..].find('}') else {
+        return false;
+    };
+    let expression = &template[start + 1..start + len];
+    let template_rest = &template[start + len + 1..];
+    let (prefix, any): (&str, bool) = match expression.chars().next() {
+        Some('+') => ("", true),
+        Some('#') => ("#", true),
+        Some('/') => ("/", false),
+        Some('.') => (".", false),
+        Some(';') => (";", true),
+        Some('?') => ("?", true),
+        Some('&') => ("&", true),
+        _ => ("", false),
+    };
+    let Some(uri_rest) = uri_rest.strip_prefix(prefix) else {
+        return false;
+    };
+    // try every split of the rest of the URI, shortest expansion first
+    (1..=uri_rest.len())
+        .filter(|&end| uri_rest.is_char_boundary(end))
+        .take_while(|&end| any || !uri_rest[..end].contains(['/', '?', '#']))
+        .any(|end| template_matches(template_rest, &uri_rest[end..]))
+}
+
+#[derive(Debug, Default)]
+struct MemberState {
+    catalog: RwLock<Catalog>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl MemberState {
+    fn record_error(&self, error: &dyn std::fmt::Display) {
+        *self.last_error.lock().expect("pool lock poisoned") = Some(error.to_string());
+    }
+}
+
+/// The client handler each pool member is served with.
+///
+/// It keeps the member's cached catalog in step with the server's
+/// `list_changed` notifications, and announces each refreshed list to the
+/// pool's downstream clients.
+pub struct PoolMemberHandler {
+    name: Arc<str>,
+    client_info: Arc<ClientInfo>,
+    state: Arc<MemberState>,
+    changes: broadcast::Sender<ListChange>,
+}
+
+impl PoolMemberHandler {
+    /// Drops the cached list of `T` and fetches it again in the background.
+    /// Notifications are handled inline by the service loop, so the refresh
+    /// must not be awaited here.
+    fn refresh<T: CatalogItem>(&self, peer: Peer<RoleClient>) {
+        let version = {
+            let mut catalog = self.state.catalog.write().expect("pool lock poisoned");
+            let slot = T::slot_mut(&mut catalog);
+            slot.items = None;
+            slot.version += 1;
+            slot.version
+        };
+        let name = self.name.clone();
+        let state = self.state.clone();
+        let changes = self.changes.clone();
+        tokio::spawn(async move {
+            match T::fetch(peer).await {
+                Ok(items) => {
+                    let stored =
+                        T::slot_mut(&mut state.catalog.write().expect("pool lock poisoned"))
+                            .store(version, items);
+                    if stored {
+                        // nobody may be listening, which is fine
+                        let _ = changes.send(T::CHANGE);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(server = %name, "failed to refresh catalog: {e}");
+                    state.record_error(&e);
+                }
+            }
+        });
+    }
+}
+
+impl ClientHandler for PoolMemberHandler {
+    async fn on_tool_list_changed(&self, context: NotificationContext<RoleClient>) {
+        self.refresh::<Tool>(context.peer);
+    }
+
+    async fn on_prompt_list_changed(&self, context: NotificationContext<RoleClient>) {
+        self.refresh::<Prompt>(context.peer);
+    }
+
+    async fn on_resource_list_changed(&self, context: NotificationContext<RoleClient>) {
+        self.refresh::<Resource>(context.peer.clone());
+        self.refresh::<ResourceTemplate>(context.peer);
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        self.client_info.as_ref().clone()
+    }
+}
+
+struct Member {
+    service: RunningService<RoleClient, PoolMemberHandler>,
+    state: Arc<MemberState>,
+}
+
+/// A snapshot of one member, taken so no lock is held across requests.
+struct MemberRef {
+    name: String,
+    peer: Peer<RoleClient>,
+    state: Arc<MemberState>,
+}
+
+/// A set of MCP client connections exposed as one namespaced catalog.
+#[derive(Clone)]
+pub struct ClientPool {
+    separator: Arc<str>,
+    client_info: Arc<ClientInfo>,
+    members: Arc<tokio::sync::RwLock<BTreeMap<String, Member>>>,
+    changes: broadcast::Sender<ListChange>,
+}
+
+impl Default for ClientPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ClientPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientPool")
+            .field("separator", &self.separator)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientPool {
+    pub fn new() -> Self {
+        Self {
+            separator: DEFAULT_SEPARATOR.into(),
+            client_info: Arc::new(ClientInfo::default()),
+            members: Default::default(),
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
+        }
+    }
+
+    /// Use `separator` between server and item names instead of `.`.
+    pub fn with_separator(mut self, separator: impl Into<Arc<str>>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// The client info sent to every server on initialization.
+    pub fn with_client_info(mut self, client_info: ClientInfo) -> Self {
+        self.client_info = Arc::new(client_info);
+        self
+    }
+
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    /// Connects to a server and adds it to the pool under `name`.
+    pub async fn connect<T, E, A>(
+        &self,
+        name: impl Into<String>,
+        transport: T,
+    ) -> Result<(), ClientPoolError>
+    where
+        T: IntoTransport<RoleClient, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let name = name.into();
+        if name.is_empty() || name.contains(self.separator.as_ref()) {
+            return Err(ClientPoolError::InvalidName(name));
+        }
+        if self.members.read().await.contains_key(&name) {
+            return Err(ClientPoolError::DuplicateServer(name));
+        }
+        let state = Arc::new(MemberState::default());
+        let handler = PoolMemberHandler {
+            name: name.as_str().into(),
+            client_info: self.client_info.clone(),
+            state: state.clone(),
+            changes: self.changes.clone(),
+        };
+        let service =
+            serve_client(handler, transport)
+                .await
+                .map_err(|e| ClientPoolError::Initialize {
+                    name: name.clone(),
+                    source: Box::new(e),
+                })?;
+        let mut members = self.members.write().await;
+        if members.contains_key(&name) {
+            return Err(ClientPoolError::DuplicateServer(name));
+        }
+        members.insert(name, Member { service, state });
+        Ok(())
+    }
+
+    /// Removes a server from the pool and closes its connection.
+    pub async fn disconnect(&self, name: &str) -> Result<(), ClientPoolError> {
+        let member = self
+            .members
+            .write()
+            .await
+            .remove(name)
+            .ok_or_else(|| ClientPoolError::UnknownServer(name.to_owned()))?;
+        if let Err(e) = member.service.cancel().await {
+            tracing::warn!(server = %name, "failed to close connection: {e}");
+        }
+        Ok(())
+    }
+
+    /// The names of all connected servers, in sorted order.
+    pub async fn names(&self) -> Vec<String> {
+        self.members.read().await.keys().cloned().collect()
+    }
+
+    /// The peer of the server registered as `name`.
+    pub async fn peer(&self, name: &str) -> Option<Peer<RoleClient>> {
+        self.members
+            .read()
+            .await
+            .get(name)
+            .map(|member| member.service.peer().clone())
+    }
+
+    async fn snapshot(&self) -> Vec<MemberRef> {
+        self.members
+            .read()
+            .await
+            .iter()
+            .map(|(name, member)| MemberRef {
+                name: name.clone(),
+                peer: member.service.peer().clone(),
+                state: member.state.clone(),
+            })
+            .collect()
+    }
+
+    async fn member(&self, name: &str) -> Result<MemberRef, ClientPoolError> {
+        let members = self.members.read().await;
+        let member = members
+            .get(name)
+            .ok_or_else(|| ClientPoolError::UnknownServer(name.to_owned()))?;
+        Ok(MemberRef {
+            name: name.to_owned(),
+            peer: member.service.peer().clone(),
+            state: member.state.clone(),
+        })
+    }
+
+    fn namespaced(&self, server: &str, item: &str) -> String {
+        format!("{server}{}{item}", self.separator)
+    }
+
+    /// Splits `server.item` into its server and item parts.
+    fn split<'a>(&self, name: &'a str) -> Result<(&'a str, &'a str), ClientPoolError> {
+        name.split_once(self.separator.as_ref())
+            .ok_or_else(|| ClientPoolError::NotNamespaced(name.to_owned()))
+    }
+
+    /// Returns one member's cached list, fetching it first if needed.
+    async fn cached<T: CatalogItem>(member: &MemberRef) -> Option<Vec<T>> {
+        let version = {
+            let catalog = member.state.catalog.read().expect("pool lock poisoned");
+            let slot = T::slot(&catalog);
+            if let Some(items) = &slot.items {
+                return Some(items.clone());
+            }
+            slot.version
+        };
+        match T::fetch(member.peer.clone()).await {
+            Ok(items) => {
+                T::slot_mut(&mut member.state.catalog.write().expect("pool lock poisoned"))
+                    .store(version, items.clone());
+                Some(items)
+            }
+            Err(e) => {
+                tracing::warn!(server = %member.name, "failed to list catalog: {e}");
+                member.state.record_error(&e);
+                None
+            }
+        }
+    }
+
+    /// Lists the tools of every server, with names prefixed by the server
+    /// name. Servers that fail to answer are skipped and reported through
+    /// [`health`](Self::health).
+    pub async fn list_all_tools(&self) -> Vec<Tool> {
+        let members = self.snapshot().await;
+        let lists = join_all(members.iter().map(Self::cached::<Tool>)).await;
+        members
+            .iter()
+            .zip(lists)
+            .flat_map(|(member, tools)| {
+                tools.unwrap_or_default().into_iter().map(|mut tool| {
+                    tool.name = self.namespaced(&member.name, &tool.name).into();
+                    tool
+                })
+            })
+            .collect()
+    }
+
+    /// Lists the prompts of every server, with names prefixed by the server
+    /// name.
+    pub async fn list_all_prompts(&self) -> Vec<Prompt> {
+        let members = self.snapshot().await;
+        let lists = join_all(members.iter().map(Self::cached::<Prompt>)).await;
+        members
+            .iter()
+            .zip(lists)
+            .flat_map(|(member, prompts)| {
+                prompts.unwrap_or_default().into_iter().map(|mut prompt| {
+                    prompt.name = self.namespaced(&member.name, &prompt.name);
+                    prompt
+                })
+            })
+            .collect()
+    }
+
+    /// Lists the resources of every server. URIs are left untouched so they
+    /// stay readable; the `name` is prefixed by the server name.
+    pub async fn list_all_resources(&self) -> Vec<Resource> {
+        let members = self.snapshot().await;
+        let lists = join_all(members.iter().map(Self::cached::<Resource>)).await;
+        members
+            .iter()
+            .zip(lists)
+            .flat_map(|(member, resources)| {
+                resources
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|mut resource| {
+                        resource.raw.name = self.namespaced(&member.name, &resource.raw.name);
+                        resource
+                    })
+            })
+            .collect()
+    }
+
+    /// Calls a namespaced tool on the server that owns it.
+    pub async fn call_tool(
+        &self,
+        mut params: CallToolRequestParams,
+    ) -> Result<CallToolResult, ClientPoolError> {
+        let (server, tool) = self.split(&params.name)?;
+        let member = self.member(server).await?;
+        params.name = tool.to_owned().into();
+        member
+            .peer
+            .call_tool(params)
+            .await
+            .map_err(|e| member.service_error(e))
+    }
+
+    /// Gets a namespaced prompt from the server that owns it.
+    pub async fn get_prompt(
+        &self,
+        mut params: GetPromptRequestParams,
+    ) -> Result<GetPromptResult, ClientPoolError> {
+        let (server, prompt) = self.split(&params.name)?;
+        let member = self.member(server).await?;
+        params.name = prompt.to_owned();
+        member
+            .peer
+            .get_prompt(params)
+            .await
+            .map_err(|e| member.service_error(e))
+    }
+
+    /// Lists the resource templates of every server. Like resources, templates
+    /// keep their URI template and get a namespaced `name`.
+    pub async fn list_all_resource_templates(&self) -> Vec<ResourceTemplate> {
+        let members = self.snapshot().await;
+        let lists = join_all(members.iter().map(Self::cached::<ResourceTemplate>)).await;
+        members
+            .iter()
+            .zip(lists)
+            .flat_map(|(member, templates)| {
+                templates
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|mut template| {
+                        template.raw.name = self.namespaced(&member.name, &template.raw.name);
+                        template
+                    })
+            })
+            .collect()
+    }
+
+    /// Reads a resource from the server that lists its URI, or else from the
+    /// server with a resource template matching it. Fails if more than one
+    /// server claims the URI at the same step.
+    pub async fn read_resource(
+        &self,
+        params: ReadResourceRequestParams,
+    ) -> Result<ReadResourceResult, ClientPoolError> {
+        let members = self.snapshot().await;
+        let resources = join_all(members.iter().map(Self::cached::<Resource>)).await;
+        let mut owners: Vec<&MemberRef> = members
+            .iter()
+            .zip(resources)
+            .filter(|(_, resources)| {
+                resources
+                    .iter()
+                    .flatten()
+                    .any(|resource| resource.raw.uri == params.uri)
+            })
+            .map(|(member, _)| member)
+            .collect();
+        if owners.is_empty() {
+            let templates = join_all(members.iter().map(Self::cached::<ResourceTemplate>)).await;
+            owners = members
+                .iter()
+                .zip(templates)
+                .filter(|(_, templates)| {
+                    templates
+                        .iter()
+                        .flatten()
+                        .any(|template| template_matches(&template.raw.uri_template, &params.uri))
+                })
+                .map(|(member, _)| member)
+                .collect();
+        }
+        match owners.as_slice() {
+            [] => Err(ClientPoolError::UnknownResource(params.uri)),
+            [member] => member
+                .peer
+                .read_resource(params)
+                .await
+                .map_err(|e| member.service_error(e)),
+            _ => Err(ClientPoolError::AmbiguousResource {
+                servers: owners.iter().map(|member| member.name.clone()).collect(),
+                uri: params.uri,
+            }),
+        }
+    }
+
+    /// Pings every server and reports its state.
+    pub async fn health(&self) -> Vec<ServerHealth> {
+        let members = self.snapshot().await;
+        join_all(members.into_iter().map(|member| async move {
+            let started = Instant::now();
+            let latency = match member
+                .peer
+                .send_request(ClientRequest::PingRequest(Default::default()))
+                .await
+            {
+                Ok(_) => Some(started.elapsed()),
+                Err(e) => {
+                    member.state.record_error(&e);
+                    None
+                }
+            };
+            ServerHealth {
+                connected: !member.peer.is_transport_closed(),
+                latency,
+                last_error: member
+                    .state
+                    .last_error
+                    .lock()
+                    .expect("pool lock poisoned")
+                    .clone(),
+                name: member.name,
+            }
+        }))
+        .await
+    }
+}
+
+impl MemberRef {
+    fn service_error(&self, source: ServiceError) -> ClientPoolError {
+        if !matches!(source, ServiceError::McpError(_)) {
+            self.state.record_error(&source);
+        }
+        ClientPoolError::Service {
+            name: self.name.clone(),
+            source,
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod gateway {
+    use tokio::sync::broadcast::error::RecvError;
+
+    use super::{ClientPool, ClientPoolError, ListChange};
+    use crate::{
+        ErrorData as McpError, Peer, ServerHandler, ServiceError,
+        model::*,
+        service::{NotificationContext, RequestContext, RoleServer},
+    };
+
+    async fn notify(peer: &Peer<RoleServer>, change: ListChange) -> Result<(), ServiceError> {
+        match change {
+            ListChange::Tools => peer.notify_tool_list_changed().await,
+            ListChange::Prompts => peer.notify_prompt_list_changed().await,
+            ListChange::Resources => peer.notify_resource_list_changed().await,
+        }
+    }
+
+    impl From<ClientPoolError> for McpError {
+        fn from(error: ClientPoolError) -> Self {
+            match error {
+                ClientPoolError::Service {
+                    source: ServiceError::McpError(error),
+                    ..
+                } => error,
+                ClientPoolError::UnknownResource(uri) => McpError::resource_not_found(
+                    format!("no server exposes resource {uri:?}"),
+                    None,
+                ),
+                error @ (ClientPoolError::UnknownServer(_)
+                | ClientPoolError::NotNamespaced(_)
+                | ClientPoolError::AmbiguousResource { .. }) => {
+                    McpError::invalid_params(error.to_string(), None)
+                }
+                error => McpError::internal_error(error.to_string(), None),
+            }
+        }
+    }
+
+    impl ServerHandler for ClientPool {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo::new(
+                ServerCapabilities::builder()
+                    .enable_tools()
+                    .enable_tool_list_changed()
+                    .enable_prompts()
+                    .enable_prompts_list_changed()
+                    .enable_resources()
+                    .enable_resources_list_changed()
+                    .build(),
+            )
+        }
+
+        /// Passes member list changes on to this client until it goes away.
+        async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+            let mut changes = self.changes.subscribe();
+            let peer = context.peer;
+            tokio::spawn(async move {
+                loop {
+                    let sent = match changes.recv().await {
+                        Ok(change) => notify(&peer, change).await,
+                        // some changes were missed, so any list may be stale
+                        Err(RecvError::Lagged(_)) => futures::future::try_join3(
+                            notify(&peer, ListChange::Tools),
+                            notify(&peer, ListChange::Prompts),
+                            notify(&peer, ListChange::Resources),
+                        )
+                        .await
+                        .map(drop),
+                        Err(RecvError::Closed) => break,
+                    };
+                    if sent.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<PaginatedRequestParams>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListToolsResult, McpError> {
+            Ok(ListToolsResult::with_all_items(self.list_all_tools().await))
+        }
+
+        async fn call_tool(
+            &self,
+            request: CallToolRequestParams,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<CallToolResult, McpError> {
+            Ok(ClientPool::call_tool(self, request).await?)
+        }
+
+        async fn list_prompts(
+            &self,
+            _request: Option<PaginatedRequestParams>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListPromptsResult, McpError> {
+            Ok(ListPromptsResult::with_all_items(
+                self.list_all_prompts().await,
+            ))
+        }
+
+        async fn get_prompt(
+            &self,
+            request: GetPromptRequestParams,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<GetPromptResult, McpError> {
+            Ok(ClientPool::get_prompt(self, request).await?)
+        }
+
+        async fn list_resources(
+            &self,
+            _request: Option<PaginatedRequestParams>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListResourcesResult, McpError> {
+            Ok(ListResourcesResult::with_all_items(
+                self.list_all_resources().await,
+            ))
+        }
+
+        async fn list_resource_templates(
+            &self,
+            _request: Option<PaginatedRequestParams>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListResourceTemplatesResult, McpError> {
+            Ok(ListResourceTemplatesResult::with_all_items(
+                self.list_all_resource_templates().await,
+            ))
+        }
+
+        async fn read_resource(
+            &self,
+            request: ReadResourceRequestParams,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ReadResourceResult, McpError> {
+            Ok(ClientPool::read_resource(self, request).await?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::template_matches;
+
+    #[test]
+    fn test_template_matches() {
+        assert!(template_matches("memo://readme", "memo://readme"));
+        assert!(!template_matches("memo://readme", "memo://readme2"));
+        assert!(template_matches(
+            "repo://{owner}/{repo}",
+            "repo://rust-lang/rust"
+        ));
+        assert!(!template_matches(
+            "repo://{owner}/{repo}",
+            "repo://rust-lang"
+        ));
+        assert!(!template_matches("repo://{owner}/{repo}", "repo://a/b/c"));
+        assert!(!template_matches("repo://{owner}", "repo://"));
+        assert!(template_matches("file://{+path}", "file:///etc/hosts"));
+        assert!(template_matches(
+            "search://{query}{?page}",
+            "search://mcp?page=2"
+        ));
+        assert!(template_matches(
+            "users://{id}/profile",
+            "users://é/profile"
+        ));
+    }
+}
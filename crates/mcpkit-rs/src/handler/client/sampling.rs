@@ -0,0 +1,486 @@
+//! Answering sampling requests with a pluggable LLM backend.
+//!
+//! Implement [`SamplingProvider`] for a backend, or use
+//! [`OpenAiProvider`](openai::OpenAiProvider) with the `sampling-openai`
+//! feature, and wrap the client handler in a [`SamplingHandler`]. The handler
+//! answers `sampling/createMessage` by:
+//!
+//! 1. picking one of the configured [`SamplingModel`]s from the request's
+//!    [`ModelPreferences`]: the first hint that is a substring of a model name
+//!    wins, otherwise the model scoring best on the cost, speed and
+//!    intelligence priorities, otherwise the first model;
+//! 2. enforcing the [`SamplingLimits`] of the requesting connection, so a
+//!    server over its limits cannot keep prompting the user;
+//! 3. passing the request to the approval hook, if any, which may edit it or
+//!    reject it;
+//! 4. sending the request to the provider.
+//!
+//! ```rust,ignore
+//! let provider = OpenAiProvider::new("https://api.openai.com/v1").with_api_key(key);
+//! let client = SamplingHandler::new(ClientInfo::default(), provider)
+//!     .with_models(vec![
+//!         SamplingModel::new("gpt-4o-mini").with_cost(0.1).with_speed(0.9).with_intelligence(0.5),
+//!         SamplingModel::new("gpt-4o").with_cost(0.6).with_speed(0.5).with_intelligence(0.9),
+//!     ])
+//!     .with_limits(SamplingLimits::default().with_max_requests(10))
+//!     .with_approval(|review: SamplingReview| async move {
+//!         if confirm(&review) { ApprovalDecision::Approve(review) } else { ApprovalDecision::reject() }
+//!     })
+//!     .serve(transport)
+//!     .await?;
+//! ```
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::future::{BoxFuture, FutureExt};
+
+use super::forward_client_handler;
+use crate::{
+    ClientHandler,
+    error::ErrorData as McpError,
+    model::*,
+    service::{NotificationContext, RequestContext, RoleClient},
+};
+
+#[cfg(feature = "sampling-openai")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sampling-openai")))]
+pub mod openai;
+
+/// The error code for a sampling request the user rejected.
+pub const USER_REJECTED: ErrorCode = ErrorCode(-1);
+
+/// An LLM backend answering sampling requests.
+pub trait SamplingProvider: Send + Sync + 'static {
+    /// Generate the assistant's response to `params` with `model`.
+    fn create_message<'a>(
+        &'a self,
+        model: &'a str,
+        params: CreateMessageRequestParams,
+    ) -> BoxFuture<'a, Result<CreateMessageResult, McpError>>;
+
+    /// Whether the backend accepts `tools` and `toolChoice`. When it does,
+    /// the handler advertises tool support in sampling.
+    fn supports_tools(&self) -> bool {
+        false
+    }
+}
+
+/// A model the client may sample with, scored from 0.0 to 1.0 on each of the
+/// axes of [`ModelPreferences`]. Higher is cheaper, faster and more capable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingModel {
+    pub name: String,
+    pub cost: f32,
+    pub speed: f32,
+    pub intelligence: f32,
+}
+
+impl SamplingModel {
+    /// A model scoring 0.5 on every axis.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            cost: 0.5,
+            speed: 0.5,
+            intelligence: 0.5,
+        }
+    }
+
+    /// How cheap the model is, 1.0 being the cheapest.
+    pub fn with_cost(mut self, cost: f32) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_intelligence(mut self, intelligence: f32) -> Self {
+        self.intelligence = intelligence;
+        self
+    }
+
+    fn score(&self, preferences: &ModelPreferences) -> f32 {
+        preferences.cost_priority.unwrap_or(0.0) * self.cost
+            + preferences.speed_priority.unwrap_or(0.0) * self.speed
+            + preferences.intelligence_priority.unwrap_or(0.0) * self.intelligence
+    }
+}
+
+/// Pick the model for a request, see the [module docs](self).
+pub fn select_model<'a>(
+    models: &'a [SamplingModel],
+    preferences: Option<&ModelPreferences>,
+) -> Option<&'a SamplingModel> {
+    let Some(preferences) = preferences else {
+        return models.first();
+    };
+    let hinted = preferences
+        .hints
+        .iter()
+        .flatten()
+        .filter_map(|hint| hint.name.as_deref())
+        .find_map(|hint| models.iter().find(|model| model.name.contains(hint)));
+    if hinted.is_some() {
+        return hinted;
+    }
+    // the first of equally scored models wins
+    models.iter().rev().max_by(|a, b| {
+        a.score(preferences)
+            .partial_cmp(&b.score(preferences))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })
+}
+
+/// A sampling request awaiting approval.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingReview {
+    /// The name of the requesting server, if it introduced itself.
+    pub server: Option<String>,
+    /// The model selected for the request.
+    pub model: String,
+    pub params: CreateMessageRequestParams,
+}
+
+/// The outcome of reviewing a [`SamplingReview`].
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ApprovalDecision {
+    /// Send the request, possibly edited, with the model it names.
+    Approve(SamplingReview),
+    /// Answer the server with a [`USER_REJECTED`] error.
+    Reject { reason: Option<String> },
+}
+
+impl ApprovalDecision {
+    pub fn reject() -> Self {
+        Self::Reject { reason: None }
+    }
+}
+
+type ApprovalHook =
+    Arc<dyn Fn(SamplingReview) -> BoxFuture<'static, ApprovalDecision> + Send + Sync>;
+
+/// Limits applied to each connection separately, over a fixed window.
+///
+/// Tokens are counted as the `maxTokens` of the requests, after
+/// `max_tokens_per_request` is applied, since sampling results do not report
+/// usage. Requests count once they pass the limits, whether or not they are
+/// approved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingLimits {
+    pub window: Duration,
+    /// Requests per window.
+    pub max_requests: Option<u32>,
+    /// Tokens per window.
+    pub max_tokens: Option<u32>,
+    /// Requests asking for more tokens are capped to this many.
+    pub max_tokens_per_request: Option<u32>,
+}
+
+impl Default for SamplingLimits {
+    /// No limits, over a one minute window.
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            max_requests: None,
+            max_tokens: None,
+            max_tokens_per_request: None,
+        }
+    }
+}
+
+impl SamplingLimits {
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_max_requests(mut self, max_requests: u32) -> Self {
+        self.max_requests = Some(max_requests);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_max_tokens_per_request(mut self, max_tokens: u32) -> Self {
+        self.max_tokens_per_request = Some(max_tokens);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Usage {
+    started_at: Instant,
+    requests: u32,
+    tokens: u32,
+}
+
+/// A [`ClientHandler`] answering sampling requests with a
+/// [`SamplingProvider`] and forwarding everything else to the inner handler.
+#[derive(Clone)]
+pub struct SamplingHandler<H> {
+    inner: H,
+    provider: Arc<dyn SamplingProvider>,
+    models: Arc<[SamplingModel]>,
+    approval: Option<ApprovalHook>,
+    limits: SamplingLimits,
+    usage: Arc<Mutex<HashMap<u64, Usage>>>,
+}
+
+impl<H> std::fmt::Debug for SamplingHandler<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SamplingHandler")
+            .field("models", &self.models)
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H: ClientHandler> SamplingHandler<H> {
+    /// A handler with no models configured: requests are rejected until
+    /// [`with_models`](Self::with_models) is called.
+    pub fn new(inner: H, provider: impl SamplingProvider) -> Self {
+        Self {
+            inner,
+            provider: Arc::new(provider),
+            models: Arc::new([]),
+            approval: None,
+            limits: SamplingLimits::default(),
+            usage: Default::default(),
+        }
+    }
+
+    /// The models to choose from, in order of preference when the request
+    /// expresses none.
+    pub fn with_models(mut self, models: impl Into<Vec<SamplingModel>>) -> Self {
+        self.models = models.into().into();
+        self
+    }
+
+    pub fn with_limits(mut self, limits: SamplingLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Review every request before it is sent to the provider.
+    pub fn with_approval<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(SamplingReview) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ApprovalDecision> + Send + 'static,
+    {
+        self.approval = Some(Arc::new(move |review| hook(review).boxed()));
+        self
+    }
+
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    async fn sample(
+        &self,
+        mut params: CreateMessageRequestParams,
+        context: &RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        if (params.tools.is_some() || params.tool_choice.is_some())
+            && !self.provider.supports_tools()
+        {
+            return Err(McpError::invalid_params(
+                "the sampling provider does not support tools",
+                None,
+            ));
+        }
+        if let Some(max_tokens) = self.limits.max_tokens_per_request {
+            params.max_tokens = params.max_tokens.min(max_tokens);
+        }
+        let model = select_model(&self.models, params.model_preferences.as_ref())
+            .ok_or_else(|| McpError::internal_error("no sampling model is configured", None))?;
+        let connection = context.peer.connection_id();
+        self.consume(connection, 1, params.max_tokens)?;
+        let server = context
+            .peer
+            .peer_info()
+            .map(|info| info.server_info.name.clone());
+        let counted = params.max_tokens;
+        let mut review = SamplingReview {
+            server,
+            model: model.name.clone(),
+            params,
+        };
+        if let Some(approval) = &self.approval {
+            review = match approval(review).await {
+                ApprovalDecision::Approve(review) => review,
+                ApprovalDecision::Reject { reason } => {
+                    return Err(McpError::new(
+                        USER_REJECTED,
+                        reason.unwrap_or_else(|| "User rejected sampling request".to_owned()),
+                        None,
+                    ));
+                }
+            };
+            if let Some(max_tokens) = self.limits.max_tokens_per_request {
+                review.params.max_tokens = review.params.max_tokens.min(max_tokens);
+            }
+            // the hook may have raised maxTokens past what was counted
+            if review.params.max_tokens > counted {
+                self.consume(connection, 0, review.params.max_tokens - counted)?;
+            }
+        }
+        let result = self
+            .provider
+            .create_message(&review.model, review.params)
+            .await?;
+        result
+            .validate()
+            .map_err(|error| McpError::internal_error(error, None))?;
+        Ok(result)
+    }
+
+    /// Count `requests` and `tokens` against the limits of `connection`,
+    /// failing if they would exceed them.
+    fn consume(&self, connection: u64, requests: u32, tokens: u32) -> Result<(), McpError> {
+        let mut usage = self.usage.lock().expect("sampling usage poisoned");
+        let now = Instant::now();
+        // forget connections whose window is over, closed ones included
+        usage.retain(|id, usage| {
+            *id == connection || now.duration_since(usage.started_at) < self.limits.window
+        });
+        let usage = usage.entry(connection).or_insert(Usage {
+            started_at: now,
+            requests: 0,
+            tokens: 0,
+        });
+        if now.duration_since(usage.started_at) >= self.limits.window {
+            *usage = Usage {
+                started_at: now,
+                requests: 0,
+                tokens: 0,
+            };
+        }
+        if self
+            .limits
+            .max_requests
+            .is_some_and(|max| usage.requests.saturating_add(requests) > max)
+        {
+            return Err(McpError::invalid_request(
+                "sampling request limit reached, try again later",
+                None,
+            ));
+        }
+        if self
+            .limits
+            .max_tokens
+            .is_some_and(|max| usage.tokens.saturating_add(tokens) > max)
+        {
+            return Err(McpError::invalid_request(
+                "sampling token limit reached, try again later",
+                None,
+            ));
+        }
+        usage.requests += requests;
+        usage.tokens = usage.tokens.saturating_add(tokens);
+        Ok(())
+    }
+}
+
+impl<H: ClientHandler> ClientHandler for SamplingHandler<H> {
+    forward_client_handler!(
+        inner;
+        ping,
+        list_roots,
+        create_elicitation,
+        on_custom_request,
+        on_cancelled,
+        on_progress,
+        on_logging_message,
+        on_resource_updated,
+        on_resource_list_changed,
+        on_tool_list_changed,
+        on_prompt_list_changed,
+        on_url_elicitation_notification_complete,
+        on_task_status,
+        on_custom_notification,
+    );
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        self.sample(params, &context).await
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = self.inner.get_info();
+        let sampling = info
+            .capabilities
+            .sampling
+            .get_or_insert_with(Default::default);
+        if self.provider.supports_tools() {
+            sampling.tools.get_or_insert_with(JsonObject::new);
+        }
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn models() -> Vec<SamplingModel> {
+        vec![
+            SamplingModel::new("small-fast")
+                .with_cost(0.9)
+                .with_speed(0.9)
+                .with_intelligence(0.2),
+            SamplingModel::new("large-smart")
+                .with_cost(0.1)
+                .with_speed(0.3)
+                .with_intelligence(0.9),
+        ]
+    }
+
+    #[test]
+    fn test_select_model() {
+        let models = models();
+        assert_eq!(select_model(&models, None).unwrap().name, "small-fast");
+        assert!(select_model(&[], None).is_none());
+
+        let smart = ModelPreferences {
+            intelligence_priority: Some(0.9),
+            cost_priority: Some(0.1),
+            ..ModelPreferences::new()
+        };
+        assert_eq!(
+            select_model(&models, Some(&smart)).unwrap().name,
+            "large-smart"
+        );
+
+        // hints win over priorities, in order
+        let hinted = ModelPreferences {
+            hints: Some(vec![ModelHint::new("unknown"), ModelHint::new("fast")]),
+            ..smart
+        };
+        assert_eq!(
+            select_model(&models, Some(&hinted)).unwrap().name,
+            "small-fast"
+        );
+
+        // without priorities every model scores the same
+        assert_eq!(
+            select_model(&models, Some(&ModelPreferences::new()))
+                .unwrap()
+                .name,
+            "small-fast"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! A [`SamplingProvider`] for OpenAI-compatible chat completion APIs.
+use futures::future::{BoxFuture, FutureExt};
+use serde_json::{Value, json};
+
+use super::SamplingProvider;
+use crate::{error::ErrorData as McpError, model::*};
+
+/// Sends sampling requests to `{base_url}/chat/completions`, as served by
+/// OpenAI and compatible servers (vLLM, Ollama, LM Studio, ...).
+///
+/// Text, image, tool use and tool result content are supported; audio, and
+/// tool results with anything but text or structured content, are rejected.
+#[derive(Debug, Clone)]
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiProvider {
+    /// A provider for the API at `base_url`, such as
+    /// `https://api.openai.com/v1`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    pub fn with_client(client: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            api_key: None,
+        }
+    }
+
+    /// Send `key` as a bearer token.
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    async fn complete(
+        &self,
+        model: &str,
+        params: CreateMessageRequestParams,
+    ) -> Result<CreateMessageResult, McpError> {
+        let body = request_body(model, &params)?;
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|error| {
+            McpError::internal_error(format!("sampling backend unreachable: {error}"), None)
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(McpError::internal_error(
+                format!("sampling backend returned {status}: {body}"),
+                None,
+            ));
+        }
+        let body: Value = response.json().await.map_err(|error| {
+            McpError::internal_error(format!("invalid sampling backend response: {error}"), None)
+        })?;
+        parse_response(model, &body)
+    }
+}
+
+impl SamplingProvider for OpenAiProvider {
+    fn create_message<'a>(
+        &'a self,
+        model: &'a str,
+        params: CreateMessageRequestParams,
+    ) -> BoxFuture<'a, Result<CreateMessageResult, McpError>> {
+        self.complete(model, params).boxed()
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+}
+
+fn unsupported(what: &str) -> McpError {
+    McpError::invalid_params(
+        format!("{what} is not supported by the sampling backend"),
+        None,
+    )
+}
+
+fn request_body(model: &str, params: &CreateMessageRequestParams) -> Result<Value, McpError> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = &params.system_prompt {
+        messages.push(json!({ "role": "system", "content": system_prompt }));
+    }
+    for message in &params.messages {
+        push_message(&mut messages, message)?;
+    }
+    let mut body = json!({
+        "model": model,
+        "messages": messages,
+        "max_tokens": params.max_tokens,
+    });
+    if let Some(temperature) = params.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(stop) = &params.stop_sequences {
+        body["stop"] = json!(stop);
+    }
+    if let Some(tools) = &params.tools {
+        let tools: Vec<_> = tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.input_schema,
+                    },
+                })
+            })
+            .collect();
+        body["tools"] = json!(tools);
+    }
+    if let Some(mode) = params
+        .tool_choice
+        .as_ref()
+        .and_then(|choice| choice.mode.as_ref())
+    {
+        body["tool_choice"] = json!(match mode {
+            ToolChoiceMode::Auto => "auto",
+            ToolChoiceMode::Required => "required",
+            ToolChoiceMode::None => "none",
+        });
+    }
+    Ok(body)
+}
+
+/// Append the chat messages for `message`. Tool results become `tool`
+/// messages of their own, and tool uses become the `tool_calls` of the
+/// assistant message.
+fn push_message(messages: &mut Vec<Value>, message: &SamplingMessage) -> Result<(), McpError> {
+    let role = match message.role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+    };
+    let mut parts = Vec::new();
+    let mut tool_calls = Vec::new();
+    for content in message.content.iter() {
+        match content {
+            SamplingMessageContent::Text(text) => {
+                parts.push(json!({ "type": "text", "text": text.text }))
+            }
+            SamplingMessageContent::Image(image) => parts.push(json!({
+                "type": "image_url",
+                "image_url": { "url": format!("data:{};base64,{}", image.mime_type, image.data) },
+            })),
+            SamplingMessageContent::Audio(_) => return Err(unsupported("audio content")),
+            SamplingMessageContent::ToolUse(tool_use) => tool_calls.push(json!({
+                "id": tool_use.id,
+                "type": "function",
+                "function": {
+                    "name": tool_use.name,
+                    "arguments": Value::Object(tool_use.input.clone()).to_string(),
+                },
+            })),
+            SamplingMessageContent::ToolResult(result) => messages.push(tool_message(result)?),
+        }
+    }
+    if parts.is_empty() && tool_calls.is_empty() {
+        return Ok(());
+    }
+    // an assistant message that only calls tools has no content
+    let content = if parts.is_empty() {
+        Value::Null
+    } else {
+        json!(parts)
+    };
+    let mut chat = json!({ "role": role, "content": content });
+    if !tool_calls.is_empty() {
+        chat["tool_calls"] = json!(tool_calls);
+    }
+    messages.push(chat);
+    Ok(())
+}
+
+/// The `tool` message for a tool result. The API only takes text, so
+/// structured content is sent as JSON when there is no text, and a failed
+/// call is marked as such; other content is rejected.
+fn tool_message(result: &ToolResultContent) -> Result<Value, McpError> {
+    let mut text = Vec::new();
+    for content in &result.content {
+        match content.as_text() {
+            Some(content) => text.push(content.text.clone()),
+            None => return Err(unsupported("non-text tool result content")),
+        }
+    }
+    if text.is_empty() {
+        if let Some(structured) = &result.structured_content {
+            text.push(Value::Object(structured.clone()).to_string());
+        }
+    }
+    let mut text = text.join("\n");
+    if result.is_error == Some(true) {
+        text = format!("Error: {text}");
+    }
+    Ok(json!({
+        "role": "tool",
+        "tool_call_id": result.tool_use_id,
+        "content": text,
+    }))
+}
+
+fn parse_response(model: &str, body: &Value) -> Result<CreateMessageResult, McpError> {
+    let invalid = |what: &str| {
+        McpError::internal_error(format!("invalid sampling backend response: {what}"), None)
+    };
+    let choice = body["choices"]
+        .get(0)
+        .ok_or_else(|| invalid("no choices"))?;
+    let message = &choice["message"];
+    let mut content = Vec::new();
+    if let Some(text) = message["content"].as_str().filter(|text| !text.is_empty()) {
+        content.push(SamplingMessageContent::text(text));
+    }
+    for call in message["tool_calls"].as_array().into_iter().flatten() {
+        let function = &call["function"];
+        let arguments = function["arguments"].as_str().unwrap_or("{}");
+        let input = match serde_json::from_str(arguments) {
+            Ok(Value::Object(input)) => input,
+            _ => return Err(invalid("tool call arguments are not a JSON object")),
+        };
+        content.push(SamplingMessageContent::tool_use(
+            call["id"]
+                .as_str()
+                .ok_or_else(|| invalid("tool call without id"))?,
+            function["name"]
+                .as_str()
+                .ok_or_else(|| invalid("tool call without name"))?,
+            input,
+        ));
+    }
+    let stop_reason = match choice["finish_reason"].as_str() {
+        Some("stop") => Some(CreateMessageResult::STOP_REASON_END_TURN.to_owned()),
+        Some("length") => Some(CreateMessageResult::STOP_REASON_END_MAX_TOKEN.to_owned()),
+        Some("tool_calls") => Some(CreateMessageResult::STOP_REASON_TOOL_USE.to_owned()),
+        other => other.map(ToOwned::to_owned),
+    };
+    let mut result = CreateMessageResult::new(
+        SamplingMessage::new_multiple(Role::Assistant, content),
+        body["model"].as_str().unwrap_or(model).to_owned(),
+    );
+    result.stop_reason = stop_reason;
+    Ok(result)
+}
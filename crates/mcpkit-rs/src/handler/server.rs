@@ -7,11 +7,16 @@ use crate::{
 };
 
 pub mod common;
+pub mod completion;
+#[cfg(feature = "logging-layer")]
+pub mod logging;
 #[cfg(feature = "policy")]
 pub mod policy;
 pub mod prompt;
 mod resource;
+pub mod roots;
 pub mod router;
+pub mod sampling;
 pub mod tool;
 pub mod tool_name_validation;
 pub mod wrapper;
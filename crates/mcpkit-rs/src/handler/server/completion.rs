@@ -0,0 +1,297 @@
+//! Argument completion for prompts and resource templates.
+//!
+//! An [`ArgumentCompletions`] maps the arguments of a prompt, or the variables
+//! of a resource template, to completion providers. [`PromptRouter`] and
+//! [`ResourceTemplateRouter`] keep one per route and answer
+//! `completion/complete` requests with it, so a handler no longer matches on
+//! the reference and argument name itself.
+//!
+//! With the macros, providers are declared on the prompt. `values` completes
+//! from a static list with [`fuzzy_match`], `with` names an async method
+//! taking a [`CompletionContext`] and returning anything that implements
+//! [`IntoCompletionResult`]:
+//!
+//! ```rust,ignore
+//! #[prompt_router]
+//! impl CodeReview {
+//!     #[prompt(
+//!         complete(arg = "language", values = ["Rust", "Python", "TypeScript"]),
+//!         complete(arg = "framework", with = Self::complete_framework),
+//!     )]
+//!     async fn review(&self, Parameters(args): Parameters<ReviewArgs>) -> Vec<PromptMessage> {
+//!         // ...
+//!     }
+//!
+//!     async fn complete_framework(&self, context: &CompletionContext<'_, Self>) -> Vec<String> {
+//!         let frameworks: &[&str] = match context.get_argument("language") {
+//!             Some("Rust") => &["axum", "actix-web", "rocket"],
+//!             Some("Python") => &["django", "flask", "fastapi"],
+//!             _ => &[],
+//!         };
+//!         fuzzy_match(context.value(), frameworks)
+//!     }
+//! }
+//! ```
+//!
+//! [`prompt_handler`](crate::prompt_handler) then implements
+//! `ServerHandler::complete` with the router, unless the handler implements
+//! it itself. Resource templates are completed by the router given as
+//! `resource_router`, if any.
+//!
+//! [`PromptRouter`]: super::router::prompt::PromptRouter
+//! [`ResourceTemplateRouter`]: super::router::resource::ResourceTemplateRouter
+use std::{collections::HashMap, sync::Arc};
+
+use futures::future::BoxFuture;
+
+use super::common::AsRequestContext;
+use crate::{
+    RoleServer,
+    model::{ArgumentInfo, CompleteRequestParams, CompletionInfo, Reference},
+    service::RequestContext,
+};
+
+/// Context for completing one argument of a prompt or resource template
+pub struct CompletionContext<'a, S> {
+    pub server: &'a S,
+    pub r#ref: Reference,
+    /// The argument being completed, with what the user typed so far
+    pub argument: ArgumentInfo,
+    /// Arguments the user already resolved
+    pub arguments: HashMap<String, String>,
+    pub context: RequestContext<RoleServer>,
+}
+
+impl<'a, S> CompletionContext<'a, S> {
+    pub fn new(
+        server: &'a S,
+        request: CompleteRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Self {
+        Self {
+            server,
+            r#ref: request.r#ref,
+            argument: request.argument,
+            arguments: request
+                .context
+                .and_then(|context| context.arguments)
+                .unwrap_or_default(),
+            context,
+        }
+    }
+
+    /// The name of the argument being completed
+    pub fn argument_name(&self) -> &str {
+        &self.argument.name
+    }
+
+    /// What the user typed so far
+    pub fn value(&self) -> &str {
+        &self.argument.value
+    }
+
+    /// The value of a previously resolved argument
+    pub fn get_argument(&self, name: &str) -> Option<&str> {
+        self.arguments.get(name).map(String::as_str)
+    }
+}
+
+impl<S> AsRequestContext for CompletionContext<'_, S> {
+    fn as_request_context(&self) -> &RequestContext<RoleServer> {
+        &self.context
+    }
+
+    fn as_request_context_mut(&mut self) -> &mut RequestContext<RoleServer> {
+        &mut self.context
+    }
+}
+
+/// Trait for types that can be converted into the values of a completion
+pub trait IntoCompletionResult {
+    fn into_completion_result(self) -> Result<CompletionInfo, crate::ErrorData>;
+}
+
+impl IntoCompletionResult for CompletionInfo {
+    fn into_completion_result(self) -> Result<CompletionInfo, crate::ErrorData> {
+        Ok(self)
+    }
+}
+
+/// Keeps the first [`CompletionInfo::MAX_VALUES`] values and reports the rest
+/// through `total` and `hasMore`.
+impl IntoCompletionResult for Vec<String> {
+    fn into_completion_result(self) -> Result<CompletionInfo, crate::ErrorData> {
+        let total = self.len();
+        let mut values = self;
+        values.truncate(CompletionInfo::MAX_VALUES);
+        Ok(CompletionInfo {
+            values,
+            total: Some(total as u32),
+            has_more: Some(total > CompletionInfo::MAX_VALUES),
+        })
+    }
+}
+
+impl<T: IntoCompletionResult> IntoCompletionResult for Result<T, crate::ErrorData> {
+    fn into_completion_result(self) -> Result<CompletionInfo, crate::ErrorData> {
+        self.and_then(IntoCompletionResult::into_completion_result)
+    }
+}
+
+/// Type alias for dynamic completion providers
+pub type DynCompletionProvider<S> = dyn for<'a> Fn(CompletionContext<'a, S>) -> BoxFuture<'a, Result<CompletionInfo, crate::ErrorData>>
+    + Send
+    + Sync;
+
+/// Completion providers for the arguments of one prompt or resource template
+pub struct ArgumentCompletions<S> {
+    #[allow(clippy::type_complexity)]
+    pub map: HashMap<String, Arc<DynCompletionProvider<S>>>,
+}
+
+impl<S> std::fmt::Debug for ArgumentCompletions<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.map.keys()).finish()
+    }
+}
+
+impl<S> Default for ArgumentCompletions<S> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<S> Clone for ArgumentCompletions<S> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<S: Send + Sync + 'static> ArgumentCompletions<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Complete `argument` with a provider
+    pub fn with_provider<H>(mut self, argument: impl Into<String>, provider: H) -> Self
+    where
+        H: for<'a> Fn(
+                CompletionContext<'a, S>,
+            ) -> BoxFuture<'a, Result<CompletionInfo, crate::ErrorData>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.map.insert(argument.into(), Arc::new(provider));
+        self
+    }
+
+    /// Complete `argument` from a static list with [`fuzzy_match`]
+    pub fn with_values<I>(self, argument: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let values: Arc<[String]> = values.into_iter().map(Into::into).collect();
+        self.with_provider(argument, move |context| {
+            let matches = fuzzy_match(context.value(), values.iter());
+            Box::pin(std::future::ready(matches.into_completion_result()))
+        })
+    }
+
+    pub fn merge(&mut self, other: ArgumentCompletions<S>) {
+        self.map.extend(other.map);
+    }
+
+    pub fn has_provider(&self, argument: &str) -> bool {
+        self.map.contains_key(argument)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Complete the argument of `context`, with no values when it has no
+    /// provider
+    pub async fn complete(
+        &self,
+        context: CompletionContext<'_, S>,
+    ) -> Result<CompletionInfo, crate::ErrorData> {
+        match self.map.get(context.argument_name()) {
+            Some(provider) => provider(context).await,
+            None => Ok(CompletionInfo::default()),
+        }
+    }
+}
+
+/// Filter `candidates` by `query` and order them by how well they match.
+///
+/// Matching ignores case. Exact matches come first, then prefixes of the
+/// candidate, prefixes of one of its words (split on separators and
+/// `camelCase` humps), substrings, and finally candidates containing the
+/// characters of the query in order. Candidates that match equally well keep
+/// their order, and an empty query matches every candidate.
+pub fn fuzzy_match<I>(query: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let query = query.to_lowercase();
+    let mut scored: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let candidate = candidate.as_ref();
+            match_rank(&query, candidate).map(|rank| (rank, candidate.to_string()))
+        })
+        .collect();
+    scored.sort_by_key(|(rank, _)| *rank);
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+fn match_rank(query: &str, candidate: &str) -> Option<u8> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let lower = candidate.to_lowercase();
+    if lower == query {
+        Some(0)
+    } else if lower.starts_with(query) {
+        Some(1)
+    } else if words(candidate).any(|word| word.to_lowercase().starts_with(query)) {
+        Some(2)
+    } else if lower.contains(query) {
+        Some(3)
+    } else if is_subsequence(query, &lower) {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+/// Split on non-alphanumeric characters and before an uppercase letter that
+/// follows a lowercase one.
+fn words(candidate: &str) -> impl Iterator<Item = &str> {
+    let mut starts = Vec::new();
+    let mut previous: Option<char> = None;
+    for (index, c) in candidate.char_indices() {
+        let starts_word = c.is_alphanumeric()
+            && match previous {
+                None => true,
+                Some(p) => !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase()),
+            };
+        if starts_word {
+            starts.push(index);
+        }
+        previous = Some(c);
+    }
+    starts.into_iter().map(move |start| &candidate[start..])
+}
+
+fn is_subsequence(query: &str, candidate: &str) -> bool {
+    let mut chars = candidate.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
@@ -0,0 +1,504 @@
+//! Forwarding the server's `tracing` events to the client.
+//!
+//! An [`McpLoggingLayer`] is a [`tracing_subscriber::Layer`] bound to the
+//! [`Peer`] of one session. Each event at or above the level the client asked
+//! for with `logging/setLevel` is sent as a `notifications/message`, with the
+//! event's target as the logger and its fields, together with the fields of
+//! the spans it happened in, as structured `data`:
+//!
+//! ```json
+//! {
+//!   "message": "fetched page",
+//!   "fields": { "status": 200 },
+//!   "spans": [{ "name": "fetch", "fields": { "url": "https://example.com" } }]
+//! }
+//! ```
+//!
+//! `tracing` has five levels and MCP eight. `ERROR`, `WARN`, `INFO` and
+//! `DEBUG` map onto their MCP namesakes and `TRACE` onto `debug`; an
+//! `mcp_level` field picks any of the eight levels explicitly:
+//!
+//! ```rust,ignore
+//! tracing::error!(mcp_level = "critical", "database unreachable");
+//! ```
+//!
+//! The client's level is kept in a [`LoggingLevelHandle`], which the handler
+//! updates from `set_level`:
+//!
+//! ```rust,ignore
+//! let levels = LoggingLevelHandle::default();
+//! let service = Server { levels: levels.clone() }.serve(stdio()).await?;
+//! tracing_subscriber::registry()
+//!     .with(McpLoggingLayer::new(service.peer().clone()).with_level_handle(levels))
+//!     .init();
+//!
+//! impl ServerHandler for Server {
+//!     async fn set_level(&self, request: SetLevelRequestParams, _: RequestContext<RoleServer>) -> Result<(), ErrorData> {
+//!         self.levels.set(request.level);
+//!         Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! The layer sees every event of the subscriber it is installed in, whichever
+//! session caused it. Without a session, it sends them all to its one client,
+//! which suits servers with a single session, such as stdio servers. A server
+//! with several sessions, such as a streamable HTTP server, gives each layer
+//! a session with [`McpLoggingLayer::with_session`] and handles the session's
+//! requests inside a span with a matching [`SESSION_FIELD`]; the layer then
+//! forwards only the events in that span:
+//!
+//! ```rust,ignore
+//! let layer = McpLoggingLayer::new(peer).with_session(session_id.to_string());
+//! let span = tracing::info_span!("session", mcp_session = %session_id);
+//! async move { /* ... */ }.instrument(span).await;
+//! ```
+//!
+//! Notifications are sent from a background task, so the layer must be
+//! created inside a Tokio runtime. To avoid feedback loops, events from
+//! `mcpkit_rs` itself are never forwarded: the service emits some for every
+//! message it sends, notifications included. Events from the HTTP stack are
+//! not forwarded either unless configured otherwise, and neither are events
+//! emitted by the task sending the notifications. Messages beyond the rate
+//! limit, or while the queue is full, are dropped; the next message that gets
+//! through is preceded by a warning saying how many were lost.
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU8, AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use serde_json::{Map, Value, json};
+use tokio::{sync::mpsc, time::Instant};
+use tracing::{
+    Event, Level, Subscriber,
+    field::{Field, Visit},
+    span,
+};
+use tracing_subscriber::{layer, registry::LookupSpan};
+
+use crate::{
+    RoleServer,
+    model::{LoggingLevel, LoggingMessageNotificationParam},
+    service::Peer,
+};
+
+/// How many notifications may wait to be sent before new ones are dropped.
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Targets whose events are not forwarded unless configured otherwise: the
+/// HTTP stack the transports are built on.
+pub const DEFAULT_IGNORED_TARGETS: &[&str] = &["hyper", "hyper_util", "h2"];
+
+/// The span field naming the session an event belongs to, see
+/// [`McpLoggingLayer::with_session`].
+pub const SESSION_FIELD: &str = "mcp_session";
+
+/// This crate, whose events are never forwarded.
+const CRATE_TARGET: &str = "mcpkit_rs";
+
+/// The minimum level the client asked for with `logging/setLevel`.
+///
+/// Clones share the level, so the handler and the layer of a session can each
+/// keep one.
+#[derive(Debug, Clone)]
+pub struct LoggingLevelHandle {
+    level: Arc<AtomicU8>,
+}
+
+impl Default for LoggingLevelHandle {
+    /// Starts at `info`, until the client sets a level.
+    fn default() -> Self {
+        Self::new(LoggingLevel::Info)
+    }
+}
+
+impl LoggingLevelHandle {
+    pub fn new(level: LoggingLevel) -> Self {
+        Self {
+            level: Arc::new(AtomicU8::new(level as u8)),
+        }
+    }
+
+    pub fn set(&self, level: LoggingLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> LoggingLevel {
+        LEVELS[usize::from(self.level.load(Ordering::Relaxed))]
+    }
+
+    /// Whether a message at `level` should be sent to the client
+    pub fn allows(&self, level: LoggingLevel) -> bool {
+        level as u8 >= self.level.load(Ordering::Relaxed)
+    }
+}
+
+/// Every level, from least to most severe.
+const LEVELS: [LoggingLevel; 8] = [
+    LoggingLevel::Debug,
+    LoggingLevel::Info,
+    LoggingLevel::Notice,
+    LoggingLevel::Warning,
+    LoggingLevel::Error,
+    LoggingLevel::Critical,
+    LoggingLevel::Alert,
+    LoggingLevel::Emergency,
+];
+
+/// The MCP level of a `tracing` level.
+pub fn logging_level(level: &Level) -> LoggingLevel {
+    match *level {
+        Level::ERROR => LoggingLevel::Error,
+        Level::WARN => LoggingLevel::Warning,
+        Level::INFO => LoggingLevel::Info,
+        Level::DEBUG | Level::TRACE => LoggingLevel::Debug,
+    }
+}
+
+/// A token bucket refilled at `per_second`, holding at most `burst` tokens.
+#[derive(Debug)]
+struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(per_second: u32, burst: u32) -> Self {
+        Self {
+            per_second: f64::from(per_second),
+            burst: f64::from(burst.max(1)),
+            state: Mutex::new((f64::from(burst.max(1)), Instant::now())),
+        }
+    }
+
+    fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, last) = &mut *state;
+        let now = Instant::now();
+        *tokens =
+            (*tokens + now.duration_since(*last).as_secs_f64() * self.per_second).min(self.burst);
+        *last = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+thread_local! {
+    static FORWARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+pin_project_lite::pin_project! {
+    /// Marks the thread as forwarding while the inner future is polled, so
+    /// events the forwarding task emits itself are not forwarded again.
+    struct Forwarding<F> {
+        #[pin]
+        inner: F,
+    }
+}
+
+impl<F: Future> Future for Forwarding<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let previous = FORWARDING.with(|forwarding| forwarding.replace(true));
+        let output = self.project().inner.poll(cx);
+        FORWARDING.with(|forwarding| forwarding.set(previous));
+        output
+    }
+}
+
+/// A [`tracing_subscriber::Layer`] sending events to the client of one session
+/// as `notifications/message`, see the [module docs](self).
+#[derive(Debug)]
+pub struct McpLoggingLayer {
+    sender: mpsc::Sender<LoggingMessageNotificationParam>,
+    level: LoggingLevelHandle,
+    ignored_targets: Vec<String>,
+    rate_limiter: Option<RateLimiter>,
+    session: Option<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl McpLoggingLayer {
+    /// Forward events to the client of `peer`, with room for
+    /// [`DEFAULT_QUEUE_CAPACITY`] pending notifications.
+    pub fn new(peer: Peer<RoleServer>) -> Self {
+        Self::with_capacity(peer, DEFAULT_QUEUE_CAPACITY)
+    }
+
+    /// Forward events to the client of `peer`, dropping new ones while
+    /// `capacity` notifications are waiting to be sent.
+    pub fn with_capacity(peer: Peer<RoleServer>, capacity: usize) -> Self {
+        let (sender, mut receiver) = mpsc::channel(capacity.max(1));
+        tokio::spawn(Forwarding {
+            inner: async move {
+                while let Some(message) = receiver.recv().await {
+                    if peer.notify_logging_message(message).await.is_err() {
+                        break;
+                    }
+                }
+            },
+        });
+        Self {
+            sender,
+            level: LoggingLevelHandle::default(),
+            ignored_targets: DEFAULT_IGNORED_TARGETS
+                .iter()
+                .map(|target| target.to_string())
+                .collect(),
+            rate_limiter: None,
+            session: None,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Share the client's level with the handler, which sets it from
+    /// `set_level`.
+    pub fn with_level_handle(mut self, level: LoggingLevelHandle) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Send at most `per_second` messages per second on average, and at
+    /// most `burst` at once.
+    pub fn with_rate_limit(mut self, per_second: u32, burst: u32) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(per_second, burst));
+        self
+    }
+
+    /// Forward only the events inside a span whose [`SESSION_FIELD`] is
+    /// `session`, so that several sessions can each have a layer in the same
+    /// subscriber.
+    ///
+    /// The innermost span with the field decides; events outside any such
+    /// span are not forwarded.
+    pub fn with_session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Do not forward events whose target is `target` or one of its modules.
+    pub fn ignore_target(mut self, target: impl Into<String>) -> Self {
+        self.ignored_targets.push(target.into());
+        self
+    }
+
+    /// Forward events from every target but `mcpkit_rs`, including the HTTP
+    /// stack.
+    ///
+    /// On HTTP transports, sending a notification then emits events that are
+    /// forwarded in turn.
+    pub fn without_ignored_targets(mut self) -> Self {
+        self.ignored_targets.clear();
+        self
+    }
+
+    pub fn level_handle(&self) -> LoggingLevelHandle {
+        self.level.clone()
+    }
+
+    /// How many messages were dropped by the rate limit or a full queue.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn is_ignored(&self, target: &str) -> bool {
+        std::iter::once(CRATE_TARGET)
+            .chain(self.ignored_targets.iter().map(String::as_str))
+            .any(|ignored| {
+                target
+                    .strip_prefix(ignored)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+    }
+
+    fn in_session<S>(&self, event: &Event<'_>, ctx: &layer::Context<'_, S>) -> bool
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let Some(session) = &self.session else {
+            return true;
+        };
+        ctx.event_scope(event)
+            .and_then(|mut scope| {
+                scope.find_map(|span| {
+                    span.extensions()
+                        .get::<SpanFields>()
+                        .and_then(|fields| fields.0.get(SESSION_FIELD).cloned())
+                })
+            })
+            .is_some_and(|value| value.as_str() == Some(session.as_str()))
+    }
+
+    fn send(&self, message: LoggingMessageNotificationParam) {
+        if self
+            .rate_limiter
+            .as_ref()
+            .is_some_and(|limiter| !limiter.try_acquire())
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            let warning = LoggingMessageNotificationParam {
+                level: LoggingLevel::Warning,
+                logger: message.logger.clone(),
+                data: json!({ "message": format!("dropped {dropped} log messages") }),
+            };
+            if self.sender.try_send(warning).is_err() {
+                self.dropped.fetch_add(dropped, Ordering::Relaxed);
+            }
+        }
+        if self.sender.try_send(message).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The fields of a span, kept in its extensions.
+struct SpanFields(Map<String, Value>);
+
+/// Collects fields as JSON, keeping `message` and `mcp_level` apart.
+#[derive(Default)]
+struct JsonVisitor {
+    fields: Map<String, Value>,
+    message: Option<String>,
+    level: Option<LoggingLevel>,
+}
+
+impl JsonVisitor {
+    fn record(&mut self, field: &Field, value: Value) {
+        match field.name() {
+            "message" => {
+                self.message = Some(match value {
+                    Value::String(message) => message,
+                    value => value.to_string(),
+                })
+            }
+            "mcp_level" => self.level = serde_json::from_value(value).ok(),
+            name => {
+                self.fields.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, json!(value));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.record(field, json!(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record(field, json!(format!("{value:?}")));
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for McpLoggingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        // another layer, of another session, may have recorded them already
+        let mut extensions = span.extensions_mut();
+        if extensions.get_mut::<SpanFields>().is_some() {
+            return;
+        }
+        let mut visitor = JsonVisitor::default();
+        attrs.record(&mut visitor);
+        extensions.insert(SpanFields(visitor.fields));
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = JsonVisitor::default();
+        values.record(&mut visitor);
+        if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+            fields.0.extend(visitor.fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: layer::Context<'_, S>) {
+        let metadata = event.metadata();
+        if FORWARDING.with(Cell::get)
+            || self.is_ignored(metadata.target())
+            || !self.in_session(event, &ctx)
+        {
+            return;
+        }
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        let level = visitor
+            .level
+            .unwrap_or_else(|| logging_level(metadata.level()));
+        if !self.level.allows(level) {
+            return;
+        }
+
+        let mut data = Map::new();
+        if let Some(message) = visitor.message {
+            data.insert("message".to_string(), Value::String(message));
+        }
+        if !visitor.fields.is_empty() {
+            data.insert("fields".to_string(), Value::Object(visitor.fields));
+        }
+        if let Some(scope) = ctx.event_scope(event) {
+            let spans: Vec<Value> = scope
+                .from_root()
+                .map(|span| {
+                    let fields = span
+                        .extensions()
+                        .get::<SpanFields>()
+                        .map(|fields| fields.0.clone())
+                        .unwrap_or_default();
+                    json!({ "name": span.name(), "fields": fields })
+                })
+                .collect();
+            if !spans.is_empty() {
+                data.insert("spans".to_string(), Value::Array(spans));
+            }
+        }
+
+        self.send(LoggingMessageNotificationParam {
+            level,
+            logger: Some(metadata.target().to_string()),
+            data: Value::Object(data),
+        });
+    }
+}
@@ -3,9 +3,10 @@
 //! This module provides transparent policy enforcement that works with any
 //! ServerHandler implementation without modifying the MCP protocol.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use crate::{
+    audit::{AuditLog, AuditOutcome, PolicyDecision},
     error::ErrorData,
     handler::server::ServerHandler,
     model::*,
@@ -21,6 +22,8 @@ use crate::{
 pub struct PolicyEnabledServer<H: ServerHandler> {
     inner: H,
     policy: Option<Arc<mcpkit_rs_policy::CompiledPolicy>>,
+    audit: Option<AuditLog>,
+    policy_state: Option<Arc<mcpkit_rs_policy::core::PolicyState>>,
 }
 
 impl<H: ServerHandler> PolicyEnabledServer<H> {
@@ -29,6 +32,8 @@ impl<H: ServerHandler> PolicyEnabledServer<H> {
         Self {
             inner,
             policy: None,
+            audit: None,
+            policy_state: None,
         }
     }
 
@@ -44,6 +49,8 @@ impl<H: ServerHandler> PolicyEnabledServer<H> {
         Ok(Self {
             inner,
             policy: Some(Arc::new(compiled)),
+            audit: None,
+            policy_state: None,
         })
     }
 
@@ -52,6 +59,27 @@ impl<H: ServerHandler> PolicyEnabledServer<H> {
         Self {
             inner,
             policy: Some(policy),
+            audit: None,
+            policy_state: None,
+        }
+    }
+
+    /// Record tool calls and resource reads, including denials, to `audit`
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Fold the violations a runtime enforcer records in `state` into the
+    /// audit log after every audited request
+    pub fn with_policy_state(mut self, state: Arc<mcpkit_rs_policy::core::PolicyState>) -> Self {
+        self.policy_state = Some(state);
+        self
+    }
+
+    async fn record_violations(&self, audit: &AuditLog) {
+        if let Some(state) = &self.policy_state {
+            audit.record_violations(state).await;
         }
     }
 
@@ -70,6 +98,17 @@ impl<H: ServerHandler> PolicyEnabledServer<H> {
         self.policy.is_some()
     }
 
+    fn decision(
+        &self,
+        allowed: impl FnOnce(&mcpkit_rs_policy::CompiledPolicy) -> bool,
+    ) -> PolicyDecision {
+        match &self.policy {
+            Some(policy) if allowed(policy) => PolicyDecision::Allowed,
+            Some(_) => PolicyDecision::Denied,
+            None => PolicyDecision::NotEvaluated,
+        }
+    }
+
     /// Standard MCP error for permission denied
     fn permission_denied(action: &str, resource: &str) -> ErrorData {
         ErrorData {
@@ -123,14 +162,41 @@ impl<H: ServerHandler> ServerHandler for PolicyEnabledServer<H> {
         params: CallToolRequestParams,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        // Check tool permission if policy is enabled
-        if let Some(policy) = &self.policy {
-            if !policy.is_tool_allowed(&params.name) {
+        let decision = self.decision(|policy| policy.is_tool_allowed(&params.name));
+
+        let Some(audit) = &self.audit else {
+            if decision == PolicyDecision::Denied {
                 return Err(Self::permission_denied("tool", &params.name));
             }
+            return self.inner.call_tool(params, context).await;
+        };
+
+        let started = Instant::now();
+        let mut record = crate::audit::server_record("tools/call", &context)
+            .with_target(params.name.as_ref())
+            .with_decision(decision);
+        if let Some(arguments) = &params.arguments {
+            record = record.with_arguments_digest(audit.digest_arguments(arguments));
         }
 
-        self.inner.call_tool(params, context).await
+        let result = if decision == PolicyDecision::Denied {
+            Err(Self::permission_denied("tool", &params.name))
+        } else {
+            self.inner.call_tool(params, context).await
+        };
+
+        let outcome = match &result {
+            Ok(result) if result.is_error == Some(true) => AuditOutcome::ToolError,
+            Ok(_) => AuditOutcome::Success,
+            Err(error) => AuditOutcome::from(error),
+        };
+        audit.record(
+            &record
+                .with_duration(started.elapsed())
+                .with_outcome(outcome),
+        );
+        self.record_violations(audit).await;
+        result
     }
 
     async fn list_resources(
@@ -146,14 +212,37 @@ impl<H: ServerHandler> ServerHandler for PolicyEnabledServer<H> {
         params: ReadResourceRequestParams,
         context: RequestContext<RoleServer>,
     ) -> Result<ReadResourceResult, ErrorData> {
-        // Check resource permission if policy is enabled
-        if let Some(policy) = &self.policy {
-            if !policy.is_storage_allowed(&params.uri, "read") {
+        let decision = self.decision(|policy| policy.is_storage_allowed(&params.uri, "read"));
+
+        let Some(audit) = &self.audit else {
+            if decision == PolicyDecision::Denied {
                 return Err(Self::permission_denied("resource", &params.uri));
             }
-        }
+            return self.inner.read_resource(params, context).await;
+        };
+
+        let started = Instant::now();
+        let record = crate::audit::server_record("resources/read", &context)
+            .with_target(params.uri.clone())
+            .with_decision(decision);
+
+        let result = if decision == PolicyDecision::Denied {
+            Err(Self::permission_denied("resource", &params.uri))
+        } else {
+            self.inner.read_resource(params, context).await
+        };
 
-        self.inner.read_resource(params, context).await
+        let outcome = match &result {
+            Ok(_) => AuditOutcome::Success,
+            Err(error) => AuditOutcome::from(error),
+        };
+        audit.record(
+            &record
+                .with_duration(started.elapsed())
+                .with_outcome(outcome),
+        );
+        self.record_violations(audit).await;
+        result
     }
 
     async fn list_prompts(
@@ -0,0 +1,316 @@
+//! Scoping file access to the client's roots.
+//!
+//! A [`RootsGuard`] caches the roots the client shared through `roots/list`
+//! and checks paths against them: [`RootsGuard::resolve`] canonicalizes a
+//! path, following symlinks and `..`, and rejects it unless it lies inside
+//! one of the roots. Roots that are not `file://` URIs, or that do not exist
+//! on this machine, are ignored.
+//!
+//! Requests are handled one at a time by the service loop, so the roots can
+//! not be listed while a request or notification is being handled. Keep one
+//! guard per session, for example as a field of a handler created for each
+//! connection, list the roots in the background once the session is
+//! initialized and whenever they change, and resolve paths against the cache
+//! in request handlers with [`RootsGuard::resolve_cached`]:
+//!
+//! ```rust,ignore
+//! impl ServerHandler for FileServer {
+//!     async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+//!         self.roots.refresh_in_background(context.peer);
+//!     }
+//!
+//!     async fn on_roots_list_changed(&self, context: NotificationContext<RoleServer>) {
+//!         self.roots.on_roots_list_changed(context.peer);
+//!     }
+//! }
+//!
+//! #[tool(description = "Read a file")]
+//! async fn read_file(&self, Parameters(ReadFile { path }): Parameters<ReadFile>) -> Result<String, ErrorData> {
+//!     let path = self.roots.resolve_cached(path)?;
+//!     std::fs::read_to_string(path).map_err(|e| ErrorData::internal_error(e.to_string(), None))
+//! }
+//! ```
+//!
+//! Tasks and spawned futures can await [`RootsGuard::resolve`] instead, which
+//! lists the roots when they are not cached.
+//!
+//! With the `wasm-tools` feature, [`RootsGuard::preopen_dirs`] turns the roots
+//! into directories for [`WasmContext::with_preopen_dirs`](crate::wasm::WasmContext::with_preopen_dirs),
+//! so WASM tools see exactly the directories the user shared.
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use thiserror::Error;
+
+use crate::{ErrorData, RoleServer, ServiceError, model::Root, service::Peer};
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum RootsError {
+    #[error("failed to list roots: {0}")]
+    Service(#[from] ServiceError),
+    #[error("the client has not shared any roots")]
+    NoRoots,
+    #[error("the client's roots have not been listed yet")]
+    NotListed,
+    #[error("{} is outside the client's roots", .0.display())]
+    OutsideRoots(PathBuf),
+    #[error("failed to resolve {}: {error}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+}
+
+impl From<RootsError> for ErrorData {
+    fn from(err: RootsError) -> Self {
+        match err {
+            RootsError::NoRoots | RootsError::OutsideRoots(_) | RootsError::Io { .. } => {
+                ErrorData::invalid_params(err.to_string(), None)
+            }
+            _ => ErrorData::internal_error(err.to_string(), None),
+        }
+    }
+}
+
+/// A root shared by the client, with its canonical path on this machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedRoot {
+    pub root: Root,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Default)]
+struct RootsState {
+    roots: Option<Vec<ResolvedRoot>>,
+    /// Bumped whenever the cache is dropped, so that a refresh started
+    /// before the roots changed does not store stale roots.
+    generation: u64,
+}
+
+/// Caches the client's roots and checks paths against them.
+///
+/// Clones share the same cache.
+#[derive(Debug, Clone, Default)]
+pub struct RootsGuard {
+    state: Arc<Mutex<RootsState>>,
+}
+
+impl RootsGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The client's roots, listed on first use and cached until
+    /// [`invalidate`](Self::invalidate) is called. A client without the roots
+    /// capability has no roots.
+    ///
+    /// Listing the roots waits for the client, so do not await this directly
+    /// in a request handler.
+    pub async fn roots(&self, peer: &Peer<RoleServer>) -> Result<Vec<ResolvedRoot>, RootsError> {
+        if let Some(roots) = &self.state.lock().expect("roots lock poisoned").roots {
+            return Ok(roots.clone());
+        }
+        self.refresh(peer).await
+    }
+
+    /// List the client's roots again and cache them.
+    pub async fn refresh(&self, peer: &Peer<RoleServer>) -> Result<Vec<ResolvedRoot>, RootsError> {
+        let generation = self.state.lock().expect("roots lock poisoned").generation;
+        let supported = peer
+            .peer_info()
+            .is_some_and(|info| info.capabilities.roots.is_some());
+        let roots = if supported {
+            peer.list_roots()
+                .await?
+                .roots
+                .into_iter()
+                .filter_map(resolve_root)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut state = self.state.lock().expect("roots lock poisoned");
+        if state.generation == generation {
+            state.roots = Some(roots.clone());
+        }
+        Ok(roots)
+    }
+
+    /// The cached roots, if they have been listed.
+    pub fn cached_roots(&self) -> Option<Vec<ResolvedRoot>> {
+        self.state
+            .lock()
+            .expect("roots lock poisoned")
+            .roots
+            .clone()
+    }
+
+    /// Drop the cached roots, so that the next use lists them again.
+    pub fn invalidate(&self) {
+        let mut state = self.state.lock().expect("roots lock poisoned");
+        state.roots = None;
+        state.generation += 1;
+    }
+
+    /// List the client's roots in a spawned task, for use in handlers.
+    pub fn refresh_in_background(&self, peer: Peer<RoleServer>) {
+        let guard = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = guard.refresh(&peer).await {
+                tracing::warn!("failed to refresh roots: {e}");
+            }
+        });
+    }
+
+    /// Handle `notifications/roots/list_changed`: drop the cached roots and
+    /// list them again in the background. Until they are listed, paths are
+    /// rejected rather than checked against roots the user may have removed.
+    pub fn on_roots_list_changed(&self, peer: Peer<RoleServer>) {
+        self.invalidate();
+        self.refresh_in_background(peer);
+    }
+
+    /// Canonicalize `path` and check that it lies inside one of the roots,
+    /// listing them if they are not cached.
+    ///
+    /// Relative paths are taken relative to the first root. The path itself
+    /// need not exist, so that it can be created, but its parent directory
+    /// must, and it must not be a dangling symlink.
+    pub async fn resolve(
+        &self,
+        peer: &Peer<RoleServer>,
+        path: impl AsRef<Path>,
+    ) -> Result<PathBuf, RootsError> {
+        let roots = self.roots(peer).await?;
+        resolve_in(&roots, path.as_ref())
+    }
+
+    /// Like [`resolve`](Self::resolve), against the cached roots only, so it
+    /// is safe to call in a request handler.
+    pub fn resolve_cached(&self, path: impl AsRef<Path>) -> Result<PathBuf, RootsError> {
+        let roots = self.cached_roots().ok_or(RootsError::NotListed)?;
+        resolve_in(&roots, path.as_ref())
+    }
+
+    /// The cached roots as WASI preopened directories, each granted `access`
+    /// (`"read"` and/or `"write"`, as for
+    /// [`FsPermissionMapper::map_to_wasi`](crate::wasm::fs::FsPermissionMapper::map_to_wasi)).
+    /// Each root appears inside the guest at its host path.
+    #[cfg(feature = "wasm-tools")]
+    pub fn preopen_dirs(
+        &self,
+        access: &[String],
+    ) -> Result<Vec<crate::wasm::fs::PreopenDir>, RootsError> {
+        let (dir_perms, file_perms) = crate::wasm::fs::FsPermissionMapper::map_to_wasi(access);
+        let roots = self.cached_roots().ok_or(RootsError::NotListed)?;
+        Ok(roots
+            .into_iter()
+            .map(|root| crate::wasm::fs::PreopenDir {
+                host_path: root.path.clone(),
+                guest_path: root.path,
+                dir_perms,
+                file_perms,
+            })
+            .collect())
+    }
+}
+
+fn resolve_root(root: Root) -> Option<ResolvedRoot> {
+    let Some(path) = file_uri_to_path(&root.uri) else {
+        tracing::warn!(uri = %root.uri, "ignoring root that is not a file URI");
+        return None;
+    };
+    match std::fs::canonicalize(&path) {
+        Ok(path) => Some(ResolvedRoot { root, path }),
+        Err(e) => {
+            tracing::warn!(uri = %root.uri, "ignoring root that can not be resolved: {e}");
+            None
+        }
+    }
+}
+
+fn resolve_in(roots: &[ResolvedRoot], path: &Path) -> Result<PathBuf, RootsError> {
+    let first = roots.first().ok_or(RootsError::NoRoots)?;
+    let path = first.path.join(path);
+    let io = |error| RootsError::Io {
+        path: path.clone(),
+        error,
+    };
+    let resolved = match std::fs::canonicalize(&path) {
+        Ok(resolved) => resolved,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            // a file yet to be created: resolve its directory instead
+            let (Some(parent), Some(Component::Normal(name))) =
+                (path.parent(), path.components().next_back())
+            else {
+                return Err(io(e));
+            };
+            // unless it is a dangling symlink, which writing through would
+            // create wherever it points
+            if std::fs::symlink_metadata(&path).is_ok() {
+                return Err(io(e));
+            }
+            std::fs::canonicalize(parent).map_err(io)?.join(name)
+        }
+        Err(e) => return Err(io(e)),
+    };
+    if roots.iter().any(|root| resolved.starts_with(&root.path)) {
+        Ok(resolved)
+    } else {
+        Err(RootsError::OutsideRoots(path))
+    }
+}
+
+/// The local path of a `file://` URI, such as `file:///home/user/project`.
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let path = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !path.starts_with('/') {
+        return None;
+    }
+    let path = percent_decode(path)?;
+    // file:///C:/dir on Windows
+    if cfg!(windows) && path.as_bytes().get(2) == Some(&b':') {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    Some(PathBuf::from(path))
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut iter = input.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri_to_path() {
+        assert_eq!(
+            file_uri_to_path("file:///home/user/my%20project"),
+            Some(PathBuf::from("/home/user/my project"))
+        );
+        assert_eq!(
+            file_uri_to_path("file://localhost/tmp"),
+            Some(PathBuf::from("/tmp"))
+        );
+        assert_eq!(file_uri_to_path("https://example.com/tmp"), None);
+        assert_eq!(file_uri_to_path("file://server/share"), None);
+        assert_eq!(file_uri_to_path("file:///bad%2"), None);
+    }
+}
@@ -1,21 +1,27 @@
 use std::sync::Arc;
 
 use prompt::{IntoPromptRoute, PromptRoute};
+use resource::ResourceTemplateRoute;
 use tool::{IntoToolRoute, ToolRoute};
 
 use super::ServerHandler;
 use crate::{
     RoleServer, Service,
-    model::{ClientRequest, ListPromptsResult, ListToolsResult, ServerResult},
+    model::{
+        ClientRequest, ListPromptsResult, ListResourceTemplatesResult, ListToolsResult, Reference,
+        ServerResult,
+    },
     service::NotificationContext,
 };
 
 pub mod prompt;
+pub mod resource;
 pub mod tool;
 
 pub struct Router<S> {
     pub tool_router: tool::ToolRouter<S>,
     pub prompt_router: prompt::PromptRouter<S>,
+    pub resource_template_router: resource::ResourceTemplateRouter<S>,
     pub service: Arc<S>,
 }
 
@@ -27,6 +33,7 @@ where
         Self {
             tool_router: tool::ToolRouter::new(),
             prompt_router: prompt::PromptRouter::new(),
+            resource_template_router: resource::ResourceTemplateRouter::new(),
             service: Arc::new(service),
         }
     }
@@ -60,6 +67,21 @@ where
         }
         self
     }
+
+    pub fn with_resource_template(mut self, route: ResourceTemplateRoute<S>) -> Self {
+        self.resource_template_router.add_route(route);
+        self
+    }
+
+    pub fn with_resource_templates(
+        mut self,
+        routes: impl IntoIterator<Item = ResourceTemplateRoute<S>>,
+    ) -> Self {
+        for route in routes {
+            self.resource_template_router.add_route(route);
+        }
+        self
+    }
 }
 
 impl<S> Service<RoleServer> for Router<S>
@@ -128,6 +150,48 @@ where
                     ..Default::default()
                 }))
             }
+            ClientRequest::CompleteRequest(request) => {
+                let routed = match &request.params.r#ref {
+                    Reference::Prompt(prompt) => self.prompt_router.has_route(&prompt.name),
+                    Reference::Resource(resource) => {
+                        self.resource_template_router.has_route(&resource.uri)
+                    }
+                };
+                if routed {
+                    let completion_context =
+                        crate::handler::server::completion::CompletionContext::new(
+                            self.service.as_ref(),
+                            request.params,
+                            context,
+                        );
+                    let result = match completion_context.r#ref {
+                        Reference::Prompt(_) => {
+                            self.prompt_router.complete(completion_context).await?
+                        }
+                        Reference::Resource(_) => {
+                            self.resource_template_router
+                                .complete(completion_context)
+                                .await?
+                        }
+                    };
+                    Ok(ServerResult::CompleteResult(result))
+                } else {
+                    self.service
+                        .handle_request(ClientRequest::CompleteRequest(request), context)
+                        .await
+                }
+            }
+            ClientRequest::ListResourceTemplatesRequest(_)
+                if !self.resource_template_router.is_empty() =>
+            {
+                let resource_templates = self.resource_template_router.list_all();
+                Ok(ServerResult::ListResourceTemplatesResult(
+                    ListResourceTemplatesResult {
+                        resource_templates,
+                        ..Default::default()
+                    },
+                ))
+            }
             rest => self.service.handle_request(rest, context).await,
         }
     }
@@ -3,14 +3,18 @@ use std::{borrow::Cow, sync::Arc};
 use futures::future::BoxFuture;
 
 use crate::{
-    handler::server::prompt::{DynGetPromptHandler, GetPromptHandler, PromptContext},
-    model::{GetPromptResult, Prompt},
+    handler::server::{
+        completion::{ArgumentCompletions, CompletionContext},
+        prompt::{DynGetPromptHandler, GetPromptHandler, PromptContext},
+    },
+    model::{CompleteResult, GetPromptResult, Prompt, Reference},
 };
 
 pub struct PromptRoute<S> {
     #[allow(clippy::type_complexity)]
     pub get: Arc<DynGetPromptHandler<S>>,
     pub attr: crate::model::Prompt,
+    pub completions: ArgumentCompletions<S>,
 }
 
 impl<S> std::fmt::Debug for PromptRoute<S> {
@@ -19,6 +23,7 @@ impl<S> std::fmt::Debug for PromptRoute<S> {
             .field("name", &self.attr.name)
             .field("description", &self.attr.description)
             .field("arguments", &self.attr.arguments)
+            .field("completions", &self.completions)
             .finish()
     }
 }
@@ -28,6 +33,7 @@ impl<S> Clone for PromptRoute<S> {
         Self {
             get: self.get.clone(),
             attr: self.attr.clone(),
+            completions: self.completions.clone(),
         }
     }
 }
@@ -43,6 +49,7 @@ impl<S: Send + Sync + 'static> PromptRoute<S> {
                 handler.handle(context)
             }),
             attr: attr.into(),
+            completions: ArgumentCompletions::new(),
         }
     }
 
@@ -58,9 +65,16 @@ impl<S: Send + Sync + 'static> PromptRoute<S> {
         Self {
             get: Arc::new(handler),
             attr: attr.into(),
+            completions: ArgumentCompletions::new(),
         }
     }
 
+    /// Complete the arguments of this prompt with `completions`
+    pub fn with_completions(mut self, completions: ArgumentCompletions<S>) -> Self {
+        self.completions.merge(completions);
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.attr.name
     }
@@ -186,6 +200,23 @@ where
         (item.get)(context).await
     }
 
+    /// Complete an argument of a prompt with the providers of its route.
+    ///
+    /// Resource references are left to other routers and get no values.
+    pub async fn complete(
+        &self,
+        context: CompletionContext<'_, S>,
+    ) -> Result<CompleteResult, crate::ErrorData> {
+        let Reference::Prompt(prompt) = &context.r#ref else {
+            return Ok(CompleteResult::default());
+        };
+        let item = self.map.get(prompt.name.as_str()).ok_or_else(|| {
+            crate::ErrorData::invalid_params(format!("prompt '{}' not found", prompt.name), None)
+        })?;
+        let completion = item.completions.complete(context).await?;
+        Ok(CompleteResult::new(completion))
+    }
+
     pub fn list_all(&self) -> Vec<crate::model::Prompt> {
         let mut prompts: Vec<_> = self.map.values().map(|item| item.attr.clone()).collect();
         prompts.sort_by(|a, b| a.name.cmp(&b.name));
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crate::{
+    handler::server::completion::{ArgumentCompletions, CompletionContext},
+    model::{CompleteResult, Reference, ResourceTemplate},
+};
+
+pub struct ResourceTemplateRoute<S> {
+    pub attr: ResourceTemplate,
+    pub completions: ArgumentCompletions<S>,
+}
+
+impl<S> std::fmt::Debug for ResourceTemplateRoute<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceTemplateRoute")
+            .field("uri_template", &self.attr.uri_template)
+            .field("name", &self.attr.name)
+            .field("completions", &self.completions)
+            .finish()
+    }
+}
+
+impl<S> Clone for ResourceTemplateRoute<S> {
+    fn clone(&self) -> Self {
+        Self {
+            attr: self.attr.clone(),
+            completions: self.completions.clone(),
+        }
+    }
+}
+
+impl<S: Send + Sync + 'static> ResourceTemplateRoute<S> {
+    pub fn new(attr: ResourceTemplate) -> Self {
+        Self {
+            attr,
+            completions: ArgumentCompletions::new(),
+        }
+    }
+
+    /// Complete the variables of this template with `completions`.
+    ///
+    /// There is no attribute form for resource templates, unlike
+    /// `#[prompt(complete(...))]`; build the providers with
+    /// [`ArgumentCompletions`] instead.
+    pub fn with_completions(mut self, completions: ArgumentCompletions<S>) -> Self {
+        self.completions.merge(completions);
+        self
+    }
+
+    pub fn uri_template(&self) -> &str {
+        &self.attr.uri_template
+    }
+}
+
+/// Routes resource templates by their URI template, which is how
+/// `completion/complete` refers to them.
+#[derive(Debug)]
+pub struct ResourceTemplateRouter<S> {
+    pub map: HashMap<String, ResourceTemplateRoute<S>>,
+}
+
+impl<S> Default for ResourceTemplateRouter<S> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<S> Clone for ResourceTemplateRouter<S> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<S> IntoIterator for ResourceTemplateRouter<S> {
+    type Item = ResourceTemplateRoute<S>;
+    type IntoIter = std::collections::hash_map::IntoValues<String, ResourceTemplateRoute<S>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_values()
+    }
+}
+
+impl<S> ResourceTemplateRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_route(mut self, route: ResourceTemplateRoute<S>) -> Self {
+        self.add_route(route);
+        self
+    }
+
+    pub fn add_route(&mut self, item: ResourceTemplateRoute<S>) {
+        self.map.insert(item.attr.uri_template.clone(), item);
+    }
+
+    pub fn merge(&mut self, other: ResourceTemplateRouter<S>) {
+        for item in other.map.into_values() {
+            self.add_route(item);
+        }
+    }
+
+    pub fn remove_route(&mut self, uri_template: &str) {
+        self.map.remove(uri_template);
+    }
+
+    pub fn has_route(&self, uri_template: &str) -> bool {
+        self.map.contains_key(uri_template)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Complete a variable of a resource template with the providers of its
+    /// route.
+    ///
+    /// Prompt references are left to other routers and get no values.
+    pub async fn complete(
+        &self,
+        context: CompletionContext<'_, S>,
+    ) -> Result<CompleteResult, crate::ErrorData> {
+        let Reference::Resource(resource) = &context.r#ref else {
+            return Ok(CompleteResult::default());
+        };
+        let item = self.map.get(resource.uri.as_str()).ok_or_else(|| {
+            crate::ErrorData::invalid_params(
+                format!("resource template '{}' not found", resource.uri),
+                None,
+            )
+        })?;
+        let completion = item.completions.complete(context).await?;
+        Ok(CompleteResult::new(completion))
+    }
+
+    pub fn list_all(&self) -> Vec<ResourceTemplate> {
+        let mut templates: Vec<_> = self.map.values().map(|item| item.attr.clone()).collect();
+        templates.sort_by(|a, b| a.uri_template.cmp(&b.uri_template));
+        templates
+    }
+}
+
+impl<S> std::ops::Add<ResourceTemplateRouter<S>> for ResourceTemplateRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Output = Self;
+
+    fn add(mut self, other: ResourceTemplateRouter<S>) -> Self::Output {
+        self.merge(other);
+        self
+    }
+}
+
+impl<S> std::ops::AddAssign<ResourceTemplateRouter<S>> for ResourceTemplateRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    fn add_assign(&mut self, other: ResourceTemplateRouter<S>) {
+        self.merge(other);
+    }
+}
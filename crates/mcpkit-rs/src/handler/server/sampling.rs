@@ -0,0 +1,248 @@
+//! Sampling with the server's own tools.
+//!
+//! A [`SamplingSession`] asks the client's LLM to respond to a conversation
+//! with the tools of a [`ToolRouter`] available. Whenever the model answers
+//! with tool calls, the session runs them against the router, appends the
+//! results to the conversation and samples again, until the model ends its
+//! turn without calling a tool or a guard stops the loop:
+//!
+//! - at most [`SamplingSession::with_max_iterations`] sampling requests are
+//!   sent, [`DEFAULT_MAX_ITERATIONS`] by default;
+//! - with [`SamplingSession::with_token_budget`], the `maxTokens` granted to
+//!   the requests add up to at most the budget. Sampling results do not report
+//!   usage, so the budget bounds what the model may generate rather than what
+//!   it did.
+//!
+//! Requests are handled one at a time by the service loop, so a session that
+//! waits for the client must not be awaited directly in a request handler.
+//! Run it in a task (see [`task_handler`](crate::task_handler)) or a spawned
+//! future.
+//!
+//! ```rust,ignore
+//! let params = CreateMessageRequestParams::new(
+//!     vec![SamplingMessage::user_text("What is 2 + 3 * 4?")],
+//!     1024,
+//! );
+//! let transcript = SamplingSession::new(&self.tool_router, self, context, params)
+//!     .with_token_budget(8192)
+//!     .on_message(|message| tracing::info!(?message, "sampling"))
+//!     .run()
+//!     .await?;
+//! let answer = transcript.final_text();
+//! ```
+use thiserror::Error;
+
+use super::{router::tool::ToolRouter, tool::ToolCallContext};
+use crate::{RoleServer, ServiceError, model::*, service::RequestContext};
+
+/// How many sampling requests a session sends unless configured otherwise.
+pub const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum SamplingError {
+    #[error("sampling request failed: {0}")]
+    Service(#[from] ServiceError),
+    #[error("invalid sampling result: {0}")]
+    InvalidResult(String),
+}
+
+/// Why a [`SamplingSession`] stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SamplingStop {
+    /// The model ended its turn without calling a tool.
+    Finished {
+        /// The stop reason reported by the client, such as `endTurn`.
+        stop_reason: Option<String>,
+    },
+    /// The maximum number of sampling requests was sent.
+    MaxIterations,
+    /// The token budget was used up.
+    TokenBudget,
+}
+
+/// The conversation produced by a [`SamplingSession`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SamplingTranscript {
+    /// Every message of the conversation, starting with those the session
+    /// was created with.
+    pub messages: Vec<SamplingMessage>,
+    /// The model that produced the last response.
+    pub model: Option<String>,
+    /// The number of sampling requests sent.
+    pub iterations: usize,
+    /// The sum of the `maxTokens` granted to those requests.
+    pub tokens_requested: u32,
+    pub stop: SamplingStop,
+}
+
+impl SamplingTranscript {
+    /// The text of the last assistant message, if it has any.
+    pub fn final_text(&self) -> Option<String> {
+        let message = self
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::Assistant)?;
+        let texts: Vec<_> = message
+            .content
+            .iter()
+            .filter_map(|content| content.as_text())
+            .map(|text| text.text.as_str())
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n"))
+    }
+}
+
+type MessageCallback<'a> = Box<dyn FnMut(&SamplingMessage) + Send + 'a>;
+
+/// Drives a sampling conversation in which the model may call the tools of
+/// a [`ToolRouter`], see the [module docs](self).
+pub struct SamplingSession<'a, S> {
+    router: &'a ToolRouter<S>,
+    service: &'a S,
+    context: RequestContext<RoleServer>,
+    params: CreateMessageRequestParams,
+    max_iterations: usize,
+    token_budget: Option<u32>,
+    on_message: Option<MessageCallback<'a>>,
+}
+
+impl<'a, S> SamplingSession<'a, S>
+where
+    S: Send + Sync + 'static,
+{
+    /// A session sampling through the peer of `context`.
+    ///
+    /// `params` is used for every request: its messages start the
+    /// conversation and its `maxTokens` bounds each response. Unless
+    /// `params.tools` is set, every tool of `router` is offered to the model.
+    /// Tools are called with `service` and `context`.
+    pub fn new(
+        router: &'a ToolRouter<S>,
+        service: &'a S,
+        context: RequestContext<RoleServer>,
+        params: CreateMessageRequestParams,
+    ) -> Self {
+        Self {
+            router,
+            service,
+            context,
+            params,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            token_budget: None,
+            on_message: None,
+        }
+    }
+
+    /// Send at most `max_iterations` sampling requests.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Grant at most `tokens` in total across the `maxTokens` of the
+    /// sampling requests; the last request gets whatever is left.
+    pub fn with_token_budget(mut self, tokens: u32) -> Self {
+        self.token_budget = Some(tokens);
+        self
+    }
+
+    /// Call `callback` with each message added to the conversation: the
+    /// model's responses and the tool results sent back to it.
+    pub fn on_message(mut self, callback: impl FnMut(&SamplingMessage) + Send + 'a) -> Self {
+        self.on_message = Some(Box::new(callback));
+        self
+    }
+
+    /// Run the conversation until the model stops calling tools or a guard
+    /// stops it.
+    pub async fn run(mut self) -> Result<SamplingTranscript, SamplingError> {
+        let mut params = std::mem::take(&mut self.params);
+        if params.tools.is_none() {
+            params.tools = Some(self.router.list_all());
+        }
+        let mut transcript = SamplingTranscript {
+            messages: std::mem::take(&mut params.messages),
+            model: None,
+            iterations: 0,
+            tokens_requested: 0,
+            stop: SamplingStop::MaxIterations,
+        };
+        transcript.stop = loop {
+            if transcript.iterations >= self.max_iterations {
+                break SamplingStop::MaxIterations;
+            }
+            let max_tokens = match self.token_budget {
+                Some(budget) => match budget.saturating_sub(transcript.tokens_requested) {
+                    0 => break SamplingStop::TokenBudget,
+                    remaining => params.max_tokens.min(remaining),
+                },
+                None => params.max_tokens,
+            };
+            let mut request = params.clone();
+            request.messages = transcript.messages.clone();
+            request.max_tokens = max_tokens;
+
+            let result = self.context.peer.create_message(request).await?;
+            result.validate().map_err(SamplingError::InvalidResult)?;
+            transcript.iterations += 1;
+            transcript.tokens_requested = transcript.tokens_requested.saturating_add(max_tokens);
+            transcript.model = Some(result.model);
+
+            let tool_uses: Vec<_> = result
+                .message
+                .content
+                .iter()
+                .filter_map(|content| content.as_tool_use())
+                .cloned()
+                .collect();
+            self.push(&mut transcript, result.message);
+            if tool_uses.is_empty() {
+                break SamplingStop::Finished {
+                    stop_reason: result.stop_reason,
+                };
+            }
+
+            let mut results = Vec::with_capacity(tool_uses.len());
+            for tool_use in tool_uses {
+                results.push(SamplingMessageContent::ToolResult(
+                    self.call_tool(tool_use).await,
+                ));
+            }
+            self.push(
+                &mut transcript,
+                SamplingMessage::new_multiple(Role::User, results),
+            );
+        };
+        Ok(transcript)
+    }
+
+    fn push(&mut self, transcript: &mut SamplingTranscript, message: SamplingMessage) {
+        if let Some(callback) = &mut self.on_message {
+            callback(&message);
+        }
+        transcript.messages.push(message);
+    }
+
+    /// Run one tool call requested by the model. Failures are reported back
+    /// to the model as error results rather than ending the session.
+    async fn call_tool(&self, tool_use: ToolUseContent) -> ToolResultContent {
+        let params = CallToolRequestParams::new(tool_use.name).with_arguments(tool_use.input);
+        let context = ToolCallContext::new(self.service, params, self.context.clone());
+        match self.router.call(context).await {
+            Ok(result) => {
+                let mut content = ToolResultContent::new(tool_use.id, result.content);
+                content.structured_content = match result.structured_content {
+                    Some(serde_json::Value::Object(object)) => Some(object),
+                    _ => None,
+                };
+                content.is_error = result.is_error;
+                content
+            }
+            Err(error) => ToolResultContent::error(tool_use.id, vec![Content::text(error.message)]),
+        }
+    }
+}
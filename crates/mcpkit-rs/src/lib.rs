@@ -178,16 +178,31 @@ pub use service::{RoleClient, serve_client};
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub use service::{RoleServer, serve_server};
 
+pub mod audit;
 pub mod handler;
 #[cfg(feature = "server")]
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub mod task_manager;
 pub mod transport;
 
+#[cfg(any(
+    feature = "server",
+    feature = "transport-streamable-http-server-session"
+))]
+pub(crate) mod storage;
+
+#[cfg(feature = "otel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "otel")))]
+pub mod telemetry;
+
 #[cfg(feature = "config")]
 #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
 pub mod config;
 
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+
 #[cfg(feature = "wasm-tools")]
 #[cfg_attr(docsrs, doc(cfg(feature = "wasm-tools")))]
 pub mod wasm;
@@ -2,6 +2,7 @@ use std::{borrow::Cow, sync::Arc};
 mod annotated;
 mod capabilities;
 mod content;
+mod elicit;
 mod elicitation_schema;
 mod extension;
 mod meta;
@@ -13,6 +14,7 @@ mod tool;
 pub use annotated::*;
 pub use capabilities::*;
 pub use content::*;
+pub use elicit::*;
 pub use elicitation_schema::*;
 pub use extension::*;
 pub use meta::*;
@@ -1262,6 +1264,16 @@ pub struct UnsubscribeRequestParams {
     pub uri: String,
 }
 
+impl UnsubscribeRequestParams {
+    /// Create a new UnsubscribeRequestParams.
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            meta: None,
+            uri: uri.into(),
+        }
+    }
+}
+
 impl RequestParamsMeta for UnsubscribeRequestParams {
     fn meta(&self) -> Option<&Meta> {
         self.meta.as_ref()
@@ -2378,6 +2390,22 @@ pub struct Root {
     pub name: Option<String>,
 }
 
+impl Root {
+    /// Create a new Root with the given URI.
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            name: None,
+        }
+    }
+
+    /// Set the human-readable name of this root.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
 const_string!(ListRootsRequestMethod = "roots/list");
 pub type ListRootsRequest = RequestNoParam<ListRootsRequestMethod>;
 
@@ -2389,6 +2417,13 @@ pub struct ListRootsResult {
     pub roots: Vec<Root>,
 }
 
+impl ListRootsResult {
+    /// Create a new ListRootsResult with the given roots.
+    pub fn new(roots: Vec<Root>) -> Self {
+        Self { roots }
+    }
+}
+
 const_string!(RootsListChangedNotificationMethod = "notifications/roots/list_changed");
 pub type RootsListChangedNotification = NotificationNoParam<RootsListChangedNotificationMethod>;
 
@@ -3041,6 +3076,33 @@ impl ListTasksResult {
     }
 }
 
+const_string!(TaskStatusNotificationMethod = "notifications/tasks/status");
+
+/// Parameters for a task status notification.
+///
+/// Per spec, the params are the task as of the change, with the Task fields
+/// flattened at the top level like [`GetTaskResult`].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TaskStatusNotificationParam {
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+    #[serde(flatten)]
+    pub task: Task,
+}
+
+impl TaskStatusNotificationParam {
+    /// Create a new TaskStatusNotificationParam.
+    pub fn new(task: Task) -> Self {
+        Self { meta: None, task }
+    }
+}
+
+/// Notification sent by the receiver of a task when its status changes
+pub type TaskStatusNotification =
+    Notification<TaskStatusNotificationMethod, TaskStatusNotificationParam>;
+
 // =============================================================================
 // MESSAGE TYPE UNIONS
 // =============================================================================
@@ -3176,6 +3238,20 @@ ts_union!(
     | CustomRequest;
 );
 
+impl ServerRequest {
+    /// The JSON-RPC method name of this request, such as
+    /// `sampling/createMessage`.
+    pub fn method(&self) -> &str {
+        match &self {
+            ServerRequest::PingRequest(r) => r.method.as_str(),
+            ServerRequest::CreateMessageRequest(r) => r.method.as_str(),
+            ServerRequest::ListRootsRequest(r) => r.method.as_str(),
+            ServerRequest::CreateElicitationRequest(r) => r.method.as_str(),
+            ServerRequest::CustomRequest(r) => r.method.as_str(),
+        }
+    }
+}
+
 ts_union!(
     export type ServerNotification =
     | CancelledNotification
@@ -3186,6 +3262,7 @@ ts_union!(
     | ToolListChangedNotification
     | PromptListChangedNotification
     | ElicitationCompletionNotification
+    | TaskStatusNotification
     | CustomNotification;
 );
 
@@ -0,0 +1,528 @@
+//! Form elicitation of Rust types, see [`Elicit`].
+use std::borrow::Cow;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{
+    BooleanSchema, ConstTitle, ElicitationSchema, EnumSchema, IntegerSchema, MultiSelectEnumSchema,
+    NumberSchema, PrimitiveSchema, SingleSelectEnumSchema, StringFormat, StringSchema, TitledItems,
+    TitledMultiSelectEnumSchema, TitledSingleSelectEnumSchema, UntitledItems,
+    UntitledMultiSelectEnumSchema, UntitledSingleSelectEnumSchema,
+};
+
+/// A type the user can fill in with a form elicitation.
+///
+/// Derive it with `#[derive(Elicit)]` (feature `macros`) on a struct whose
+/// fields are all [`ElicitField`]s: strings, numbers, booleans, enums that
+/// derive `Elicit` themselves, `Vec`s of such enums for multi-select, and
+/// `Option`s of any of these for optional properties. The schema is built
+/// from the fields directly, with `serde` renames applied to property names
+/// and enum values.
+///
+/// ```rust,ignore
+/// #[derive(Deserialize, Elicit)]
+/// #[elicit(title = "Sign up")]
+/// struct SignUp {
+///     /// Where to send the confirmation
+///     #[elicit(format = "email")]
+///     email: String,
+///     #[elicit(title = "Age", min = 13, max = 150)]
+///     age: u8,
+///     #[elicit(default = true)]
+///     newsletter: Option<bool>,
+///     plan: Plan,
+/// }
+///
+/// #[derive(Deserialize, Elicit)]
+/// #[serde(rename_all = "lowercase")]
+/// enum Plan {
+///     #[elicit(title = "Free tier")]
+///     Free,
+///     Pro,
+/// }
+///
+/// let sign_up: SignUp = peer.elicit_form("Create your account").await?;
+/// ```
+///
+/// Field attributes:
+///
+/// | attribute | fields | meaning |
+/// | :- | :- | :- |
+/// | `title`, `description` | all | shown to the user; doc comments are the default description |
+/// | `format` | strings | `"email"`, `"uri"`, `"date"` or `"date-time"` |
+/// | `min_length`, `max_length` | strings | length bounds in characters |
+/// | `min`, `max` | numbers | inclusive bounds |
+/// | `min_items`, `max_items` | multi-select enums | how many options to pick |
+/// | `default` | all | the value suggested to the user, an array for multi-select |
+///
+/// Variants take a `title`. The struct takes a `title` and a `description`,
+/// its doc comment being the default description.
+pub trait Elicit: DeserializeOwned {
+    /// The schema requested from the client.
+    fn elicitation_schema() -> ElicitationSchema;
+
+    /// Check the content returned by the client against
+    /// [`elicitation_schema`](Self::elicitation_schema).
+    fn validate_content(content: &Value) -> Result<(), ElicitationValidationError> {
+        Self::elicitation_schema().validate(content)
+    }
+
+    /// Validate the content returned by the client and deserialize it.
+    fn from_content(content: Value) -> Result<Self, ElicitationValidationError> {
+        Self::validate_content(&content)?;
+        serde_json::from_value(content)
+            .map_err(|error| ElicitationValidationError::Deserialize(error.to_string()))
+    }
+}
+
+/// A type that can be a property of an [`Elicit`] form.
+///
+/// Forms are flat, so nested structs and lists of anything but enum options
+/// are rejected when deriving:
+///
+/// ```compile_fail
+/// # use mcpkit_rs::Elicit;
+/// # use serde::Deserialize;
+/// #[derive(Deserialize, Elicit)]
+/// struct Address {
+///     street: String,
+/// }
+///
+/// #[derive(Deserialize, Elicit)]
+/// struct Order {
+///     shipping: Address,
+///     quantities: Vec<u32>,
+/// }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be a property of an elicitation form",
+    note = "form properties are strings, numbers, booleans, enums deriving `Elicit`, `Vec`s of such enums, or `Option`s of these"
+)]
+pub trait ElicitField {
+    /// The schema of the property, before the field's attributes apply.
+    type Schema: Into<PrimitiveSchema>;
+    /// Whether the property must be filled in.
+    const REQUIRED: bool = true;
+
+    fn field_schema() -> Self::Schema;
+}
+
+/// An enum offered as options, implemented by `#[derive(Elicit)]` on enums
+/// with unit variants.
+pub trait ElicitOptions {
+    /// The value and title of each option. The title is `None` when the
+    /// variant has none.
+    fn options() -> Vec<(&'static str, Option<&'static str>)>;
+}
+
+impl ElicitField for String {
+    type Schema = StringSchema;
+
+    fn field_schema() -> StringSchema {
+        StringSchema::new()
+    }
+}
+
+impl ElicitField for bool {
+    type Schema = BooleanSchema;
+
+    fn field_schema() -> BooleanSchema {
+        BooleanSchema::new()
+    }
+}
+
+macro_rules! elicit_integer {
+    ($($t:ty),*) => {
+        $(
+            impl ElicitField for $t {
+                type Schema = IntegerSchema;
+
+                fn field_schema() -> IntegerSchema {
+                    let schema = IntegerSchema::new();
+                    match (i64::try_from(<$t>::MIN), i64::try_from(<$t>::MAX)) {
+                        (Ok(i64::MIN), Ok(i64::MAX)) => schema,
+                        (Ok(min), Ok(max)) => schema.range(min, max),
+                        (Ok(min), Err(_)) => schema.minimum(min),
+                        _ => schema,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+elicit_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl ElicitField for f32 {
+    type Schema = NumberSchema;
+
+    fn field_schema() -> NumberSchema {
+        NumberSchema::new()
+    }
+}
+
+impl ElicitField for f64 {
+    type Schema = NumberSchema;
+
+    fn field_schema() -> NumberSchema {
+        NumberSchema::new()
+    }
+}
+
+impl<T: ElicitField> ElicitField for Option<T> {
+    type Schema = T::Schema;
+    const REQUIRED: bool = false;
+
+    fn field_schema() -> T::Schema {
+        T::field_schema()
+    }
+}
+
+impl<T: ElicitOptions> ElicitField for Vec<T> {
+    type Schema = EnumSchema;
+
+    fn field_schema() -> EnumSchema {
+        let options = T::options();
+        let multi = if options.iter().any(|(_, title)| title.is_some()) {
+            MultiSelectEnumSchema::Titled(TitledMultiSelectEnumSchema::new(TitledItems::new(
+                const_titles(&options),
+            )))
+        } else {
+            MultiSelectEnumSchema::Untitled(UntitledMultiSelectEnumSchema {
+                type_: Default::default(),
+                title: None,
+                description: None,
+                min_items: None,
+                max_items: None,
+                items: UntitledItems {
+                    type_: Default::default(),
+                    enum_: values(&options),
+                },
+                default: None,
+            })
+        };
+        EnumSchema::Multi(multi)
+    }
+}
+
+/// The single-select schema of an [`ElicitOptions`] enum, used by
+/// `#[derive(Elicit)]`.
+pub fn single_select_schema<T: ElicitOptions>() -> EnumSchema {
+    let options = T::options();
+    let single = if options.iter().any(|(_, title)| title.is_some()) {
+        SingleSelectEnumSchema::Titled(TitledSingleSelectEnumSchema::new(const_titles(&options)))
+    } else {
+        SingleSelectEnumSchema::Untitled(UntitledSingleSelectEnumSchema {
+            type_: Default::default(),
+            title: None,
+            description: None,
+            enum_: values(&options),
+            default: None,
+        })
+    };
+    EnumSchema::Single(single)
+}
+
+fn values(options: &[(&str, Option<&str>)]) -> Vec<String> {
+    options.iter().map(|(value, _)| value.to_string()).collect()
+}
+
+fn const_titles(options: &[(&str, Option<&str>)]) -> Vec<ConstTitle> {
+    options
+        .iter()
+        .map(|(value, title)| ConstTitle::new(*value, title.unwrap_or(value)))
+        .collect()
+}
+
+impl From<StringSchema> for PrimitiveSchema {
+    fn from(schema: StringSchema) -> Self {
+        Self::String(schema)
+    }
+}
+
+impl From<NumberSchema> for PrimitiveSchema {
+    fn from(schema: NumberSchema) -> Self {
+        Self::Number(schema)
+    }
+}
+
+impl From<IntegerSchema> for PrimitiveSchema {
+    fn from(schema: IntegerSchema) -> Self {
+        Self::Integer(schema)
+    }
+}
+
+impl From<BooleanSchema> for PrimitiveSchema {
+    fn from(schema: BooleanSchema) -> Self {
+        Self::Boolean(schema)
+    }
+}
+
+impl From<EnumSchema> for PrimitiveSchema {
+    fn from(schema: EnumSchema) -> Self {
+        Self::Enum(schema)
+    }
+}
+
+impl EnumSchema {
+    /// Set the title.
+    pub fn title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        let title = Some(title.into());
+        match &mut self {
+            Self::Single(SingleSelectEnumSchema::Untitled(schema)) => schema.title = title,
+            Self::Single(SingleSelectEnumSchema::Titled(schema)) => schema.title = title,
+            Self::Multi(MultiSelectEnumSchema::Untitled(schema)) => schema.title = title,
+            Self::Multi(MultiSelectEnumSchema::Titled(schema)) => schema.title = title,
+            Self::Legacy(schema) => schema.title = title,
+        }
+        self
+    }
+
+    /// Set the description.
+    pub fn description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        let description = Some(description.into());
+        match &mut self {
+            Self::Single(SingleSelectEnumSchema::Untitled(schema)) => {
+                schema.description = description
+            }
+            Self::Single(SingleSelectEnumSchema::Titled(schema)) => {
+                schema.description = description
+            }
+            Self::Multi(MultiSelectEnumSchema::Untitled(schema)) => {
+                schema.description = description
+            }
+            Self::Multi(MultiSelectEnumSchema::Titled(schema)) => schema.description = description,
+            Self::Legacy(schema) => schema.description = description,
+        }
+        self
+    }
+
+    /// Set the default: one value for single-select, a list of values for
+    /// multi-select. Legacy enums have no default.
+    pub fn with_default(mut self, default: impl Into<Value>) -> Self {
+        let default = default.into();
+        let list = || {
+            default.as_array().map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(ToOwned::to_owned))
+                    .collect()
+            })
+        };
+        match &mut self {
+            Self::Single(SingleSelectEnumSchema::Untitled(schema)) => {
+                schema.default = default.as_str().map(ToOwned::to_owned)
+            }
+            Self::Single(SingleSelectEnumSchema::Titled(schema)) => {
+                schema.default = default.as_str().map(ToOwned::to_owned)
+            }
+            Self::Multi(MultiSelectEnumSchema::Untitled(schema)) => schema.default = list(),
+            Self::Multi(MultiSelectEnumSchema::Titled(schema)) => schema.default = list(),
+            Self::Legacy(_) => {}
+        }
+        self
+    }
+
+    /// Set the minimum number of options to pick from a multi-select enum.
+    pub fn min_items(mut self, min_items: u64) -> Self {
+        match &mut self {
+            Self::Multi(MultiSelectEnumSchema::Untitled(schema)) => {
+                schema.min_items = Some(min_items)
+            }
+            Self::Multi(MultiSelectEnumSchema::Titled(schema)) => {
+                schema.min_items = Some(min_items)
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Set the maximum number of options to pick from a multi-select enum.
+    pub fn max_items(mut self, max_items: u64) -> Self {
+        match &mut self {
+            Self::Multi(MultiSelectEnumSchema::Untitled(schema)) => {
+                schema.max_items = Some(max_items)
+            }
+            Self::Multi(MultiSelectEnumSchema::Titled(schema)) => {
+                schema.max_items = Some(max_items)
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// The values the user may pick from.
+    pub fn values(&self) -> Vec<&str> {
+        match self {
+            Self::Single(SingleSelectEnumSchema::Untitled(schema)) => {
+                schema.enum_.iter().map(String::as_str).collect()
+            }
+            Self::Single(SingleSelectEnumSchema::Titled(schema)) => schema
+                .one_of
+                .iter()
+                .map(|option| option.const_.as_str())
+                .collect(),
+            Self::Multi(MultiSelectEnumSchema::Untitled(schema)) => {
+                schema.items.enum_.iter().map(String::as_str).collect()
+            }
+            Self::Multi(MultiSelectEnumSchema::Titled(schema)) => schema
+                .items
+                .any_of
+                .iter()
+                .map(|option| option.const_.as_str())
+                .collect(),
+            Self::Legacy(schema) => schema.enum_.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl StringFormat {
+    /// Whether `value` is in this format.
+    ///
+    /// Emails and URIs are checked for their overall shape only.
+    pub fn matches(&self, value: &str) -> bool {
+        if value.contains(char::is_whitespace) {
+            return false;
+        }
+        match self {
+            StringFormat::Email => value.split_once('@').is_some_and(|(local, domain)| {
+                !local.is_empty()
+                    && !domain.contains('@')
+                    && domain.contains('.')
+                    && domain.split('.').all(|label| !label.is_empty())
+            }),
+            StringFormat::Uri => value.split_once(':').is_some_and(|(scheme, rest)| {
+                scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                    && !rest.is_empty()
+            }),
+            StringFormat::Date => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            StringFormat::DateTime => chrono::DateTime::parse_from_rfc3339(value).is_ok(),
+        }
+    }
+}
+
+/// Why elicitation content does not match its schema.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ElicitationValidationError {
+    #[error("elicitation content is not a JSON object")]
+    NotAnObject,
+    #[error("missing required property `{0}`")]
+    MissingProperty(String),
+    #[error("invalid value for `{property}`: {reason}")]
+    InvalidValue { property: String, reason: String },
+    #[error("failed to deserialize elicitation content: {0}")]
+    Deserialize(String),
+}
+
+impl ElicitationSchema {
+    /// Check elicitation content against this schema: required properties
+    /// are present and every known property matches its schema. Unknown
+    /// properties are ignored.
+    pub fn validate(&self, content: &Value) -> Result<(), ElicitationValidationError> {
+        let object = content
+            .as_object()
+            .ok_or(ElicitationValidationError::NotAnObject)?;
+        for name in self.required.iter().flatten() {
+            if object.get(name).is_none_or(Value::is_null) {
+                return Err(ElicitationValidationError::MissingProperty(name.clone()));
+            }
+        }
+        for (name, value) in object {
+            let Some(schema) = self.properties.get(name) else {
+                continue;
+            };
+            if value.is_null() {
+                continue;
+            }
+            schema
+                .validate(value)
+                .map_err(|reason| ElicitationValidationError::InvalidValue {
+                    property: name.clone(),
+                    reason,
+                })?;
+        }
+        Ok(())
+    }
+}
+
+impl PrimitiveSchema {
+    /// Check a property value against this schema, explaining why it does
+    /// not match.
+    pub fn validate(&self, value: &Value) -> Result<(), String> {
+        match self {
+            PrimitiveSchema::String(schema) => {
+                let value = value.as_str().ok_or("expected a string")?;
+                let length = value.chars().count() as u64;
+                if let Some(min) = schema.min_length.filter(|min| length < u64::from(*min)) {
+                    return Err(format!("shorter than {min} characters"));
+                }
+                if let Some(max) = schema.max_length.filter(|max| length > u64::from(*max)) {
+                    return Err(format!("longer than {max} characters"));
+                }
+                if let Some(format) = schema.format.filter(|format| !format.matches(value)) {
+                    return Err(format!(
+                        "not a valid {}",
+                        serde_json::to_value(format).unwrap_or_default()
+                    ));
+                }
+                Ok(())
+            }
+            PrimitiveSchema::Number(schema) => {
+                let value = value.as_f64().ok_or("expected a number")?;
+                check_range(value, schema.minimum, schema.maximum)
+            }
+            PrimitiveSchema::Integer(schema) => {
+                let value = value.as_i64().ok_or("expected an integer")?;
+                check_range(value, schema.minimum, schema.maximum)
+            }
+            PrimitiveSchema::Boolean(_) => value
+                .is_boolean()
+                .then_some(())
+                .ok_or_else(|| "expected a boolean".to_owned()),
+            PrimitiveSchema::Enum(schema) => {
+                let allowed = schema.values();
+                let check = |value: &Value| match value.as_str() {
+                    Some(value) if allowed.contains(&value) => Ok(()),
+                    _ => Err(format!("{value} is not one of {allowed:?}")),
+                };
+                let EnumSchema::Multi(multi) = schema else {
+                    return check(value);
+                };
+                let values = value.as_array().ok_or("expected an array")?;
+                values.iter().try_for_each(check)?;
+                let (min_items, max_items) = match multi {
+                    MultiSelectEnumSchema::Untitled(schema) => (schema.min_items, schema.max_items),
+                    MultiSelectEnumSchema::Titled(schema) => (schema.min_items, schema.max_items),
+                };
+                let count = values.len() as u64;
+                if let Some(min) = min_items.filter(|min| count < *min) {
+                    return Err(format!("fewer than {min} items"));
+                }
+                if let Some(max) = max_items.filter(|max| count > *max) {
+                    return Err(format!("more than {max} items"));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    value: T,
+    minimum: Option<T>,
+    maximum: Option<T>,
+) -> Result<(), String> {
+    if let Some(minimum) = minimum.filter(|minimum| value < *minimum) {
+        return Err(format!("less than {minimum}"));
+    }
+    if let Some(maximum) = maximum.filter(|maximum| value > *maximum) {
+        return Err(format!("greater than {maximum}"));
+    }
+    Ok(())
+}
@@ -189,6 +189,7 @@ variant_extension! {
         ToolListChangedNotification
         PromptListChangedNotification
         ElicitationCompletionNotification
+        TaskStatusNotification
         CustomNotification
     }
 }
@@ -21,6 +21,14 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+impl TaskStatus {
+    /// Whether the task completed, failed or was cancelled and will not change
+    /// again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
 /// Primary Task object that surfaces metadata during the task lifecycle.
 ///
 /// Per spec, `lastUpdatedAt` and `ttl` are required fields.
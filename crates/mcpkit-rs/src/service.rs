@@ -16,6 +16,18 @@ use crate::{
 mod client;
 #[cfg(feature = "client")]
 pub use client::*;
+#[cfg(feature = "client")]
+mod reconnect;
+#[cfg(feature = "client")]
+pub use reconnect::*;
+#[cfg(feature = "client")]
+mod task;
+#[cfg(feature = "client")]
+pub use task::*;
+#[cfg(all(feature = "client", feature = "server"))]
+mod proxy;
+#[cfg(all(feature = "client", feature = "server"))]
+pub use proxy::*;
 #[cfg(feature = "server")]
 mod server;
 #[cfg(feature = "server")]
@@ -311,6 +323,8 @@ pub struct Peer<R: ServiceRole> {
     request_id_provider: Arc<dyn RequestIdProvider>,
     progress_token_provider: Arc<dyn ProgressTokenProvider>,
     info: Arc<tokio::sync::OnceCell<R::PeerInfo>>,
+    #[cfg(feature = "client")]
+    connection_id: u64,
 }
 
 impl<R: ServiceRole> std::fmt::Debug for Peer<R> {
@@ -336,6 +350,9 @@ impl PeerRequestOptions {
     }
 }
 
+#[cfg(feature = "client")]
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(0);
+
 impl<R: ServiceRole> Peer<R> {
     const CLIENT_CHANNEL_BUFFER_SIZE: usize = 1024;
     pub(crate) fn new(
@@ -349,6 +366,9 @@ impl<R: ServiceRole> Peer<R> {
                 request_id_provider,
                 progress_token_provider: Arc::new(AtomicU32ProgressTokenProvider::default()),
                 info: Arc::new(tokio::sync::OnceCell::new_with(peer_info)),
+                #[cfg(feature = "client")]
+                connection_id: NEXT_CONNECTION_ID
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed),
             },
             rx,
         )
@@ -392,6 +412,11 @@ impl<R: ServiceRole> Peer<R> {
         if let Some(meta) = options.meta.clone() {
             request.get_meta_mut().extend(meta);
         }
+        #[cfg(feature = "otel")]
+        crate::telemetry::inject_context(
+            &opentelemetry::Context::current(),
+            request.get_meta_mut(),
+        );
         let (responder, receiver) = tokio::sync::oneshot::channel();
         self.tx
             .send(PeerSinkMessage::Request {
@@ -424,6 +449,18 @@ impl<R: ServiceRole> Peer<R> {
     pub fn is_transport_closed(&self) -> bool {
         self.tx.is_closed()
     }
+
+    /// Wait until the connection is closed.
+    pub async fn closed(&self) {
+        self.tx.closed().await
+    }
+
+    /// Identifies the connection this peer belongs to, unique within the
+    /// process. Clones of a peer share it.
+    #[cfg(feature = "client")]
+    pub(crate) fn connection_id(&self) -> u64 {
+        self.connection_id
+    }
 }
 
 #[derive(Debug)]
@@ -470,7 +507,7 @@ impl<R: ServiceRole, S: Service<R>> RunningService<R, S> {
     pub async fn waiting(mut self) -> Result<QuitReason, tokio::task::JoinError> {
         match self.handle.take() {
             Some(handle) => handle.await,
-            None => Ok(QuitReason::Closed),
+            None => Ok(QuitReason::Closed { reason: None }),
         }
     }
 
@@ -499,7 +536,7 @@ impl<R: ServiceRole, S: Service<R>> RunningService<R, S> {
             handle.await
         } else {
             // Already closed
-            Ok(QuitReason::Closed)
+            Ok(QuitReason::Closed { reason: None })
         }
     }
 
@@ -528,7 +565,7 @@ impl<R: ServiceRole, S: Service<R>> RunningService<R, S> {
                 }
             }
         } else {
-            Ok(Some(QuitReason::Closed))
+            Ok(Some(QuitReason::Closed { reason: None }))
         }
     }
 
@@ -569,7 +606,12 @@ impl RunningServiceCancellationToken {
 #[non_exhaustive]
 pub enum QuitReason {
     Cancelled,
-    Closed,
+    /// The peer closed the connection.
+    Closed {
+        /// What the transport knows about why, e.g. the last stderr lines of
+        /// a child process, see [`Transport::closed_reason`].
+        reason: Option<String>,
+    },
     JoinError(tokio::task::JoinError),
 }
 
@@ -717,6 +759,10 @@ where
                             // [PATCH: ra0x3/mcpkit-rs] Drain pending messages on EOF to fix tokio 1.36 race condition
                             // Input stream closed - but don't break immediately. Continue processing pending messages from sink_proxy_rx
                             tracing::info!("input stream terminated, draining pending messages");
+                            let reason = transport.closed_reason();
+                            if let Some(reason) = &reason {
+                                tracing::warn!("peer closed the connection: {reason}");
+                            }
 
                             // Give spawned tasks time to send their messages
                             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -729,7 +775,7 @@ where
                                 }
                             }
 
-                            break QuitReason::Closed
+                            break QuitReason::Closed { reason }
                         }
                     }
                     m = peer_rx.recv() => {
@@ -11,9 +11,10 @@ use crate::{
         CompleteResult, CompletionContext, CompletionInfo, ErrorData, GetPromptRequest,
         GetPromptRequestParams, GetPromptResult, InitializeRequest, InitializedNotification,
         JsonRpcResponse, ListPromptsRequest, ListPromptsResult, ListResourceTemplatesRequest,
-        ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ListToolsRequest,
-        ListToolsResult, PaginatedRequestParams, ProgressNotification, ProgressNotificationParam,
-        ReadResourceRequest, ReadResourceRequestParams, ReadResourceResult, Reference, RequestId,
+        ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ListTasksRequest,
+        ListTasksResult, ListToolsRequest, ListToolsResult, PaginatedRequestParams,
+        ProgressNotification, ProgressNotificationParam, ReadResourceRequest,
+        ReadResourceRequestParams, ReadResourceResult, Reference, RequestId,
         RootsListChangedNotification, ServerInfo, ServerJsonRpcMessage, ServerNotification,
         ServerRequest, ServerResult, SetLevelRequest, SetLevelRequestParams, SubscribeRequest,
         SubscribeRequestParams, UnsubscribeRequest, UnsubscribeRequestParams,
@@ -72,10 +73,15 @@ async fn expect_next_message<T>(
 where
     T: Transport<RoleClient>,
 {
-    transport
-        .receive()
-        .await
-        .ok_or_else(|| ClientInitializeError::ConnectionClosed(context.to_string()))
+    if let Some(message) = transport.receive().await {
+        return Ok(message);
+    }
+    Err(ClientInitializeError::ConnectionClosed(
+        match transport.closed_reason() {
+            Some(reason) => format!("{context}\n{reason}"),
+            None => context.to_string(),
+        },
+    ))
 }
 
 /// Helper function to expect a response from the stream
@@ -362,6 +368,7 @@ impl Peer<RoleClient> {
     method!(peer_req unsubscribe UnsubscribeRequest(UnsubscribeRequestParams));
     method!(peer_req call_tool CallToolRequest(CallToolRequestParams) => CallToolResult);
     method!(peer_req list_tools ListToolsRequest(PaginatedRequestParams)? => ListToolsResult);
+    method!(peer_req list_tasks ListTasksRequest(PaginatedRequestParams)? => ListTasksResult);
 
     method!(peer_not notify_cancelled CancelledNotification(CancelledNotificationParam));
     method!(peer_not notify_progress ProgressNotification(ProgressNotificationParam));
@@ -0,0 +1,254 @@
+//! Relay an MCP session between two transports.
+//!
+//! A [`Proxy`] sits between a host (on a server-role transport, the
+//! *downstream*) and an MCP server (on a client-role transport, the
+//! *upstream*) and forwards every message unchanged in both directions:
+//! requests, responses, notifications, progress, cancellation and the
+//! server's own requests (sampling, elicitation, roots).
+//!
+//! Messages are relayed at the JSON-RPC level rather than through a
+//! [`Service`](crate::Service), so request ids, progress tokens and
+//! cancellation notifications line up on both sides without translation and
+//! a server request issued while a host request is in flight is forwarded
+//! immediately.
+//!
+//! ```rust,ignore
+//! // Let a stdio-only host talk to a remote Streamable HTTP server.
+//! let upstream = StreamableHttpClientTransport::from_uri("http://localhost:8000/mcp");
+//! Proxy::new(upstream).serve(stdio()).await?;
+//! ```
+//!
+//! With the `transport-streamable-http-server` feature a proxy can also be
+//! served by a [`StreamableHttpService`](crate::transport::StreamableHttpService),
+//! one upstream connection per session:
+//!
+//! ```rust,ignore
+//! let service = StreamableHttpService::new(
+//!     || Ok(Proxy::new(TokioChildProcess::new(Command::new("my-stdio-server"))?)),
+//!     LocalSessionManager::default().into(),
+//!     Default::default(),
+//! );
+//! ```
+use futures::future::BoxFuture;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+    service::{RoleClient, RoleServer},
+    transport::{DynamicTransportError, IntoTransport, Transport},
+};
+
+#[derive(Debug, Error)]
+pub enum ProxyError {
+    #[error("upstream transport error: {0}")]
+    Upstream(#[source] DynamicTransportError),
+    #[error("downstream transport error: {0}")]
+    Downstream(#[source] DynamicTransportError),
+}
+
+/// Why a [`Proxy`] stopped relaying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyQuitReason {
+    Cancelled,
+    /// The host side of the session went away.
+    DownstreamClosed,
+    /// The server side of the session went away.
+    UpstreamClosed,
+}
+
+/// Relays one session between a downstream host and an upstream server.
+pub struct Proxy<U> {
+    upstream: U,
+    ct: CancellationToken,
+}
+
+impl<U> std::fmt::Debug for Proxy<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Proxy")
+            .field("upstream", &std::any::type_name::<U>())
+            .finish()
+    }
+}
+
+impl Proxy<()> {
+    /// Create a proxy in front of the upstream server reached through `upstream`.
+    ///
+    /// Nothing is sent upstream until the host's `initialize` request arrives,
+    /// so the server sees the host's own client info and capabilities.
+    pub fn new<T, E, A>(upstream: T) -> Proxy<impl Transport<RoleClient, Error = E> + 'static>
+    where
+        T: IntoTransport<RoleClient, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Proxy {
+            upstream: upstream.into_transport(),
+            ct: CancellationToken::new(),
+        }
+    }
+}
+
+impl<U> Proxy<U>
+where
+    U: Transport<RoleClient> + 'static,
+{
+    /// Stop relaying when `ct` is cancelled.
+    pub fn with_cancellation_token(mut self, ct: CancellationToken) -> Self {
+        self.ct = ct;
+        self
+    }
+
+    /// Relay messages between `downstream` and the upstream server until
+    /// either side closes or the proxy is cancelled.
+    ///
+    /// Both transports are closed before this returns.
+    pub async fn serve<T, E, A>(self, downstream: T) -> Result<ProxyQuitReason, ProxyError>
+    where
+        T: IntoTransport<RoleServer, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        relay(downstream.into_transport(), self.upstream, self.ct).await
+    }
+}
+
+enum Event {
+    Downstream(Option<ClientJsonRpcMessage>),
+    Upstream(Option<ServerJsonRpcMessage>),
+    SendFailed(ProxyError),
+    Cancelled,
+}
+
+type SendQueue = mpsc::Sender<BoxFuture<'static, Result<(), DynamicTransportError>>>;
+
+/// How many messages may wait for one side before the relay stops reading
+/// from the other.
+const SEND_QUEUE_CAPACITY: usize = 64;
+
+/// Await queued sends one at a time, so each side receives messages in the
+/// order they were read while the relay keeps reading.
+fn send_queue(
+    wrap: fn(DynamicTransportError) -> ProxyError,
+    failed: mpsc::Sender<ProxyError>,
+) -> (SendQueue, tokio::task::JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::channel(SEND_QUEUE_CAPACITY);
+    let task = tokio::spawn(async move {
+        while let Some(send) = rx.recv().await {
+            if let Err(e) = send.await {
+                let _ = failed.send(wrap(e)).await;
+                return;
+            }
+        }
+    });
+    (tx, task)
+}
+
+async fn relay<D, U>(
+    mut downstream: D,
+    mut upstream: U,
+    ct: CancellationToken,
+) -> Result<ProxyQuitReason, ProxyError>
+where
+    D: Transport<RoleServer> + 'static,
+    U: Transport<RoleClient> + 'static,
+{
+    // each queue reports at most one failure
+    let (failed_tx, mut failed_rx) = mpsc::channel(2);
+    let (to_upstream, upstream_task) = send_queue(ProxyError::Upstream, failed_tx.clone());
+    let (to_downstream, downstream_task) = send_queue(ProxyError::Downstream, failed_tx);
+
+    let result = loop {
+        let event = tokio::select! {
+            message = downstream.receive() => Event::Downstream(message),
+            message = upstream.receive() => Event::Upstream(message),
+            Some(error) = failed_rx.recv() => Event::SendFailed(error),
+            _ = ct.cancelled() => Event::Cancelled,
+        };
+        match event {
+            Event::Downstream(Some(message)) => {
+                tracing::trace!(?message, "proxy downstream -> upstream");
+                let send = upstream.send(message);
+                let send = Box::pin(async move {
+                    send.await
+                        .map_err(DynamicTransportError::new::<U, RoleClient>)
+                });
+                let queued = tokio::select! {
+                    queued = to_upstream.send(send) => queued.is_ok(),
+                    _ = ct.cancelled() => break Ok(ProxyQuitReason::Cancelled),
+                };
+                if !queued {
+                    // the queue's task stopped on a failed send
+                    if let Some(error) = failed_rx.recv().await {
+                        break Err(error);
+                    }
+                }
+            }
+            Event::Upstream(Some(message)) => {
+                tracing::trace!(?message, "proxy upstream -> downstream");
+                let send = downstream.send(message);
+                let send = Box::pin(async move {
+                    send.await
+                        .map_err(DynamicTransportError::new::<D, RoleServer>)
+                });
+                let queued = tokio::select! {
+                    queued = to_downstream.send(send) => queued.is_ok(),
+                    _ = ct.cancelled() => break Ok(ProxyQuitReason::Cancelled),
+                };
+                if !queued {
+                    // the queue's task stopped on a failed send
+                    if let Some(error) = failed_rx.recv().await {
+                        break Err(error);
+                    }
+                }
+            }
+            Event::Downstream(None) => break Ok(ProxyQuitReason::DownstreamClosed),
+            Event::Upstream(None) => {
+                if let Some(reason) = upstream.closed_reason() {
+                    tracing::warn!("upstream closed the connection: {reason}");
+                }
+                break Ok(ProxyQuitReason::UpstreamClosed);
+            }
+            Event::SendFailed(error) => break Err(error),
+            Event::Cancelled => break Ok(ProxyQuitReason::Cancelled),
+        }
+    };
+
+    drop((to_upstream, to_downstream));
+    if matches!(
+        result,
+        Ok(ProxyQuitReason::DownstreamClosed | ProxyQuitReason::UpstreamClosed)
+    ) {
+        // flush what was already read, e.g. the last response before a close
+        let _ = tokio::join!(upstream_task, downstream_task);
+    } else {
+        upstream_task.abort();
+        downstream_task.abort();
+    }
+    if let Err(e) = upstream.close().await {
+        tracing::warn!("failed to close upstream transport: {e}");
+    }
+    if let Err(e) = downstream.close().await {
+        tracing::warn!("failed to close downstream transport: {e}");
+    }
+    tracing::debug!(?result, "proxy stopped");
+    result
+}
+
+#[cfg(feature = "transport-streamable-http-server")]
+impl<U> crate::transport::streamable_http_server::tower::SessionService for Proxy<U>
+where
+    U: Transport<RoleClient> + 'static,
+{
+    async fn serve_session<T>(self, transport: T, initialize: bool)
+    where
+        T: Transport<RoleServer> + 'static,
+    {
+        if !initialize {
+            tracing::warn!("a proxy cannot answer stateless requests, enable stateful mode");
+            return;
+        }
+        if let Err(e) = self.serve(transport).await {
+            tracing::warn!("proxy session failed: {e}");
+        }
+    }
+}
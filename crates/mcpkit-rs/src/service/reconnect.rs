@@ -0,0 +1,582 @@
+//! An opt-in supervisor that keeps a client connected.
+//!
+//! [`serve_client`] ends for good when its transport goes away: a child
+//! process that crashed, or an HTTP server that forgot the session. A
+//! [`ReconnectingClient`] instead builds a fresh transport from a factory,
+//! replays `initialize`, restores resource subscriptions and retries
+//! idempotent requests that failed because the connection dropped.
+//!
+//! ```rust,ignore
+//! let client = ReconnectingClient::connect(
+//!     ClientInfo::default(),
+//!     ReconnectConfig::default(),
+//!     || async { TokioChildProcess::new(Command::new("my-server")) },
+//! )
+//! .await?;
+//! let mut events = client.events();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         tracing::info!(?event, "connection event");
+//!     }
+//! });
+//! let tools = client.list_all_tools().await?;
+//! ```
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{FutureExt, future::BoxFuture};
+use thiserror::Error;
+use tokio::sync::{Notify, broadcast, watch};
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use super::{Peer, RoleClient, RunningService, ServiceError, serve_client};
+use crate::{ClientHandler, model::*, transport::IntoTransport};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type Connector<H> = Arc<
+    dyn Fn(H) -> BoxFuture<'static, Result<RunningService<RoleClient, H>, BoxError>> + Send + Sync,
+>;
+
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// How many reconnect attempts to make after a disconnect before giving
+    /// up. `None` keeps trying forever.
+    pub max_attempts: Option<usize>,
+    /// Delay before the first reconnect attempt; doubled on each failure.
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts.
+    pub max_delay: Duration,
+    /// How many times an idempotent request is retried after a connection
+    /// failure.
+    pub request_retries: usize,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            request_retries: 3,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    pub fn with_max_attempts(mut self, max_attempts: impl Into<Option<usize>>) -> Self {
+        self.max_attempts = max_attempts.into();
+        self
+    }
+
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_request_retries(mut self, request_retries: usize) -> Self {
+        self.request_retries = request_retries;
+        self
+    }
+
+    /// The delay before reconnect attempt `attempt` (starting at 1), or
+    /// `None` once the attempts are used up.
+    fn delay(&self, attempt: usize) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| attempt > max) {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        Some(
+            self.initial_delay
+                .saturating_mul(factor)
+                .min(self.max_delay),
+        )
+    }
+}
+
+/// Connection lifecycle events published by a [`ReconnectingClient`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ReconnectEvent {
+    /// The connection of `generation` ended.
+    Disconnected {
+        generation: u64,
+        reason: String,
+    },
+    /// A reconnect attempt will start after `delay`.
+    Reconnecting {
+        attempt: usize,
+        delay: Duration,
+    },
+    /// A new connection is initialized and subscriptions were replayed.
+    Reconnected {
+        generation: u64,
+        attempt: usize,
+    },
+    ReconnectFailed {
+        attempt: usize,
+        error: String,
+    },
+    /// A resource subscription could not be restored on the new connection.
+    SubscriptionRestoreFailed {
+        uri: String,
+        error: String,
+    },
+    /// The retry budget is exhausted; the client is closed.
+    GaveUp {
+        attempts: usize,
+    },
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ReconnectError {
+    #[error("failed to connect: {0}")]
+    Connect(#[source] BoxError),
+    #[error("client is closed")]
+    Closed,
+    #[error(transparent)]
+    Service(#[from] ServiceError),
+}
+
+#[derive(Debug, Clone)]
+enum Connection {
+    Connected {
+        peer: Peer<RoleClient>,
+        generation: u64,
+    },
+    Reconnecting {
+        generation: u64,
+    },
+    Closed,
+}
+
+impl Connection {
+    fn generation(&self) -> Option<u64> {
+        match self {
+            Connection::Connected { generation, .. } | Connection::Reconnecting { generation } => {
+                Some(*generation)
+            }
+            Connection::Closed => None,
+        }
+    }
+}
+
+struct Inner<H: ClientHandler> {
+    handler: H,
+    connector: Connector<H>,
+    config: ReconnectConfig,
+    state: watch::Sender<Connection>,
+    events: broadcast::Sender<ReconnectEvent>,
+    subscriptions: Mutex<BTreeSet<String>>,
+    reconnect: Notify,
+    /// The generation [`reconnect`](Self::reconnect) was last asked to drop,
+    /// so a request kept for a connection that already ended is ignored.
+    reconnect_generation: Mutex<Option<u64>>,
+    ct: CancellationToken,
+    task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+/// A client that re-creates its transport whenever the connection drops.
+///
+/// Clones share the same connection; it is closed when the last clone is
+/// dropped or [`close`](Self::close) is called.
+#[derive(Clone)]
+pub struct ReconnectingClient<H: ClientHandler + Clone> {
+    inner: Arc<Inner<H>>,
+    _guard: Arc<DropGuard>,
+}
+
+impl<H: ClientHandler + Clone> std::fmt::Debug for ReconnectingClient<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectingClient")
+            .field("state", &*self.inner.state.borrow())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H: ClientHandler + Clone> ReconnectingClient<H> {
+    /// Connects using a transport from `make_transport`, and calls it again
+    /// for every reconnect. The first connection is not retried: its error is
+    /// returned directly.
+    pub async fn connect<F, Fut, T, FE, E, A>(
+        handler: H,
+        config: ReconnectConfig,
+        make_transport: F,
+    ) -> Result<Self, ReconnectError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, FE>> + Send + 'static,
+        FE: Into<BoxError>,
+        T: IntoTransport<RoleClient, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+        A: 'static,
+    {
+        let connector: Connector<H> = Arc::new(move |handler| {
+            let transport = make_transport();
+            async move {
+                let transport = transport.await.map_err(Into::into)?;
+                serve_client(handler, transport)
+                    .await
+                    .map_err(|e| Box::new(e) as BoxError)
+            }
+            .boxed()
+        });
+        let running = connector(handler.clone())
+            .await
+            .map_err(ReconnectError::Connect)?;
+        let ct = CancellationToken::new();
+        let inner = Arc::new(Inner {
+            handler,
+            connector,
+            config,
+            state: watch::Sender::new(Connection::Connected {
+                peer: running.peer().clone(),
+                generation: 0,
+            }),
+            events: broadcast::channel(64).0,
+            subscriptions: Default::default(),
+            reconnect: Notify::new(),
+            reconnect_generation: Default::default(),
+            ct: ct.clone(),
+            task: Default::default(),
+        });
+        let task = tokio::spawn(supervise(inner.clone(), running));
+        *inner.task.lock().expect("task lock poisoned") = Some(task);
+        Ok(Self {
+            inner,
+            _guard: Arc::new(ct.drop_guard()),
+        })
+    }
+
+    /// The peer of the current connection, or `None` while reconnecting or
+    /// after the client closed.
+    pub fn peer(&self) -> Option<Peer<RoleClient>> {
+        match &*self.inner.state.borrow() {
+            Connection::Connected { peer, .. } => Some(peer.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        matches!(&*self.inner.state.borrow(), Connection::Connected { .. })
+    }
+
+    /// Subscribes to connection lifecycle events.
+    pub fn events(&self) -> broadcast::Receiver<ReconnectEvent> {
+        self.inner.events.subscribe()
+    }
+
+    /// Drops the current connection and builds a new one.
+    pub fn reconnect(&self) {
+        if let Connection::Connected { generation, .. } = &*self.inner.state.borrow() {
+            self.inner.reconnect(*generation);
+        }
+    }
+
+    /// Closes the connection and stops reconnecting.
+    pub async fn close(&self) {
+        self.inner.ct.cancel();
+        let task = self.inner.task.lock().expect("task lock poisoned").take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+
+    /// Waits for a connection newer than `after`, if any.
+    async fn connected_peer(
+        &self,
+        after: Option<u64>,
+    ) -> Result<(Peer<RoleClient>, u64), ReconnectError> {
+        let mut state = self.inner.state.subscribe();
+        let state = state
+            .wait_for(|state| match state {
+                Connection::Connected { generation, .. } => Some(*generation) > after,
+                Connection::Closed => true,
+                Connection::Reconnecting { .. } => false,
+            })
+            .await
+            .map_err(|_| ReconnectError::Closed)?;
+        match &*state {
+            Connection::Connected { peer, generation } => Ok((peer.clone(), *generation)),
+            _ => Err(ReconnectError::Closed),
+        }
+    }
+
+    /// Runs an idempotent request, retrying it on a new connection if the
+    /// current one fails underneath it.
+    ///
+    /// Only use this for requests that are safe to repeat, such as listing
+    /// or reading; a `tools/call` may have taken effect before the
+    /// connection dropped.
+    pub async fn retry_idempotent<T, F, Fut>(&self, request: F) -> Result<T, ReconnectError>
+    where
+        F: Fn(Peer<RoleClient>) -> Fut,
+        Fut: Future<Output = Result<T, ServiceError>>,
+    {
+        let mut after = None;
+        let mut retries = 0;
+        loop {
+            let (peer, generation) = self.connected_peer(after).await?;
+            let error = match request(peer.clone()).await {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+            let disconnected = match error {
+                ServiceError::TransportSend(_) | ServiceError::TransportClosed => true,
+                ServiceError::Timeout { .. } => false,
+                error => return Err(error.into()),
+            };
+            if retries >= self.inner.config.request_retries {
+                return Err(error.into());
+            }
+            retries += 1;
+            tracing::debug!(retries, "retrying request after: {error}");
+            if disconnected {
+                // wait for a fresh connection; make sure the supervisor knows
+                // this one is unusable even if the transport has not noticed
+                if self.inner.state.borrow().generation() == Some(generation) {
+                    self.inner.reconnect(generation);
+                }
+                after = Some(generation);
+            } else if let Some(delay) = self.inner.config.delay(retries) {
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
+    pub async fn list_all_tools(&self) -> Result<Vec<Tool>, ReconnectError> {
+        self.retry_idempotent(|peer| async move { peer.list_all_tools().await })
+            .await
+    }
+
+    pub async fn list_all_prompts(&self) -> Result<Vec<Prompt>, ReconnectError> {
+        self.retry_idempotent(|peer| async move { peer.list_all_prompts().await })
+            .await
+    }
+
+    pub async fn list_all_resources(&self) -> Result<Vec<Resource>, ReconnectError> {
+        self.retry_idempotent(|peer| async move { peer.list_all_resources().await })
+            .await
+    }
+
+    pub async fn read_resource(
+        &self,
+        params: ReadResourceRequestParams,
+    ) -> Result<ReadResourceResult, ReconnectError> {
+        self.retry_idempotent(|peer| {
+            let params = params.clone();
+            async move { peer.read_resource(params).await }
+        })
+        .await
+    }
+
+    pub async fn get_prompt(
+        &self,
+        params: GetPromptRequestParams,
+    ) -> Result<GetPromptResult, ReconnectError> {
+        self.retry_idempotent(|peer| {
+            let params = params.clone();
+            async move { peer.get_prompt(params).await }
+        })
+        .await
+    }
+
+    /// Calls a tool on the current connection. Tool calls are not retried.
+    pub async fn call_tool(
+        &self,
+        params: CallToolRequestParams,
+    ) -> Result<CallToolResult, ReconnectError> {
+        let (peer, _) = self.connected_peer(None).await?;
+        Ok(peer.call_tool(params).await?)
+    }
+
+    /// Subscribes to updates of a resource. The subscription is replayed on
+    /// every new connection.
+    pub async fn subscribe(&self, uri: impl Into<String>) -> Result<(), ReconnectError> {
+        let uri = uri.into();
+        // recorded first, so a reconnect while the request is in flight
+        // replays it too
+        let added = self
+            .inner
+            .subscriptions
+            .lock()
+            .expect("subscriptions lock poisoned")
+            .insert(uri.clone());
+        let result = self
+            .retry_idempotent(|peer| {
+                let params = SubscribeRequestParams::new(uri.clone());
+                async move { peer.subscribe(params).await }
+            })
+            .await;
+        if result.is_err() && added {
+            self.inner
+                .subscriptions
+                .lock()
+                .expect("subscriptions lock poisoned")
+                .remove(&uri);
+        }
+        result
+    }
+
+    pub async fn unsubscribe(&self, uri: &str) -> Result<(), ReconnectError> {
+        self.inner
+            .subscriptions
+            .lock()
+            .expect("subscriptions lock poisoned")
+            .remove(uri);
+        self.retry_idempotent(|peer| {
+            let params = UnsubscribeRequestParams::new(uri);
+            async move { peer.unsubscribe(params).await }
+        })
+        .await
+    }
+}
+
+impl<H: ClientHandler> Inner<H> {
+    /// Asks the supervisor to drop connection `generation`. The request is
+    /// kept if the supervisor is not waiting for it yet.
+    fn reconnect(&self, generation: u64) {
+        *self
+            .reconnect_generation
+            .lock()
+            .expect("reconnect lock poisoned") = Some(generation);
+        self.reconnect.notify_one();
+    }
+
+    async fn reconnect_requested(&self, generation: u64) {
+        loop {
+            self.reconnect.notified().await;
+            if *self
+                .reconnect_generation
+                .lock()
+                .expect("reconnect lock poisoned")
+                == Some(generation)
+            {
+                return;
+            }
+        }
+    }
+}
+
+async fn supervise<H: ClientHandler + Clone>(
+    inner: Arc<Inner<H>>,
+    mut running: RunningService<RoleClient, H>,
+) {
+    let emit = |event: ReconnectEvent| {
+        let _ = inner.events.send(event);
+    };
+    let mut generation = 0;
+    loop {
+        let stop = running.cancellation_token();
+        let waiting = running.waiting();
+        tokio::pin!(waiting);
+        let reason = tokio::select! {
+            reason = &mut waiting => reason,
+            _ = inner.ct.cancelled() => {
+                stop.cancel();
+                let _ = waiting.await;
+                inner.state.send_replace(Connection::Closed);
+                return;
+            }
+            _ = inner.reconnect_requested(generation) => {
+                stop.cancel();
+                waiting.await
+            }
+        };
+        let reason = match reason {
+            Ok(reason) => format!("{reason:?}"),
+            Err(e) => e.to_string(),
+        };
+        tracing::info!(generation, %reason, "client connection ended");
+        inner
+            .state
+            .send_replace(Connection::Reconnecting { generation });
+        emit(ReconnectEvent::Disconnected { generation, reason });
+
+        let mut attempt = 0;
+        running = loop {
+            attempt += 1;
+            let Some(delay) = inner.config.delay(attempt) else {
+                tracing::warn!(attempts = attempt - 1, "giving up reconnecting");
+                emit(ReconnectEvent::GaveUp {
+                    attempts: attempt - 1,
+                });
+                inner.state.send_replace(Connection::Closed);
+                return;
+            };
+            emit(ReconnectEvent::Reconnecting { attempt, delay });
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = inner.ct.cancelled() => {
+                    inner.state.send_replace(Connection::Closed);
+                    return;
+                }
+            }
+            match (inner.connector)(inner.handler.clone()).await {
+                Ok(running) => break running,
+                Err(e) => {
+                    tracing::warn!(attempt, "reconnect failed: {e}");
+                    emit(ReconnectEvent::ReconnectFailed {
+                        attempt,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        };
+
+        let subscriptions = inner
+            .subscriptions
+            .lock()
+            .expect("subscriptions lock poisoned")
+            .clone();
+        for uri in subscriptions {
+            if let Err(e) = running
+                .peer()
+                .subscribe(SubscribeRequestParams::new(uri.clone()))
+                .await
+            {
+                tracing::warn!(%uri, "failed to restore subscription: {e}");
+                emit(ReconnectEvent::SubscriptionRestoreFailed {
+                    uri,
+                    error: e.to_string(),
+                });
+            }
+        }
+        generation += 1;
+        inner.state.send_replace(Connection::Connected {
+            peer: running.peer().clone(),
+            generation,
+        });
+        tracing::info!(generation, attempt, "client reconnected");
+        emit(ReconnectEvent::Reconnected {
+            generation,
+            attempt,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_delay_backs_off_and_gives_up() {
+        let config = ReconnectConfig::default()
+            .with_max_attempts(4)
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500));
+        assert_eq!(config.delay(1), Some(Duration::from_millis(100)));
+        assert_eq!(config.delay(2), Some(Duration::from_millis(200)));
+        assert_eq!(config.delay(3), Some(Duration::from_millis(400)));
+        assert_eq!(config.delay(4), Some(Duration::from_millis(500)));
+        assert_eq!(config.delay(5), None);
+    }
+}
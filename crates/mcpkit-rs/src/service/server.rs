@@ -21,7 +21,8 @@ use crate::{
         ProgressNotification, ProgressNotificationParam, PromptListChangedNotification,
         ProtocolVersion, ResourceListChangedNotification, ResourceUpdatedNotification,
         ResourceUpdatedNotificationParam, ServerInfo, ServerNotification, ServerRequest,
-        ServerResult, ToolListChangedNotification,
+        ServerResult, TaskStatusNotification, TaskStatusNotificationParam,
+        ToolListChangedNotification,
     },
     transport::DynamicTransportError,
 };
@@ -125,10 +126,15 @@ async fn expect_next_message<T>(
 where
     T: Transport<RoleServer>,
 {
-    transport
-        .receive()
-        .await
-        .ok_or_else(|| ServerInitializeError::ConnectionClosed(context.to_string()))
+    if let Some(message) = transport.receive().await {
+        return Ok(message);
+    }
+    Err(ServerInitializeError::ConnectionClosed(
+        match transport.closed_reason() {
+            Some(reason) => format!("{context}\n{reason}"),
+            None => context.to_string(),
+        },
+    ))
 }
 
 /// Helper function to expect a request from the stream
@@ -448,6 +454,7 @@ impl Peer<RoleServer> {
     method!(peer_not notify_resource_list_changed ResourceListChangedNotification);
     method!(peer_not notify_tool_list_changed ToolListChangedNotification);
     method!(peer_not notify_prompt_list_changed PromptListChangedNotification);
+    method!(peer_not notify_task_status TaskStatusNotification(TaskStatusNotificationParam));
 }
 
 // =============================================================================
@@ -483,6 +490,13 @@ pub enum ElicitationError {
         data: serde_json::Value,
     },
 
+    /// The response content does not match the requested form
+    #[error("Invalid response content: {error}\nReceived data: {data}")]
+    InvalidContent {
+        error: crate::model::ElicitationValidationError,
+        data: serde_json::Value,
+    },
+
     /// No response content was provided by the user
     #[error("No response content provided")]
     NoContent,
@@ -776,6 +790,88 @@ impl Peer<RoleServer> {
         }
     }
 
+    /// Request a form from the user, described by a type deriving
+    /// [`Elicit`](crate::model::Elicit).
+    ///
+    /// Unlike [`elicit`](Self::elicit), the schema is built from the type's
+    /// fields and `#[elicit(...)]` attributes rather than `schemars`, and the
+    /// response is checked against it before it is deserialized.
+    ///
+    /// # Returns
+    /// * `Ok(data)` if the user accepted and the content matches the form
+    /// * `Err(ElicitationError::InvalidContent { .. })` if the content does not match the form
+    /// * The other errors as for [`elicit`](Self::elicit)
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use mcpkit_rs::*;
+    /// # use serde::Deserialize;
+    /// #
+    /// #[derive(Debug, Deserialize, Elicit)]
+    /// struct Contact {
+    ///     #[elicit(format = "email")]
+    ///     email: String,
+    ///     /// Whether to subscribe to the newsletter
+    ///     #[serde(default)]
+    ///     newsletter: bool,
+    /// }
+    ///
+    /// # async fn example(peer: Peer<RoleServer>) -> Result<(), Box<dyn std::error::Error>> {
+    /// let contact: Contact = peer.elicit_form("How can we reach you?").await?;
+    /// println!("Email: {}", contact.email);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn elicit_form<T>(&self, message: impl Into<String>) -> Result<T, ElicitationError>
+    where
+        T: crate::model::Elicit,
+    {
+        self.elicit_form_with_timeout(message, None).await
+    }
+
+    /// Request a form from the user with custom timeout.
+    ///
+    /// Same as `elicit_form()` but may also return `ServiceError::Timeout` if
+    /// the timeout expires.
+    pub async fn elicit_form_with_timeout<T>(
+        &self,
+        message: impl Into<String>,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<T, ElicitationError>
+    where
+        T: crate::model::Elicit,
+    {
+        if !self
+            .supported_elicitation_modes()
+            .contains(&ElicitationMode::Form)
+        {
+            return Err(ElicitationError::CapabilityNotSupported);
+        }
+
+        let response = self
+            .create_elicitation_with_timeout(
+                CreateElicitationRequestParams::FormElicitationParams {
+                    meta: None,
+                    message: message.into(),
+                    requested_schema: T::elicitation_schema(),
+                },
+                timeout,
+            )
+            .await?;
+
+        match response.action {
+            ElicitationAction::Accept => {
+                let Some(value) = response.content else {
+                    return Err(ElicitationError::NoContent);
+                };
+                T::from_content(value.clone())
+                    .map_err(|error| ElicitationError::InvalidContent { error, data: value })
+            }
+            ElicitationAction::Decline => Err(ElicitationError::UserDeclined),
+            ElicitationAction::Cancel => Err(ElicitationError::UserCancelled),
+        }
+    }
+
     /// Request the user to visit a URL and confirm completion.
     ///
     /// This method sends a URL elicitation request to the client, prompting the user
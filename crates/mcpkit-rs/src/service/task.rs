@@ -0,0 +1,310 @@
+//! Calling tools as tasks.
+//!
+//! [`Peer::call_tool_as_task`] asks the server to run a tool call as a task
+//! (SEP-1686) and returns a [`TaskHandle`] right away, so a long job does not
+//! hold a request open. The handle polls `tasks/get` at the interval the
+//! server suggests, streams status changes, fetches the result with
+//! `tasks/result` once the task is done, and cancels with `tasks/cancel`.
+//!
+//! When the server does not advertise task support for `tools/call`, or the
+//! tool's `execution.taskSupport` does not allow task invocation, the call
+//! runs as a normal request and the handle holds its result.
+//!
+//! ```rust,ignore
+//! let mut handle = client
+//!     .call_tool_as_task(CallToolRequestParams::new("build"))
+//!     .await?;
+//! let mut changes = handle.status_changes();
+//! while let Some(task) = changes.next().await {
+//!     let task = task?;
+//!     println!("{:?}: {:?}", task.status, task.status_message);
+//! }
+//! let result = handle.result().await?;
+//! ```
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream, StreamExt};
+
+use super::{Peer, RoleClient, ServiceError};
+use crate::model::*;
+
+/// How often a [`TaskHandle`] polls when the server does not suggest an
+/// interval.
+pub const DEFAULT_TASK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+impl Peer<RoleClient> {
+    pub async fn get_task_info(
+        &self,
+        params: GetTaskInfoParams,
+    ) -> Result<GetTaskResult, ServiceError> {
+        let result = self
+            .send_request(ClientRequest::GetTaskInfoRequest(GetTaskInfoRequest::new(
+                params,
+            )))
+            .await?;
+        match result {
+            ServerResult::GetTaskResult(result) => Ok(result),
+            // both results are a bare task, so either may be picked
+            ServerResult::CancelTaskResult(CancelTaskResult { meta, task }) => {
+                Ok(GetTaskResult { meta, task })
+            }
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Fetch the result of a task, waiting for it to finish if needed.
+    pub async fn get_task_result(
+        &self,
+        params: GetTaskResultParams,
+    ) -> Result<GetTaskPayloadResult, ServiceError> {
+        let result = self
+            .send_request(ClientRequest::GetTaskResultRequest(
+                GetTaskResultRequest::new(params),
+            ))
+            .await?;
+        // the payload is the result of the original request, which decodes as
+        // whatever variant matches it first
+        let value = serde_json::to_value(result).map_err(|_| ServiceError::UnexpectedResponse)?;
+        Ok(GetTaskPayloadResult::new(value))
+    }
+
+    pub async fn cancel_task(
+        &self,
+        params: CancelTaskParams,
+    ) -> Result<CancelTaskResult, ServiceError> {
+        let result = self
+            .send_request(ClientRequest::CancelTaskRequest(CancelTaskRequest::new(
+                params,
+            )))
+            .await?;
+        match result {
+            ServerResult::CancelTaskResult(result) => Ok(result),
+            ServerResult::GetTaskResult(GetTaskResult { meta, task }) => {
+                Ok(CancelTaskResult { meta, task })
+            }
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Whether the server advertised task support for `tools/call`.
+    pub fn supports_tool_tasks(&self) -> bool {
+        self.peer_info()
+            .and_then(|info| info.capabilities.tasks.as_ref())
+            .is_some_and(TasksCapability::supports_tools_call)
+    }
+
+    /// The task support the server declares for the tool named `name`,
+    /// looked up with `tools/list`.
+    pub async fn tool_task_support(&self, name: &str) -> Result<TaskSupport, ServiceError> {
+        let tools = self.list_all_tools().await?;
+        Ok(tools
+            .iter()
+            .find(|tool| tool.name == name)
+            .map(Tool::task_support)
+            .unwrap_or_default())
+    }
+
+    /// Call a tool as a task, see the [module docs](self).
+    ///
+    /// `params.task` is sent as is when set, e.g. to request a TTL, and as an
+    /// empty object otherwise. If the server does not support tasks for
+    /// `tools/call`, or the tool forbids task invocation, the tool is called
+    /// normally; finding out lists the server's tools.
+    pub async fn call_tool_as_task(
+        &self,
+        mut params: CallToolRequestParams,
+    ) -> Result<TaskHandle, ServiceError> {
+        if !self.supports_tool_tasks()
+            || self.tool_task_support(&params.name).await? == TaskSupport::Forbidden
+        {
+            params.task = None;
+            return self.call_tool_immediately(params).await;
+        }
+        let mut task_params = params.clone();
+        task_params.task.get_or_insert_with(JsonObject::new);
+        let result = self
+            .send_request(ClientRequest::CallToolRequest(CallToolRequest::new(
+                task_params,
+            )))
+            .await;
+        match result {
+            Ok(ServerResult::CreateTaskResult(result)) => Ok(TaskHandle {
+                peer: self.clone(),
+                state: TaskState::Task(result.task),
+            }),
+            // the server ran the call right away
+            Ok(ServerResult::CallToolResult(result)) => Ok(TaskHandle {
+                peer: self.clone(),
+                state: TaskState::Immediate(result),
+            }),
+            Ok(_) => Err(ServiceError::UnexpectedResponse),
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn call_tool_immediately(
+        &self,
+        params: CallToolRequestParams,
+    ) -> Result<TaskHandle, ServiceError> {
+        let result = self.call_tool(params).await?;
+        Ok(TaskHandle {
+            peer: self.clone(),
+            state: TaskState::Immediate(result),
+        })
+    }
+}
+
+#[derive(Debug)]
+enum TaskState {
+    Task(Task),
+    Immediate(CallToolResult),
+}
+
+/// A tool call started by [`Peer::call_tool_as_task`].
+#[derive(Debug)]
+pub struct TaskHandle {
+    peer: Peer<RoleClient>,
+    state: TaskState,
+}
+
+fn poll_interval(task: &Task) -> Duration {
+    task.poll_interval
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_TASK_POLL_INTERVAL)
+}
+
+/// Whether `next` differs from `previous` in a way worth reporting.
+fn status_changed(previous: &Task, next: &Task) -> bool {
+    previous.status != next.status || previous.status_message != next.status_message
+}
+
+impl TaskHandle {
+    /// Whether the call runs as a task; `false` when it fell back to a
+    /// normal call.
+    pub fn is_task(&self) -> bool {
+        matches!(self.state, TaskState::Task(_))
+    }
+
+    /// The task as of the last poll.
+    pub fn task(&self) -> Option<&Task> {
+        match &self.state {
+            TaskState::Task(task) => Some(task),
+            TaskState::Immediate(_) => None,
+        }
+    }
+
+    pub fn task_id(&self) -> Option<&str> {
+        self.task().map(|task| task.task_id.as_str())
+    }
+
+    /// The interval suggested by the server, or
+    /// [`DEFAULT_TASK_POLL_INTERVAL`].
+    pub fn poll_interval(&self) -> Duration {
+        self.task()
+            .map(poll_interval)
+            .unwrap_or(DEFAULT_TASK_POLL_INTERVAL)
+    }
+
+    /// Whether the task has finished; always `true` for a normal call.
+    pub fn is_finished(&self) -> bool {
+        self.task().is_none_or(|task| task.status.is_terminal())
+    }
+
+    /// Fetch the current state of the task with `tasks/get`.
+    pub async fn refresh(&mut self) -> Result<Option<&Task>, ServiceError> {
+        if let TaskState::Task(task) = &mut self.state {
+            let result = self
+                .peer
+                .get_task_info(GetTaskInfoParams {
+                    meta: None,
+                    task_id: task.task_id.clone(),
+                })
+                .await?;
+            *task = result.task;
+        }
+        Ok(self.task())
+    }
+
+    /// Poll until the task reaches a terminal status.
+    pub async fn wait(&mut self) -> Result<Option<&Task>, ServiceError> {
+        while !self.is_finished() {
+            tokio::time::sleep(self.poll_interval()).await;
+            self.refresh().await?;
+        }
+        Ok(self.task())
+    }
+
+    /// A stream of the task each time its status or status message changes,
+    /// polling at the server's suggested interval. The stream ends after a
+    /// terminal status, or after the first error; it is empty for a normal
+    /// call or a finished task.
+    pub fn status_changes(&self) -> BoxStream<'static, Result<Task, ServiceError>> {
+        let Some(task) = self.task().filter(|task| !task.status.is_terminal()) else {
+            return stream::empty().boxed();
+        };
+        let peer = self.peer.clone();
+        stream::unfold(Some(task.clone()), move |last| {
+            let peer = peer.clone();
+            async move {
+                let mut last = last?;
+                loop {
+                    tokio::time::sleep(poll_interval(&last)).await;
+                    let next = match peer
+                        .get_task_info(GetTaskInfoParams {
+                            meta: None,
+                            task_id: last.task_id.clone(),
+                        })
+                        .await
+                    {
+                        Ok(result) => result.task,
+                        Err(error) => return Some((Err(error), None)),
+                    };
+                    if status_changed(&last, &next) {
+                        let state = (!next.status.is_terminal()).then(|| next.clone());
+                        return Some((Ok(next), state));
+                    }
+                    last = next;
+                }
+            }
+        })
+        .boxed()
+    }
+
+    /// Wait for the task to finish, then fetch its result with
+    /// `tasks/result`.
+    ///
+    /// Waiting is done by polling, so no request stays open for the duration
+    /// of the task. A failed or cancelled task yields the server's error.
+    pub async fn result(mut self) -> Result<CallToolResult, ServiceError> {
+        self.wait().await?;
+        match self.state {
+            TaskState::Immediate(result) => Ok(result),
+            TaskState::Task(task) => {
+                let payload = self
+                    .peer
+                    .get_task_result(GetTaskResultParams {
+                        meta: None,
+                        task_id: task.task_id,
+                    })
+                    .await?;
+                serde_json::from_value(payload.0).map_err(|_| ServiceError::UnexpectedResponse)
+            }
+        }
+    }
+
+    /// Ask the server to cancel the task with `tasks/cancel`. Does nothing
+    /// for a normal call.
+    pub async fn cancel(&mut self) -> Result<Option<&Task>, ServiceError> {
+        if let TaskState::Task(task) = &mut self.state {
+            let result = self
+                .peer
+                .cancel_task(CancelTaskParams {
+                    meta: None,
+                    task_id: task.task_id.clone(),
+                })
+                .await?;
+            *task = result.task;
+        }
+        Ok(self.task())
+    }
+}
@@ -0,0 +1,35 @@
+//! Helpers shared by the stores that keep state on disk.
+
+/// A file name for state keyed by `id`, safe to use whatever the ID
+/// contains: characters other than ASCII letters, digits, `-` and `_` are
+/// percent-encoded byte by byte.
+pub(crate) fn file_name(id: &str, extension: &str) -> String {
+    let mut name = String::with_capacity(id.len() + extension.len() + 1);
+    for c in id.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            name.push(c);
+        } else {
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                name.push_str(&format!("%{byte:02x}"));
+            }
+        }
+    }
+    name.push('.');
+    name.push_str(extension);
+    name
+}
+
+/// Run blocking file or database I/O on the blocking thread pool, so async
+/// callers never stall a runtime worker.
+pub(crate) async fn blocking<T, E>(
+    f: impl FnOnce() -> Result<T, E> + Send + 'static,
+) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<std::io::Error> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|error| E::from(std::io::Error::other(error)))?
+}
@@ -1,4 +1,4 @@
-use std::{any::Any, collections::HashMap, pin::Pin};
+use std::{any::Any, collections::HashMap, pin::Pin, sync::Arc};
 
 use futures::Future;
 use tokio::{
@@ -9,10 +9,20 @@ use tokio::{
 use crate::{
     RoleServer,
     error::{ErrorData as McpError, RmcpError as Error},
-    model::{CallToolResult, ClientRequest},
+    model::{CallToolResult, ClientRequest, TaskStatus},
     service::RequestContext,
 };
 
+mod reporter;
+pub mod store;
+pub use reporter::{RELATED_TASK_META_KEY, TaskReporter};
+#[cfg(feature = "task-store-sqlite")]
+#[cfg_attr(docsrs, doc(cfg(feature = "task-store-sqlite")))]
+pub use store::SqliteTaskStore;
+pub use store::{
+    FileTaskStore, InMemoryTaskStore, TaskOutcome, TaskRecord, TaskStore, TaskStoreError,
+};
+
 /// Boxed future that represents an asynchronous operation managed by the processor.
 pub type OperationFuture =
     Pin<Box<dyn Future<Output = Result<Box<dyn OperationResultTransport>, Error>> + Send>>;
@@ -24,7 +34,12 @@ pub struct OperationDescriptor {
     pub name: String,
     pub client_request: Option<ClientRequest>,
     pub context: Option<RequestContext<RoleServer>>,
-    pub ttl: Option<u64>,
+    /// Seconds the operation may run before it is aborted, defaulting to
+    /// [`DEFAULT_TASK_TIMEOUT_SECS`].
+    pub timeout: Option<u64>,
+    /// Milliseconds the [`TaskRecord`] is kept after creation, as requested
+    /// by the client; `None` keeps it until it is removed.
+    pub retention_ttl: Option<u64>,
 }
 
 impl OperationDescriptor {
@@ -34,7 +49,8 @@ impl OperationDescriptor {
             name: name.into(),
             client_request: None,
             context: None,
-            ttl: None,
+            timeout: None,
+            retention_ttl: None,
         }
     }
 
@@ -48,8 +64,13 @@ impl OperationDescriptor {
         self
     }
 
-    pub fn with_ttl(mut self, ttl: u64) -> Self {
-        self.ttl = Some(ttl);
+    pub fn with_timeout(mut self, timeout: u64) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retention_ttl(mut self, retention_ttl: u64) -> Self {
+        self.retention_ttl = Some(retention_ttl);
         self
     }
 }
@@ -82,18 +103,60 @@ pub struct OperationProcessor {
     completed_results: Vec<TaskResult>,
     task_result_receiver: mpsc::UnboundedReceiver<TaskResult>,
     task_result_sender: mpsc::UnboundedSender<TaskResult>,
+    /// Records of every task, including those whose result was collected
+    store: Arc<dyn TaskStore>,
 }
 
 struct RunningTask {
     task_handle: tokio::task::JoinHandle<()>,
-    started_at: std::time::Instant,
+    started_at: tokio::time::Instant,
     timeout: Option<u64>,
     descriptor: OperationDescriptor,
+    reporter: TaskReporter,
 }
 
 pub struct TaskResult {
     pub descriptor: OperationDescriptor,
     pub result: Result<Box<dyn OperationResultTransport>, Error>,
+    /// The task in its terminal status, as saved to the store.
+    pub record: TaskRecord,
+}
+
+impl TaskResult {
+    /// A result for a task that was stopped before finishing, with the
+    /// terminal `record` given to it by [`TaskReporter::stop`].
+    fn stopped(task: RunningTask, record: TaskRecord) -> Self {
+        let message = record.task.status_message.clone().unwrap_or_default();
+        Self {
+            descriptor: task.descriptor,
+            result: Err(Error::TaskError(message)),
+            record,
+        }
+    }
+}
+
+/// The terminal record of a task that produced `result`.
+fn finished_record(
+    mut record: TaskRecord,
+    result: &Result<Box<dyn OperationResultTransport>, Error>,
+) -> TaskRecord {
+    match result {
+        Ok(boxed) => match boxed.as_any().downcast_ref::<ToolCallTaskResult>() {
+            Some(tool) => record.finish(match &tool.result {
+                Ok(result) => TaskOutcome::Result(result.clone()),
+                Err(error) => TaskOutcome::Error(error.clone()),
+            }),
+            None => record.transition(TaskStatus::Completed, None),
+        },
+        Err(error) => record.transition(TaskStatus::Failed, Some(error.to_string())),
+    }
+    record
+}
+
+async fn save_record(store: &dyn TaskStore, record: &TaskRecord) {
+    if let Err(error) = store.save(record).await {
+        tracing::warn!(task_id = record.task_id(), %error, "failed to save task record");
+    }
 }
 
 /// Helper to generate an ISO 8601 timestamp for task metadata.
@@ -101,6 +164,11 @@ pub fn current_timestamp() -> String {
     chrono::Utc::now().to_rfc3339()
 }
 
+/// A new, unique task id.
+pub fn new_task_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
 /// Result transport for tool calls executed as tasks.
 pub struct ToolCallTaskResult {
     id: String,
@@ -133,16 +201,30 @@ impl Default for OperationProcessor {
 }
 
 impl OperationProcessor {
+    /// A processor that keeps task records in memory.
     pub fn new() -> Self {
+        Self::with_store(Arc::new(InMemoryTaskStore::new()))
+    }
+
+    /// A processor that keeps task records in `store`.
+    ///
+    /// Records of tasks that were still working when a previous process
+    /// stopped are reported as failed.
+    pub fn with_store(store: Arc<dyn TaskStore>) -> Self {
         let (task_result_sender, task_result_receiver) = mpsc::unbounded_channel();
         Self {
             running_tasks: HashMap::new(),
             completed_results: Vec::new(),
             task_result_receiver,
             task_result_sender,
+            store,
         }
     }
 
+    pub fn store(&self) -> &Arc<dyn TaskStore> {
+        &self.store
+    }
+
     /// Submit an operation for asynchronous execution.
     #[allow(clippy::result_large_err)]
     pub fn submit_operation(&mut self, message: OperationMessage) -> Result<(), Error> {
@@ -162,9 +244,15 @@ impl OperationProcessor {
     fn spawn_async_task(&mut self, message: OperationMessage) {
         let OperationMessage { descriptor, future } = message;
         let task_id = descriptor.operation_id.clone();
-        let timeout_secs = descriptor.ttl.or(Some(DEFAULT_TASK_TIMEOUT_SECS));
+        let timeout_secs = descriptor.timeout.or(Some(DEFAULT_TASK_TIMEOUT_SECS));
         let sender = self.task_result_sender.clone();
         let descriptor_for_result = descriptor.clone();
+        let reporter = TaskReporter::new(
+            TaskRecord::new(&descriptor),
+            self.store.clone(),
+            descriptor.context.as_ref(),
+        );
+        let task_reporter = reporter.clone();
 
         let timed_future = async move {
             if let Some(secs) = timeout_secs {
@@ -178,18 +266,26 @@ impl OperationProcessor {
         };
 
         let handle = tokio::spawn(async move {
-            let result = timed_future.await;
+            task_reporter.save_initial().await;
+            let result = task_reporter.clone().scope(timed_future).await;
+            let record = finished_record(task_reporter.record(), &result);
+            // a task stopped meanwhile already has its result
+            let Some(record) = task_reporter.finish(record).await else {
+                return;
+            };
             let task_result = TaskResult {
                 descriptor: descriptor_for_result,
                 result,
+                record,
             };
             let _ = sender.send(task_result);
         });
         let running_task = RunningTask {
             task_handle: handle,
-            started_at: std::time::Instant::now(),
+            started_at: tokio::time::Instant::now(),
             timeout: timeout_secs,
             descriptor,
+            reporter,
         };
         self.running_tasks.insert(task_id, running_task);
     }
@@ -205,25 +301,28 @@ impl OperationProcessor {
     /// Check for tasks that have exceeded their timeout and handle them appropriately.
     pub fn check_timeouts(&mut self) {
         self.collect_completed_results();
-        let now = std::time::Instant::now();
+        let now = tokio::time::Instant::now();
         let mut timed_out_tasks = Vec::new();
 
         for (task_id, task) in &self.running_tasks {
             if let Some(timeout_duration) = task.timeout {
                 if now.duration_since(task.started_at).as_secs() > timeout_duration {
-                    task.task_handle.abort();
-                    timed_out_tasks.push(task_id.clone());
+                    // a task that finished on its own sends its result instead
+                    if let Some(record) = task
+                        .reporter
+                        .stop(TaskStatus::Failed, "Operation timed out")
+                    {
+                        task.task_handle.abort();
+                        timed_out_tasks.push((task_id.clone(), record));
+                    }
                 }
             }
         }
 
-        for task_id in timed_out_tasks {
+        for (task_id, record) in timed_out_tasks {
             if let Some(task) = self.running_tasks.remove(&task_id) {
-                let timeout_result = TaskResult {
-                    descriptor: task.descriptor,
-                    result: Err(Error::TaskError("Operation timed out".to_string())),
-                };
-                self.completed_results.push(timeout_result);
+                self.completed_results
+                    .push(TaskResult::stopped(task, record));
             }
         }
     }
@@ -237,6 +336,8 @@ impl OperationProcessor {
     /// Cancel all running tasks.
     pub fn cancel_all_tasks(&mut self) {
         for (_, task) in self.running_tasks.drain() {
+            task.reporter
+                .stop(TaskStatus::Cancelled, "Operation cancelled");
             task.task_handle.abort();
         }
         while self.task_result_receiver.try_recv().is_ok() {}
@@ -268,19 +369,23 @@ impl OperationProcessor {
     }
 
     /// Attempt to cancel a running task.
+    ///
+    /// Returns `false` if the task is unknown or finished on its own first.
     pub fn cancel_task(&mut self, task_id: &str) -> bool {
         self.collect_completed_results();
+        let Some(record) = self.running_tasks.get(task_id).and_then(|task| {
+            task.reporter
+                .stop(TaskStatus::Cancelled, "Operation cancelled")
+        }) else {
+            return false;
+        };
         if let Some(task) = self.running_tasks.remove(task_id) {
             task.task_handle.abort();
             // Insert a cancelled result so callers can observe the terminal state.
-            let cancel_result = TaskResult {
-                descriptor: task.descriptor,
-                result: Err(Error::TaskError("Operation cancelled".to_string())),
-            };
-            self.completed_results.push(cancel_result);
-            return true;
+            self.completed_results
+                .push(TaskResult::stopped(task, record));
         }
-        false
+        true
     }
 
     /// Retrieve a completed task result if available.
@@ -296,4 +401,85 @@ impl OperationProcessor {
             None
         }
     }
+
+    /// The current record of a task.
+    ///
+    /// Running tasks and results not yet collected are answered from memory,
+    /// other tasks from the store.
+    pub async fn task_record(
+        &mut self,
+        task_id: &str,
+    ) -> Result<Option<TaskRecord>, TaskStoreError> {
+        self.collect_completed_results();
+        if let Some(task) = self.running_tasks.get(task_id) {
+            return Ok(Some(task.reporter.record()));
+        }
+        if let Some(result) = self
+            .completed_results
+            .iter()
+            .rev()
+            .find(|result| result.descriptor.operation_id == task_id)
+        {
+            return Ok(Some(result.record.clone()));
+        }
+        match self.store.load(task_id).await? {
+            Some(record) => Ok(Some(self.reconcile(record).await)),
+            None => Ok(None),
+        }
+    }
+
+    /// Records of every known task, oldest first, after dropping the
+    /// expired ones.
+    pub async fn task_records(&mut self) -> Result<Vec<TaskRecord>, TaskStoreError> {
+        self.collect_garbage().await?;
+        let mut records = Vec::new();
+        for record in self.store.list().await? {
+            let current = match self.running_tasks.get(record.task_id()) {
+                Some(task) => task.reporter.record(),
+                None => match self
+                    .completed_results
+                    .iter()
+                    .find(|result| result.descriptor.operation_id == record.task_id())
+                {
+                    Some(result) => result.record.clone(),
+                    None => self.reconcile(record).await,
+                },
+            };
+            records.push(current);
+        }
+        // tasks submitted so recently that the store has not seen them yet
+        for task in self.running_tasks.values() {
+            let record = task.reporter.record();
+            if !records
+                .iter()
+                .any(|stored| stored.task_id() == record.task_id())
+            {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Drop expired records from the store and from the uncollected
+    /// results, returning how many were dropped from the store.
+    pub async fn collect_garbage(&mut self) -> Result<usize, TaskStoreError> {
+        self.collect_completed_results();
+        let now = chrono::Utc::now();
+        self.completed_results
+            .retain(|result| !result.record.is_expired(now));
+        self.store.remove_expired(now).await
+    }
+
+    /// A stored record of a task that is not running here: if it never
+    /// reached a terminal status, the process running it stopped.
+    async fn reconcile(&self, mut record: TaskRecord) -> TaskRecord {
+        if !record.is_terminal() && !self.running_tasks.contains_key(record.task_id()) {
+            record.transition(
+                TaskStatus::Failed,
+                Some("Operation interrupted before it finished".to_owned()),
+            );
+            save_record(self.store.as_ref(), &record).await;
+        }
+        record
+    }
 }
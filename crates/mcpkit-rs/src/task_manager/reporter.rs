@@ -0,0 +1,272 @@
+use std::sync::{Arc, Mutex};
+
+use futures::Future;
+
+use super::{TaskRecord, TaskStore, save_record};
+use crate::{
+    RoleServer,
+    model::{
+        ProgressNotificationParam, ProgressToken, Task, TaskStatus, TaskStatusNotificationParam,
+    },
+    service::{Peer, RequestContext},
+};
+#[cfg(feature = "elicitation")]
+use crate::{
+    model::{CreateElicitationRequestParams, CreateElicitationResult, RequestParamsMeta},
+    service::ServiceError,
+};
+
+/// Metadata key tying a request to the task it was sent for.
+pub const RELATED_TASK_META_KEY: &str = "io.modelcontextprotocol/related-task";
+
+tokio::task_local! {
+    static CURRENT: TaskReporter;
+}
+
+/// A handle for an operation running in an
+/// [`OperationProcessor`](super::OperationProcessor) to report on itself.
+///
+/// Every status change is saved to the processor's [`TaskStore`] and sent to
+/// the client as a `notifications/tasks/status` notification, through the
+/// peer of the [`RequestContext`] given to
+/// [`OperationDescriptor::with_context`](super::OperationDescriptor::with_context).
+/// Without a context the changes are only saved.
+///
+/// The processor runs each operation with its reporter in scope, so the
+/// handler does not need to be passed one:
+///
+/// ```rust,ignore
+/// async fn call_tool(&self, request: CallToolRequestParams, context: RequestContext<RoleServer>)
+///     -> Result<CallToolResult, McpError>
+/// {
+///     if let Some(reporter) = TaskReporter::current() {
+///         reporter.report_progress(1.0, Some(3.0), Some("Downloading".into())).await;
+///     }
+///     // ...
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TaskReporter {
+    inner: Arc<ReporterInner>,
+}
+
+struct ReporterInner {
+    record: Mutex<Versioned>,
+    /// Generation of the last record saved, so that a slow save of an older
+    /// record cannot overwrite a newer one
+    saved: tokio::sync::Mutex<u64>,
+    store: Arc<dyn TaskStore>,
+    peer: Option<Peer<RoleServer>>,
+    progress_token: Option<ProgressToken>,
+}
+
+/// A record and the number of changes made to it.
+struct Versioned {
+    record: TaskRecord,
+    generation: u64,
+}
+
+impl std::fmt::Debug for TaskReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskReporter")
+            .field("task", &self.task())
+            .field("progress_token", &self.inner.progress_token)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TaskReporter {
+    pub(super) fn new(
+        record: TaskRecord,
+        store: Arc<dyn TaskStore>,
+        context: Option<&RequestContext<RoleServer>>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(ReporterInner {
+                record: Mutex::new(Versioned {
+                    record,
+                    generation: 1,
+                }),
+                saved: tokio::sync::Mutex::new(0),
+                store,
+                peer: context.map(|context| context.peer.clone()),
+                progress_token: context.and_then(|context| context.meta.get_progress_token()),
+            }),
+        }
+    }
+
+    /// The reporter of the operation being run, if any.
+    ///
+    /// Only available inside an operation submitted to an
+    /// [`OperationProcessor`](super::OperationProcessor), including the tool
+    /// calls run by `#[task_handler]`.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Run `future` with this reporter as [`TaskReporter::current`].
+    pub(super) fn scope<F: Future>(self, future: F) -> impl Future<Output = F::Output> {
+        CURRENT.scope(self, future)
+    }
+
+    pub fn task_id(&self) -> String {
+        self.record().task_id().to_owned()
+    }
+
+    /// The task as last reported.
+    pub fn task(&self) -> Task {
+        self.record().task
+    }
+
+    pub(super) fn record(&self) -> TaskRecord {
+        self.inner
+            .record
+            .lock()
+            .expect("task record poisoned")
+            .record
+            .clone()
+    }
+
+    /// Send a progress notification for the original request, if the client
+    /// asked for progress with a progress token, and update the task's
+    /// status message when `message` is set.
+    pub async fn report_progress(
+        &self,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+    ) {
+        if let Some(message) = &message {
+            self.set_status_message(message.clone()).await;
+        }
+        let (Some(peer), Some(token)) = (&self.inner.peer, &self.inner.progress_token) else {
+            return;
+        };
+        let params = ProgressNotificationParam {
+            progress_token: token.clone(),
+            progress,
+            total,
+            message,
+        };
+        if let Err(error) = peer.notify_progress(params).await {
+            tracing::debug!(task_id = %self.task_id(), %error, "failed to send task progress");
+        }
+    }
+
+    /// Update the status message without changing the status.
+    pub async fn set_status_message(&self, message: impl Into<String>) {
+        let status = self.task().status;
+        self.transition(status, Some(message.into())).await;
+    }
+
+    /// Ask the client for input, moving the task to `input_required` until
+    /// the client answers and back to `working` afterwards.
+    ///
+    /// The request is tagged with the task it belongs to.
+    #[cfg(feature = "elicitation")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "elicitation")))]
+    pub async fn elicit(
+        &self,
+        mut params: CreateElicitationRequestParams,
+    ) -> Result<CreateElicitationResult, ServiceError> {
+        let Some(peer) = self.inner.peer.clone() else {
+            return Err(ServiceError::McpError(crate::ErrorData::internal_error(
+                "task has no request context to ask the client for input",
+                None,
+            )));
+        };
+        let message = match &params {
+            CreateElicitationRequestParams::FormElicitationParams { message, .. }
+            | CreateElicitationRequestParams::UrlElicitationParams { message, .. } => {
+                message.clone()
+            }
+        };
+        params
+            .meta_mut()
+            .get_or_insert_with(Default::default)
+            .insert(
+                RELATED_TASK_META_KEY.to_owned(),
+                serde_json::json!({ "taskId": self.task_id() }),
+            );
+        self.transition(TaskStatus::InputRequired, Some(message))
+            .await;
+        let result = peer.create_elicitation(params).await;
+        self.transition(TaskStatus::Working, None).await;
+        result
+    }
+
+    /// Move the task to `status`, unless it already finished.
+    pub(super) async fn transition(&self, status: TaskStatus, message: Option<String>) {
+        if let Some((record, generation)) = self.update(|record| record.transition(status, message))
+        {
+            self.publish(record, generation, true).await;
+        }
+    }
+
+    /// Save the record the task started with, without notifying the client,
+    /// which learns of the task from the response.
+    pub(super) async fn save_initial(&self) {
+        self.publish(self.record(), 1, false).await;
+    }
+
+    /// Replace the record with the task's terminal one and announce it.
+    ///
+    /// Returns `None`, announcing nothing, if the task was stopped first.
+    pub(super) async fn finish(&self, record: TaskRecord) -> Option<TaskRecord> {
+        let (record, generation) = self.update(|current| *current = record)?;
+        self.publish(record.clone(), generation, true).await;
+        Some(record)
+    }
+
+    /// Move a task that is being aborted to the terminal `status`, saving and
+    /// announcing it in the background.
+    ///
+    /// Returns `None`, announcing nothing, if the task finished first.
+    pub(super) fn stop(&self, status: TaskStatus, message: &str) -> Option<TaskRecord> {
+        let (record, generation) =
+            self.update(|record| record.transition(status, Some(message.to_owned())))?;
+        let reporter = self.clone();
+        let published = record.clone();
+        tokio::spawn(async move { reporter.publish(published, generation, true).await });
+        Some(record)
+    }
+
+    /// Apply `change` to the record, unless the task already reached a
+    /// terminal status, returning the new record and its generation.
+    fn update(&self, change: impl FnOnce(&mut TaskRecord)) -> Option<(TaskRecord, u64)> {
+        let mut current = self.inner.record.lock().expect("task record poisoned");
+        if current.record.is_terminal() {
+            return None;
+        }
+        change(&mut current.record);
+        current.generation += 1;
+        Some((current.record.clone(), current.generation))
+    }
+
+    /// Save `record` and, if `notify` is set, notify the client of its
+    /// status, unless a newer record was saved already.
+    ///
+    /// The work runs on its own task so that aborting the operation cannot
+    /// interrupt it halfway.
+    async fn publish(&self, record: TaskRecord, generation: u64, notify: bool) {
+        let inner = self.inner.clone();
+        let publish = tokio::spawn(async move {
+            let mut saved = inner.saved.lock().await;
+            if *saved >= generation {
+                return;
+            }
+            save_record(inner.store.as_ref(), &record).await;
+            *saved = generation;
+            let Some(peer) = inner.peer.as_ref().filter(|_| notify) else {
+                return;
+            };
+            let params = TaskStatusNotificationParam::new(record.task.clone());
+            if let Err(error) = peer.notify_task_status(params).await {
+                tracing::debug!(task_id = record.task_id(), %error, "failed to send task status");
+            }
+        });
+        if let Err(error) = publish.await {
+            tracing::warn!(%error, "failed to publish task record");
+        }
+    }
+}
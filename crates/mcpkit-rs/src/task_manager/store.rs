@@ -0,0 +1,435 @@
+//! Persistent storage for task state.
+//!
+//! [`OperationProcessor`](super::OperationProcessor) writes a [`TaskRecord`]
+//! to its [`TaskStore`] when a task is submitted and again when it reaches a
+//! terminal status, so `tasks/list`, `tasks/get` and `tasks/result` keep
+//! answering after the result has been collected, and, with a durable store,
+//! after the server restarts.
+//!
+//! # Implementations
+//!
+//! * [`InMemoryTaskStore`] — process-local, the default.
+//! * [`FileTaskStore`] — one JSON file per task in a directory.
+//! * `SqliteTaskStore` — a single SQLite database, with the
+//!   `task-store-sqlite` feature.
+//!
+//! Records whose [`Task::ttl`] has elapsed since creation are dropped by
+//! [`TaskStore::remove_expired`].
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{OperationDescriptor, current_timestamp};
+use crate::{
+    error::ErrorData as McpError,
+    model::{CallToolResult, Task, TaskStatus},
+    storage::{blocking, file_name},
+};
+
+#[cfg(feature = "task-store-sqlite")]
+mod sqlite;
+#[cfg(feature = "task-store-sqlite")]
+#[cfg_attr(docsrs, doc(cfg(feature = "task-store-sqlite")))]
+pub use sqlite::SqliteTaskStore;
+
+#[derive(Debug, Error)]
+pub enum TaskStoreError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[cfg(feature = "task-store-sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// Final outcome of a tool call run as a task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskOutcome {
+    Result(CallToolResult),
+    Error(McpError),
+}
+
+/// A task as kept by a [`TaskStore`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRecord {
+    /// The task as reported to clients: status, status message, timestamps
+    /// and TTL.
+    pub task: Task,
+    /// Name of the operation, the tool name for tool calls.
+    pub name: String,
+    /// Set once a tool call has finished, successfully or not. Tasks that
+    /// failed to run, timed out or were cancelled have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<TaskOutcome>,
+}
+
+impl TaskRecord {
+    /// A freshly submitted, working task.
+    pub fn new(descriptor: &OperationDescriptor) -> Self {
+        let timestamp = current_timestamp();
+        let mut task = Task::new(
+            descriptor.operation_id.clone(),
+            TaskStatus::Working,
+            timestamp.clone(),
+            timestamp,
+        );
+        if let Some(ttl) = descriptor.retention_ttl {
+            task = task.with_ttl(ttl);
+        }
+        Self {
+            task,
+            name: descriptor.name.clone(),
+            outcome: None,
+        }
+    }
+
+    pub fn task_id(&self) -> &str {
+        &self.task.task_id
+    }
+
+    /// Whether the task completed, failed or was cancelled.
+    pub fn is_terminal(&self) -> bool {
+        self.task.status.is_terminal()
+    }
+
+    /// Move the task to `status`, updating `lastUpdatedAt`.
+    pub fn transition(&mut self, status: TaskStatus, status_message: Option<String>) {
+        self.task.status = status;
+        self.task.status_message = status_message;
+        self.task.last_updated_at = current_timestamp();
+    }
+
+    /// Record the outcome of the tool call and the matching terminal status.
+    pub fn finish(&mut self, outcome: TaskOutcome) {
+        let status = match outcome {
+            TaskOutcome::Result(_) => TaskStatus::Completed,
+            TaskOutcome::Error(_) => TaskStatus::Failed,
+        };
+        self.transition(status, None);
+        self.outcome = Some(outcome);
+    }
+
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.task.created_at)
+            .ok()
+            .map(|created_at| created_at.with_timezone(&Utc))
+    }
+
+    /// When the record may be dropped, `None` for unlimited retention.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let ttl = chrono::Duration::milliseconds(self.task.ttl?.try_into().ok()?);
+        self.created_at()?.checked_add_signed(ttl)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at()
+            .is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Storage for the [`TaskRecord`]s of an
+/// [`OperationProcessor`](super::OperationProcessor).
+pub trait TaskStore: std::fmt::Debug + Send + Sync + 'static {
+    /// Insert the record, or replace the one with the same task ID.
+    fn save<'a>(&'a self, record: &'a TaskRecord) -> BoxFuture<'a, Result<(), TaskStoreError>>;
+
+    fn load<'a>(
+        &'a self,
+        task_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<TaskRecord>, TaskStoreError>>;
+
+    /// Every stored record, oldest first.
+    fn list(&self) -> BoxFuture<'_, Result<Vec<TaskRecord>, TaskStoreError>>;
+
+    fn remove<'a>(&'a self, task_id: &'a str) -> BoxFuture<'a, Result<(), TaskStoreError>>;
+
+    /// Drop the records that expired by `now`, returning how many were
+    /// dropped.
+    fn remove_expired(&self, now: DateTime<Utc>) -> BoxFuture<'_, Result<usize, TaskStoreError>>;
+}
+
+fn sort_by_creation(records: &mut [TaskRecord]) {
+    records.sort_by_key(TaskRecord::created_at);
+}
+
+/// A [`TaskStore`] that keeps records in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryTaskStore {
+    records: Mutex<HashMap<String, TaskRecord>>,
+}
+
+impl InMemoryTaskStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TaskStore for InMemoryTaskStore {
+    fn save<'a>(&'a self, record: &'a TaskRecord) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        self.records
+            .lock()
+            .expect("task store poisoned")
+            .insert(record.task_id().to_owned(), record.clone());
+        futures::future::ready(Ok(())).boxed()
+    }
+
+    fn load<'a>(
+        &'a self,
+        task_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<TaskRecord>, TaskStoreError>> {
+        let record = self
+            .records
+            .lock()
+            .expect("task store poisoned")
+            .get(task_id)
+            .cloned();
+        futures::future::ready(Ok(record)).boxed()
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<TaskRecord>, TaskStoreError>> {
+        let mut records: Vec<_> = self
+            .records
+            .lock()
+            .expect("task store poisoned")
+            .values()
+            .cloned()
+            .collect();
+        sort_by_creation(&mut records);
+        futures::future::ready(Ok(records)).boxed()
+    }
+
+    fn remove<'a>(&'a self, task_id: &'a str) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        self.records
+            .lock()
+            .expect("task store poisoned")
+            .remove(task_id);
+        futures::future::ready(Ok(())).boxed()
+    }
+
+    fn remove_expired(&self, now: DateTime<Utc>) -> BoxFuture<'_, Result<usize, TaskStoreError>> {
+        let mut records = self.records.lock().expect("task store poisoned");
+        let before = records.len();
+        records.retain(|_, record| !record.is_expired(now));
+        futures::future::ready(Ok(before - records.len())).boxed()
+    }
+}
+
+/// A [`TaskStore`] that writes each task to a JSON file in a directory.
+///
+/// Files are replaced atomically, so a crash while saving leaves the previous
+/// state of the task behind rather than a torn file. File I/O runs on the
+/// blocking thread pool.
+#[derive(Debug)]
+pub struct FileTaskStore {
+    dir: PathBuf,
+    /// Serializes writers within this process.
+    lock: Arc<Mutex<()>>,
+}
+
+impl FileTaskStore {
+    /// Open a store in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            lock: Default::default(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn task_path(&self, task_id: &str) -> PathBuf {
+        self.dir.join(file_name(task_id, "json"))
+    }
+
+    fn read(path: &Path) -> Result<Option<TaskRecord>, TaskStoreError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(serde_json::from_reader(BufReader::new(file))?))
+    }
+
+    fn write(path: &Path, json: &[u8]) -> Result<(), TaskStoreError> {
+        let tmp = path.with_extension("json.tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        writer.write_all(json)?;
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn delete(path: &Path) -> Result<(), TaskStoreError> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn list_dir(dir: &Path) -> Result<Vec<(PathBuf, TaskRecord)>, TaskStoreError> {
+        let mut records = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            match Self::read(&path) {
+                Ok(Some(record)) => records.push((path, record)),
+                Ok(None) => {}
+                Err(error) => tracing::warn!(?path, %error, "skipping unreadable task record"),
+            }
+        }
+        Ok(records)
+    }
+}
+
+impl TaskStore for FileTaskStore {
+    fn save<'a>(&'a self, record: &'a TaskRecord) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        let path = self.task_path(record.task_id());
+        let lock = self.lock.clone();
+        async move {
+            let json = serde_json::to_vec(record)?;
+            blocking(move || {
+                let _lock = lock.lock().expect("task store poisoned");
+                Self::write(&path, &json)
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn load<'a>(
+        &'a self,
+        task_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<TaskRecord>, TaskStoreError>> {
+        let path = self.task_path(task_id);
+        blocking(move || Self::read(&path)).boxed()
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<TaskRecord>, TaskStoreError>> {
+        let dir = self.dir.clone();
+        blocking(move || {
+            let mut records: Vec<_> = Self::list_dir(&dir)?
+                .into_iter()
+                .map(|(_, record)| record)
+                .collect();
+            sort_by_creation(&mut records);
+            Ok(records)
+        })
+        .boxed()
+    }
+
+    fn remove<'a>(&'a self, task_id: &'a str) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        let path = self.task_path(task_id);
+        let lock = self.lock.clone();
+        blocking(move || {
+            let _lock = lock.lock().expect("task store poisoned");
+            Self::delete(&path)
+        })
+        .boxed()
+    }
+
+    fn remove_expired(&self, now: DateTime<Utc>) -> BoxFuture<'_, Result<usize, TaskStoreError>> {
+        let dir = self.dir.clone();
+        let lock = self.lock.clone();
+        blocking(move || {
+            let _lock = lock.lock().expect("task store poisoned");
+            let mut removed = 0;
+            for (path, record) in Self::list_dir(&dir)? {
+                if record.is_expired(now) {
+                    Self::delete(&path)?;
+                    removed += 1;
+                }
+            }
+            Ok(removed)
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A record kept for `ttl` seconds.
+    pub(super) fn record(task_id: &str, ttl: Option<u64>) -> TaskRecord {
+        let mut descriptor = OperationDescriptor::new(task_id, "tool");
+        descriptor.retention_ttl = ttl.map(|secs| secs * 1000);
+        TaskRecord::new(&descriptor)
+    }
+
+    pub(super) async fn exercise(store: &dyn TaskStore) {
+        let mut first = record("first", None);
+        let second = record("second/../x", Some(60));
+        store.save(&first).await.unwrap();
+        store.save(&second).await.unwrap();
+
+        first.finish(TaskOutcome::Result(CallToolResult::success(vec![])));
+        store.save(&first).await.unwrap();
+        let loaded = store.load("first").await.unwrap().unwrap();
+        assert_eq!(loaded, first);
+        assert_eq!(loaded.task.status, TaskStatus::Completed);
+        assert_eq!(store.list().await.unwrap().len(), 2);
+
+        // the first record never expires, the second after a minute
+        let later = Utc::now() + chrono::Duration::seconds(61);
+        assert_eq!(store.remove_expired(Utc::now()).await.unwrap(), 0);
+        assert_eq!(store.remove_expired(later).await.unwrap(), 1);
+        assert!(store.load("second/../x").await.unwrap().is_none());
+
+        store.remove("first").await.unwrap();
+        assert!(store.list().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_record_expires_after_retention_ttl() {
+        let record = record("task", Some(2));
+        assert_eq!(record.task.ttl, Some(2000));
+        let created_at = record.created_at().unwrap();
+        assert_eq!(
+            record.expires_at(),
+            Some(created_at + chrono::Duration::seconds(2))
+        );
+        assert!(!record.is_expired(created_at));
+        assert!(record.is_expired(created_at + chrono::Duration::seconds(2)));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store() {
+        exercise(&InMemoryTaskStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_store() {
+        let dir = tempfile::tempdir().unwrap();
+        exercise(&FileTaskStore::open(dir.path()).unwrap()).await;
+
+        // records survive reopening the store
+        let record = record("persisted", None);
+        FileTaskStore::open(dir.path())
+            .unwrap()
+            .save(&record)
+            .await
+            .unwrap();
+        let reopened = FileTaskStore::open(dir.path()).unwrap();
+        assert_eq!(reopened.load("persisted").await.unwrap(), Some(record));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::future::{BoxFuture, FutureExt};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::{TaskRecord, TaskStore, TaskStoreError};
+use crate::storage::blocking;
+
+/// Timestamps are stored in a fixed-width UTC format so they order as text.
+fn sql_timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// A [`TaskStore`] backed by a SQLite database.
+///
+/// Each task is one row holding the JSON-encoded [`TaskRecord`], keyed by
+/// task ID, with its expiry time in a separate column so expired tasks are
+/// dropped with a single statement. Queries run on the blocking thread pool.
+#[derive(Debug, Clone)]
+pub struct SqliteTaskStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteTaskStore {
+    /// Open the database at `path`, creating it and the `tasks` table if
+    /// needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TaskStoreError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// A store in a private in-memory database.
+    pub fn open_in_memory() -> Result<Self, TaskStoreError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// Use an already opened connection, creating the `tasks` table if needed.
+    pub fn with_connection(connection: Connection) -> Result<Self, TaskStoreError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                task_id TEXT PRIMARY KEY NOT NULL,
+                expires_at TEXT,
+                record TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS tasks_expires_at ON tasks (expires_at);",
+        )?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Run `f` with the connection on the blocking thread pool.
+    async fn with<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, TaskStoreError> + Send + 'static,
+    ) -> Result<T, TaskStoreError> {
+        let connection = self.connection.clone();
+        blocking(move || f(&connection.lock().expect("task store poisoned"))).await
+    }
+}
+
+impl TaskStore for SqliteTaskStore {
+    fn save<'a>(&'a self, record: &'a TaskRecord) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        async move {
+            let json = serde_json::to_string(record)?;
+            let task_id = record.task_id().to_owned();
+            let expires_at = record.expires_at().map(sql_timestamp);
+            self.with(move |connection| {
+                connection.execute(
+                    "INSERT INTO tasks (task_id, expires_at, record) VALUES (?1, ?2, ?3)
+                     ON CONFLICT (task_id) DO UPDATE SET expires_at = excluded.expires_at, record = excluded.record",
+                    params![task_id, expires_at, json],
+                )?;
+                Ok(())
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn load<'a>(
+        &'a self,
+        task_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<TaskRecord>, TaskStoreError>> {
+        let task_id = task_id.to_owned();
+        self.with(move |connection| {
+            let json: Option<String> = connection
+                .query_row(
+                    "SELECT record FROM tasks WHERE task_id = ?1",
+                    params![task_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+        })
+        .boxed()
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<TaskRecord>, TaskStoreError>> {
+        self.with(|connection| {
+            // rowids follow insertion order, and upserts keep the original row
+            let mut statement = connection.prepare("SELECT record FROM tasks ORDER BY rowid")?;
+            let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+            let mut records = Vec::new();
+            for json in rows {
+                records.push(serde_json::from_str(&json?)?);
+            }
+            Ok(records)
+        })
+        .boxed()
+    }
+
+    fn remove<'a>(&'a self, task_id: &'a str) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        let task_id = task_id.to_owned();
+        self.with(move |connection| {
+            connection.execute("DELETE FROM tasks WHERE task_id = ?1", params![task_id])?;
+            Ok(())
+        })
+        .boxed()
+    }
+
+    fn remove_expired(&self, now: DateTime<Utc>) -> BoxFuture<'_, Result<usize, TaskStoreError>> {
+        let now = sql_timestamp(now);
+        self.with(move |connection| {
+            Ok(connection.execute(
+                "DELETE FROM tasks WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+                params![now],
+            )?)
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        super::super::tests::exercise(&SqliteTaskStore::open_in_memory().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.db");
+        let record = super::super::tests::record("persisted", Some(60));
+        SqliteTaskStore::open(&path)
+            .unwrap()
+            .save(&record)
+            .await
+            .unwrap();
+        let reopened = SqliteTaskStore::open(&path).unwrap();
+        assert_eq!(reopened.load("persisted").await.unwrap(), Some(record));
+    }
+}
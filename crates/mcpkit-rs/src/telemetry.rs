@@ -0,0 +1,475 @@
+//! OpenTelemetry metrics and W3C trace context propagation
+//!
+//! Enabled by the `otel` feature. The crate only depends on the OpenTelemetry
+//! API: install a meter provider and a text map propagator (typically
+//! `TraceContextPropagator`) through [`opentelemetry::global`] and the hooks
+//! below start producing data.
+//!
+//! - [`TelemetryService`] wraps any [`Service`] and records request counts,
+//!   errors and latency by method and tool name. It also makes the trace
+//!   context found in the request `_meta` current while the handler runs.
+//! - Outgoing requests sent through a [`Peer`](crate::Peer) carry the current
+//!   context in `_meta` (`traceparent`/`tracestate`), and the streamable HTTP
+//!   transports mirror it into HTTP headers.
+//! - [`McpMetrics::record_wasm_execution`] records WASM fuel and memory
+//!   gauges from `FuelMetrics`.
+//! - Byte stream transports count the incoming lines they skip, because they
+//!   are too large or malformed, in `mcp.transport.discarded_frames`.
+//!
+//! With the `otel-testing` feature, [`testing::InMemoryTelemetry`] collects
+//! metrics in memory so tests can assert on them.
+
+use std::time::{Duration, Instant};
+
+use opentelemetry::{
+    Context, KeyValue,
+    context::FutureExt as _,
+    global,
+    metrics::{Counter, Histogram, Meter},
+    propagation::{Extractor, Injector},
+    trace::TraceContextExt,
+};
+
+#[cfg(any(feature = "client", feature = "server"))]
+use crate::service::{NotificationContext, RequestContext, Service, ServiceRole};
+use crate::{error::ErrorData as McpError, model::Meta};
+
+/// Instrumentation scope name used for the crate's meter
+pub const METER_NAME: &str = "mcpkit-rs";
+
+const METHOD_KEY: &str = "mcp.method.name";
+const TOOL_KEY: &str = "gen_ai.tool.name";
+const ERROR_KEY: &str = "error.type";
+const DISCARD_REASON_KEY: &str = "mcp.frame.discard_reason";
+
+/// Injects propagation fields into a request `_meta` object
+pub struct MetaInjector<'a>(pub &'a mut Meta);
+
+impl Injector for MetaInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0
+            .insert(key.to_owned(), serde_json::Value::String(value));
+    }
+}
+
+/// Extracts propagation fields from a request `_meta` object
+pub struct MetaExtractor<'a>(pub &'a Meta);
+
+impl Extractor for MetaExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.as_str())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+}
+
+/// Write `cx` into `meta` using the global text map propagator
+pub fn inject_context(cx: &Context, meta: &mut Meta) {
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(cx, &mut MetaInjector(meta))
+    });
+}
+
+/// Read a remote context from `meta` using the global text map propagator
+pub fn extract_context(meta: &Meta) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(&MetaExtractor(meta)))
+}
+
+fn has_remote_context(cx: &Context) -> bool {
+    cx.span().span_context().is_valid()
+}
+
+#[cfg(any(feature = "server-side-http", feature = "client-side-sse"))]
+mod http_headers {
+    use http::{HeaderMap, HeaderName, HeaderValue};
+
+    use super::*;
+
+    struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+    impl Injector for HeaderInjector<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    struct HeaderExtractor<'a>(&'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(HeaderName::as_str).collect()
+        }
+    }
+
+    /// Write `cx` into HTTP headers using the global text map propagator
+    pub fn inject_http_headers(cx: &Context, headers: &mut HeaderMap) {
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(cx, &mut HeaderInjector(headers))
+        });
+    }
+
+    /// Read a remote context from HTTP headers using the global text map propagator
+    pub fn extract_http_headers(headers: &HeaderMap) -> Context {
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+    }
+
+    /// Headers carrying the trace context found in `meta`, for outgoing HTTP requests
+    pub(crate) fn headers_from_meta(meta: &Meta) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let cx = extract_context(meta);
+        if has_remote_context(&cx) {
+            inject_http_headers(&cx, &mut headers);
+        }
+        headers
+    }
+
+    /// Copy the trace context from incoming HTTP headers into `meta`, unless the
+    /// message already carries its own
+    pub(crate) fn headers_into_meta(headers: &HeaderMap, meta: &mut Meta) {
+        if has_remote_context(&extract_context(meta)) {
+            return;
+        }
+        let cx = extract_http_headers(headers);
+        if has_remote_context(&cx) {
+            inject_context(&cx, meta);
+        }
+    }
+}
+
+#[cfg(any(feature = "server-side-http", feature = "client-side-sse"))]
+pub use http_headers::{extract_http_headers, inject_http_headers};
+#[cfg(any(feature = "server-side-http", feature = "client-side-sse"))]
+pub(crate) use http_headers::{headers_from_meta, headers_into_meta};
+
+/// Request and WASM execution instruments
+#[derive(Debug, Clone)]
+pub struct McpMetrics {
+    requests: Counter<u64>,
+    errors: Counter<u64>,
+    duration: Histogram<f64>,
+    discarded_frames: Counter<u64>,
+    #[cfg(feature = "wasm-tools")]
+    wasm_fuel: opentelemetry::metrics::Gauge<u64>,
+    #[cfg(feature = "wasm-tools")]
+    wasm_memory: opentelemetry::metrics::Gauge<u64>,
+    #[cfg(feature = "wasm-tools")]
+    wasm_duration: Histogram<f64>,
+}
+
+impl McpMetrics {
+    /// Create the instruments on `meter`
+    pub fn new(meter: &Meter) -> Self {
+        Self {
+            requests: meter
+                .u64_counter("mcp.requests")
+                .with_description("Number of MCP requests handled")
+                .build(),
+            errors: meter
+                .u64_counter("mcp.request.errors")
+                .with_description("Number of MCP requests that returned an error")
+                .build(),
+            duration: meter
+                .f64_histogram("mcp.request.duration")
+                .with_description("Duration of MCP request handling")
+                .with_unit("s")
+                .build(),
+            discarded_frames: meter
+                .u64_counter("mcp.transport.discarded_frames")
+                .with_description("Number of incoming lines skipped by a byte stream transport")
+                .build(),
+            #[cfg(feature = "wasm-tools")]
+            wasm_fuel: meter
+                .u64_gauge("mcp.wasm.fuel.consumed")
+                .with_description("Compute units consumed by the last WASM tool execution")
+                .build(),
+            #[cfg(feature = "wasm-tools")]
+            wasm_memory: meter
+                .u64_gauge("mcp.wasm.memory.size")
+                .with_description("Linear memory size after the last WASM tool execution")
+                .with_unit("By")
+                .build(),
+            #[cfg(feature = "wasm-tools")]
+            wasm_duration: meter
+                .f64_histogram("mcp.wasm.execution.duration")
+                .with_description("Duration of WASM tool executions")
+                .with_unit("s")
+                .build(),
+        }
+    }
+
+    /// Create the instruments on the global meter provider
+    pub fn global() -> Self {
+        Self::new(&global::meter(METER_NAME))
+    }
+
+    /// Record one handled request
+    pub fn record_request(
+        &self,
+        method: &str,
+        tool: Option<&str>,
+        duration: Duration,
+        error: Option<&McpError>,
+    ) {
+        let mut attributes = vec![KeyValue::new(METHOD_KEY, method.to_owned())];
+        if let Some(tool) = tool {
+            attributes.push(KeyValue::new(TOOL_KEY, tool.to_owned()));
+        }
+        self.requests.add(1, &attributes);
+        self.duration.record(duration.as_secs_f64(), &attributes);
+        if let Some(error) = error {
+            attributes.push(KeyValue::new(ERROR_KEY, error.code.0.to_string()));
+            self.errors.add(1, &attributes);
+        }
+    }
+
+    /// Record one incoming line skipped by a transport, by reason
+    pub fn record_discarded_frame(&self, reason: &'static str) {
+        self.discarded_frames
+            .add(1, &[KeyValue::new(DISCARD_REASON_KEY, reason)]);
+    }
+
+    /// Record fuel, memory and duration of a WASM tool execution
+    #[cfg(feature = "wasm-tools")]
+    pub fn record_wasm_execution(&self, tool: &str, metrics: &crate::wasm::FuelMetrics) {
+        let attributes = [KeyValue::new(TOOL_KEY, tool.to_owned())];
+        self.wasm_fuel.record(metrics.compute_units.0, &attributes);
+        if let Some(memory_bytes) = metrics.memory_bytes {
+            self.wasm_memory.record(memory_bytes, &attributes);
+        }
+        self.wasm_duration
+            .record(metrics.execution_time.as_secs_f64(), &attributes);
+    }
+}
+
+/// Method and tool name used to label a request
+pub trait RequestLabels {
+    fn method_name(&self) -> &str;
+
+    fn tool_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+#[cfg(feature = "server")]
+impl RequestLabels for crate::model::ClientRequest {
+    fn method_name(&self) -> &str {
+        self.method()
+    }
+
+    fn tool_name(&self) -> Option<&str> {
+        match self {
+            crate::model::ClientRequest::CallToolRequest(request) => Some(&request.params.name),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl RequestLabels for crate::model::ServerRequest {
+    fn method_name(&self) -> &str {
+        self.method()
+    }
+}
+
+/// A [`Service`] wrapper that records [`McpMetrics`] for every request and
+/// runs handlers inside the trace context propagated in `_meta`
+#[derive(Debug, Clone)]
+pub struct TelemetryService<S> {
+    inner: S,
+    metrics: McpMetrics,
+}
+
+impl<S> TelemetryService<S> {
+    /// Wrap `inner`, recording to the global meter provider
+    pub fn new(inner: S) -> Self {
+        Self::with_metrics(inner, McpMetrics::global())
+    }
+
+    /// Wrap `inner`, recording to `metrics`
+    pub fn with_metrics(inner: S, metrics: McpMetrics) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// Get a reference to the inner service
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl<R, S> Service<R> for TelemetryService<S>
+where
+    R: ServiceRole,
+    R::PeerReq: RequestLabels,
+    S: Service<R>,
+{
+    async fn handle_request(
+        &self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+    ) -> Result<R::Resp, McpError> {
+        let method = request.method_name().to_owned();
+        let tool = request.tool_name().map(str::to_owned);
+        let cx = extract_context(&context.meta);
+        let started = Instant::now();
+        let result = if has_remote_context(&cx) {
+            self.inner
+                .handle_request(request, context)
+                .with_context(cx)
+                .await
+        } else {
+            self.inner.handle_request(request, context).await
+        };
+        self.metrics.record_request(
+            &method,
+            tool.as_deref(),
+            started.elapsed(),
+            result.as_ref().err(),
+        );
+        result
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: R::PeerNot,
+        context: NotificationContext<R>,
+    ) -> Result<(), McpError> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> R::Info {
+        self.inner.get_info()
+    }
+}
+
+/// In-memory exporters for asserting on telemetry in tests
+#[cfg(feature = "otel-testing")]
+pub mod testing {
+    use opentelemetry::{KeyValue, Value, metrics::MeterProvider as _};
+    use opentelemetry_sdk::metrics::{
+        InMemoryMetricExporter, PeriodicReader, SdkMeterProvider,
+        data::{AggregatedMetrics, MetricData, ResourceMetrics},
+    };
+
+    use super::{METER_NAME, McpMetrics};
+
+    /// A meter provider backed by an [`InMemoryMetricExporter`]
+    pub struct InMemoryTelemetry {
+        provider: SdkMeterProvider,
+        exporter: InMemoryMetricExporter,
+    }
+
+    impl Default for InMemoryTelemetry {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl InMemoryTelemetry {
+        pub fn new() -> Self {
+            let exporter = InMemoryMetricExporter::default();
+            let provider = SdkMeterProvider::builder()
+                .with_reader(PeriodicReader::builder(exporter.clone()).build())
+                .build();
+            Self { provider, exporter }
+        }
+
+        /// Instruments recording into this provider
+        pub fn metrics(&self) -> McpMetrics {
+            McpMetrics::new(&self.provider.meter(METER_NAME))
+        }
+
+        pub fn meter_provider(&self) -> &SdkMeterProvider {
+            &self.provider
+        }
+
+        /// Flush and return the latest cumulative export
+        pub fn collect(&self) -> Option<ResourceMetrics> {
+            if let Err(error) = self.provider.force_flush() {
+                tracing::warn!(%error, "failed to flush in-memory metrics");
+            }
+            self.exporter.get_finished_metrics().ok()?.pop()
+        }
+
+        /// Sum of the `u64` counter `name` across data points matching `attributes`
+        pub fn counter(&self, name: &str, attributes: &[(&str, &str)]) -> u64 {
+            let Some(metrics) = self.collect() else {
+                return 0;
+            };
+            find(&metrics, name)
+                .and_then(|data| match data {
+                    AggregatedMetrics::U64(MetricData::Sum(sum)) => Some(
+                        sum.data_points()
+                            .filter(|p| matches_attributes(p.attributes(), attributes))
+                            .map(|p| p.value())
+                            .sum(),
+                    ),
+                    _ => None,
+                })
+                .unwrap_or_default()
+        }
+
+        /// Number of recordings of the `f64` histogram `name` matching `attributes`
+        pub fn histogram_count(&self, name: &str, attributes: &[(&str, &str)]) -> u64 {
+            let Some(metrics) = self.collect() else {
+                return 0;
+            };
+            find(&metrics, name)
+                .and_then(|data| match data {
+                    AggregatedMetrics::F64(MetricData::Histogram(histogram)) => Some(
+                        histogram
+                            .data_points()
+                            .filter(|p| matches_attributes(p.attributes(), attributes))
+                            .map(|p| p.count())
+                            .sum(),
+                    ),
+                    _ => None,
+                })
+                .unwrap_or_default()
+        }
+
+        /// Last value of the `u64` gauge `name` matching `attributes`
+        pub fn gauge(&self, name: &str, attributes: &[(&str, &str)]) -> Option<u64> {
+            let metrics = self.collect()?;
+            match find(&metrics, name)? {
+                AggregatedMetrics::U64(MetricData::Gauge(gauge)) => gauge
+                    .data_points()
+                    .find(|p| matches_attributes(p.attributes(), attributes))
+                    .map(|p| p.value()),
+                _ => None,
+            }
+        }
+    }
+
+    fn find<'a>(metrics: &'a ResourceMetrics, name: &str) -> Option<&'a AggregatedMetrics> {
+        metrics
+            .scope_metrics()
+            .flat_map(|scope| scope.metrics())
+            .find(|metric| metric.name() == name)
+            .map(|metric| metric.data())
+    }
+
+    fn matches_attributes<'a>(
+        actual: impl Iterator<Item = &'a KeyValue>,
+        expected: &[(&str, &str)],
+    ) -> bool {
+        let actual: Vec<_> = actual.collect();
+        expected.iter().all(|(key, value)| {
+            actual.iter().any(|kv| {
+                kv.key.as_str() == *key
+                    && matches!(&kv.value, Value::String(s) if s.as_str() == *value)
+            })
+        })
+    }
+}
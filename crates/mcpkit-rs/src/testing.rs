@@ -0,0 +1,351 @@
+//! In-process harness for testing server handlers
+//!
+//! [`TestHarness::new`] serves a [`ServerHandler`] over an in-memory duplex
+//! stream and connects a client to it, so a test can talk to the handler
+//! through a real [`Peer`] without spawning processes or binding sockets. The
+//! client side of the harness:
+//!
+//! - records every server notification, see [`TestHarness::notifications`]
+//!   and [`TestHarness::wait_for_notification`];
+//! - answers `sampling/createMessage` and `elicitation/create` from scripts
+//!   queued with [`TestHarness::script_sampling`] and
+//!   [`TestHarness::script_elicitation`], and records the requests;
+//! - declares the sampling, elicitation and roots capabilities, so handlers
+//!   that check them before calling back see a capable client.
+//!
+//! ```rust,ignore
+//! #[tokio::test(start_paused = true)]
+//! async fn test_sum() -> anyhow::Result<()> {
+//!     let harness = TestHarness::new(Calculator::new()).await?;
+//!     harness
+//!         .assert_tool_text("sum", json!({ "a": 1, "b": 2 }), "3")
+//!         .await;
+//!     harness.advance(Duration::from_secs(60)).await;
+//!     harness.shutdown().await;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Enabled by the `testing` feature.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use thiserror::Error;
+use tokio::sync::Notify;
+
+use crate::{
+    ErrorData as McpError, Peer, RoleClient, RoleServer, ServerHandler, ServiceError,
+    model::*,
+    service::{
+        ClientInitializeError, NotificationContext, RequestContext, RunningService,
+        ServerInitializeError, Service, ServiceRole, serve_client, serve_server,
+    },
+};
+
+/// How long [`TestHarness::wait_for_notification`] waits by default.
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Buffer size of the in-memory stream between client and server.
+const DUPLEX_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum TestHarnessError {
+    #[error("server failed to initialize: {0}")]
+    Server(#[from] ServerInitializeError),
+    #[error("client failed to initialize: {0}")]
+    Client(#[from] ClientInitializeError),
+    #[cfg(feature = "wasm-tools")]
+    #[error("failed to load WASM tools: {0}")]
+    Wasm(#[from] crate::wasm::WasmError),
+}
+
+#[derive(Default)]
+struct HarnessState {
+    notifications: Mutex<Vec<ServerNotification>>,
+    notified: Notify,
+    sampling_script: Mutex<VecDeque<Result<CreateMessageResult, McpError>>>,
+    sampling_requests: Mutex<Vec<CreateMessageRequestParams>>,
+    elicitation_script: Mutex<VecDeque<Result<CreateElicitationResult, McpError>>>,
+    elicitation_requests: Mutex<Vec<CreateElicitationRequestParams>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().expect("test harness lock poisoned")
+}
+
+/// The client side of a [`TestHarness`].
+#[derive(Clone)]
+pub struct HarnessClient {
+    state: Arc<HarnessState>,
+}
+
+impl std::fmt::Debug for HarnessClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HarnessClient").finish_non_exhaustive()
+    }
+}
+
+impl Service<RoleClient> for HarnessClient {
+    async fn handle_request(
+        &self,
+        request: ServerRequest,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ClientResult, McpError> {
+        match request {
+            ServerRequest::PingRequest(_) => Ok(ClientResult::empty(())),
+            ServerRequest::CreateMessageRequest(request) => {
+                lock(&self.state.sampling_requests).push(request.params);
+                let scripted = lock(&self.state.sampling_script).pop_front();
+                scripted
+                    .unwrap_or_else(|| {
+                        Err(McpError::internal_error(
+                            "no sampling response scripted in the test harness",
+                            None,
+                        ))
+                    })
+                    .map(Box::new)
+                    .map(ClientResult::CreateMessageResult)
+            }
+            ServerRequest::CreateElicitationRequest(request) => {
+                lock(&self.state.elicitation_requests).push(request.params);
+                let scripted = lock(&self.state.elicitation_script).pop_front();
+                scripted
+                    .unwrap_or_else(|| {
+                        Err(McpError::internal_error(
+                            "no elicitation response scripted in the test harness",
+                            None,
+                        ))
+                    })
+                    .map(ClientResult::CreateElicitationResult)
+            }
+            ServerRequest::ListRootsRequest(_) => {
+                Ok(ClientResult::ListRootsResult(ListRootsResult::default()))
+            }
+            ServerRequest::CustomRequest(request) => Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                request.method,
+                None,
+            )),
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ServerNotification,
+        _context: NotificationContext<RoleClient>,
+    ) -> Result<(), McpError> {
+        lock(&self.state.notifications).push(notification);
+        self.state.notified.notify_waiters();
+        Ok(())
+    }
+
+    fn get_info(&self) -> <RoleClient as ServiceRole>::Info {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder()
+                .enable_roots()
+                .enable_sampling()
+                .enable_sampling_tools()
+                .enable_elicitation()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A server handler connected to an in-process client, see the
+/// [module docs](self).
+///
+/// Dereferences to the client's [`Peer`], so requests can be sent directly:
+/// `harness.list_all_tools().await?`.
+pub struct TestHarness<S: ServerHandler> {
+    client: RunningService<RoleClient, HarnessClient>,
+    server: RunningService<RoleServer, S>,
+    state: Arc<HarnessState>,
+}
+
+impl<S: ServerHandler> std::fmt::Debug for TestHarness<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestHarness")
+            .field("peer", self.client.peer())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: ServerHandler> std::ops::Deref for TestHarness<S> {
+    type Target = Peer<RoleClient>;
+
+    fn deref(&self) -> &Self::Target {
+        self.client.peer()
+    }
+}
+
+impl<S: ServerHandler> TestHarness<S> {
+    /// Serve `handler` and connect a client to it.
+    pub async fn new(handler: S) -> Result<Self, TestHarnessError> {
+        let (server_io, client_io) = tokio::io::duplex(DUPLEX_BUFFER_SIZE);
+        let state = Arc::new(HarnessState::default());
+        let client = HarnessClient {
+            state: state.clone(),
+        };
+        let (server, client) = tokio::join!(
+            serve_server(handler, server_io),
+            serve_client(client, client_io)
+        );
+        Ok(Self {
+            server: server?,
+            client: client?,
+            state,
+        })
+    }
+
+    /// The client's peer, for requests to the handler.
+    pub fn peer(&self) -> &Peer<RoleClient> {
+        self.client.peer()
+    }
+
+    /// The handler under test.
+    pub fn handler(&self) -> &S {
+        self.server.service()
+    }
+
+    /// The server's peer, e.g. to send notifications to the client.
+    pub fn server_peer(&self) -> &Peer<RoleServer> {
+        self.server.peer()
+    }
+
+    /// Call the tool `name` with `arguments`, which must be a JSON object or
+    /// null.
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<CallToolResult, ServiceError> {
+        let mut params = CallToolRequestParams::new(name.to_owned());
+        if let serde_json::Value::Object(arguments) = arguments {
+            params = params.with_arguments(arguments);
+        }
+        self.peer().call_tool(params).await
+    }
+
+    /// Call the tool `name` and assert that it succeeded with the text
+    /// content `expected`.
+    ///
+    /// # Panics
+    /// If the call fails, the tool reports an error, or the text differs.
+    pub async fn assert_tool_text(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+        expected: &str,
+    ) -> CallToolResult {
+        let result = match self.call_tool(name, arguments).await {
+            Ok(result) => result,
+            Err(e) => panic!("calling tool `{name}` failed: {e}"),
+        };
+        assert_ne!(
+            result.is_error,
+            Some(true),
+            "tool `{name}` returned an error: {:?}",
+            result.content
+        );
+        let text: String = result
+            .content
+            .iter()
+            .filter_map(|content| content.as_text())
+            .map(|text| text.text.as_str())
+            .collect();
+        assert_eq!(text, expected, "unexpected content from tool `{name}`");
+        result
+    }
+
+    /// Every notification the client has received so far.
+    pub fn notifications(&self) -> Vec<ServerNotification> {
+        lock(&self.state.notifications).clone()
+    }
+
+    /// Wait for the first received notification, in order of arrival, for
+    /// which `predicate` holds, including those that arrived earlier.
+    ///
+    /// Returns `None` if none arrives within five seconds.
+    pub async fn wait_for_notification(
+        &self,
+        predicate: impl Fn(&ServerNotification) -> bool,
+    ) -> Option<ServerNotification> {
+        let wait = async {
+            loop {
+                let notified = self.state.notified.notified();
+                if let Some(notification) = lock(&self.state.notifications)
+                    .iter()
+                    .find(|notification| predicate(notification))
+                {
+                    return notification.clone();
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(NOTIFICATION_TIMEOUT, wait).await.ok()
+    }
+
+    /// Answer the next `sampling/createMessage` request with `response`.
+    ///
+    /// Responses are used in the order they are scripted; a request with no
+    /// scripted response gets an internal error.
+    pub fn script_sampling(&self, response: Result<CreateMessageResult, McpError>) {
+        lock(&self.state.sampling_script).push_back(response);
+    }
+
+    /// The `sampling/createMessage` requests received so far.
+    pub fn sampling_requests(&self) -> Vec<CreateMessageRequestParams> {
+        lock(&self.state.sampling_requests).clone()
+    }
+
+    /// Answer the next `elicitation/create` request with `response`.
+    ///
+    /// Responses are used in the order they are scripted; a request with no
+    /// scripted response gets an internal error.
+    pub fn script_elicitation(&self, response: Result<CreateElicitationResult, McpError>) {
+        lock(&self.state.elicitation_script).push_back(response);
+    }
+
+    /// The `elicitation/create` requests received so far.
+    pub fn elicitation_requests(&self) -> Vec<CreateElicitationRequestParams> {
+        lock(&self.state.elicitation_requests).clone()
+    }
+
+    /// Move the paused tokio clock forward by `duration`, firing the timers
+    /// that fall due.
+    ///
+    /// # Panics
+    /// If the clock is not paused, e.g. with `#[tokio::test(start_paused = true)]`
+    /// or [`tokio::time::pause`].
+    pub async fn advance(&self, duration: Duration) {
+        tokio::time::advance(duration).await
+    }
+
+    /// Close the client, then wait for the server to stop.
+    pub async fn shutdown(self) {
+        if let Err(e) = self.client.cancel().await {
+            tracing::warn!("test harness client failed to stop: {e}");
+        }
+        if let Err(e) = self.server.waiting().await {
+            tracing::warn!("test harness server failed to stop: {e}");
+        }
+    }
+}
+
+#[cfg(feature = "wasm-tools")]
+impl TestHarness<crate::wasm::WasmToolHandler> {
+    /// Serve the WASM tools found in `tool_dir`, one subdirectory with a
+    /// `manifest.json` per tool, without credentials.
+    pub async fn with_wasm_tools(
+        tool_dir: impl AsRef<std::path::Path>,
+    ) -> Result<Self, TestHarnessError> {
+        let credentials = Arc::new(crate::wasm::credentials::InMemoryCredentialProvider::new());
+        let handler = crate::wasm::load_wasm_tools_from_directory(tool_dir, credentials).await?;
+        Self::new(handler).await
+    }
+}
@@ -2,12 +2,15 @@
 //! The transport type must implemented [`Transport`] trait, which allow it send message concurrently and receive message sequentially.
 //！
 //! ## Standard Transport Types
-//! There are 2 pairs of standard transport types:
+//! There are 5 pairs of standard transport types:
 //!
 //! | transport         | client                                                    | server                                                |
 //! |:-:                |:-:                                                        |:-:                                                    |
 //! | std IO            | [`child_process::TokioChildProcess`]                      | [`io::stdio`]                                         |
 //! | streamable http   | [`streamable_http_client::StreamableHttpClientTransport`] | [`streamable_http_server::StreamableHttpService`]     |
+//! | legacy http+sse   | [`sse_client::SseClientTransport`]                        | [`sse_server::SseService`]                            |
+//! | tcp               | [`tcp::connect`]                                          | [`tcp::TcpServer`]                                    |
+//! | unix socket       | [`unix::connect`]                                         | [`unix::UnixServer`]                                  |
 //!
 //！## Helper Transport Types
 //! Thers are several helper transport types that can help you to create transport quickly.
@@ -27,6 +30,11 @@
 //!
 //! This could be very helpful when you want to create a transport from a duplex object stream, such as a websocket connection.
 //!
+//! ### [Recording](`cassette::RecordingTransport`) and [Replay](`cassette::ReplayTransport`) Transport
+//! Record the messages of any transport to a JSONL cassette, and replay the cassette later as a fake peer.
+//!
+//! This could be very helpful when you want to test a client against a recorded session with a real server, without running it.
+//!
 //! ## [IntoTransport](`IntoTransport`) trait
 //! [`IntoTransport`] is a helper trait that implicitly convert a type into a transport type.
 //!
@@ -77,9 +85,17 @@ use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
 
 pub mod sink_stream;
 
+#[cfg(any(feature = "client", feature = "server"))]
+pub mod cassette;
+#[cfg(any(feature = "client", feature = "server"))]
+pub use cassette::{MatchMode, RecordingTransport, ReplayTransport};
+
 #[cfg(feature = "transport-async-rw")]
 pub mod async_rw;
 
+/// Largest message, in bytes, that the byte stream transports accept by default.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
 #[cfg(feature = "transport-worker")]
 pub mod worker;
 #[cfg(feature = "transport-worker")]
@@ -88,12 +104,34 @@ pub use worker::WorkerTransport;
 #[cfg(feature = "transport-child-process")]
 pub mod child_process;
 #[cfg(feature = "transport-child-process")]
-pub use child_process::{ConfigureCommandExt, TokioChildProcess};
+pub use child_process::{
+    ChildSandbox, ConfigureCommandExt, StderrLog, StderrTail, TokioChildProcess,
+};
 
 #[cfg(feature = "transport-io")]
 pub mod io;
 #[cfg(feature = "transport-io")]
-pub use io::stdio;
+pub use io::{stdio, stdio_with_max_message_size};
+
+#[cfg(all(
+    feature = "server",
+    any(feature = "transport-tcp", all(unix, feature = "transport-unix"))
+))]
+mod listener;
+
+#[cfg(feature = "transport-tcp")]
+pub mod tcp;
+#[cfg(all(feature = "transport-tcp", feature = "server"))]
+pub use tcp::TcpServer;
+#[cfg(feature = "transport-tcp")]
+pub use tcp::TcpServerConfig;
+
+#[cfg(all(unix, feature = "transport-unix"))]
+pub mod unix;
+#[cfg(all(unix, feature = "transport-unix", feature = "server"))]
+pub use unix::UnixServer;
+#[cfg(all(unix, feature = "transport-unix"))]
+pub use unix::UnixServerConfig;
 
 #[cfg(feature = "auth")]
 pub mod auth;
@@ -112,13 +150,25 @@ pub use auth::{
 #[cfg(feature = "transport-streamable-http-server-session")]
 pub mod streamable_http_server;
 #[cfg(feature = "transport-streamable-http-server")]
-pub use streamable_http_server::tower::{StreamableHttpServerConfig, StreamableHttpService};
+pub use streamable_http_server::tower::{
+    SessionService, StreamableHttpServerConfig, StreamableHttpService,
+};
 
 #[cfg(feature = "transport-streamable-http-client")]
 pub mod streamable_http_client;
 #[cfg(feature = "transport-streamable-http-client")]
 pub use streamable_http_client::StreamableHttpClientTransport;
 
+#[cfg(feature = "transport-sse-server")]
+pub mod sse_server;
+#[cfg(feature = "transport-sse-server")]
+pub use sse_server::{SseServerConfig, SseService};
+
+#[cfg(feature = "transport-sse-client")]
+pub mod sse_client;
+#[cfg(feature = "transport-sse-client")]
+pub use sse_client::SseClientTransport;
+
 /// Common use codes
 pub mod common;
 
@@ -146,6 +196,14 @@ where
 
     /// Close the transport
     fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Explain why the other side went away, if the transport knows more than
+    /// that the stream ended, e.g. the last stderr lines of a child process.
+    ///
+    /// Called after [`receive`](Self::receive) returned `None`.
+    fn closed_reason(&self) -> Option<String> {
+        None
+    }
 }
 
 #[cfg(any(feature = "client", feature = "server"))]
@@ -1,4 +1,10 @@
-use std::{marker::PhantomData, sync::Arc};
+use std::{
+    marker::PhantomData,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 // use crate::schema::*;
 use futures::{SinkExt, StreamExt};
@@ -13,8 +19,11 @@ use tokio_util::{
     codec::{Decoder, Encoder, FramedRead, FramedWrite},
 };
 
-use super::{IntoTransport, Transport};
-use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+use super::{DEFAULT_MAX_MESSAGE_SIZE, IntoTransport, Transport};
+use crate::{
+    model::{ErrorData, RequestId},
+    service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage},
+};
 
 pub enum TransportAdapterAsyncRW {}
 
@@ -44,9 +53,21 @@ where
 
 pub type TransportWriter<Role, W> = FramedWrite<W, JsonRpcMessageCodec<TxJsonRpcMessage<Role>>>;
 
+/// Newline delimited JSON-RPC over a byte stream.
+///
+/// Incoming messages larger than the maximum message size
+/// ([`DEFAULT_MAX_MESSAGE_SIZE`] unless set with
+/// [`new_with_max_message_size`](Self::new_with_max_message_size)) are
+/// skipped without being buffered, and so are lines that are not a valid
+/// message. The connection stays open in both cases; if the discarded line was
+/// a request whose id could be read, the peer gets an `invalid request` error
+/// for it. Discarded lines are counted in [`discarded_frames`](Self::discarded_frames).
 pub struct AsyncRwTransport<Role: ServiceRole, R: AsyncRead, W: AsyncWrite> {
-    read: FramedRead<R, JsonRpcMessageCodec<RxJsonRpcMessage<Role>>>,
+    read: FramedRead<R, FrameCodec<RxJsonRpcMessage<Role>>>,
     write: Arc<Mutex<Option<TransportWriter<Role, W>>>>,
+    discarded: DiscardedFrames,
+    #[cfg(feature = "otel")]
+    metrics: crate::telemetry::McpMetrics,
 }
 
 impl<Role: ServiceRole, R, W> AsyncRwTransport<Role, R, W>
@@ -55,15 +76,69 @@ where
     W: Send + AsyncWrite + Unpin + 'static,
 {
     pub fn new(read: R, write: W) -> Self {
+        Self::new_with_max_message_size(read, write, DEFAULT_MAX_MESSAGE_SIZE)
+    }
+
+    /// Create a transport that discards incoming messages longer than
+    /// `max_message_size` bytes.
+    pub fn new_with_max_message_size(read: R, write: W, max_message_size: usize) -> Self {
         let read = FramedRead::new(
             read,
-            JsonRpcMessageCodec::<RxJsonRpcMessage<Role>>::default(),
+            FrameCodec(JsonRpcMessageCodec::new_with_max_length(max_message_size)),
         );
         let write = Arc::new(Mutex::new(Some(FramedWrite::new(
             write,
             JsonRpcMessageCodec::<TxJsonRpcMessage<Role>>::default(),
         ))));
-        Self { read, write }
+        Self {
+            read,
+            write,
+            discarded: DiscardedFrames::default(),
+            #[cfg(feature = "otel")]
+            metrics: crate::telemetry::McpMetrics::global(),
+        }
+    }
+
+    pub fn max_message_size(&self) -> usize {
+        self.read.decoder().0.max_length()
+    }
+
+    /// Counters of the incoming lines this transport has skipped.
+    pub fn discarded_frames(&self) -> &DiscardedFrames {
+        &self.discarded
+    }
+
+    async fn reject(&mut self, frame: DiscardedFrame) {
+        let reason = frame.reason();
+        tracing::warn!(
+            reason = reason.as_str(),
+            "discarding incoming frame: {}",
+            frame.error
+        );
+        self.discarded.record(reason);
+        #[cfg(feature = "otel")]
+        self.metrics.record_discarded_frame(reason.as_str());
+        let Some(id) = frame.request_id else {
+            return;
+        };
+        let error = match &frame.error {
+            JsonRpcMessageCodecError::MaxLineLengthExceeded => ErrorData::invalid_request(
+                format!(
+                    "message exceeds the maximum size of {} bytes",
+                    self.max_message_size()
+                ),
+                None,
+            ),
+            // not JSON at all, as opposed to JSON that is not a valid message
+            JsonRpcMessageCodecError::Serde(e) if e.is_syntax() || e.is_eof() => {
+                ErrorData::parse_error(format!("invalid JSON: {e}"), None)
+            }
+            error => ErrorData::invalid_request(format!("invalid message: {error}"), None),
+        };
+        let reply = TxJsonRpcMessage::<Role>::error(error, id);
+        if let Err(e) = self.send(reply).await {
+            tracing::warn!("failed to reply to discarded request: {e}");
+        }
     }
 }
 
@@ -114,15 +189,16 @@ where
         }
     }
 
-    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<Role>>> {
-        let next = self.read.next();
-        async {
-            next.await.and_then(|e| {
-                e.inspect_err(|e| {
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<Role>> {
+        loop {
+            match self.read.next().await? {
+                Ok(Frame::Message(message)) => return Some(message),
+                Ok(Frame::Discarded(frame)) => self.reject(frame).await,
+                Err(e) => {
                     tracing::error!("Error reading from stream: {}", e);
-                })
-                .ok()
-            })
+                    return None;
+                }
+            }
         }
     }
 
@@ -304,15 +380,162 @@ impl From<JsonRpcMessageCodecError> for std::io::Error {
     }
 }
 
-impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
-    type Item = T;
+/// Why an incoming line was skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscardReason {
+    /// The line was longer than the maximum message size.
+    TooLarge,
+    /// The line was not a valid JSON-RPC message.
+    Malformed,
+}
+
+impl DiscardReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscardReason::TooLarge => "too_large",
+            DiscardReason::Malformed => "malformed",
+        }
+    }
+}
+
+/// Counts of the incoming lines a transport has skipped, shared by clones.
+#[derive(Debug, Clone, Default)]
+pub struct DiscardedFrames {
+    too_large: Arc<AtomicU64>,
+    malformed: Arc<AtomicU64>,
+}
+
+impl DiscardedFrames {
+    fn record(&self, reason: DiscardReason) {
+        let counter = match reason {
+            DiscardReason::TooLarge => &self.too_large,
+            DiscardReason::Malformed => &self.malformed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Lines skipped for exceeding the maximum message size.
+    pub fn too_large(&self) -> u64 {
+        self.too_large.load(Ordering::Relaxed)
+    }
+
+    /// Lines skipped for not being a valid message.
+    pub fn malformed(&self) -> u64 {
+        self.malformed.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.too_large() + self.malformed()
+    }
+}
+
+/// A decoded line: either a message or the reason it was skipped.
+enum Frame<T> {
+    Message(T),
+    Discarded(DiscardedFrame),
+}
+
+struct DiscardedFrame {
+    error: JsonRpcMessageCodecError,
+    /// Id of the skipped line, if it was recognisably a request.
+    request_id: Option<RequestId>,
+}
+
+impl DiscardedFrame {
+    fn reason(&self) -> DiscardReason {
+        match self.error {
+            JsonRpcMessageCodecError::MaxLineLengthExceeded => DiscardReason::TooLarge,
+            _ => DiscardReason::Malformed,
+        }
+    }
+}
+
+/// Decoder for the transport's read half.
+///
+/// Unlike [`JsonRpcMessageCodec`] it reports skipped lines as items rather
+/// than errors, since `FramedRead` ends the stream after the first error.
+struct FrameCodec<T>(JsonRpcMessageCodec<T>);
+
+impl<T: DeserializeOwned> Decoder for FrameCodec<T> {
+    type Item = Frame<T>;
 
     type Error = JsonRpcMessageCodecError;
 
-    fn decode(
-        &mut self,
-        buf: &mut BytesMut,
-    ) -> Result<Option<Self::Item>, JsonRpcMessageCodecError> {
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Frame<T>>, Self::Error> {
+        Ok(self.0.decode_frame(buf))
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Frame<T>>, Self::Error> {
+        Ok(self.0.decode_frame_eof(buf))
+    }
+}
+
+/// Read the id of a JSON-RPC request from `bytes`, which may be truncated.
+///
+/// Returns `None` unless both the `id` and the `method` member were found,
+/// so that responses and notifications never get an error reply.
+fn request_id_of(bytes: &[u8]) -> Option<RequestId> {
+    use serde::{
+        Deserializer,
+        de::{IgnoredAny, MapAccess, Visitor},
+    };
+
+    #[derive(Default)]
+    struct Scan {
+        id: Option<RequestId>,
+        has_method: bool,
+    }
+
+    struct ScanVisitor<'a>(&'a mut Scan);
+
+    impl<'de> Visitor<'de> for ScanVisitor<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a JSON-RPC message")
+        }
+
+        // members are recorded as they are read, so a truncated message still
+        // yields everything before the cut
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+            while let Some(key) = map.next_key::<String>()? {
+                match key.as_str() {
+                    "id" => self.0.id = Some(map.next_value()?),
+                    "method" => {
+                        map.next_value::<IgnoredAny>()?;
+                        self.0.has_method = true;
+                    }
+                    _ => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let mut scan = Scan::default();
+    let _ = serde_json::Deserializer::from_slice(bytes).deserialize_map(ScanVisitor(&mut scan));
+    scan.id.filter(|_| scan.has_method)
+}
+
+impl<T: DeserializeOwned> JsonRpcMessageCodec<T> {
+    fn parse_line(line: &[u8], context: &str) -> Option<Frame<T>> {
+        if line.trim_ascii().is_empty() {
+            return None;
+        }
+        match try_parse_with_compatibility(line, context) {
+            Ok(Some(item)) => Some(Frame::Message(item)),
+            // Skip non-standard message
+            Ok(None) => None,
+            Err(error) => Some(Frame::Discarded(DiscardedFrame {
+                error,
+                request_id: request_id_of(line),
+            })),
+        }
+    }
+
+    fn decode_frame(&mut self, buf: &mut BytesMut) -> Option<Frame<T>> {
         loop {
             // Determine how far into the buffer we'll search for a newline. If
             // there's no max_length set, we'll read to the end of the buffer.
@@ -338,7 +561,7 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                     buf.advance(read_to);
                     self.next_index = 0;
                     if buf.is_empty() {
-                        return Ok(None);
+                        return None;
                     }
                 }
                 (false, Some(offset)) => {
@@ -349,51 +572,69 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                     let line = &line[..line.len() - 1];
                     let line = without_carriage_return(line);
 
-                    // Use compatibility handling function
-                    let item = match try_parse_with_compatibility(line, "decode")? {
-                        Some(item) => item,
-                        None => return Ok(None), // Skip non-standard message
-                    };
-                    return Ok(Some(item));
+                    // Skipped lines fall through to the next one in the buffer
+                    if let Some(frame) = Self::parse_line(line, "decode") {
+                        return Some(frame);
+                    }
                 }
                 (false, None) if buf.len() > self.max_length => {
                     // Reached the maximum length without finding a
-                    // newline, return an error and start discarding on the
+                    // newline, report the line and start discarding on the
                     // next call.
                     self.is_discarding = true;
-                    return Err(JsonRpcMessageCodecError::MaxLineLengthExceeded);
+                    return Some(Frame::Discarded(DiscardedFrame {
+                        error: JsonRpcMessageCodecError::MaxLineLengthExceeded,
+                        request_id: request_id_of(&buf[..self.max_length]),
+                    }));
                 }
                 (false, None) => {
                     // We didn't find a line or reach the length limit, so the next
                     // call will resume searching at the current offset.
                     self.next_index = read_to;
-                    return Ok(None);
+                    return None;
                 }
             }
         }
     }
 
+    fn decode_frame_eof(&mut self, buf: &mut BytesMut) -> Option<Frame<T>> {
+        if let Some(frame) = self.decode_frame(buf) {
+            return Some(frame);
+        }
+        self.next_index = 0;
+        if self.is_discarding {
+            buf.clear();
+            self.is_discarding = false;
+            return None;
+        }
+        // No terminating newline - return remaining data, if any
+        let line = buf.split_to(buf.len());
+        Self::parse_line(without_carriage_return(&line), "decode_eof")
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
+    type Item = T;
+
+    type Error = JsonRpcMessageCodecError;
+
+    fn decode(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> Result<Option<Self::Item>, JsonRpcMessageCodecError> {
+        match self.decode_frame(buf) {
+            Some(Frame::Message(item)) => Ok(Some(item)),
+            Some(Frame::Discarded(frame)) => Err(frame.error),
+            None => Ok(None),
+        }
+    }
+
     fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<T>, JsonRpcMessageCodecError> {
-        Ok(match self.decode(buf)? {
-            Some(frame) => Some(frame),
-            None => {
-                self.next_index = 0;
-                // No terminating newline - return remaining data, if any
-                if buf.is_empty() || buf == &b"\r"[..] {
-                    None
-                } else {
-                    let line = buf.split_to(buf.len());
-                    let line = without_carriage_return(&line);
-
-                    // Use compatibility handling function
-                    let item = match try_parse_with_compatibility(line, "decode_eof")? {
-                        Some(item) => item,
-                        None => return Ok(None), // Skip non-standard message
-                    };
-                    Some(item)
-                }
-            }
-        })
+        match self.decode_frame_eof(buf) {
+            Some(Frame::Message(item)) => Ok(Some(item)),
+            Some(Frame::Discarded(frame)) => Err(frame.error),
+            None => Ok(None),
+        }
     }
 }
 
@@ -558,4 +799,121 @@ mod test {
 
         println!("Standard notifications are preserved, non-standard are handled gracefully");
     }
+
+    #[tokio::test]
+    async fn test_decode_resynchronises() {
+        let data = format!(
+            "{}\nnot json\n\n{}\n",
+            "x".repeat(100),
+            r#"{"jsonrpc":"2.0","method":"ping","id":1}"#
+        );
+        let mut stream = FramedRead::new(
+            data.as_bytes(),
+            FrameCodec(JsonRpcMessageCodec::<serde_json::Value>::new_with_max_length(64)),
+        );
+        let mut reasons = Vec::new();
+        let message = loop {
+            match stream.next().await.unwrap().unwrap() {
+                Frame::Message(message) => break message,
+                Frame::Discarded(frame) => reasons.push(frame.reason()),
+            }
+        };
+        assert_eq!(reasons, [DiscardReason::TooLarge, DiscardReason::Malformed]);
+        assert_eq!(message["method"], "ping");
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn test_request_id_of() {
+        let request = br#"{"jsonrpc":"2.0","id":"a","method":"tools/call","params":{"arguments":{"blob":"AAAA"#;
+        assert_eq!(request_id_of(request), Some(RequestId::String("a".into())));
+        let request = br#"{"method":"tools/call","params":{},"id":7,"jsonrpc":"2.0"}"#;
+        assert_eq!(request_id_of(request), Some(RequestId::Number(7)));
+        // responses must not be answered
+        let response = br#"{"jsonrpc":"2.0","id":7,"result":{"content":"AAAA"#;
+        assert_eq!(request_id_of(response), None);
+        // the id is cut off
+        let request = br#"{"jsonrpc":"2.0","method":"tools/call","params":{"data":"AAAA"#;
+        assert_eq!(request_id_of(request), None);
+        assert_eq!(request_id_of(b"\xff\xfe"), None);
+    }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    async fn test_oversized_request_gets_error_reply() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        use crate::{RoleServer, model::ErrorCode};
+
+        let (server, client) = tokio::io::duplex(4096);
+        let (read, write) = tokio::io::split(server);
+        let mut transport =
+            AsyncRwTransport::<RoleServer, _, _>::new_with_max_message_size(read, write, 256);
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut replies = BufReader::new(client_read).lines();
+
+        let oversized = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{{"name":"echo","arguments":{{"text":"{}"}}}}}}"#,
+            "a".repeat(1000)
+        );
+        let ping = r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#;
+        let input = format!("{oversized}\n{{oops\n{ping}\n");
+        tokio::spawn(async move { client_write.write_all(input.as_bytes()).await });
+
+        let message = transport.receive().await.expect("connection stays open");
+        assert_eq!(message.into_request().unwrap().1, RequestId::Number(2));
+        assert_eq!(transport.discarded_frames().too_large(), 1);
+        assert_eq!(transport.discarded_frames().malformed(), 1);
+
+        let reply: serde_json::Value =
+            serde_json::from_str(&replies.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"]["code"], ErrorCode::INVALID_REQUEST.0);
+        assert_eq!(
+            reply["error"]["message"],
+            "message exceeds the maximum size of 256 bytes"
+        );
+    }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    async fn test_malformed_request_error_codes() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        use crate::{RoleServer, model::ErrorCode};
+
+        let (server, client) = tokio::io::duplex(4096);
+        let (read, write) = tokio::io::split(server);
+        let mut transport = AsyncRwTransport::<RoleServer, _, _>::new(read, write);
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut replies = BufReader::new(client_read).lines();
+
+        let not_json = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":{"#;
+        let not_a_request = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":7}"#;
+        let ping = r#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#;
+        let input = format!(
+            "{not_json}
+{not_a_request}
+{ping}
+"
+        );
+        tokio::spawn(async move { client_write.write_all(input.as_bytes()).await });
+
+        let message = transport.receive().await.expect("connection stays open");
+        assert_eq!(message.into_request().unwrap().1, RequestId::Number(3));
+
+        let mut codes = Vec::new();
+        for _ in 0..2 {
+            let reply: serde_json::Value =
+                serde_json::from_str(&replies.next_line().await.unwrap().unwrap()).unwrap();
+            codes.push((reply["id"].clone(), reply["error"]["code"].clone()));
+        }
+        assert_eq!(
+            codes,
+            [
+                (1.into(), ErrorCode::PARSE_ERROR.0.into()),
+                (2.into(), ErrorCode::INVALID_REQUEST.0.into()),
+            ]
+        );
+    }
 }
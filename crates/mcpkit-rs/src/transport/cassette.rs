@@ -0,0 +1,427 @@
+//! Record a session to a cassette and replay it later as a fake peer.
+//!
+//! [`RecordingTransport`] wraps any transport and appends every message it
+//! sends and receives to a JSONL cassette, one [`CassetteEntry`] per line:
+//!
+//! ```text
+//! {"time":"2025-06-18T10:00:00.000Z","direction":"sent","message":{"jsonrpc":"2.0","id":0,"method":"initialize",...}}
+//! {"time":"2025-06-18T10:00:00.012Z","direction":"received","message":{"jsonrpc":"2.0","id":0,"result":{...}}}
+//! ```
+//!
+//! [`ReplayTransport`] reads a cassette and plays the other side of it: every
+//! message the service sends is matched against the recorded `sent` entries
+//! according to a [`MatchMode`], and the `received` entries are delivered
+//! once all the messages recorded before them have been sent. This makes it
+//! possible to capture a session with a real server once and run the client
+//! tests offline.
+//!
+//! ```rust,ignore
+//! // once, against the real server
+//! let transport = RecordingTransport::create(TokioChildProcess::new(cmd)?, "tests/fixtures/git.jsonl")?;
+//! let client = ().serve(transport).await?;
+//!
+//! // in the test
+//! let replay = ReplayTransport::load("tests/fixtures/git.jsonl", MatchMode::IgnoreIds)?;
+//! let handle = replay.handle();
+//! let client = ().serve(replay).await?;
+//! // ...
+//! assert_eq!(handle.remaining(), 0);
+//! ```
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Notify;
+
+use super::Transport;
+use crate::{
+    model::RequestId,
+    service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage},
+};
+
+/// Which way a recorded message travelled, seen from the recording side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// One line of a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub time: DateTime<Utc>,
+    pub direction: Direction,
+    pub message: Value,
+}
+
+impl CassetteEntry {
+    pub fn new(direction: Direction, message: Value) -> Self {
+        Self {
+            time: Utc::now(),
+            direction,
+            message,
+        }
+    }
+}
+
+/// Read the entries of a JSONL cassette.
+pub fn read_cassette(reader: impl BufRead) -> io::Result<Vec<CassetteEntry>> {
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(&line)?);
+    }
+    Ok(entries)
+}
+
+type CassetteWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// A transport wrapper that writes every message to a cassette.
+///
+/// Failing to write the cassette is logged and does not affect the session.
+pub struct RecordingTransport<T> {
+    inner: T,
+    writer: CassetteWriter,
+}
+
+impl<T> std::fmt::Debug for RecordingTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordingTransport").finish_non_exhaustive()
+    }
+}
+
+impl<T> RecordingTransport<T> {
+    /// Record the messages of `inner` to `writer`.
+    pub fn new(inner: T, writer: impl Write + Send + 'static) -> Self {
+        Self {
+            inner,
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    /// Record the messages of `inner` to a new cassette file at `path`,
+    /// replacing any existing one.
+    pub fn create(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(inner, BufWriter::new(file)))
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn record(&self, direction: Direction, message: &impl Serialize) {
+        let entry = match serde_json::to_value(message) {
+            Ok(message) => CassetteEntry::new(direction, message),
+            Err(e) => {
+                tracing::warn!("failed to serialize message for the cassette: {e}");
+                return;
+            }
+        };
+        let mut writer = self.writer.lock().expect("cassette writer lock poisoned");
+        let written = serde_json::to_writer(&mut *writer, &entry)
+            .map_err(io::Error::from)
+            .and_then(|()| writer.write_all(b"\n"))
+            .and_then(|()| writer.flush());
+        if let Err(e) = written {
+            tracing::warn!("failed to write cassette: {e}");
+        }
+    }
+}
+
+impl<R, T> Transport<R> for RecordingTransport<T>
+where
+    R: ServiceRole,
+    T: Transport<R>,
+{
+    type Error = T::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        self.record(Direction::Sent, &item);
+        self.inner.send(item)
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        let message = self.inner.receive().await?;
+        self.record(Direction::Received, &message);
+        Some(message)
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.inner.close()
+    }
+
+    fn closed_reason(&self) -> Option<String> {
+        self.inner.closed_reason()
+    }
+}
+
+/// How [`ReplayTransport`] decides that a sent message is the recorded one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The whole message must be equal, request ids included.
+    #[default]
+    Exact,
+    /// The whole message must be equal apart from its id.
+    IgnoreIds,
+    /// Requests and notifications must have the same method and params,
+    /// ignoring `_meta`; responses only need to be of the same kind.
+    MethodAndParams,
+}
+
+impl MatchMode {
+    fn matches(&self, recorded: &Value, sent: &Value) -> bool {
+        match self {
+            MatchMode::Exact => recorded == sent,
+            MatchMode::IgnoreIds => without_id(recorded) == without_id(sent),
+            MatchMode::MethodAndParams => match (recorded.get("method"), sent.get("method")) {
+                (Some(recorded_method), Some(sent_method)) => {
+                    recorded_method == sent_method
+                        && params_without_meta(recorded) == params_without_meta(sent)
+                }
+                (None, None) => recorded.get("error").is_some() == sent.get("error").is_some(),
+                _ => false,
+            },
+        }
+    }
+}
+
+fn without_id(message: &Value) -> Value {
+    let mut message = message.clone();
+    if let Some(object) = message.as_object_mut() {
+        object.remove("id");
+    }
+    message
+}
+
+fn params_without_meta(message: &Value) -> Option<Value> {
+    let mut params = message.get("params")?.clone();
+    if let Some(object) = params.as_object_mut() {
+        object.remove("_meta");
+    }
+    Some(params)
+}
+
+fn request_id(message: &Value) -> Option<RequestId> {
+    message
+        .get("method")
+        .and(message.get("id"))
+        .and_then(|id| serde_json::from_value(id.clone()).ok())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error("unexpected message {message}, expected {expected}")]
+    Unexpected { message: String, expected: String },
+    #[error("failed to serialize message: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+#[derive(Debug)]
+struct ReplayState {
+    entries: Vec<CassetteEntry>,
+    consumed: Vec<bool>,
+    /// Live request ids, by the id recorded for the same request
+    ids: HashMap<RequestId, RequestId>,
+    closed: bool,
+    /// The first sent message that did not match, which ends the replay
+    failure: Option<String>,
+}
+
+impl ReplayState {
+    fn remaining(&self) -> usize {
+        self.consumed.iter().filter(|consumed| !**consumed).count()
+    }
+
+    fn match_sent(&mut self, mode: MatchMode, message: &Value) -> Result<(), ReplayError> {
+        let unconsumed = (0..self.entries.len())
+            .filter(|&index| !self.consumed[index])
+            .filter(|&index| self.entries[index].direction == Direction::Sent);
+        let mut expected = None;
+        for index in unconsumed {
+            let recorded = &self.entries[index].message;
+            expected.get_or_insert(index);
+            if !mode.matches(recorded, message) {
+                continue;
+            }
+            if let (Some(recorded_id), Some(live_id)) = (request_id(recorded), request_id(message))
+            {
+                self.ids.insert(recorded_id, live_id);
+            }
+            self.consumed[index] = true;
+            return Ok(());
+        }
+        Err(ReplayError::Unexpected {
+            message: message.to_string(),
+            expected: match expected {
+                Some(index) => self.entries[index].message.to_string(),
+                None => "the end of the cassette".to_owned(),
+            },
+        })
+    }
+
+    /// The next received entry, if every entry before it has been consumed.
+    fn next_received(&mut self) -> Option<Value> {
+        let index = self.consumed.iter().position(|consumed| !consumed)?;
+        if self.entries[index].direction != Direction::Received {
+            return None;
+        }
+        self.consumed[index] = true;
+        let mut message = self.entries[index].message.clone();
+        // responses go back to the ids the live requests were sent with
+        if message.get("method").is_none() {
+            let live_id = message
+                .get("id")
+                .and_then(|id| serde_json::from_value::<RequestId>(id.clone()).ok())
+                .and_then(|id| self.ids.get(&id));
+            if let Some(live_id) = live_id {
+                message["id"] = live_id.clone().into_json_value();
+            }
+        }
+        Some(message)
+    }
+}
+
+/// A fake peer playing back a cassette, see the [module docs](self).
+///
+/// Once the cassette is exhausted, [`receive`](Transport::receive) waits
+/// until the transport is closed, like a live peer that has nothing more to
+/// say. A sent message that matches no recorded one fails the `send` and ends
+/// the replay, with the mismatch as the [`closed_reason`](Transport::closed_reason).
+pub struct ReplayTransport<R> {
+    state: Arc<Mutex<ReplayState>>,
+    notify: Arc<Notify>,
+    mode: MatchMode,
+    _role: std::marker::PhantomData<fn() -> R>,
+}
+
+impl<R> std::fmt::Debug for ReplayTransport<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplayTransport")
+            .field("state", &self.state)
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+impl<R> ReplayTransport<R> {
+    pub fn new(entries: impl IntoIterator<Item = CassetteEntry>, mode: MatchMode) -> Self {
+        let entries: Vec<_> = entries.into_iter().collect();
+        Self {
+            state: Arc::new(Mutex::new(ReplayState {
+                consumed: vec![false; entries.len()],
+                entries,
+                ids: HashMap::new(),
+                closed: false,
+                failure: None,
+            })),
+            notify: Arc::new(Notify::new()),
+            mode,
+            _role: std::marker::PhantomData,
+        }
+    }
+
+    /// Replay the cassette file at `path`.
+    pub fn load(path: impl AsRef<Path>, mode: MatchMode) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self::new(read_cassette(BufReader::new(file))?, mode))
+    }
+
+    /// A handle to check how far the replay got after the transport has been
+    /// moved into a service.
+    pub fn handle(&self) -> ReplayHandle {
+        ReplayHandle {
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Progress of a [`ReplayTransport`].
+#[derive(Debug, Clone)]
+pub struct ReplayHandle {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplayHandle {
+    /// Number of recorded entries not yet sent or delivered.
+    pub fn remaining(&self) -> usize {
+        self.state
+            .lock()
+            .expect("replay state lock poisoned")
+            .remaining()
+    }
+}
+
+impl<R: ServiceRole> Transport<R> for ReplayTransport<R> {
+    type Error = ReplayError;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let result = serde_json::to_value(&item)
+            .map_err(ReplayError::from)
+            .and_then(|message| {
+                let mut state = self.state.lock().expect("replay state lock poisoned");
+                let result = state.match_sent(self.mode, &message);
+                if let Err(e) = &result {
+                    state.failure.get_or_insert_with(|| e.to_string());
+                }
+                result
+            });
+        if let Err(e) = &result {
+            tracing::warn!("replay: {e}");
+        }
+        self.notify.notify_one();
+        std::future::ready(result)
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        loop {
+            let notified = self.notify.notified();
+            let next = {
+                let mut state = self.state.lock().expect("replay state lock poisoned");
+                if state.closed || state.failure.is_some() {
+                    return None;
+                }
+                state.next_received()
+            };
+            match next.map(serde_json::from_value) {
+                Some(Ok(message)) => return Some(message),
+                Some(Err(e)) => tracing::warn!("replay: skipping recorded message: {e}"),
+                None => notified.await,
+            }
+        }
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.state
+            .lock()
+            .expect("replay state lock poisoned")
+            .closed = true;
+        self.notify.notify_one();
+        std::future::ready(Ok(()))
+    }
+
+    fn closed_reason(&self) -> Option<String> {
+        self.state
+            .lock()
+            .expect("replay state lock poisoned")
+            .failure
+            .clone()
+    }
+}
@@ -7,9 +7,17 @@ use tokio::{
     process::{ChildStderr, ChildStdin, ChildStdout},
 };
 
-use super::{RxJsonRpcMessage, Transport, TxJsonRpcMessage, async_rw::AsyncRwTransport};
+use super::{
+    DEFAULT_MAX_MESSAGE_SIZE, RxJsonRpcMessage, Transport, TxJsonRpcMessage,
+    async_rw::{AsyncRwTransport, DiscardedFrames},
+};
 use crate::RoleClient;
 
+mod sandbox;
+pub use sandbox::ChildSandbox;
+mod stderr;
+pub use stderr::{StderrLog, StderrTail};
+
 /// The parts of a child process.
 type ChildProcessParts = (
     Box<dyn TokioChildWrapper>,
@@ -38,6 +46,7 @@ fn child_process(mut child: Box<dyn TokioChildWrapper>) -> std::io::Result<Child
 pub struct TokioChildProcess {
     child: ChildWithCleanup,
     transport: AsyncRwTransport<RoleClient, ChildStdout, ChildStdin>,
+    stderr: Option<StderrTail>,
 }
 
 pub struct ChildWithCleanup {
@@ -100,6 +109,17 @@ impl TokioChildProcess {
         self.child.inner.as_ref()?.id()
     }
 
+    /// The last lines of stderr, if it is forwarded with
+    /// [`TokioChildProcessBuilder::log_stderr`].
+    pub fn stderr_tail(&self) -> Option<&StderrTail> {
+        self.stderr.as_ref()
+    }
+
+    /// Counters of the lines from the child's stdout that were skipped.
+    pub fn discarded_frames(&self) -> &DiscardedFrames {
+        self.transport.discarded_frames()
+    }
+
     /// Gracefully shutdown the child process
     ///
     /// This will first close the transport to the child process (the server),
@@ -173,6 +193,9 @@ pub struct TokioChildProcessBuilder {
     stdin: Stdio,
     stdout: Stdio,
     stderr: Stdio,
+    sandbox: Option<ChildSandbox>,
+    log_stderr: Option<StderrLog>,
+    max_message_size: usize,
 }
 
 impl TokioChildProcessBuilder {
@@ -182,6 +205,9 @@ impl TokioChildProcessBuilder {
             stdin: Stdio::piped(),
             stdout: Stdio::piped(),
             stderr: Stdio::inherit(),
+            sandbox: None,
+            log_stderr: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
         }
     }
 
@@ -201,8 +227,46 @@ impl TokioChildProcessBuilder {
         self
     }
 
+    /// Forward the child's stderr line by line instead of inheriting it,
+    /// see [`StderrLog`].
+    ///
+    /// This overrides [`stderr`](Self::stderr), and [`spawn`](Self::spawn)
+    /// no longer returns the stderr handle.
+    pub fn log_stderr(mut self, log: StderrLog) -> Self {
+        self.log_stderr = Some(log);
+        self
+    }
+
+    /// Discard messages from the child longer than `max_message_size` bytes
+    /// ([`DEFAULT_MAX_MESSAGE_SIZE`] by default).
+    pub fn max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Restrict the child's environment and resources, see [`ChildSandbox`].
+    pub fn sandbox(mut self, sandbox: ChildSandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Sandbox the child according to a compiled policy, see
+    /// [`ChildSandbox::from_policy`].
+    #[cfg(feature = "policy")]
+    pub fn policy(self, policy: &mcpkit_rs_policy::CompiledPolicy) -> Self {
+        self.sandbox(ChildSandbox::from_policy(policy))
+    }
+
     /// Spawn the child process. Returns the transport plus an optional captured stderr handle.
     pub fn spawn(mut self) -> std::io::Result<(TokioChildProcess, Option<ChildStderr>)> {
+        if let Some(sandbox) = &self.sandbox {
+            sandbox.apply(&mut self.cmd)?;
+        }
+
+        if self.log_stderr.is_some() {
+            self.stderr = Stdio::piped();
+        }
+
         // Configure stdio on the command
         self.cmd
             .stdin(self.stdin)
@@ -211,12 +275,21 @@ impl TokioChildProcessBuilder {
 
         // Convert to TokioCommandWrap and spawn
         let mut wrapped_cmd: TokioCommandWrap = self.cmd.into();
-        let (child, stdout, stdin, stderr_opt) = child_process(wrapped_cmd.spawn()?)?;
+        let (child, stdout, stdin, mut stderr_opt) = child_process(wrapped_cmd.spawn()?)?;
+        let stderr = match (self.log_stderr, stderr_opt.take()) {
+            (Some(log), Some(stderr)) => Some(log.spawn(stderr)),
+            (_, stderr) => {
+                stderr_opt = stderr;
+                None
+            }
+        };
 
-        let transport = AsyncRwTransport::new(stdout, stdin);
+        let transport =
+            AsyncRwTransport::new_with_max_message_size(stdout, stdin, self.max_message_size);
         let proc = TokioChildProcess {
             child: ChildWithCleanup { inner: Some(child) },
             transport,
+            stderr,
         };
         Ok((proc, stderr_opt))
     }
@@ -229,16 +302,39 @@ impl Transport<RoleClient> for TokioChildProcess {
         &mut self,
         item: TxJsonRpcMessage<RoleClient>,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
-        self.transport.send(item)
+        let send = self.transport.send(item);
+        let stderr = self.stderr.clone();
+        async move {
+            match (send.await, stderr) {
+                // a broken pipe usually means the child died, say why
+                (Err(error), Some(stderr)) => {
+                    stderr.wait_finished(stderr::STDERR_DRAIN_TIMEOUT).await;
+                    Err(stderr.annotate(error))
+                }
+                (result, _) => result,
+            }
+        }
     }
 
-    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<RoleClient>>> + Send {
-        self.transport.receive()
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<RoleClient>> {
+        let message = self.transport.receive().await;
+        if message.is_none() {
+            // stdout closed, most likely because the child exited: let the
+            // forwarder catch up so the tail includes its last words
+            if let Some(stderr) = &self.stderr {
+                stderr.wait_finished(stderr::STDERR_DRAIN_TIMEOUT).await;
+            }
+        }
+        message
     }
 
     fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
         self.graceful_shutdown()
     }
+
+    fn closed_reason(&self) -> Option<String> {
+        self.stderr.as_ref()?.describe()
+    }
 }
 
 pub trait ConfigureCommandExt {
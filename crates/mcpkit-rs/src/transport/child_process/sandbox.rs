@@ -0,0 +1,418 @@
+//! Environment and resource controls for spawned MCP servers.
+//!
+//! A [`ChildSandbox`] is applied by
+//! [`TokioChildProcessBuilder::sandbox`](super::TokioChildProcessBuilder::sandbox)
+//! just before the child is spawned:
+//!
+//! - the inherited environment is filtered, so credentials of the parent are
+//!   not handed to third-party servers;
+//! - rlimits cap address space, CPU time and open files (unix);
+//! - the child can be started in fresh user and network namespaces, leaving
+//!   it with nothing but a loopback interface (Linux).
+//!
+//! ```rust,ignore
+//! let sandbox = ChildSandbox::new()
+//!     .allow_env(["PATH", "HOME"])
+//!     .memory_bytes(512 * 1024 * 1024)
+//!     .cpu_time(Duration::from_secs(60))
+//!     .isolate_network(true);
+//! let (transport, _) = TokioChildProcess::builder(Command::new("mcp-server"))
+//!     .sandbox(sandbox)
+//!     .spawn()?;
+//! ```
+use std::{collections::HashSet, path::PathBuf, sync::Arc, time::Duration};
+
+type EnvFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Restrictions applied to a child process when it is spawned.
+///
+/// The default sandbox changes nothing: the child inherits the full
+/// environment and has no limits.
+#[derive(Clone, Default)]
+pub struct ChildSandbox {
+    env_filter: Option<EnvFilter>,
+    working_dir: Option<PathBuf>,
+    memory_bytes: Option<u64>,
+    cpu_time: Option<Duration>,
+    open_files: Option<u64>,
+    isolate_network: bool,
+}
+
+impl std::fmt::Debug for ChildSandbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChildSandbox")
+            .field("env_filter", &self.env_filter.is_some())
+            .field("working_dir", &self.working_dir)
+            .field("memory_bytes", &self.memory_bytes)
+            .field("cpu_time", &self.cpu_time)
+            .field("open_files", &self.open_files)
+            .field("isolate_network", &self.isolate_network)
+            .finish()
+    }
+}
+
+impl ChildSandbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a sandbox from a compiled policy.
+    ///
+    /// Inherited variables are kept only if
+    /// [`is_env_allowed`](mcpkit_rs_policy::CompiledPolicy::is_env_allowed)
+    /// accepts them, and the policy's memory, CPU time and open file limits
+    /// become the address space, CPU time and file descriptor rlimits. The
+    /// per-call `execution_time` limit is not applied: `RLIMIT_CPU` caps the
+    /// CPU time of the whole server process, which is what `cpu_time` sets.
+    #[cfg(feature = "policy")]
+    pub fn from_policy(policy: &mcpkit_rs_policy::CompiledPolicy) -> Self {
+        let limits = &policy.resource_limits;
+        let policy = Arc::new(policy.clone());
+        Self {
+            env_filter: Some(Arc::new(move |key| policy.is_env_allowed(key))),
+            memory_bytes: limits.memory_bytes,
+            cpu_time: limits.cpu_time_ms.map(Duration::from_millis),
+            open_files: limits.open_files,
+            ..Self::default()
+        }
+    }
+
+    /// Start the child without any inherited environment variables.
+    ///
+    /// Variables set explicitly on the [`Command`](tokio::process::Command)
+    /// are still passed.
+    pub fn clear_env(self) -> Self {
+        self.env_filter(|_| false)
+    }
+
+    /// Inherit only the named environment variables.
+    pub fn allow_env<I, S>(self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let keys: HashSet<String> = keys.into_iter().map(Into::into).collect();
+        self.env_filter(move |key| keys.contains(key))
+    }
+
+    /// Inherit the environment variables for which `filter` returns `true`.
+    pub fn env_filter(mut self, filter: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.env_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Run the child in `dir`.
+    pub fn working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Limit the child's address space (`RLIMIT_AS`).
+    pub fn memory_bytes(mut self, bytes: u64) -> Self {
+        self.memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Limit the child's CPU time (`RLIMIT_CPU`), rounded up to whole seconds.
+    pub fn cpu_time(mut self, time: Duration) -> Self {
+        self.cpu_time = Some(time);
+        self
+    }
+
+    /// Limit the number of file descriptors the child can open (`RLIMIT_NOFILE`).
+    pub fn open_files(mut self, count: u64) -> Self {
+        self.open_files = Some(count);
+        self
+    }
+
+    /// Start the child in new user and network namespaces, so it has no
+    /// network access beyond its own loopback interface.
+    ///
+    /// Only supported on Linux, and only where unprivileged user namespaces
+    /// are enabled or the parent runs as root.
+    pub fn isolate_network(mut self, isolate: bool) -> Self {
+        self.isolate_network = isolate;
+        self
+    }
+
+    fn has_limits(&self) -> bool {
+        self.memory_bytes.is_some() || self.cpu_time.is_some() || self.open_files.is_some()
+    }
+
+    /// Configure `cmd` so the child is spawned inside this sandbox.
+    pub(super) fn apply(&self, cmd: &mut tokio::process::Command) -> std::io::Result<()> {
+        if let Some(filter) = &self.env_filter {
+            filter_env(cmd, filter.as_ref());
+        }
+        if let Some(dir) = &self.working_dir {
+            cmd.current_dir(dir);
+        }
+        if self.isolate_network && !cfg!(target_os = "linux") {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "network isolation is only supported on Linux",
+            ));
+        }
+        #[cfg(unix)]
+        if self.has_limits() || self.isolate_network {
+            let pre_exec = unix::PreExec::new(self);
+            // SAFETY: the hook only makes async-signal-safe libc calls and
+            // does not allocate.
+            unsafe {
+                cmd.pre_exec(move || pre_exec.run());
+            }
+        }
+        #[cfg(not(unix))]
+        if self.has_limits() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "resource limits are only supported on unix",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Replace the inherited environment of `cmd` with the variables `filter`
+/// accepts, keeping those set explicitly on the command.
+fn filter_env(cmd: &mut tokio::process::Command, filter: &(dyn Fn(&str) -> bool + Send + Sync)) {
+    let explicit: Vec<_> = cmd
+        .as_std()
+        .get_envs()
+        .map(|(key, value)| (key.to_owned(), value.map(ToOwned::to_owned)))
+        .collect();
+    let inherited: Vec<_> = std::env::vars_os()
+        .filter(|(key, _)| {
+            let allowed = key.to_str().is_some_and(filter);
+            if !allowed {
+                tracing::trace!(?key, "not passing environment variable to child process");
+            }
+            allowed
+        })
+        .filter(|(key, _)| !explicit.iter().any(|(explicit, _)| explicit == key))
+        .collect();
+    cmd.env_clear();
+    cmd.envs(inherited);
+    for (key, value) in explicit {
+        if let Some(value) = value {
+            cmd.env(key, value);
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::io;
+
+    use super::ChildSandbox;
+
+    /// Everything the child does between `fork` and `exec`, prepared up front
+    /// so nothing is allocated in the forked process.
+    pub(super) struct PreExec {
+        limits: Vec<(Resource, libc::rlim_t)>,
+        #[cfg(target_os = "linux")]
+        namespaces: Option<namespaces::Unshare>,
+    }
+
+    // glibc and uClibc declare the resource argument of `setrlimit` as an
+    // enum; musl, bionic and the BSDs use a plain int
+    #[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "uclibc")))]
+    type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(all(target_os = "linux", any(target_env = "gnu", target_env = "uclibc"))))]
+    type Resource = libc::c_int;
+
+    impl PreExec {
+        pub(super) fn new(sandbox: &ChildSandbox) -> Self {
+            let mut limits = Vec::new();
+            if let Some(bytes) = sandbox.memory_bytes {
+                limits.push((libc::RLIMIT_AS, bytes as libc::rlim_t));
+            }
+            if let Some(time) = sandbox.cpu_time {
+                let secs = time.as_secs() + u64::from(time.subsec_nanos() > 0);
+                limits.push((libc::RLIMIT_CPU, secs.max(1) as libc::rlim_t));
+            }
+            if let Some(count) = sandbox.open_files {
+                limits.push((libc::RLIMIT_NOFILE, count as libc::rlim_t));
+            }
+            Self {
+                limits,
+                #[cfg(target_os = "linux")]
+                namespaces: sandbox.isolate_network.then(namespaces::Unshare::new),
+            }
+        }
+
+        pub(super) fn run(&self) -> io::Result<()> {
+            #[cfg(target_os = "linux")]
+            if let Some(namespaces) = &self.namespaces {
+                namespaces.run()?;
+            }
+            for &(resource, value) in &self.limits {
+                let limit = libc::rlimit {
+                    rlim_cur: value,
+                    rlim_max: value,
+                };
+                // SAFETY: `limit` is a valid rlimit for the duration of the call.
+                if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    mod namespaces {
+        use std::{ffi::CStr, io};
+
+        /// Enter new network (and, unless root, user) namespaces, mapping the
+        /// caller's ids so the child keeps access to its own files.
+        pub(super) struct Unshare {
+            privileged: bool,
+            uid_map: Vec<u8>,
+            gid_map: Vec<u8>,
+        }
+
+        impl Unshare {
+            pub(super) fn new() -> Self {
+                // SAFETY: these calls cannot fail.
+                let (uid, gid, euid) = unsafe { (libc::getuid(), libc::getgid(), libc::geteuid()) };
+                Self {
+                    privileged: euid == 0,
+                    uid_map: format!("{uid} {uid} 1").into_bytes(),
+                    gid_map: format!("{gid} {gid} 1").into_bytes(),
+                }
+            }
+
+            pub(super) fn run(&self) -> io::Result<()> {
+                if self.privileged {
+                    return unshare(libc::CLONE_NEWNET);
+                }
+                unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET)?;
+                write_file(c"/proc/self/setgroups", b"deny")?;
+                write_file(c"/proc/self/uid_map", &self.uid_map)?;
+                write_file(c"/proc/self/gid_map", &self.gid_map)
+            }
+        }
+
+        fn unshare(flags: libc::c_int) -> io::Result<()> {
+            // SAFETY: unshare only affects the calling process.
+            if unsafe { libc::unshare(flags) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        fn write_file(path: &CStr, contents: &[u8]) -> io::Result<()> {
+            // SAFETY: `path` is nul-terminated and `contents` outlives the
+            // write; the descriptor is closed on every path.
+            unsafe {
+                let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+                let result = if written < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(())
+                };
+                libc::close(fd);
+                result
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+#[cfg(test)]
+mod tests {
+    use tokio::process::Command;
+
+    use super::*;
+    use crate::transport::{ConfigureCommandExt, TokioChildProcess};
+
+    /// Run `script` in a sandboxed shell and return what it wrote to `out`.
+    async fn run(sandbox: ChildSandbox, script: &str) -> std::io::Result<String> {
+        let dir = tempfile::tempdir()?;
+        let command = Command::new("sh").configure(|cmd| {
+            cmd.arg("-c")
+                .arg(format!("{{ {script}; }} > out"))
+                .env("EXPLICIT", "1");
+        });
+        let (child, _) = TokioChildProcess::builder(command)
+            .sandbox(sandbox.working_dir(dir.path()))
+            .spawn()?;
+        let mut child = child.into_inner().expect("child is running");
+        child.inner_mut().wait().await?;
+        std::fs::read_to_string(dir.path().join("out"))
+    }
+
+    #[tokio::test]
+    async fn test_env_is_filtered() {
+        let env = run(ChildSandbox::new().allow_env(["PATH"]), "env")
+            .await
+            .unwrap();
+        let keys: Vec<_> = env
+            .lines()
+            .filter_map(|line| line.split_once('=').map(|(key, _)| key))
+            .filter(|key| *key != "PWD" && *key != "SHLVL" && *key != "_")
+            .collect();
+        assert!(keys.contains(&"PATH"));
+        assert!(keys.contains(&"EXPLICIT"));
+        assert!(!keys.contains(&"HOME"), "unexpected variables: {keys:?}");
+
+        let env = run(ChildSandbox::new().clear_env(), "env").await.unwrap();
+        assert!(!env.contains("PATH="));
+    }
+
+    #[tokio::test]
+    async fn test_rlimits_are_applied() {
+        let sandbox = ChildSandbox::new()
+            .open_files(64)
+            .cpu_time(Duration::from_millis(1500));
+        let limits = run(sandbox, "ulimit -n; ulimit -t").await.unwrap();
+        assert_eq!(limits.lines().collect::<Vec<_>>(), ["64", "2"]);
+    }
+
+    #[cfg(feature = "policy")]
+    #[test]
+    fn test_policy_limits_are_mapped() {
+        let policy = mcpkit_rs_policy::Policy::from_yaml(
+            r#"
+version: "1.0"
+core:
+  resources:
+    limits:
+      memory: "64Mi"
+      execution_time: "1500ms"
+      cpu_time: "1m"
+      open_files: 32
+"#,
+        )
+        .unwrap();
+        let sandbox =
+            ChildSandbox::from_policy(&mcpkit_rs_policy::CompiledPolicy::compile(&policy).unwrap());
+        assert_eq!(sandbox.memory_bytes, Some(64 * 1024 * 1024));
+        // the per-call execution time is not a process CPU limit
+        assert_eq!(sandbox.cpu_time, Some(Duration::from_secs(60)));
+        assert_eq!(sandbox.open_files, Some(32));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_network_is_isolated() {
+        let interfaces = match run(
+            ChildSandbox::new().isolate_network(true),
+            "tail -n +3 /proc/net/dev | cut -d: -f1",
+        )
+        .await
+        {
+            Ok(interfaces) => interfaces,
+            // user namespaces are disabled on this host
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => return,
+            Err(e) => panic!("failed to spawn isolated child: {e}"),
+        };
+        let interfaces: Vec<_> = interfaces.lines().map(str::trim).collect();
+        assert_eq!(interfaces, ["lo"]);
+    }
+}
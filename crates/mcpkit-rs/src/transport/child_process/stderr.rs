@@ -0,0 +1,280 @@
+//! Forward a child server's stderr into the client's logs.
+//!
+//! With [`TokioChildProcessBuilder::log_stderr`](super::TokioChildProcessBuilder::log_stderr)
+//! the child's stderr is read line by line, tagged with the server name and
+//! emitted as a `tracing` event (or passed to a callback), instead of being
+//! interleaved with the parent's own terminal output. The last lines are kept
+//! so that a server which exits during `initialize` leaves something to go on:
+//! they are appended to send errors and to
+//! [`ClientInitializeError::ConnectionClosed`](crate::service::ClientInitializeError::ConnectionClosed),
+//! and are available from [`TokioChildProcess::stderr_tail`](super::TokioChildProcess::stderr_tail).
+//!
+//! ```rust,ignore
+//! let (transport, _) = TokioChildProcess::builder(Command::new("mcp-server-git"))
+//!     .log_stderr(StderrLog::new("git").tail_lines(50))
+//!     .spawn()?;
+//! ```
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::ChildStderr,
+};
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_TAIL_LINES: usize = 20;
+
+/// How long to wait for the rest of stderr once stdout has closed.
+pub(super) const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+type LineCallback = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Options for forwarding a child's stderr.
+#[derive(Clone)]
+pub struct StderrLog {
+    name: String,
+    tail_lines: usize,
+    on_line: Option<LineCallback>,
+}
+
+impl std::fmt::Debug for StderrLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StderrLog")
+            .field("name", &self.name)
+            .field("tail_lines", &self.tail_lines)
+            .field("on_line", &self.on_line.is_some())
+            .finish()
+    }
+}
+
+impl StderrLog {
+    /// Forward stderr as `tracing` events tagged with `server = name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tail_lines: DEFAULT_TAIL_LINES,
+            on_line: None,
+        }
+    }
+
+    /// Keep the last `lines` lines of stderr (20 by default).
+    pub fn tail_lines(mut self, lines: usize) -> Self {
+        self.tail_lines = lines;
+        self
+    }
+
+    /// Call `f(name, line)` for every line instead of emitting a `tracing` event.
+    pub fn on_line(mut self, f: impl Fn(&str, &str) + Send + Sync + 'static) -> Self {
+        self.on_line = Some(Arc::new(f));
+        self
+    }
+
+    /// Start reading `stderr` in the background.
+    pub(super) fn spawn(self, stderr: ChildStderr) -> StderrTail {
+        let tail = StderrTail {
+            name: self.name.clone().into(),
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(self.tail_lines))),
+            finished: CancellationToken::new(),
+        };
+        let task_tail = tail.clone();
+        tokio::spawn(async move {
+            let _finished = task_tail.finished.clone().drop_guard();
+            let mut reader = BufReader::new(stderr);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf).await {
+                    Ok(0) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::debug!(server = %self.name, "failed to read child stderr: {e}");
+                        break;
+                    }
+                }
+                let line = String::from_utf8_lossy(&buf);
+                let line = line.trim_end_matches(['\n', '\r']);
+                match &self.on_line {
+                    Some(on_line) => on_line(&self.name, line),
+                    None => tracing::info!(server = %self.name, "{line}"),
+                }
+                task_tail.push(line, self.tail_lines);
+            }
+        });
+        tail
+    }
+}
+
+/// The most recent stderr lines of a child process.
+#[derive(Clone, Debug)]
+pub struct StderrTail {
+    name: Arc<str>,
+    lines: Arc<Mutex<VecDeque<String>>>,
+    finished: CancellationToken,
+}
+
+impl StderrTail {
+    fn push(&self, line: &str, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        let mut lines = self.lines.lock().expect("stderr tail lock poisoned");
+        if lines.len() == capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_owned());
+    }
+
+    /// The server name given to [`StderrLog::new`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The retained lines, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        let lines = self.lines.lock().expect("stderr tail lock poisoned");
+        lines.iter().cloned().collect()
+    }
+
+    /// Whether the child has closed its stderr.
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_cancelled()
+    }
+
+    /// Wait until the child closes its stderr, or `timeout` elapses.
+    pub(super) async fn wait_finished(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.finished.cancelled()).await;
+    }
+
+    /// The retained lines formatted for an error message, if there are any.
+    pub(super) fn describe(&self) -> Option<String> {
+        let lines = self.lines.lock().expect("stderr tail lock poisoned");
+        if lines.is_empty() {
+            return None;
+        }
+        let mut description = format!("last stderr of `{}`:", self.name);
+        for line in lines.iter() {
+            description.push_str("\n  ");
+            description.push_str(line);
+        }
+        Some(description)
+    }
+
+    /// Append the retained lines to a transport error.
+    pub(super) fn annotate(&self, error: std::io::Error) -> std::io::Error {
+        match self.describe() {
+            Some(description) => {
+                std::io::Error::new(error.kind(), format!("{error}\n{description}"))
+            }
+            None => error,
+        }
+    }
+}
+
+#[cfg(unix)]
+#[cfg(test)]
+mod tests {
+    use tokio::process::Command;
+
+    use super::*;
+    use crate::{
+        service::{ClientInitializeError, QuitReason, serve_client},
+        transport::{ConfigureCommandExt, TokioChildProcess},
+    };
+
+    fn failing_server() -> Command {
+        Command::new("sh").configure(|cmd| {
+            cmd.arg("-c")
+                .arg("echo starting >&2; echo 'missing API_KEY' >&2; exit 1");
+        })
+    }
+
+    #[tokio::test]
+    async fn test_stderr_lines_are_forwarded() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let log = StderrLog::new("failing").on_line({
+            let lines = lines.clone();
+            move |name, line| lines.lock().unwrap().push(format!("{name}: {line}"))
+        });
+        let (child, stderr) = TokioChildProcess::builder(failing_server())
+            .log_stderr(log)
+            .spawn()
+            .unwrap();
+        assert!(stderr.is_none());
+        let tail = child.stderr_tail().unwrap().clone();
+        tail.wait_finished(Duration::from_secs(5)).await;
+        assert!(tail.is_finished());
+        assert_eq!(
+            *lines.lock().unwrap(),
+            ["failing: starting", "failing: missing API_KEY"]
+        );
+        assert_eq!(tail.lines(), ["starting", "missing API_KEY"]);
+    }
+
+    #[tokio::test]
+    async fn test_tail_is_bounded() {
+        let command = Command::new("sh").configure(|cmd| {
+            cmd.arg("-c")
+                .arg("for i in 1 2 3 4 5; do echo $i >&2; done");
+        });
+        let (child, _) = TokioChildProcess::builder(command)
+            .log_stderr(StderrLog::new("counter").tail_lines(2))
+            .spawn()
+            .unwrap();
+        let tail = child.stderr_tail().unwrap();
+        tail.wait_finished(Duration::from_secs(5)).await;
+        assert_eq!(tail.lines(), ["4", "5"]);
+    }
+
+    #[tokio::test]
+    async fn test_quit_reason_includes_stderr() {
+        // answers initialize, then dies once the client is initialized
+        let command = Command::new("sh").configure(|cmd| {
+            cmd.arg("-c").arg(
+                r#"read line
+echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2025-03-26","capabilities":{},"serverInfo":{"name":"dying","version":"1.0.0"}}}'
+read line
+echo 'lost the database' >&2
+exit 1"#,
+            );
+        });
+        let (child, _) = TokioChildProcess::builder(command)
+            .log_stderr(StderrLog::new("dying"))
+            .spawn()
+            .unwrap();
+        let client = serve_client((), child).await.unwrap();
+        match client.waiting().await.unwrap() {
+            QuitReason::Closed {
+                reason: Some(reason),
+            } => assert_eq!(reason, "last stderr of `dying`:\n  lost the database"),
+            other => panic!("unexpected quit reason {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initialize_error_includes_stderr() {
+        let (child, _) = TokioChildProcess::builder(failing_server())
+            .log_stderr(StderrLog::new("failing"))
+            .spawn()
+            .unwrap();
+        // depending on timing, sending initialize fails or the response never comes
+        let error = serve_client((), child).await.unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ClientInitializeError::ConnectionClosed(_)
+                    | ClientInitializeError::TransportError { .. }
+            ),
+            "{error}"
+        );
+        assert!(
+            error
+                .to_string()
+                .contains("last stderr of `failing`:\n  starting\n  missing API_KEY"),
+            "{error}"
+        );
+    }
+}
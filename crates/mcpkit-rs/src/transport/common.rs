@@ -1,4 +1,7 @@
-#[cfg(feature = "transport-streamable-http-server")]
+#[cfg(any(
+    feature = "transport-streamable-http-server",
+    feature = "transport-sse-server"
+))]
 pub mod server_side_http;
 
 pub mod http_header;
@@ -7,8 +10,8 @@ pub mod http_header;
 mod reqwest;
 
 // Note: This module provides SSE stream parsing and auto-reconnect utilities.
-// It's used by the streamable HTTP client (which receives SSE-formatted responses),
-// not the removed SSE transport. The name is historical.
+// It's used by the streamable HTTP client (which receives SSE-formatted responses)
+// and by the legacy HTTP+SSE client transport.
 #[cfg(feature = "client-side-sse")]
 pub mod client_side_sse;
 
@@ -1,2 +1,4 @@
+#[cfg(feature = "transport-sse-client-reqwest")]
+mod sse_client;
 #[cfg(feature = "transport-streamable-http-client-reqwest")]
 mod streamable_http_client;
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use reqwest::header::ACCEPT;
+use sse_stream::SseStream;
+
+use crate::{
+    model::ClientJsonRpcMessage,
+    transport::{
+        common::{
+            client_side_sse::BoxedSseResponse,
+            http_header::{EVENT_STREAM_MIME_TYPE, HEADER_LAST_EVENT_ID},
+        },
+        sse_client::*,
+    },
+};
+
+impl From<reqwest::Error> for SseTransportError<reqwest::Error> {
+    fn from(e: reqwest::Error) -> Self {
+        SseTransportError::Client(e)
+    }
+}
+
+impl SseClient for reqwest::Client {
+    type Error = reqwest::Error;
+
+    async fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        auth_token: Option<String>,
+    ) -> Result<(), SseTransportError<Self::Error>> {
+        let mut request = self.post(uri.as_ref());
+        if let Some(auth_header) = auth_token {
+            request = request.bearer_auth(auth_header);
+        }
+        #[cfg(feature = "otel")]
+        if let ClientJsonRpcMessage::Request(req) = &message {
+            use crate::model::GetMeta;
+            request = request.headers(crate::telemetry::headers_from_meta(req.request.get_meta()));
+        }
+        let response = request.json(&message).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<failed to read response body>".to_owned());
+            return Err(SseTransportError::UnexpectedServerResponse(
+                format!("HTTP {status}: {body}").into(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn get_stream(
+        &self,
+        uri: Arc<str>,
+        last_event_id: Option<String>,
+        auth_token: Option<String>,
+    ) -> Result<BoxedSseResponse, SseTransportError<Self::Error>> {
+        let mut request_builder = self
+            .get(uri.as_ref())
+            .header(ACCEPT, EVENT_STREAM_MIME_TYPE);
+        if let Some(last_event_id) = last_event_id {
+            request_builder = request_builder.header(HEADER_LAST_EVENT_ID, last_event_id);
+        }
+        if let Some(auth_header) = auth_token {
+            request_builder = request_builder.bearer_auth(auth_header);
+        }
+        let response = request_builder.send().await?.error_for_status()?;
+        match response.headers().get(reqwest::header::CONTENT_TYPE) {
+            Some(ct) if ct.as_bytes().starts_with(EVENT_STREAM_MIME_TYPE.as_bytes()) => {}
+            ct => {
+                return Err(SseTransportError::UnexpectedContentType(
+                    ct.map(|ct| String::from_utf8_lossy(ct.as_bytes()).to_string()),
+                ));
+            }
+        }
+        Ok(SseStream::from_bytes_stream(response.bytes_stream()).boxed())
+    }
+}
+
+impl SseClientTransport<reqwest::Client> {
+    /// Creates a new transport using reqwest with the specified event stream URI.
+    ///
+    /// # Feature requirement
+    ///
+    /// This method requires the `transport-sse-client-reqwest` feature.
+    pub fn from_uri(uri: impl Into<Arc<str>>) -> Self {
+        SseClientTransport::with_client(reqwest::Client::default(), SseClientConfig::with_uri(uri))
+    }
+
+    /// Build this transport form a config
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The config to use with this transport
+    pub fn from_config(config: SseClientConfig) -> Self {
+        SseClientTransport::with_client(reqwest::Client::default(), config)
+    }
+}
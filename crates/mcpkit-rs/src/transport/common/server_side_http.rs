@@ -76,24 +76,36 @@ pub(crate) fn sse_stream_response(
     ct: CancellationToken,
 ) -> Response<BoxBody<Bytes, Infallible>> {
     use futures::StreamExt;
-    let stream = stream
-        .map(|message| {
-            let mut sse = if let Some(ref msg) = message.message {
-                let data = serde_json::to_string(msg.as_ref()).expect("valid message");
-                Sse::default().data(data)
-            } else {
-                // Priming event: empty data per SEP-1699 (just "data:\n")
-                Sse::default().data("")
-            };
-
-            sse.id = message.event_id;
-
-            if let Some(retry) = message.retry {
-                sse.retry = Some(retry.as_millis() as u64);
-            }
+    let stream = stream.map(|message| {
+        let mut sse = if let Some(ref msg) = message.message {
+            let data = serde_json::to_string(msg.as_ref()).expect("valid message");
+            Sse::default().data(data)
+        } else {
+            // Priming event: empty data per SEP-1699 (just "data:\n")
+            Sse::default().data("")
+        };
+
+        sse.id = message.event_id;
+
+        if let Some(retry) = message.retry {
+            sse.retry = Some(retry.as_millis() as u64);
+        }
+
+        sse
+    });
+    sse_response(stream, keep_alive, ct)
+}
 
-            Result::<Sse, Infallible>::Ok(sse)
-        })
+/// Builds a `text/event-stream` response from raw SSE frames, ending the
+/// stream when `ct` is cancelled.
+pub(crate) fn sse_response(
+    stream: impl futures::Stream<Item = Sse> + Send + Sync + 'static,
+    keep_alive: Option<Duration>,
+    ct: CancellationToken,
+) -> Response<BoxBody<Bytes, Infallible>> {
+    use futures::StreamExt;
+    let stream = stream
+        .map(Result::<Sse, Infallible>::Ok)
         .take_until(async move { ct.cancelled().await });
     let stream = SseBody::new(stream);
 
@@ -1,6 +1,27 @@
+use super::async_rw::AsyncRwTransport;
+use crate::service::ServiceRole;
+
 /// # StdIO Transport
 ///
 /// Create a pair of [`tokio::io::Stdin`] and [`tokio::io::Stdout`].
 pub fn stdio() -> (tokio::io::Stdin, tokio::io::Stdout) {
     (tokio::io::stdin(), tokio::io::stdout())
 }
+
+/// A stdio transport that discards incoming messages longer than
+/// `max_message_size` bytes, see [`AsyncRwTransport`].
+///
+/// ```rust,ignore
+/// let server = Calculator::new()
+///     .serve(stdio_with_max_message_size(1024 * 1024))
+///     .await?;
+/// ```
+pub fn stdio_with_max_message_size<Role: ServiceRole>(
+    max_message_size: usize,
+) -> AsyncRwTransport<Role, tokio::io::Stdin, tokio::io::Stdout> {
+    AsyncRwTransport::new_with_max_message_size(
+        tokio::io::stdin(),
+        tokio::io::stdout(),
+        max_message_size,
+    )
+}
@@ -0,0 +1,80 @@
+//! Accept loop shared by the TCP and Unix socket servers.
+use std::{fmt::Debug, future::Future, io, sync::Arc, time::Duration};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    task::JoinSet,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    RoleServer, Service, service::serve_server_with_ct, transport::async_rw::AsyncRwTransport,
+};
+
+/// How long to back off after a failed `accept`, e.g. when out of file descriptors.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A listener yielding byte streams to serve MCP over.
+pub(crate) trait Accept: Send {
+    type Stream: AsyncRead + AsyncWrite + Send + Unpin + 'static;
+    type Addr: Debug + Send + 'static;
+
+    fn accept(&self) -> impl Future<Output = io::Result<(Self::Stream, Self::Addr)>> + Send;
+}
+
+/// Serve a new service on every accepted connection until `ct` is cancelled,
+/// then cancel all sessions and wait for them to finish.
+pub(crate) async fn serve_connections<L, S, F>(
+    listener: L,
+    service_factory: F,
+    max_message_size: usize,
+    ct: CancellationToken,
+) where
+    L: Accept,
+    S: Service<RoleServer>,
+    F: Fn() -> Result<S, io::Error> + Send + Sync + 'static,
+{
+    let service_factory = Arc::new(service_factory);
+    let mut sessions = JoinSet::new();
+    loop {
+        tokio::select! {
+            _ = ct.cancelled() => break,
+            Some(_) = sessions.join_next(), if !sessions.is_empty() => {}
+            accepted = listener.accept() => {
+                let (stream, peer) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!("failed to accept connection: {e}");
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                        continue;
+                    }
+                };
+                let service_factory = service_factory.clone();
+                let ct = ct.child_token();
+                // a panicking factory only takes down this connection
+                sessions.spawn(async move {
+                    tracing::debug!(?peer, "connection accepted");
+                    let service = match service_factory() {
+                        Ok(service) => service,
+                        Err(e) => {
+                            tracing::error!(?peer, "failed to create service: {e}");
+                            return;
+                        }
+                    };
+                    let (read, write) = tokio::io::split(stream);
+                    let transport =
+                        AsyncRwTransport::new_with_max_message_size(read, write, max_message_size);
+                    match serve_server_with_ct(service, transport, ct).await {
+                        Ok(service) => {
+                            let _ = service.waiting().await;
+                        }
+                        Err(e) => tracing::warn!(?peer, "failed to initialize session: {e}"),
+                    }
+                    tracing::debug!(?peer, "connection closed");
+                });
+            }
+        }
+    }
+    tracing::debug!(sessions = sessions.len(), "listener shutting down");
+    while sessions.join_next().await.is_some() {}
+}
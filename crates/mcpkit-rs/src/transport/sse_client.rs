@@ -0,0 +1,450 @@
+//! Legacy HTTP+SSE client transport (protocol revision 2024-11-05).
+//!
+//! The transport opens a `GET` event stream, waits for the server's
+//! `endpoint` event and then `POST`s every client message to that endpoint.
+//! Server messages, responses included, arrive on the event stream. If the
+//! stream drops it is reopened according to the retry policy. A server that
+//! announces a different endpoint on the new stream has started a new,
+//! uninitialized session, so the transport fails with
+//! [`SseTransportError::SessionReset`] instead of posting to it.
+use std::{borrow::Cow, sync::Arc};
+
+use futures::{StreamExt, future::BoxFuture};
+pub use sse_stream::Error as SseError;
+use sse_stream::Sse;
+use thiserror::Error;
+
+use super::common::client_side_sse::{
+    BoxedSseResponse, ExponentialBackoff, SseAutoReconnectStream, SseRetryPolicy,
+    SseStreamReconnect,
+};
+use crate::{
+    RoleClient,
+    model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+    transport::worker::{Worker, WorkerQuitReason, WorkerSendRequest, WorkerTransport},
+};
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum SseTransportError<E: std::error::Error + Send + Sync + 'static> {
+    #[error("SSE error: {0}")]
+    Sse(#[from] SseError),
+    #[error("Io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Client error: {0}")]
+    Client(E),
+    #[error("unexpected end of stream")]
+    UnexpectedEndOfStream,
+    #[error("unexpected server response: {0}")]
+    UnexpectedServerResponse(Cow<'static, str>),
+    #[error("Unexpected content type: {0:?}")]
+    UnexpectedContentType(Option<String>),
+    #[error("Invalid message endpoint: {0}")]
+    InvalidEndpoint(String),
+    #[error(
+        "server started a new session at {0} after reconnecting; the client must initialize again"
+    )]
+    SessionReset(Arc<str>),
+    #[error("Tokio join error: {0}")]
+    TokioJoinError(#[from] tokio::task::JoinError),
+    #[error("Transport channel closed")]
+    TransportChannelClosed,
+}
+
+/// The HTTP operations the legacy SSE transport needs.
+pub trait SseClient: Clone + Send + Sync + 'static {
+    type Error: std::error::Error + Send + Sync + 'static;
+    fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        auth_header: Option<String>,
+    ) -> impl Future<Output = Result<(), SseTransportError<Self::Error>>> + Send + '_;
+    fn get_stream(
+        &self,
+        uri: Arc<str>,
+        last_event_id: Option<String>,
+        auth_header: Option<String>,
+    ) -> impl Future<Output = Result<BoxedSseResponse, SseTransportError<Self::Error>>> + Send + '_;
+}
+
+#[derive(Debug, Clone)]
+pub struct SseClientConfig {
+    /// The URL of the server's event stream, e.g. `http://localhost:8000/sse`.
+    pub sse_endpoint: Arc<str>,
+    pub retry_config: Arc<dyn SseRetryPolicy>,
+    pub channel_buffer_capacity: usize,
+    /// The value to send in the authorization header
+    pub auth_header: Option<String>,
+}
+
+impl SseClientConfig {
+    pub fn with_uri(uri: impl Into<Arc<str>>) -> Self {
+        Self {
+            sse_endpoint: uri.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the authorization header to send with requests
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A bearer token without the `Bearer ` prefix
+    pub fn auth_header<T: Into<String>>(mut self, value: T) -> Self {
+        self.auth_header = Some(value.into());
+        self
+    }
+}
+
+impl Default for SseClientConfig {
+    fn default() -> Self {
+        Self {
+            sse_endpoint: "localhost".into(),
+            retry_config: Arc::new(ExponentialBackoff::default()),
+            channel_buffer_capacity: 16,
+            auth_header: None,
+        }
+    }
+}
+
+/// The scheme, lowercased host and port of an absolute URL, with the port
+/// defaulted from the scheme.
+fn origin(uri: &http::Uri) -> Option<(&str, String, u16)> {
+    let scheme = uri.scheme_str()?;
+    let host = uri.host()?.to_ascii_lowercase();
+    let port = match (uri.port_u16(), scheme) {
+        (Some(port), _) => port,
+        (None, "https") => 443,
+        (None, _) => 80,
+    };
+    Some((scheme, host, port))
+}
+
+/// Resolves the data of an `endpoint` event against the event stream URL.
+///
+/// Servers usually send a path such as `/messages?sessionId=..`, but absolute
+/// URLs and paths relative to the stream are accepted as well. Absolute URLs
+/// must share the stream's scheme, host and port: messages carry the
+/// authorization header, which must not be sent to another origin.
+fn resolve_endpoint(sse_endpoint: &str, endpoint: &str) -> Result<Arc<str>, String> {
+    let endpoint = endpoint.trim();
+    if endpoint.is_empty() {
+        return Err("empty endpoint event".into());
+    }
+    let base = sse_endpoint
+        .parse::<http::Uri>()
+        .map_err(|e| format!("{sse_endpoint}: {e}"))?;
+    let (Some(scheme), Some(authority)) = (base.scheme_str(), base.authority()) else {
+        return Err(format!("{sse_endpoint}: not an absolute url"));
+    };
+    if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        let uri = endpoint
+            .parse::<http::Uri>()
+            .map_err(|e| format!("{endpoint}: {e}"))?;
+        if origin(&uri) != origin(&base) {
+            return Err(format!(
+                "{endpoint}: not on the same origin as {sse_endpoint}"
+            ));
+        }
+        return Ok(endpoint.into());
+    }
+    if endpoint.starts_with('/') {
+        return Ok(format!("{scheme}://{authority}{endpoint}").into());
+    }
+    let dir = base
+        .path()
+        .rsplit_once('/')
+        .map(|(dir, _)| dir)
+        .unwrap_or_default();
+    Ok(format!("{scheme}://{authority}{dir}/{endpoint}").into())
+}
+
+struct SseClientReconnect<C> {
+    client: C,
+    sse_endpoint: Arc<str>,
+    message_endpoint: Arc<str>,
+    auth_header: Option<String>,
+}
+
+impl<C: SseClient> SseStreamReconnect for SseClientReconnect<C> {
+    type Error = SseTransportError<C::Error>;
+    type Future = BoxFuture<'static, Result<BoxedSseResponse, Self::Error>>;
+    fn retry_connection(&mut self, last_event_id: Option<&str>) -> Self::Future {
+        let client = self.client.clone();
+        let uri = self.sse_endpoint.clone();
+        let auth_header = self.auth_header.clone();
+        let last_event_id = last_event_id.map(|s| s.to_owned());
+        Box::pin(async move { client.get_stream(uri, last_event_id, auth_header).await })
+    }
+    fn handle_control_event(&mut self, event: &Sse) -> Result<(), Self::Error> {
+        if event.event.as_deref() != Some("endpoint") {
+            return Ok(());
+        }
+        let endpoint = resolve_endpoint(&self.sse_endpoint, event.data.as_deref().unwrap_or(""))
+            .map_err(SseTransportError::InvalidEndpoint)?;
+        if endpoint != self.message_endpoint {
+            // the session this client initialized is gone on the server
+            return Err(SseTransportError::SessionReset(endpoint));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SseClientWorker<C: SseClient> {
+    pub client: C,
+    pub config: SseClientConfig,
+}
+
+impl<C: SseClient> SseClientWorker<C> {
+    pub fn new(client: C, config: SseClientConfig) -> Self {
+        Self { client, config }
+    }
+
+    /// Reads the stream until the server announces where to post messages.
+    async fn wait_for_endpoint(
+        &self,
+        stream: &mut BoxedSseResponse,
+    ) -> Result<Arc<str>, SseTransportError<C::Error>> {
+        while let Some(event) = stream.next().await {
+            let event = event?;
+            if event.event.as_deref() == Some("endpoint") {
+                return resolve_endpoint(
+                    &self.config.sse_endpoint,
+                    event.data.as_deref().unwrap_or(""),
+                )
+                .map_err(SseTransportError::InvalidEndpoint);
+            }
+            tracing::debug!(?event, "skipping event received before endpoint");
+        }
+        Err(SseTransportError::UnexpectedEndOfStream)
+    }
+
+    async fn connect(&self) -> Result<(BoxedSseResponse, Arc<str>), SseTransportError<C::Error>> {
+        let mut stream = self
+            .client
+            .get_stream(
+                self.config.sse_endpoint.clone(),
+                None,
+                self.config.auth_header.clone(),
+            )
+            .await?;
+        let endpoint = self.wait_for_endpoint(&mut stream).await?;
+        Ok((stream, endpoint))
+    }
+}
+
+impl<C: SseClient> Worker for SseClientWorker<C> {
+    type Role = RoleClient;
+    type Error = SseTransportError<C::Error>;
+    fn err_closed() -> Self::Error {
+        SseTransportError::TransportChannelClosed
+    }
+    fn err_join(e: tokio::task::JoinError) -> Self::Error {
+        SseTransportError::TokioJoinError(e)
+    }
+    fn config(&self) -> super::worker::WorkerConfig {
+        super::worker::WorkerConfig {
+            name: Some("SseClientWorker".into()),
+            channel_buffer_capacity: self.config.channel_buffer_capacity,
+        }
+    }
+    async fn run(
+        self,
+        mut context: super::worker::WorkerContext<Self>,
+    ) -> Result<(), WorkerQuitReason<Self::Error>> {
+        let transport_task_ct = context.cancellation_token.clone();
+        let _drop_guard = transport_task_ct.clone().drop_guard();
+        let WorkerSendRequest {
+            responder,
+            message: initialize_request,
+        } = context.recv_from_handler().await?;
+        let connected = match self.connect().await {
+            Ok((stream, endpoint)) => self
+                .client
+                .post_message(
+                    endpoint.clone(),
+                    initialize_request,
+                    self.config.auth_header.clone(),
+                )
+                .await
+                .map(|()| (stream, endpoint)),
+            Err(e) => Err(e),
+        };
+        let (stream, endpoint) = match connected {
+            Ok(connected) => {
+                let _ = responder.send(Ok(()));
+                connected
+            }
+            Err(err) => {
+                let msg = format!("{:?}", err);
+                let _ = responder.send(Err(err));
+                return Err(WorkerQuitReason::fatal(
+                    SseTransportError::TransportChannelClosed,
+                    msg,
+                ));
+            }
+        };
+        let sse_stream = SseAutoReconnectStream::new(
+            stream,
+            SseClientReconnect {
+                client: self.client.clone(),
+                sse_endpoint: self.config.sse_endpoint.clone(),
+                message_endpoint: endpoint.clone(),
+                auth_header: self.config.auth_header.clone(),
+            },
+            self.config.retry_config.clone(),
+        );
+        let mut sse_stream = std::pin::pin!(sse_stream);
+        #[allow(clippy::large_enum_variant)]
+        enum Event<W: Worker, E: std::error::Error + Send + Sync + 'static> {
+            ClientMessage(WorkerSendRequest<W>),
+            ServerMessage(Option<Result<ServerJsonRpcMessage, SseTransportError<E>>>),
+        }
+        loop {
+            let event: Event<Self, C::Error> = tokio::select! {
+                _ = transport_task_ct.cancelled() => {
+                    tracing::debug!("cancelled");
+                    return Err(WorkerQuitReason::Cancelled);
+                }
+                message = context.recv_from_handler() => Event::ClientMessage(message?),
+                message = sse_stream.next() => Event::ServerMessage(message),
+            };
+            match event {
+                Event::ClientMessage(WorkerSendRequest { message, responder }) => {
+                    let result = self
+                        .client
+                        .post_message(endpoint.clone(), message, self.config.auth_header.clone())
+                        .await;
+                    let _ = responder.send(result);
+                }
+                Event::ServerMessage(Some(Ok(message))) => {
+                    context.send_to_handler(message).await?;
+                }
+                Event::ServerMessage(Some(Err(e))) => {
+                    return Err(WorkerQuitReason::fatal(e, "receive server message"));
+                }
+                Event::ServerMessage(None) => {
+                    return Err(WorkerQuitReason::fatal(
+                        SseTransportError::UnexpectedEndOfStream,
+                        "receive server message",
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// A client transport for servers that only speak the legacy HTTP+SSE
+/// protocol.
+///
+/// ```rust,no_run
+/// use mcpkit_rs::transport::SseClientTransport;
+///
+/// // Enable the `transport-sse-client-reqwest` feature in Cargo.toml.
+/// let transport = SseClientTransport::from_uri("http://localhost:8000/sse");
+/// ```
+///
+/// # Feature Flags
+///
+/// - `transport-sse-client`: Base feature providing the generic transport infrastructure
+/// - `transport-sse-client-reqwest`: Includes reqwest HTTP client support with convenience methods
+pub type SseClientTransport<C> = WorkerTransport<SseClientWorker<C>>;
+
+impl<C: SseClient> SseClientTransport<C> {
+    /// Creates a new transport with a custom HTTP client implementation.
+    pub fn with_client(client: C, config: SseClientConfig) -> Self {
+        WorkerTransport::spawn(SseClientWorker::new(client, config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_endpoint() {
+        let base = "http://127.0.0.1:8000/mcp/sse";
+        assert_eq!(
+            resolve_endpoint(base, "/messages?sessionId=1")
+                .unwrap()
+                .as_ref(),
+            "http://127.0.0.1:8000/messages?sessionId=1"
+        );
+        assert_eq!(
+            resolve_endpoint(base, "messages?sessionId=1")
+                .unwrap()
+                .as_ref(),
+            "http://127.0.0.1:8000/mcp/messages?sessionId=1"
+        );
+        assert_eq!(
+            resolve_endpoint(base, "http://127.0.0.1:8000/messages?sessionId=1")
+                .unwrap()
+                .as_ref(),
+            "http://127.0.0.1:8000/messages?sessionId=1"
+        );
+        assert!(resolve_endpoint(base, "https://other.example/messages").is_err());
+        assert!(resolve_endpoint(base, "https://127.0.0.1:8000/messages").is_err());
+        assert!(resolve_endpoint(base, "http://127.0.0.1:9000/messages").is_err());
+        assert_eq!(
+            resolve_endpoint(
+                "https://Example.com/sse",
+                "https://example.com:443/messages"
+            )
+            .unwrap()
+            .as_ref(),
+            "https://example.com:443/messages"
+        );
+        assert!(resolve_endpoint(base, "").is_err());
+        assert!(resolve_endpoint("/sse", "/messages").is_err());
+    }
+
+    #[derive(Clone)]
+    struct UnreachableClient;
+
+    impl SseClient for UnreachableClient {
+        type Error = std::io::Error;
+
+        async fn post_message(
+            &self,
+            _uri: Arc<str>,
+            _message: ClientJsonRpcMessage,
+            _auth_header: Option<String>,
+        ) -> Result<(), SseTransportError<Self::Error>> {
+            unreachable!()
+        }
+
+        async fn get_stream(
+            &self,
+            _uri: Arc<str>,
+            _last_event_id: Option<String>,
+            _auth_header: Option<String>,
+        ) -> Result<BoxedSseResponse, SseTransportError<Self::Error>> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_new_endpoint_after_reconnect_fails() {
+        let mut reconnect = SseClientReconnect {
+            client: UnreachableClient,
+            sse_endpoint: "http://127.0.0.1:8000/sse".into(),
+            message_endpoint: "http://127.0.0.1:8000/messages?sessionId=1".into(),
+            auth_header: None,
+        };
+        let endpoint = |session: &str| Sse {
+            event: Some("endpoint".into()),
+            data: Some(format!("/messages?sessionId={session}")),
+            id: None,
+            retry: None,
+        };
+
+        assert!(reconnect.handle_control_event(&endpoint("1")).is_ok());
+        assert!(matches!(
+            reconnect.handle_control_event(&endpoint("2")),
+            Err(SseTransportError::SessionReset(endpoint))
+                if endpoint.as_ref() == "http://127.0.0.1:8000/messages?sessionId=2"
+        ));
+    }
+}
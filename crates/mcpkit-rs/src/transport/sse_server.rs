@@ -0,0 +1,293 @@
+//! Legacy HTTP+SSE server transport (protocol revision 2024-11-05).
+//!
+//! A client opens a long-lived `GET` request and receives an `endpoint` event
+//! naming the URL it must `POST` its JSON-RPC messages to. Every server
+//! message, including responses, is delivered on that SSE stream; the `POST`
+//! itself only answers `202 Accepted`.
+//!
+//! [`SseService`] answers both halves of the exchange, so mount it on both
+//! paths:
+//!
+//! ```rust,ignore
+//! let service = SseService::new(|| Ok(Counter::new()), SseServerConfig::default());
+//! let router = axum::Router::new()
+//!     .route_service("/sse", service.clone())
+//!     .route_service("/messages", service);
+//! ```
+//!
+//! New deployments should prefer the Streamable HTTP transport; this one is
+//! kept for clients that have not migrated yet.
+use std::{collections::HashMap, convert::Infallible, fmt::Display, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use futures::{StreamExt, future::BoxFuture};
+use http::{Method, Request, Response, header::ALLOW};
+use http_body::Body;
+use http_body_util::{BodyExt, Full, combinators::BoxBody};
+use sse_stream::Sse;
+use tokio::sync::{RwLock, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::{CancellationToken, PollSender};
+
+#[cfg(feature = "otel")]
+use crate::model::GetMeta;
+use crate::{
+    RoleServer,
+    model::{ClientJsonRpcMessage, GetExtensions, ServerJsonRpcMessage},
+    service::serve_server_with_ct,
+    transport::common::server_side_http::{
+        BoxResponse, SessionId, accepted_response, expect_json, session_id, sse_response,
+    },
+};
+
+/// Query parameter carrying the session id on `POST` requests.
+pub const SESSION_ID_QUERY_PARAM: &str = "sessionId";
+
+#[derive(Debug, Clone)]
+pub struct SseServerConfig {
+    /// The path announced in the `endpoint` event, as seen by the client.
+    ///
+    /// If the service is nested under a prefix, include the prefix here.
+    pub post_path: String,
+    /// The ping message duration for SSE connections.
+    pub sse_keep_alive: Option<Duration>,
+    /// Capacity of the per-session channels between HTTP and the service.
+    pub channel_capacity: usize,
+    /// Cancellation token for the SSE server.
+    ///
+    /// When this token is cancelled, all open streams are closed and every
+    /// session is terminated.
+    pub cancellation_token: CancellationToken,
+}
+
+impl Default for SseServerConfig {
+    fn default() -> Self {
+        Self {
+            post_path: "/messages".into(),
+            sse_keep_alive: Some(Duration::from_secs(15)),
+            channel_capacity: 16,
+            cancellation_token: CancellationToken::new(),
+        }
+    }
+}
+
+type SessionMap = Arc<RwLock<HashMap<SessionId, mpsc::Sender<ClientJsonRpcMessage>>>>;
+
+/// A tower service speaking the legacy HTTP+SSE transport.
+///
+/// `GET` opens a session and its event stream, `POST` delivers a client
+/// message to the session named by the `sessionId` query parameter. As with
+/// [`StreamableHttpService`](super::StreamableHttpService), the
+/// [`http::request::Parts`] of each `POST` are injected into the request's
+/// [`crate::model::Extensions`].
+pub struct SseService<S> {
+    pub config: SseServerConfig,
+    sessions: SessionMap,
+    service_factory: Arc<dyn Fn() -> Result<S, std::io::Error> + Send + Sync>,
+}
+
+impl<S> Clone for SseService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            sessions: self.sessions.clone(),
+            service_factory: self.service_factory.clone(),
+        }
+    }
+}
+
+impl<RequestBody, S> tower_service::Service<Request<RequestBody>> for SseService<S>
+where
+    RequestBody: Body + Send + 'static,
+    S: crate::Service<RoleServer>,
+    RequestBody::Error: Display,
+    RequestBody::Data: Send + 'static,
+{
+    type Response = BoxResponse;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    fn call(&mut self, req: http::Request<RequestBody>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move {
+            let response = service.handle(req).await;
+            Ok(response)
+        })
+    }
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> SseService<S>
+where
+    S: crate::Service<RoleServer> + Send + 'static,
+{
+    pub fn new(
+        service_factory: impl Fn() -> Result<S, std::io::Error> + Send + Sync + 'static,
+        config: SseServerConfig,
+    ) -> Self {
+        Self {
+            config,
+            sessions: Default::default(),
+            service_factory: Arc::new(service_factory),
+        }
+    }
+
+    /// Number of sessions with an open event stream.
+    pub async fn session_count(&self) -> usize {
+        self.sessions.read().await.len()
+    }
+
+    pub async fn handle<B>(&self, request: Request<B>) -> Response<BoxBody<Bytes, Infallible>>
+    where
+        B: Body + Send + 'static,
+        B::Error: Display,
+    {
+        const ALLOWED_METHODS: &str = "GET, POST, OPTIONS";
+        match *request.method() {
+            Method::GET => self.handle_get().await,
+            Method::POST => self.handle_post(request).await,
+            Method::OPTIONS => Response::builder()
+                .status(http::StatusCode::NO_CONTENT)
+                .header(ALLOW, ALLOWED_METHODS)
+                .header("Access-Control-Allow-Origin", "*")
+                .header("Access-Control-Allow-Methods", ALLOWED_METHODS)
+                .header("Access-Control-Allow-Headers", "Content-Type, Accept")
+                .header("Access-Control-Max-Age", "3600")
+                .body(Full::new(Bytes::new()).boxed())
+                .expect("valid response"),
+            _ => Response::builder()
+                .status(http::StatusCode::METHOD_NOT_ALLOWED)
+                .header(ALLOW, ALLOWED_METHODS)
+                .body(Full::new(Bytes::from("Method Not Allowed")).boxed())
+                .expect("valid response"),
+        }
+    }
+
+    async fn handle_get(&self) -> BoxResponse {
+        let service = match (self.service_factory)() {
+            Ok(service) => service,
+            Err(e) => return plain_response(http::StatusCode::INTERNAL_SERVER_ERROR, e),
+        };
+        let session_id = session_id();
+        let (client_tx, client_rx) = mpsc::channel(self.config.channel_capacity);
+        let (server_tx, server_rx) =
+            mpsc::channel::<ServerJsonRpcMessage>(self.config.channel_capacity);
+        let session_ct = self.config.cancellation_token.child_token();
+
+        // Register before answering, so the client can post as soon as it
+        // reads the endpoint event.
+        self.sessions
+            .write()
+            .await
+            .insert(session_id.clone(), client_tx);
+        let sessions = self.sessions.clone();
+        let id = session_id.clone();
+        let ct = session_ct.clone();
+        tokio::spawn(async move {
+            let transport = (PollSender::new(server_tx), ReceiverStream::new(client_rx));
+            match serve_server_with_ct(service, transport, ct).await {
+                Ok(running) => {
+                    if let Err(e) = running.waiting().await {
+                        tracing::warn!(session_id = %id, "sse session task failed: {e}");
+                    }
+                }
+                Err(e) => tracing::debug!(session_id = %id, "sse session not initialized: {e}"),
+            }
+            sessions.write().await.remove(&id);
+            tracing::debug!(session_id = %id, "sse session closed");
+        });
+
+        let endpoint = Sse::default().event("endpoint").data(format!(
+            "{}?{SESSION_ID_QUERY_PARAM}={session_id}",
+            self.config.post_path
+        ));
+        // The guard ends the session once the client drops the event stream.
+        let guard = session_ct.clone().drop_guard();
+        let messages = ReceiverStream::new(server_rx).map(move |message| {
+            let _ = &guard;
+            let data = serde_json::to_string(&message).expect("valid message");
+            Sse::default().event("message").data(data)
+        });
+        sse_response(
+            futures::stream::once(futures::future::ready(endpoint)).chain(messages),
+            self.config.sse_keep_alive,
+            session_ct,
+        )
+    }
+
+    async fn handle_post<B>(&self, request: Request<B>) -> BoxResponse
+    where
+        B: Body + Send + 'static,
+        B::Error: Display,
+    {
+        let Some(session_id) = request.uri().query().and_then(session_id_from_query) else {
+            return plain_response(
+                http::StatusCode::BAD_REQUEST,
+                format!("Bad Request: missing {SESSION_ID_QUERY_PARAM} query parameter"),
+            );
+        };
+        let Some(tx) = self.sessions.read().await.get(session_id.as_str()).cloned() else {
+            return plain_response(http::StatusCode::NOT_FOUND, "Not Found: Session not found");
+        };
+
+        let (part, body) = request.into_parts();
+        let mut message = match expect_json(body).await {
+            Ok(message) => message,
+            Err(response) => return response,
+        };
+        match &mut message {
+            ClientJsonRpcMessage::Request(req) => {
+                #[cfg(feature = "otel")]
+                crate::telemetry::headers_into_meta(&part.headers, req.request.get_meta_mut());
+                req.request.extensions_mut().insert(part);
+            }
+            ClientJsonRpcMessage::Notification(not) => {
+                not.notification.extensions_mut().insert(part);
+            }
+            _ => {
+                // skip
+            }
+        }
+        if tx.send(message).await.is_err() {
+            return plain_response(http::StatusCode::GONE, "Gone: Session closed");
+        }
+        accepted_response()
+    }
+}
+
+fn session_id_from_query(query: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == SESSION_ID_QUERY_PARAM && !value.is_empty()).then(|| value.to_owned())
+    })
+}
+
+fn plain_response(status: http::StatusCode, body: impl Display) -> BoxResponse {
+    Response::builder()
+        .status(status)
+        .body(Full::new(Bytes::from(body.to_string())).boxed())
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_id_from_query() {
+        assert_eq!(
+            session_id_from_query("sessionId=abc").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            session_id_from_query("foo=1&sessionId=abc&bar=2").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(session_id_from_query("sessionId="), None);
+        assert_eq!(session_id_from_query("session=abc"), None);
+    }
+}
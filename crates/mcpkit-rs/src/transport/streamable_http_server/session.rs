@@ -14,6 +14,14 @@
 //! * [`local::LocalSessionManager`] — in-memory session store (default).
 //! * [`never::NeverSessionManager`] — rejects all session operations, used
 //!   when stateful mode is disabled.
+//! * `distributed::DistributedSessionManager` — shares session ownership
+//!   between replicas through a `distributed::SessionRegistry` and forwards
+//!   requests to the owning node (feature
+//!   `transport-streamable-http-server-distributed`).
+//!
+//! Outbound events can additionally be persisted to an
+//! [`event_store::EventStore`] so that `Last-Event-ID` resumption keeps
+//! working across restarts and replicas.
 //!
 //! # Custom session managers
 //!
@@ -28,6 +36,13 @@ use crate::{
     model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
 };
 
+#[cfg(feature = "transport-streamable-http-server-distributed")]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(feature = "transport-streamable-http-server-distributed"))
+)]
+pub mod distributed;
+pub mod event_store;
 pub mod local;
 pub mod never;
 
@@ -0,0 +1,833 @@
+//! Sessions shared between several server replicas.
+//!
+//! Behind a load balancer, consecutive requests for one session can land on
+//! different nodes. [`DistributedSessionManager`] runs sessions locally, like
+//! [`LocalSessionManager`], but records which node owns each session in a
+//! shared [`SessionRegistry`]. A node that receives a request for a session it
+//! does not own forwards it over HTTP to the owner's Streamable HTTP endpoint
+//! and relays the response stream back. Forwarded requests carry the
+//! original `Authorization`, `MCP-Protocol-Version` and W3C trace context
+//! headers.
+//!
+//! The registry also tracks when each session was last used, so that any node
+//! can expire idle sessions with [`DistributedSessionManager::reap_idle`]; the
+//! owner closes its local session once the registration is gone.
+//!
+//! ```rust,ignore
+//! let registry = FileSessionRegistry::open("/var/lib/mcp/sessions")?;
+//! let manager = DistributedSessionManager::new(
+//!     NodeInfo::new("node-a", "http://10.0.0.5:8000/mcp"),
+//!     registry,
+//! )
+//! .with_idle_timeout(Duration::from_secs(600));
+//! ```
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::{
+    Stream, StreamExt,
+    future::{BoxFuture, FutureExt},
+};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use sse_stream::SseStream;
+use thiserror::Error;
+use tokio::sync::mpsc::Receiver;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    ServerSseMessage, SessionId, SessionManager,
+    local::{LocalSessionManager, LocalSessionManagerError, LocalSessionWorker, SessionConfig},
+};
+use crate::{
+    model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+    storage::{blocking, file_name},
+    transport::{
+        WorkerTransport,
+        common::http_header::{
+            EVENT_STREAM_MIME_TYPE, HEADER_LAST_EVENT_ID, HEADER_MCP_PROTOCOL_VERSION,
+            HEADER_SESSION_ID, JSON_MIME_TYPE,
+        },
+    },
+};
+
+/// Headers of the incoming request that are copied onto the request forwarded
+/// to the session's owner, so it sees the same credentials, negotiated
+/// protocol version and trace context.
+const FORWARDED_HEADERS: &[&str] = &[
+    "authorization",
+    HEADER_MCP_PROTOCOL_VERSION,
+    "traceparent",
+    "tracestate",
+];
+
+tokio::task_local! {
+    static REQUEST_HEADERS: http::HeaderMap;
+}
+
+/// Make the headers of the request being handled available to a
+/// [`DistributedSessionManager`] forwarding it.
+pub(crate) async fn with_request_headers<F: Future>(headers: http::HeaderMap, f: F) -> F::Output {
+    REQUEST_HEADERS.scope(headers, f).await
+}
+
+fn copy_forwarded_headers(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let headers = REQUEST_HEADERS
+        .try_with(|headers| {
+            FORWARDED_HEADERS
+                .iter()
+                .flat_map(|name| {
+                    headers
+                        .get_all(*name)
+                        .iter()
+                        .map(|value| (*name, value.clone()))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    headers.into_iter().fold(request, |request, (name, value)| {
+        request.header(name, value)
+    })
+}
+
+/// A server replica that can own sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeInfo {
+    /// Identifier unique among the replicas.
+    pub id: String,
+    /// Streamable HTTP endpoint other replicas forward requests to, e.g.
+    /// `http://10.0.0.5:8000/mcp`.
+    pub endpoint: String,
+}
+
+impl NodeInfo {
+    pub fn new(id: impl Into<String>, endpoint: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            endpoint: endpoint.into(),
+        }
+    }
+}
+
+/// Registry entry for one session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: SessionId,
+    pub owner: NodeInfo,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Debug, Error)]
+pub enum SessionRegistryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Shared store of session ownership and activity.
+///
+/// Every replica behind the load balancer must see the same registry.
+pub trait SessionRegistry: std::fmt::Debug + Send + Sync + 'static {
+    /// Insert or replace a record.
+    fn register<'a>(
+        &'a self,
+        record: &'a SessionRecord,
+    ) -> BoxFuture<'a, Result<(), SessionRegistryError>>;
+
+    fn get<'a>(
+        &'a self,
+        id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<Option<SessionRecord>, SessionRegistryError>>;
+
+    /// Move `last_seen` forward to `at`. Unknown sessions are ignored.
+    fn touch<'a>(
+        &'a self,
+        id: &'a SessionId,
+        at: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<(), SessionRegistryError>>;
+
+    fn remove<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<(), SessionRegistryError>>;
+
+    /// Records whose `last_seen` is before `cutoff`.
+    fn idle_since(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> BoxFuture<'_, Result<Vec<SessionRecord>, SessionRegistryError>>;
+}
+
+/// A [`SessionRegistry`] for replicas running in one process, mostly useful
+/// for tests.
+#[derive(Debug, Default)]
+pub struct InMemorySessionRegistry {
+    records: Mutex<HashMap<SessionId, SessionRecord>>,
+}
+
+impl SessionRegistry for InMemorySessionRegistry {
+    fn register<'a>(
+        &'a self,
+        record: &'a SessionRecord,
+    ) -> BoxFuture<'a, Result<(), SessionRegistryError>> {
+        self.records
+            .lock()
+            .expect("session registry poisoned")
+            .insert(record.session_id.clone(), record.clone());
+        futures::future::ready(Ok(())).boxed()
+    }
+
+    fn get<'a>(
+        &'a self,
+        id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<Option<SessionRecord>, SessionRegistryError>> {
+        let record = self
+            .records
+            .lock()
+            .expect("session registry poisoned")
+            .get(id)
+            .cloned();
+        futures::future::ready(Ok(record)).boxed()
+    }
+
+    fn touch<'a>(
+        &'a self,
+        id: &'a SessionId,
+        at: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<(), SessionRegistryError>> {
+        if let Some(record) = self
+            .records
+            .lock()
+            .expect("session registry poisoned")
+            .get_mut(id)
+        {
+            record.last_seen = record.last_seen.max(at);
+        }
+        futures::future::ready(Ok(())).boxed()
+    }
+
+    fn remove<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<(), SessionRegistryError>> {
+        self.records
+            .lock()
+            .expect("session registry poisoned")
+            .remove(id);
+        futures::future::ready(Ok(())).boxed()
+    }
+
+    fn idle_since(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> BoxFuture<'_, Result<Vec<SessionRecord>, SessionRegistryError>> {
+        let records = self
+            .records
+            .lock()
+            .expect("session registry poisoned")
+            .values()
+            .filter(|record| record.last_seen < cutoff)
+            .cloned()
+            .collect();
+        futures::future::ready(Ok(records)).boxed()
+    }
+}
+
+/// A [`SessionRegistry`] keeping one JSON file per session in a directory.
+///
+/// Point every replica at the same (shared or network) directory. Files are
+/// replaced atomically, so readers never see a partial record. File I/O runs
+/// on the blocking thread pool.
+#[derive(Debug)]
+pub struct FileSessionRegistry {
+    dir: PathBuf,
+}
+
+impl FileSessionRegistry {
+    /// Open a registry in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn record_path(&self, id: &SessionId) -> PathBuf {
+        self.dir.join(file_name(id, "json"))
+    }
+
+    fn read(path: &Path) -> Result<Option<SessionRecord>, SessionRegistryError> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write(path: &Path, record: &SessionRecord) -> Result<(), SessionRegistryError> {
+        let tmp = path.with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, serde_json::to_vec(record)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn touch_sync(path: &Path, at: DateTime<Utc>) -> Result<(), SessionRegistryError> {
+        if let Some(mut record) = Self::read(path)? {
+            if record.last_seen < at {
+                record.last_seen = at;
+                Self::write(path, &record)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn idle_since_sync(
+        dir: &Path,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<SessionRecord>, SessionRegistryError> {
+        let mut records = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            // a record removed by another replica since listing is fine to skip
+            if let Some(record) = Self::read(&path)? {
+                if record.last_seen < cutoff {
+                    records.push(record);
+                }
+            }
+        }
+        Ok(records)
+    }
+}
+
+impl SessionRegistry for FileSessionRegistry {
+    fn register<'a>(
+        &'a self,
+        record: &'a SessionRecord,
+    ) -> BoxFuture<'a, Result<(), SessionRegistryError>> {
+        let path = self.record_path(&record.session_id);
+        let record = record.clone();
+        blocking(move || Self::write(&path, &record)).boxed()
+    }
+
+    fn get<'a>(
+        &'a self,
+        id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<Option<SessionRecord>, SessionRegistryError>> {
+        let path = self.record_path(id);
+        blocking(move || Self::read(&path)).boxed()
+    }
+
+    fn touch<'a>(
+        &'a self,
+        id: &'a SessionId,
+        at: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<(), SessionRegistryError>> {
+        let path = self.record_path(id);
+        blocking(move || Self::touch_sync(&path, at)).boxed()
+    }
+
+    fn remove<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<(), SessionRegistryError>> {
+        let path = self.record_path(id);
+        blocking::<_, SessionRegistryError>(move || match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        })
+        .boxed()
+    }
+
+    fn idle_since(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> BoxFuture<'_, Result<Vec<SessionRecord>, SessionRegistryError>> {
+        let dir = self.dir.clone();
+        blocking(move || Self::idle_since_sync(&dir, cutoff)).boxed()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DistributedSessionManagerError {
+    #[error("Session not found: {0}")]
+    SessionNotFound(SessionId),
+    #[error(transparent)]
+    Local(#[from] LocalSessionManagerError),
+    #[error("Session registry error: {0}")]
+    Registry(#[from] SessionRegistryError),
+    #[error("Forwarding to node {node} failed: {source}")]
+    Forward {
+        node: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Node {node} responded with status {status}")]
+    UnexpectedStatus {
+        node: String,
+        status: reqwest::StatusCode,
+    },
+}
+
+enum Route {
+    Local,
+    Remote(NodeInfo),
+}
+
+/// A [`SessionManager`] for replicated deployments.
+///
+/// See the [module-level docs](self).
+#[derive(Debug)]
+pub struct DistributedSessionManager {
+    node: NodeInfo,
+    local: LocalSessionManager,
+    registry: Arc<dyn SessionRegistry>,
+    idle_timeout: Duration,
+    touch_interval: Duration,
+    client: reqwest::Client,
+}
+
+impl DistributedSessionManager {
+    pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+    pub const DEFAULT_TOUCH_INTERVAL: Duration = Duration::from_secs(30);
+
+    pub fn new(node: NodeInfo, registry: impl SessionRegistry) -> Self {
+        Self::with_shared_registry(node, Arc::new(registry))
+    }
+
+    /// Create a manager using a registry handle shared with other code in the
+    /// process (for example another replica in a test).
+    pub fn with_shared_registry(node: NodeInfo, registry: Arc<dyn SessionRegistry>) -> Self {
+        Self {
+            node,
+            local: LocalSessionManager::default(),
+            registry,
+            idle_timeout: Self::DEFAULT_IDLE_TIMEOUT,
+            touch_interval: Self::DEFAULT_TOUCH_INTERVAL,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Configure the sessions this node runs locally.
+    pub fn with_session_config(mut self, session_config: SessionConfig) -> Self {
+        self.local.session_config = session_config;
+        self
+    }
+
+    /// Sessions unused for longer than this are expired by [`Self::reap_idle`]
+    /// and treated as unknown by every node.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// How stale a session's `last_seen` may get before a request for it
+    /// writes a new one to the registry. Capped at half the idle timeout.
+    pub fn with_touch_interval(mut self, touch_interval: Duration) -> Self {
+        self.touch_interval = touch_interval;
+        self
+    }
+
+    /// HTTP client used to forward requests to other nodes.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn node(&self) -> &NodeInfo {
+        &self.node
+    }
+
+    /// The sessions owned by this node.
+    pub fn local(&self) -> &LocalSessionManager {
+        &self.local
+    }
+
+    pub fn registry(&self) -> &Arc<dyn SessionRegistry> {
+        &self.registry
+    }
+
+    fn idle_cutoff(&self) -> DateTime<Utc> {
+        TimeDelta::from_std(self.idle_timeout)
+            .ok()
+            .and_then(|timeout| Utc::now().checked_sub_signed(timeout))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// Expire idle sessions across the cluster and close local sessions whose
+    /// registration is gone. Returns the sessions removed or closed.
+    pub async fn reap_idle(&self) -> Result<Vec<SessionId>, DistributedSessionManagerError> {
+        let mut reaped = Vec::new();
+        for record in self.registry.idle_since(self.idle_cutoff()).await? {
+            tracing::debug!(session_id = ?record.session_id, owner = record.owner.id, "expire idle session");
+            self.registry.remove(&record.session_id).await?;
+            reaped.push(record.session_id);
+        }
+        let local_ids: Vec<SessionId> = self.local.sessions.read().await.keys().cloned().collect();
+        for id in local_ids {
+            if self.registry.get(&id).await?.is_none() {
+                self.local.close_session(&id).await?;
+                if !reaped.contains(&id) {
+                    reaped.push(id);
+                }
+            }
+        }
+        Ok(reaped)
+    }
+
+    /// Run [`Self::reap_idle`] every `interval` until `ct` is cancelled.
+    pub fn spawn_reaper(
+        self: &Arc<Self>,
+        interval: Duration,
+        ct: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = ct.cancelled() => break,
+                    _ = ticker.tick() => {
+                        if let Err(error) = manager.reap_idle().await {
+                            tracing::warn!(%error, "failed to reap idle sessions");
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    async fn route(&self, id: &SessionId) -> Result<Option<Route>, DistributedSessionManagerError> {
+        let record = self
+            .registry
+            .get(id)
+            .await?
+            .filter(|record| record.last_seen >= self.idle_cutoff());
+        let Some(record) = record else {
+            // expired (possibly by another node) or never existed
+            if self.local.has_session(id).await? {
+                self.local.close_session(id).await?;
+            }
+            return Ok(None);
+        };
+        let route = if record.owner.id == self.node.id {
+            if !self.local.has_session(id).await? {
+                // this node restarted since it created the session
+                self.registry.remove(id).await?;
+                return Ok(None);
+            }
+            Route::Local
+        } else {
+            Route::Remote(record.owner)
+        };
+        // renew the lease only once it is getting stale, rather than writing to
+        // the registry on every request
+        let now = Utc::now();
+        let touch_interval = TimeDelta::from_std(self.touch_interval.min(self.idle_timeout / 2))
+            .unwrap_or(TimeDelta::MAX);
+        if now.signed_duration_since(record.last_seen) >= touch_interval {
+            self.registry.touch(id, now).await?;
+        }
+        Ok(Some(route))
+    }
+
+    async fn require_route(&self, id: &SessionId) -> Result<Route, DistributedSessionManagerError> {
+        self.route(id)
+            .await?
+            .ok_or_else(|| DistributedSessionManagerError::SessionNotFound(id.clone()))
+    }
+
+    fn forward_post(
+        &self,
+        owner: &NodeInfo,
+        id: &SessionId,
+        message: &ClientJsonRpcMessage,
+    ) -> reqwest::RequestBuilder {
+        copy_forwarded_headers(self.client.post(&owner.endpoint))
+            .header(ACCEPT, [JSON_MIME_TYPE, EVENT_STREAM_MIME_TYPE].join(", "))
+            .header(HEADER_SESSION_ID, id.as_ref())
+            .json(message)
+    }
+
+    fn forward_get(&self, owner: &NodeInfo, id: &SessionId) -> reqwest::RequestBuilder {
+        copy_forwarded_headers(self.client.get(&owner.endpoint))
+            .header(ACCEPT, EVENT_STREAM_MIME_TYPE)
+            .header(HEADER_SESSION_ID, id.as_ref())
+    }
+
+    /// Send a forwarded request and relay the owner's response as SSE events.
+    async fn forward(
+        &self,
+        owner: &NodeInfo,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Receiver<ServerSseMessage>, DistributedSessionManagerError> {
+        let forward_error = |source| DistributedSessionManagerError::Forward {
+            node: owner.id.clone(),
+            source,
+        };
+        let response = request.send().await.map_err(forward_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(DistributedSessionManagerError::UnexpectedStatus {
+                node: owner.id.clone(),
+                status,
+            });
+        }
+        let (tx, rx) = tokio::sync::mpsc::channel(self.local.session_config.channel_capacity);
+        match response.headers().get(CONTENT_TYPE) {
+            Some(ct) if ct.as_bytes().starts_with(EVENT_STREAM_MIME_TYPE.as_bytes()) => {
+                let node = owner.id.clone();
+                let mut events = SseStream::from_bytes_stream(response.bytes_stream());
+                tokio::spawn(async move {
+                    while let Some(event) = events.next().await {
+                        let event = match event {
+                            Ok(event) => event,
+                            Err(error) => {
+                                tracing::warn!(node, %error, "forwarded sse stream failed");
+                                break;
+                            }
+                        };
+                        let message = match event.data.as_deref() {
+                            None | Some("") => None,
+                            Some(data) => {
+                                match serde_json::from_str::<ServerJsonRpcMessage>(data) {
+                                    Ok(message) => Some(Arc::new(message)),
+                                    Err(error) => {
+                                        tracing::warn!(node, %error, "skipping invalid forwarded message");
+                                        continue;
+                                    }
+                                }
+                            }
+                        };
+                        if message.is_none() && event.id.is_none() && event.retry.is_none() {
+                            // keep-alive
+                            continue;
+                        }
+                        let event = ServerSseMessage {
+                            event_id: event.id,
+                            message,
+                            retry: event.retry.map(Duration::from_millis),
+                        };
+                        if tx.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            Some(ct) if ct.as_bytes().starts_with(JSON_MIME_TYPE.as_bytes()) => {
+                let message: ServerJsonRpcMessage = response.json().await.map_err(forward_error)?;
+                let _ = tx
+                    .send(ServerSseMessage {
+                        event_id: None,
+                        message: Some(Arc::new(message)),
+                        retry: None,
+                    })
+                    .await;
+            }
+            // e.g. 202 Accepted for notifications and responses
+            _ => {}
+        }
+        Ok(rx)
+    }
+}
+
+impl SessionManager for DistributedSessionManager {
+    type Error = DistributedSessionManagerError;
+    type Transport = WorkerTransport<LocalSessionWorker>;
+
+    async fn create_session(&self) -> Result<(SessionId, Self::Transport), Self::Error> {
+        let (id, transport) = self.local.create_session().await?;
+        let now = Utc::now();
+        self.registry
+            .register(&SessionRecord {
+                session_id: id.clone(),
+                owner: self.node.clone(),
+                created_at: now,
+                last_seen: now,
+            })
+            .await?;
+        Ok((id, transport))
+    }
+
+    async fn initialize_session(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<ServerJsonRpcMessage, Self::Error> {
+        // sessions are always initialized on the node that created them
+        Ok(self.local.initialize_session(id, message).await?)
+    }
+
+    async fn has_session(&self, id: &SessionId) -> Result<bool, Self::Error> {
+        Ok(self.route(id).await?.is_some())
+    }
+
+    async fn close_session(&self, id: &SessionId) -> Result<(), Self::Error> {
+        match self.route(id).await? {
+            Some(Route::Local) => self.local.close_session(id).await?,
+            Some(Route::Remote(owner)) => {
+                let request = copy_forwarded_headers(self.client.delete(&owner.endpoint))
+                    .header(HEADER_SESSION_ID, id.as_ref());
+                self.forward(&owner, request).await?;
+            }
+            None => {}
+        }
+        self.registry.remove(id).await?;
+        Ok(())
+    }
+
+    async fn create_stream(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + 'static, Self::Error> {
+        match self.require_route(id).await? {
+            Route::Local => Ok(self.local.create_stream(id, message).await?.left_stream()),
+            Route::Remote(owner) => {
+                let request = self.forward_post(&owner, id, &message);
+                let receiver = self.forward(&owner, request).await?;
+                Ok(ReceiverStream::new(receiver).right_stream())
+            }
+        }
+    }
+
+    async fn accept_message(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<(), Self::Error> {
+        match self.require_route(id).await? {
+            Route::Local => self.local.accept_message(id, message).await?,
+            Route::Remote(owner) => {
+                let request = self.forward_post(&owner, id, &message);
+                self.forward(&owner, request).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_standalone_stream(
+        &self,
+        id: &SessionId,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + 'static, Self::Error> {
+        match self.require_route(id).await? {
+            Route::Local => Ok(self.local.create_standalone_stream(id).await?.left_stream()),
+            Route::Remote(owner) => {
+                let request = self.forward_get(&owner, id);
+                let receiver = self.forward(&owner, request).await?;
+                Ok(ReceiverStream::new(receiver).right_stream())
+            }
+        }
+    }
+
+    async fn resume(
+        &self,
+        id: &SessionId,
+        last_event_id: String,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + 'static, Self::Error> {
+        match self.route(id).await? {
+            Some(Route::Remote(owner)) => {
+                let request = self
+                    .forward_get(&owner, id)
+                    .header(HEADER_LAST_EVENT_ID, last_event_id);
+                let receiver = self.forward(&owner, request).await?;
+                Ok(ReceiverStream::new(receiver).right_stream())
+            }
+            // unknown sessions may still be replayed from a shared event store
+            Some(Route::Local) | None => {
+                Ok(self.local.resume(id, last_event_id).await?.left_stream())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, owner: &str, last_seen: DateTime<Utc>) -> SessionRecord {
+        SessionRecord {
+            session_id: id.into(),
+            owner: NodeInfo::new(owner, format!("http://{owner}/mcp")),
+            created_at: last_seen,
+            last_seen,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_registry_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = FileSessionRegistry::open(dir.path()).unwrap();
+        let start = Utc::now() - TimeDelta::minutes(10);
+        let session = record("s-1", "a", start);
+        registry.register(&session).await.unwrap();
+        registry.register(&record("s-2", "b", start)).await.unwrap();
+
+        assert_eq!(
+            registry.get(&"s-1".into()).await.unwrap(),
+            Some(session.clone())
+        );
+        assert_eq!(registry.get(&"s-3".into()).await.unwrap(), None);
+
+        registry.touch(&"s-1".into(), Utc::now()).await.unwrap();
+        let idle = registry
+            .idle_since(Utc::now() - TimeDelta::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(idle.len(), 1);
+        assert_eq!(idle[0].session_id.as_ref(), "s-2");
+
+        registry.remove(&"s-2".into()).await.unwrap();
+        registry.remove(&"s-2".into()).await.unwrap();
+        assert_eq!(registry.get(&"s-2".into()).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_reap_idle_closes_local_sessions() {
+        let registry: Arc<dyn SessionRegistry> = Arc::new(InMemorySessionRegistry::default());
+        let node_a = DistributedSessionManager::with_shared_registry(
+            NodeInfo::new("a", "http://a/mcp"),
+            registry.clone(),
+        );
+        let node_b = DistributedSessionManager::with_shared_registry(
+            NodeInfo::new("b", "http://b/mcp"),
+            registry.clone(),
+        )
+        .with_idle_timeout(Duration::ZERO);
+
+        let (id, _transport) = node_a.create_session().await.unwrap();
+        assert!(node_a.has_session(&id).await.unwrap());
+
+        // node b expires the session, node a notices on its next reap
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(node_b.reap_idle().await.unwrap(), vec![id.clone()]);
+        assert!(node_a.local().has_session(&id).await.unwrap());
+        assert_eq!(node_a.reap_idle().await.unwrap(), vec![id.clone()]);
+        assert!(!node_a.local().has_session(&id).await.unwrap());
+        assert!(!node_a.has_session(&id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_registry_is_touched_once_the_lease_is_stale() {
+        let registry: Arc<dyn SessionRegistry> = Arc::new(InMemorySessionRegistry::default());
+        let node = DistributedSessionManager::with_shared_registry(
+            NodeInfo::new("a", "http://a/mcp"),
+            registry.clone(),
+        );
+        let (id, _transport) = node.create_session().await.unwrap();
+        let created = registry.get(&id).await.unwrap().unwrap().last_seen;
+
+        assert!(node.has_session(&id).await.unwrap());
+        assert_eq!(registry.get(&id).await.unwrap().unwrap().last_seen, created);
+
+        let node = node.with_touch_interval(Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(node.has_session(&id).await.unwrap());
+        assert!(registry.get(&id).await.unwrap().unwrap().last_seen > created);
+    }
+}
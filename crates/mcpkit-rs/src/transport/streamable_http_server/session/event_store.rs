@@ -0,0 +1,553 @@
+//! Persistent storage for outbound SSE events.
+//!
+//! [`LocalSessionManager`](super::local::LocalSessionManager) keeps a small
+//! per-stream cache so that a client reconnecting with `Last-Event-ID` can pick
+//! up where it left off. That cache is bounded by the channel capacity and is
+//! lost when the process exits. An [`EventStore`] configured through
+//! [`SessionConfig::with_event_store`](super::local::SessionConfig::with_event_store)
+//! receives every event the session worker emits, and is consulted whenever the
+//! in-memory cache cannot satisfy a resume request — including after a restart,
+//! or on another replica sharing the same store.
+//!
+//! # Implementations
+//!
+//! * [`InMemoryEventStore`] — process-local, survives stream reconnects only.
+//! * [`FileEventStore`] — one JSON Lines file per session in a directory.
+//! * `SqliteEventStore` — a single SQLite database, with the
+//!   `event-store-sqlite` feature.
+//!
+//! All of them apply an [`EventRetention`] policy bounding the number of events kept
+//! per stream and their age.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    ServerSseMessage, SessionId,
+    local::{EventId, EventIdParseError},
+};
+use crate::{
+    model::ServerJsonRpcMessage,
+    storage::{blocking, file_name},
+};
+
+#[cfg(feature = "event-store-sqlite")]
+mod sqlite;
+#[cfg(feature = "event-store-sqlite")]
+#[cfg_attr(docsrs, doc(cfg(feature = "event-store-sqlite")))]
+pub use sqlite::SqliteEventStore;
+
+#[derive(Debug, Error)]
+pub enum EventStoreError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Cannot parse event id: {0}")]
+    EventIdParseError(#[from] EventIdParseError),
+    #[error("Event has no id")]
+    MissingEventId,
+    #[cfg(feature = "event-store-sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// Storage for the SSE events emitted by a session.
+///
+/// Events are grouped into streams: the standalone stream of a session, and one
+/// stream per HTTP request. The stream and the position within it are both
+/// encoded in the event's [`EventId`].
+pub trait EventStore: std::fmt::Debug + Send + Sync + 'static {
+    /// Persist an outbound event. The event always carries an `event_id`.
+    fn append<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        event: &'a ServerSseMessage,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>>;
+
+    /// Return the retained events on the same stream as `from`, starting at
+    /// `from`'s index, oldest first.
+    fn replay<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        from: &'a EventId,
+    ) -> BoxFuture<'a, Result<Vec<ServerSseMessage>, EventStoreError>>;
+
+    /// Drop every event recorded for the session.
+    fn remove_session<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>>;
+}
+
+/// How many events an [`EventStore`] keeps, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventRetention {
+    /// Maximum number of events kept per stream. Older events are dropped first.
+    pub max_events: usize,
+    /// Events older than this are dropped.
+    pub max_age: Duration,
+}
+
+impl EventRetention {
+    pub const DEFAULT_MAX_EVENTS: usize = 1024;
+    pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = max_events;
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    fn is_expired(&self, stored_at: SystemTime, now: SystemTime) -> bool {
+        now.duration_since(stored_at)
+            .is_ok_and(|age| age > self.max_age)
+    }
+
+    fn prune(&self, events: &mut VecDeque<StoredEvent>, now: SystemTime) {
+        while events
+            .front()
+            .is_some_and(|event| self.is_expired(event.stored_at, now))
+        {
+            events.pop_front();
+        }
+        while events.len() > self.max_events {
+            events.pop_front();
+        }
+    }
+}
+
+impl Default for EventRetention {
+    fn default() -> Self {
+        Self {
+            max_events: Self::DEFAULT_MAX_EVENTS,
+            max_age: Self::DEFAULT_MAX_AGE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StoredEvent {
+    id: EventId,
+    stored_at: SystemTime,
+    event: ServerSseMessage,
+}
+
+impl StoredEvent {
+    fn new(event: &ServerSseMessage) -> Result<Self, EventStoreError> {
+        let id = event
+            .event_id
+            .as_deref()
+            .ok_or(EventStoreError::MissingEventId)?
+            .parse()?;
+        Ok(Self {
+            id,
+            stored_at: SystemTime::now(),
+            event: event.clone(),
+        })
+    }
+}
+
+type Streams = HashMap<Option<u64>, VecDeque<StoredEvent>>;
+
+fn replay_stream(
+    streams: &Streams,
+    from: &EventId,
+    retention: &EventRetention,
+    now: SystemTime,
+) -> Vec<ServerSseMessage> {
+    streams
+        .get(&from.http_request_id())
+        .into_iter()
+        .flatten()
+        .filter(|stored| {
+            stored.id.index() >= from.index() && !retention.is_expired(stored.stored_at, now)
+        })
+        .map(|stored| stored.event.clone())
+        .collect()
+}
+
+/// An [`EventStore`] that keeps events in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryEventStore {
+    retention: EventRetention,
+    sessions: Mutex<HashMap<SessionId, Streams>>,
+}
+
+impl InMemoryEventStore {
+    pub fn new(retention: EventRetention) -> Self {
+        Self {
+            retention,
+            sessions: Default::default(),
+        }
+    }
+}
+
+impl EventStore for InMemoryEventStore {
+    fn append<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        event: &'a ServerSseMessage,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>> {
+        let result = StoredEvent::new(event).map(|stored| {
+            let mut sessions = self.sessions.lock().expect("event store poisoned");
+            let stream = sessions
+                .entry(session_id.clone())
+                .or_default()
+                .entry(stored.id.http_request_id())
+                .or_default();
+            stream.push_back(stored);
+            self.retention.prune(stream, SystemTime::now());
+        });
+        futures::future::ready(result).boxed()
+    }
+
+    fn replay<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        from: &'a EventId,
+    ) -> BoxFuture<'a, Result<Vec<ServerSseMessage>, EventStoreError>> {
+        let sessions = self.sessions.lock().expect("event store poisoned");
+        let events = sessions
+            .get(session_id)
+            .map(|streams| replay_stream(streams, from, &self.retention, SystemTime::now()))
+            .unwrap_or_default();
+        futures::future::ready(Ok(events)).boxed()
+    }
+
+    fn remove_session<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>> {
+        self.sessions
+            .lock()
+            .expect("event store poisoned")
+            .remove(session_id);
+        futures::future::ready(Ok(())).boxed()
+    }
+}
+
+/// On-disk representation of a [`StoredEvent`].
+#[derive(Debug, Serialize, Deserialize)]
+struct EventRecord {
+    event_id: String,
+    stored_at_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<Arc<ServerJsonRpcMessage>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_ms: Option<u64>,
+}
+
+impl From<&StoredEvent> for EventRecord {
+    fn from(stored: &StoredEvent) -> Self {
+        Self {
+            event_id: stored.id.to_string(),
+            stored_at_ms: stored
+                .stored_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            message: stored.event.message.clone(),
+            retry_ms: stored.event.retry.map(|retry| retry.as_millis() as u64),
+        }
+    }
+}
+
+impl TryFrom<EventRecord> for StoredEvent {
+    type Error = EventStoreError;
+    fn try_from(record: EventRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: record.event_id.parse()?,
+            stored_at: UNIX_EPOCH + Duration::from_millis(record.stored_at_ms),
+            event: ServerSseMessage {
+                event_id: Some(record.event_id),
+                message: record.message,
+                retry: record.retry_ms.map(Duration::from_millis),
+            },
+        })
+    }
+}
+
+/// An [`EventStore`] that writes each session's events to a JSON Lines file.
+///
+/// Files are named after the session ID inside the configured directory, so
+/// several server instances pointing at a shared directory can replay each
+/// other's streams. Appends are cheap; the retention policy is enforced when
+/// replaying and by periodically rewriting the file.
+///
+/// File I/O runs on the blocking thread pool. Operations on one session are
+/// serialized, so a compaction only holds up appends to the session it
+/// rewrites.
+#[derive(Debug)]
+pub struct FileEventStore {
+    dir: PathBuf,
+    retention: EventRetention,
+    /// Per-session lock, guarding the number of appends since the last
+    /// compaction.
+    sessions: Mutex<HashMap<SessionId, Arc<tokio::sync::Mutex<usize>>>>,
+}
+
+impl FileEventStore {
+    /// Number of appends to a session file between compactions.
+    pub const COMPACT_INTERVAL: usize = 256;
+
+    /// Open a store in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>, retention: EventRetention) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            retention,
+            sessions: Default::default(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn session_path(&self, session_id: &SessionId) -> PathBuf {
+        self.dir.join(file_name(session_id, "jsonl"))
+    }
+
+    fn session_lock(&self, session_id: &SessionId) -> Arc<tokio::sync::Mutex<usize>> {
+        self.sessions
+            .lock()
+            .expect("event store poisoned")
+            .entry(session_id.clone())
+            .or_default()
+            .clone()
+    }
+
+    fn read_streams(path: &Path, retention: &EventRetention) -> Result<Streams, EventStoreError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Streams::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let now = SystemTime::now();
+        let mut streams = Streams::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            // a torn write from a crashed process only loses that one event
+            let stored = match serde_json::from_str::<EventRecord>(&line)
+                .map_err(EventStoreError::from)
+                .and_then(StoredEvent::try_from)
+            {
+                Ok(stored) => stored,
+                Err(error) => {
+                    tracing::warn!(?path, %error, "skipping unreadable stored event");
+                    continue;
+                }
+            };
+            let stream = streams.entry(stored.id.http_request_id()).or_default();
+            stream.push_back(stored);
+            retention.prune(stream, now);
+        }
+        Ok(streams)
+    }
+
+    fn compact(path: &Path, retention: &EventRetention) -> Result<(), EventStoreError> {
+        let streams = Self::read_streams(path, retention)?;
+        let mut events: Vec<&StoredEvent> = streams.values().flatten().collect();
+        events.sort_by_key(|stored| stored.stored_at);
+        let tmp = path.with_extension("jsonl.tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        for stored in events {
+            serde_json::to_writer(&mut writer, &EventRecord::from(stored))?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+impl EventStore for FileEventStore {
+    fn append<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        event: &'a ServerSseMessage,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>> {
+        async move {
+            let stored = StoredEvent::new(event)?;
+            let mut line = serde_json::to_vec(&EventRecord::from(&stored))?;
+            line.push(b'\n');
+            let path = self.session_path(session_id);
+            let retention = self.retention;
+            let lock = self.session_lock(session_id);
+            let mut appends = lock.lock().await;
+            let compact = *appends + 1 >= Self::COMPACT_INTERVAL;
+            blocking::<_, EventStoreError>(move || {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)?
+                    .write_all(&line)?;
+                if compact {
+                    Self::compact(&path, &retention)?;
+                }
+                Ok(())
+            })
+            .await?;
+            *appends = if compact { 0 } else { *appends + 1 };
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn replay<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        from: &'a EventId,
+    ) -> BoxFuture<'a, Result<Vec<ServerSseMessage>, EventStoreError>> {
+        async move {
+            let path = self.session_path(session_id);
+            let retention = self.retention;
+            let lock = self.session_lock(session_id);
+            let _appends = lock.lock().await;
+            let streams = blocking(move || Self::read_streams(&path, &retention)).await?;
+            Ok(replay_stream(
+                &streams,
+                from,
+                &self.retention,
+                SystemTime::now(),
+            ))
+        }
+        .boxed()
+    }
+
+    fn remove_session<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>> {
+        async move {
+            let path = self.session_path(session_id);
+            let lock = self.session_lock(session_id);
+            let _appends = lock.lock().await;
+            blocking::<_, EventStoreError>(move || match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            })
+            .await?;
+            self.sessions
+                .lock()
+                .expect("event store poisoned")
+                .remove(session_id);
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{EmptyResult, JsonRpcResponse, JsonRpcVersion2_0, NumberOrString};
+
+    pub(super) fn event(event_id: &str) -> ServerSseMessage {
+        ServerSseMessage {
+            event_id: Some(event_id.to_owned()),
+            message: Some(Arc::new(ServerJsonRpcMessage::Response(JsonRpcResponse {
+                jsonrpc: JsonRpcVersion2_0,
+                id: NumberOrString::Number(1),
+                result: EmptyResult {}.into(),
+            }))),
+            retry: None,
+        }
+    }
+
+    pub(super) fn ids(events: &[ServerSseMessage]) -> Vec<&str> {
+        events
+            .iter()
+            .map(|e| e.event_id.as_deref().unwrap())
+            .collect()
+    }
+
+    /// Replays per stream with a retention of two events per stream.
+    pub(super) async fn exercise(store: &dyn EventStore) {
+        let session: SessionId = "s".into();
+        for id in ["0", "1", "2", "0/7", "1/7"] {
+            store.append(&session, &event(id)).await.unwrap();
+        }
+
+        let common = store.replay(&session, &"0".parse().unwrap()).await.unwrap();
+        assert_eq!(ids(&common), ["1", "2"]);
+        let request = store
+            .replay(&session, &"1/7".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(ids(&request), ["1/7"]);
+
+        store.remove_session(&session).await.unwrap();
+        let common = store.replay(&session, &"0".parse().unwrap()).await.unwrap();
+        assert!(common.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_replays_per_stream_with_count_limit() {
+        exercise(&InMemoryEventStore::new(
+            EventRetention::default().with_max_events(2),
+        ))
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_drops_expired_events() {
+        let store = InMemoryEventStore::new(EventRetention::default().with_max_age(Duration::ZERO));
+        let session: SessionId = "s".into();
+        store.append(&session, &event("0")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let events = store.replay(&session, &"0".parse().unwrap()).await.unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_file_store_survives_reopen_and_compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let retention = EventRetention::default().with_max_events(3);
+        let session: SessionId = "a/b".into();
+        {
+            let store = FileEventStore::open(dir.path(), retention).unwrap();
+            for index in 0..FileEventStore::COMPACT_INTERVAL + 2 {
+                store
+                    .append(&session, &event(&format!("{index}/3")))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let store = FileEventStore::open(dir.path(), retention).unwrap();
+        let path = store.session_path(&session);
+        assert_eq!(path.file_name().unwrap(), "a%2fb.jsonl");
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 5);
+
+        let events = store
+            .replay(&session, &"0/3".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(ids(&events), ["255/3", "256/3", "257/3"]);
+        assert!(events[0].message.is_some());
+
+        store.remove_session(&session).await.unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::future::{BoxFuture, FutureExt};
+use rusqlite::{Connection, params};
+
+use super::{
+    EventRecord, EventRetention, EventStore, EventStoreError, ServerSseMessage, SessionId,
+    StoredEvent,
+};
+use crate::{storage::blocking, transport::streamable_http_server::session::local::EventId};
+
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        .try_into()
+        .unwrap_or(i64::MAX)
+}
+
+/// An [`EventStore`] backed by a SQLite database.
+///
+/// Each event is one row holding the same JSON record as
+/// [`FileEventStore`](super::FileEventStore), keyed by session, stream and
+/// index. The retention policy is applied to a stream whenever an event is
+/// appended to it. Queries run on the blocking thread pool.
+#[derive(Debug, Clone)]
+pub struct SqliteEventStore {
+    connection: Arc<Mutex<Connection>>,
+    retention: EventRetention,
+}
+
+impl SqliteEventStore {
+    /// Open the database at `path`, creating it and the `sse_events` table if
+    /// needed.
+    pub fn open(
+        path: impl AsRef<Path>,
+        retention: EventRetention,
+    ) -> Result<Self, EventStoreError> {
+        Self::with_connection(Connection::open(path)?, retention)
+    }
+
+    /// A store in a private in-memory database.
+    pub fn open_in_memory(retention: EventRetention) -> Result<Self, EventStoreError> {
+        Self::with_connection(Connection::open_in_memory()?, retention)
+    }
+
+    /// Use an already opened connection, creating the `sse_events` table if
+    /// needed.
+    pub fn with_connection(
+        connection: Connection,
+        retention: EventRetention,
+    ) -> Result<Self, EventStoreError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sse_events (
+                session_id TEXT NOT NULL,
+                http_request_id INTEGER,
+                event_index INTEGER NOT NULL,
+                stored_at_ms INTEGER NOT NULL,
+                record TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS sse_events_stream
+                ON sse_events (session_id, http_request_id, event_index);
+            CREATE INDEX IF NOT EXISTS sse_events_stored_at ON sse_events (stored_at_ms);",
+        )?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            retention,
+        })
+    }
+
+    /// Oldest `stored_at_ms` still within the retention age.
+    fn cutoff_ms(&self) -> i64 {
+        let max_age: i64 = self
+            .retention
+            .max_age
+            .as_millis()
+            .try_into()
+            .unwrap_or(i64::MAX);
+        unix_millis(SystemTime::now()).saturating_sub(max_age)
+    }
+
+    /// Run `f` with the connection on the blocking thread pool.
+    async fn with<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> Result<T, EventStoreError> + Send + 'static,
+    ) -> Result<T, EventStoreError> {
+        let connection = self.connection.clone();
+        blocking(move || f(&mut connection.lock().expect("event store poisoned"))).await
+    }
+}
+
+/// Stream key of an event id. Request ids are stored bit for bit as SQLite
+/// integers.
+fn stream_key(id: &EventId) -> Option<i64> {
+    id.http_request_id().map(|id| id as i64)
+}
+
+impl EventStore for SqliteEventStore {
+    fn append<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        event: &'a ServerSseMessage,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>> {
+        async move {
+            let stored = StoredEvent::new(event)?;
+            let record = EventRecord::from(&stored);
+            let json = serde_json::to_string(&record)?;
+            let session_id = session_id.to_string();
+            let stream = stream_key(&stored.id);
+            let index = stored.id.index() as i64;
+            let max_events = i64::try_from(self.retention.max_events).unwrap_or(i64::MAX);
+            let cutoff_ms = self.cutoff_ms();
+            self.with(move |connection| {
+                let transaction = connection.transaction()?;
+                transaction.execute(
+                    "INSERT INTO sse_events (session_id, http_request_id, event_index, stored_at_ms, record)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![session_id, stream, index, record.stored_at_ms as i64, json],
+                )?;
+                transaction.execute(
+                    "DELETE FROM sse_events
+                     WHERE session_id = ?1 AND http_request_id IS ?2 AND event_index NOT IN (
+                         SELECT event_index FROM sse_events
+                         WHERE session_id = ?1 AND http_request_id IS ?2
+                         ORDER BY event_index DESC LIMIT ?3
+                     )",
+                    params![session_id, stream, max_events],
+                )?;
+                transaction.execute(
+                    "DELETE FROM sse_events WHERE stored_at_ms < ?1",
+                    params![cutoff_ms],
+                )?;
+                transaction.commit()?;
+                Ok(())
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn replay<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+        from: &'a EventId,
+    ) -> BoxFuture<'a, Result<Vec<ServerSseMessage>, EventStoreError>> {
+        let session_id = session_id.to_string();
+        let stream = stream_key(from);
+        let index = from.index() as i64;
+        let cutoff_ms = self.cutoff_ms();
+        self.with(move |connection| {
+            let mut statement = connection.prepare(
+                "SELECT record FROM sse_events
+                 WHERE session_id = ?1 AND http_request_id IS ?2
+                     AND event_index >= ?3 AND stored_at_ms >= ?4
+                 ORDER BY event_index",
+            )?;
+            let rows = statement
+                .query_map(params![session_id, stream, index, cutoff_ms], |row| {
+                    row.get::<_, String>(0)
+                })?;
+            let mut events = Vec::new();
+            for json in rows {
+                let record: EventRecord = serde_json::from_str(&json?)?;
+                events.push(StoredEvent::try_from(record)?.event);
+            }
+            Ok(events)
+        })
+        .boxed()
+    }
+
+    fn remove_session<'a>(
+        &'a self,
+        session_id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<(), EventStoreError>> {
+        let session_id = session_id.to_string();
+        self.with(move |connection| {
+            connection.execute(
+                "DELETE FROM sse_events WHERE session_id = ?1",
+                params![session_id],
+            )?;
+            Ok(())
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::streamable_http_server::session::event_store::tests::{
+        event, exercise, ids,
+    };
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        exercise(
+            &SqliteEventStore::open_in_memory(EventRetention::default().with_max_events(2))
+                .unwrap(),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.db");
+        let session: SessionId = "s".into();
+        {
+            let store = SqliteEventStore::open(&path, EventRetention::default()).unwrap();
+            for id in ["0/3", "1/3", "0"] {
+                store.append(&session, &event(id)).await.unwrap();
+            }
+        }
+
+        let store = SqliteEventStore::open(&path, EventRetention::default()).unwrap();
+        let events = store
+            .replay(&session, &"1/3".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(ids(&events), ["1/3"]);
+        assert!(events[0].message.is_some());
+    }
+}
@@ -42,6 +42,8 @@ pub enum LocalSessionManagerError {
     SessionError(#[from] SessionError),
     #[error("Invalid event id: {0}")]
     InvalidEventId(#[from] EventIdParseError),
+    #[error("Event store error: {0}")]
+    EventStore(#[from] EventStoreError),
 }
 impl SessionManager for LocalSessionManager {
     type Error = LocalSessionManagerError;
@@ -69,6 +71,9 @@ impl SessionManager for LocalSessionManager {
         if let Some(handle) = sessions.remove(id) {
             handle.close().await?;
         }
+        if let Some(store) = &self.session_config.event_store {
+            store.remove_session(id).await?;
+        }
         Ok(())
     }
     async fn has_session(&self, id: &SessionId) -> Result<bool, Self::Error> {
@@ -108,11 +113,20 @@ impl SessionManager for LocalSessionManager {
         id: &SessionId,
         last_event_id: String,
     ) -> Result<impl Stream<Item = ServerSseMessage> + Send + 'static, Self::Error> {
+        let last_event_id: EventId = last_event_id.parse()?;
         let sessions = self.sessions.read().await;
-        let handle = sessions
-            .get(id)
-            .ok_or(LocalSessionManagerError::SessionNotFound(id.clone()))?;
-        let receiver = handle.resume(last_event_id.parse()?).await?;
+        let Some(handle) = sessions.get(id) else {
+            // The session may have lived in another process sharing the event
+            // store; replay whatever that stream left behind.
+            if let Some(store) = &self.session_config.event_store {
+                let events = store.replay(id, &last_event_id).await?;
+                if !events.is_empty() {
+                    return Ok(ReceiverStream::new(replay_channel(events).await));
+                }
+            }
+            return Err(LocalSessionManagerError::SessionNotFound(id.clone()));
+        };
+        let receiver = handle.resume(last_event_id).await?;
         Ok(ReceiverStream::new(receiver.inner))
     }
 
@@ -137,6 +151,19 @@ pub struct EventId {
     index: usize,
 }
 
+impl EventId {
+    /// The HTTP request whose stream carried this event, or `None` for the
+    /// standalone stream.
+    pub fn http_request_id(&self) -> Option<u64> {
+        self.http_request_id
+    }
+
+    /// Position of the event within its stream.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
 impl std::fmt::Display for EventId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.index)?;
@@ -179,26 +206,50 @@ impl std::str::FromStr for EventId {
     }
 }
 
-use super::{ServerSseMessage, SessionManager};
+use super::{
+    ServerSseMessage, SessionManager,
+    event_store::{EventStore, EventStoreError},
+};
+
+/// Send already stored events on a fresh channel that closes once drained.
+async fn replay_channel(events: Vec<ServerSseMessage>) -> Receiver<ServerSseMessage> {
+    let (tx, rx) = tokio::sync::mpsc::channel(events.len().max(1));
+    for event in events {
+        let _ = tx.send(event).await;
+    }
+    rx
+}
+
+#[derive(Debug, Clone)]
+struct SessionEventStore {
+    session_id: SessionId,
+    store: Arc<dyn EventStore>,
+}
 
 struct CachedTx {
     tx: Sender<ServerSseMessage>,
     cache: VecDeque<ServerSseMessage>,
     http_request_id: Option<HttpRequestId>,
     capacity: usize,
+    store: Option<SessionEventStore>,
 }
 
 impl CachedTx {
-    fn new(tx: Sender<ServerSseMessage>, http_request_id: Option<HttpRequestId>) -> Self {
+    fn new(
+        tx: Sender<ServerSseMessage>,
+        http_request_id: Option<HttpRequestId>,
+        store: Option<SessionEventStore>,
+    ) -> Self {
         Self {
             cache: VecDeque::with_capacity(tx.capacity()),
             capacity: tx.capacity(),
             tx,
             http_request_id,
+            store,
         }
     }
-    fn new_common(tx: Sender<ServerSseMessage>) -> Self {
-        Self::new(tx, None)
+    fn new_common(tx: Sender<ServerSseMessage>, store: Option<SessionEventStore>) -> Self {
+        Self::new(tx, None, store)
     }
 
     fn next_event_id(&self) -> EventId {
@@ -238,6 +289,15 @@ impl CachedTx {
     }
 
     async fn cache_and_send(&mut self, message: ServerSseMessage) {
+        if let Some(SessionEventStore { session_id, store }) = &self.store {
+            let _ = store
+                .append(session_id, &message)
+                .await
+                .inspect_err(|error| {
+                    let event_id = &message.event_id;
+                    tracing::warn!(?event_id, %error, "failed to persist sse event")
+                });
+        }
         if self.cache.len() >= self.capacity {
             self.cache.pop_front();
             self.cache.push_back(message.clone());
@@ -251,20 +311,39 @@ impl CachedTx {
     }
 
     async fn sync(&mut self, index: usize) -> Result<(), SessionError> {
-        let Some(front) = self.cache.front() else {
-            return Ok(());
+        let front_index = match self.cache.front() {
+            Some(front) => Some(
+                front
+                    .event_id
+                    .as_deref()
+                    .unwrap_or_default()
+                    .parse::<EventId>()?
+                    .index,
+            ),
+            None => None,
         };
-        let front_event_id = front
-            .event_id
-            .as_deref()
-            .unwrap_or_default()
-            .parse::<EventId>()?;
-        let sync_index = index.saturating_sub(front_event_id.index);
-        if sync_index > self.cache.len() {
-            // invalid index
-            return Err(SessionError::InvalidEventId);
-        }
-        for message in self.cache.iter().skip(sync_index) {
+        let messages: Vec<ServerSseMessage> = match (front_index, &self.store) {
+            // the cache no longer reaches back to `index`, fall back to the store
+            (front_index, Some(SessionEventStore { session_id, store }))
+                if front_index.is_none_or(|front_index| index < front_index) =>
+            {
+                let from = EventId {
+                    http_request_id: self.http_request_id,
+                    index,
+                };
+                store.replay(session_id, &from).await?
+            }
+            (None, _) => return Ok(()),
+            (Some(front_index), _) => {
+                let sync_index = index.saturating_sub(front_index);
+                if sync_index > self.cache.len() {
+                    // invalid index
+                    return Err(SessionError::InvalidEventId);
+                }
+                self.cache.iter().skip(sync_index).cloned().collect()
+            }
+        };
+        for message in messages {
             let send_result = self.tx.send(message.clone()).await;
             if send_result.is_err() {
                 let event_id: EventId = message.event_id.as_deref().unwrap_or_default().parse()?;
@@ -307,6 +386,16 @@ impl LocalSessionWorker {
     pub fn id(&self) -> &SessionId {
         &self.id
     }
+
+    fn event_store(&self) -> Option<SessionEventStore> {
+        self.session_config
+            .event_store
+            .clone()
+            .map(|store| SessionEventStore {
+                session_id: self.id.clone(),
+                store,
+            })
+    }
 }
 
 #[derive(Debug, Error)]
@@ -323,6 +412,8 @@ pub enum SessionError {
     InvalidEventId,
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Event store error: {0}")]
+    EventStore(#[from] EventStoreError),
 }
 
 impl From<SessionError> for std::io::Error {
@@ -413,7 +504,7 @@ impl LocalSessionWorker {
             http_request_id,
             HttpRequestWise {
                 resources: Default::default(),
-                tx: CachedTx::new(tx, Some(http_request_id)),
+                tx: CachedTx::new(tx, Some(http_request_id), self.event_store()),
             },
         );
         tracing::debug!(http_request_id, "establish new request wise channel");
@@ -537,9 +628,24 @@ impl LocalSessionWorker {
                         inner: rx,
                     })
                 } else {
-                    // Request-wise channel completed (POST response already delivered).
-                    // The client's EventSource is reconnecting after the POST SSE stream
-                    // ended. Fall through to common channel handling below.
+                    // Request-wise channel completed. If the client lost the tail of
+                    // that stream, the event store still has it.
+                    if let Some(SessionEventStore { session_id, store }) = self.event_store() {
+                        let events = store.replay(&session_id, &last_event_id).await?;
+                        if !events.is_empty() {
+                            tracing::debug!(
+                                http_request_id,
+                                "replaying completed request-wise channel from event store"
+                            );
+                            return Ok(StreamableHttpMessageReceiver {
+                                http_request_id: Some(http_request_id),
+                                inner: replay_channel(events).await,
+                            });
+                        }
+                    }
+                    // The POST response was already delivered and the client's
+                    // EventSource is reconnecting after the POST SSE stream ended.
+                    // Fall through to common channel handling below.
                     tracing::debug!(
                         http_request_id,
                         "Request-wise channel completed, falling back to common channel"
@@ -1064,10 +1170,18 @@ pub struct SessionConfig {
     pub channel_capacity: usize,
     /// if set, the session will be closed after this duration of inactivity.
     pub keep_alive: Option<Duration>,
+    /// if set, every outbound SSE event is also written here, and resumption
+    /// replays from it once the in-memory cache has moved past the requested event.
+    pub event_store: Option<Arc<dyn EventStore>>,
 }
 
 impl SessionConfig {
     pub const DEFAULT_CHANNEL_CAPACITY: usize = 16;
+
+    pub fn with_event_store(mut self, event_store: impl EventStore) -> Self {
+        self.event_store = Some(Arc::new(event_store));
+        self
+    }
 }
 
 impl Default for SessionConfig {
@@ -1075,6 +1189,7 @@ impl Default for SessionConfig {
         Self {
             channel_capacity: Self::DEFAULT_CHANNEL_CAPACITY,
             keep_alive: None,
+            event_store: None,
         }
     }
 }
@@ -1091,7 +1206,11 @@ pub fn create_local_session(
     let id = id.into();
     let (event_tx, event_rx) = tokio::sync::mpsc::channel(config.channel_capacity);
     let (common_tx, _) = tokio::sync::mpsc::channel(config.channel_capacity);
-    let common = CachedTx::new_common(common_tx);
+    let store = config.event_store.clone().map(|store| SessionEventStore {
+        session_id: id.clone(),
+        store,
+    });
+    let common = CachedTx::new_common(common_tx, store);
     tracing::info!(session_id = ?id, "create new session");
     let handle = LocalSessionHandle {
         event_tx,
@@ -1,4 +1,4 @@
-use std::{convert::Infallible, fmt::Display, sync::Arc, time::Duration};
+use std::{convert::Infallible, fmt::Display, future::Future, sync::Arc, time::Duration};
 
 use bytes::Bytes;
 use futures::{StreamExt, future::BoxFuture};
@@ -9,13 +9,15 @@ use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::CancellationToken;
 
 use super::session::SessionManager;
+#[cfg(feature = "otel")]
+use crate::model::GetMeta;
 use crate::{
     RoleServer,
     model::{ClientJsonRpcMessage, ClientRequest, GetExtensions, ProtocolVersion},
     serve_server,
     service::serve_directly,
     transport::{
-        OneshotTransport, TransportAdapterIdentity,
+        OneshotTransport, Transport, TransportAdapterIdentity,
         common::{
             http_header::{
                 EVENT_STREAM_MIME_TYPE, HEADER_LAST_EVENT_ID, HEADER_MCP_PROTOCOL_VERSION,
@@ -62,6 +64,43 @@ impl Default for StreamableHttpServerConfig {
     }
 }
 
+/// What a [`StreamableHttpService`] runs for each session.
+///
+/// Every [`Service<RoleServer>`](crate::Service) implements this by running
+/// the regular service loop over the session's transport. A
+/// [`Proxy`](crate::service::Proxy) relays the transport to an upstream
+/// server instead.
+pub trait SessionService: Send + 'static {
+    /// Serve `transport` until the session ends.
+    ///
+    /// `initialize` is `false` in stateless mode, where the transport carries a
+    /// single request and no `initialize` handshake.
+    fn serve_session<T>(self, transport: T, initialize: bool) -> impl Future<Output = ()> + Send
+    where
+        T: Transport<RoleServer> + 'static;
+}
+
+impl<S: crate::Service<RoleServer>> SessionService for S {
+    async fn serve_session<T>(self, transport: T, initialize: bool)
+    where
+        T: Transport<RoleServer> + 'static,
+    {
+        if !initialize {
+            let _ = serve_directly(self, transport, None).waiting().await;
+            return;
+        }
+        match serve_server::<S, T, _, TransportAdapterIdentity>(self, transport).await {
+            Ok(service) => {
+                // on service created
+                let _ = service.waiting().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to create service: {e}");
+            }
+        }
+    }
+}
+
 #[expect(
     clippy::result_large_err,
     reason = "BoxResponse is intentionally large; matches other handlers in this file"
@@ -204,7 +243,7 @@ impl<S, M> Clone for StreamableHttpService<S, M> {
 impl<RequestBody, S, M> tower_service::Service<Request<RequestBody>> for StreamableHttpService<S, M>
 where
     RequestBody: Body + Send + 'static,
-    S: crate::Service<RoleServer>,
+    S: SessionService,
     M: SessionManager,
     RequestBody::Error: Display,
     RequestBody::Data: Send + 'static,
@@ -229,7 +268,7 @@ where
 
 impl<S, M> StreamableHttpService<S, M>
 where
-    S: crate::Service<RoleServer> + Send + 'static,
+    S: SessionService,
     M: SessionManager,
 {
     pub fn new(
@@ -274,22 +313,28 @@ where
             return response;
         }
 
-        let result = match (method, self.config.stateful_mode) {
-            (Method::POST, _) => self.handle_post(request).await,
-            // if we're not in stateful mode, we don't support GET or DELETE because there is no session
-            (Method::GET, true) => self.handle_get(request).await,
-            (Method::DELETE, true) => self.handle_delete(request).await,
-            _ => {
-                // Handle other methods or return an error
-                let response = Response::builder()
-                    .status(http::StatusCode::METHOD_NOT_ALLOWED)
-                    .header(ALLOW, allowed_methods)
-                    .body(Full::new(Bytes::from("Method Not Allowed")).boxed())
-                    .expect("valid response");
-                return response;
+        #[cfg(feature = "transport-streamable-http-server-distributed")]
+        let headers = request.headers().clone();
+        let result = async {
+            match (method, self.config.stateful_mode) {
+                (Method::POST, _) => self.handle_post(request).await,
+                // if we're not in stateful mode, we don't support GET or DELETE because there is no session
+                (Method::GET, true) => self.handle_get(request).await,
+                (Method::DELETE, true) => self.handle_delete(request).await,
+                _ => {
+                    // Handle other methods or return an error
+                    Err(Response::builder()
+                        .status(http::StatusCode::METHOD_NOT_ALLOWED)
+                        .header(ALLOW, allowed_methods)
+                        .body(Full::new(Bytes::from("Method Not Allowed")).boxed())
+                        .expect("valid response"))
+                }
             }
         };
-        match result {
+        // a distributed session manager forwards some of them to the session's owner
+        #[cfg(feature = "transport-streamable-http-server-distributed")]
+        let result = super::session::distributed::with_request_headers(headers, result);
+        match result.await {
             Ok(response) => response,
             Err(response) => response,
         }
@@ -335,7 +380,29 @@ where
             .has_session(&session_id)
             .await
             .map_err(internal_error_response("check session"))?;
+        // check if last event id is provided
+        let last_event_id = request
+            .headers()
+            .get(HEADER_LAST_EVENT_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_owned());
         if !has_session {
+            // An unknown session may still have a persisted stream to replay
+            // (e.g. it was served by another replica or before a restart).
+            if let Some(last_event_id) = last_event_id {
+                validate_protocol_version_header(request.headers())?;
+                if let Ok(stream) = self
+                    .session_manager
+                    .resume(&session_id, last_event_id)
+                    .await
+                {
+                    return Ok(sse_stream_response(
+                        stream,
+                        self.config.sse_keep_alive,
+                        self.config.cancellation_token.child_token(),
+                    ));
+                }
+            }
             // MCP spec: server MUST respond with 404 Not Found for terminated/unknown sessions
             return Ok(Response::builder()
                 .status(http::StatusCode::NOT_FOUND)
@@ -344,12 +411,6 @@ where
         }
         // Validate MCP-Protocol-Version header (per 2025-06-18 spec)
         validate_protocol_version_header(request.headers())?;
-        // check if last event id is provided
-        let last_event_id = request
-            .headers()
-            .get(HEADER_LAST_EVENT_ID)
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_owned());
         if let Some(last_event_id) = last_event_id {
             // check if session has this event id
             let stream = self
@@ -471,6 +532,11 @@ where
                 // inject request part to extensions
                 match &mut message {
                     ClientJsonRpcMessage::Request(req) => {
+                        #[cfg(feature = "otel")]
+                        crate::telemetry::headers_into_meta(
+                            &part.headers,
+                            req.request.get_meta_mut(),
+                        );
                         req.request.extensions_mut().insert(part);
                     }
                     ClientJsonRpcMessage::Notification(not) => {
@@ -529,6 +595,8 @@ where
                         return Err(unexpected_message_response("initialize request"));
                     }
                     // inject request part to extensions
+                    #[cfg(feature = "otel")]
+                    crate::telemetry::headers_into_meta(&part.headers, req.request.get_meta_mut());
                     req.request.extensions_mut().insert(part);
                 } else {
                     return Err(unexpected_message_response("initialize request"));
@@ -541,19 +609,7 @@ where
                     let session_manager = self.session_manager.clone();
                     let session_id = session_id.clone();
                     async move {
-                        let service = serve_server::<S, M::Transport, _, TransportAdapterIdentity>(
-                            service, transport,
-                        )
-                        .await;
-                        match service {
-                            Ok(service) => {
-                                // on service created
-                                let _ = service.waiting().await;
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to create service: {e}");
-                            }
-                        }
+                        service.serve_session(transport, true).await;
                         let _ = session_manager
                             .close_session(&session_id)
                             .await
@@ -616,14 +672,15 @@ where
                 .map_err(internal_error_response("get service"))?;
             match message {
                 ClientJsonRpcMessage::Request(mut request) => {
+                    #[cfg(feature = "otel")]
+                    crate::telemetry::headers_into_meta(
+                        &part.headers,
+                        request.request.get_meta_mut(),
+                    );
                     request.request.extensions_mut().insert(part);
                     let (transport, mut receiver) =
                         OneshotTransport::<RoleServer>::new(ClientJsonRpcMessage::Request(request));
-                    let service = serve_directly(service, transport, None);
-                    tokio::spawn(async move {
-                        // on service created
-                        let _ = service.waiting().await;
-                    });
+                    tokio::spawn(service.serve_session(transport, false));
                     if self.config.json_response {
                         // JSON-direct mode: await the single response and return as
                         // application/json, eliminating SSE framing overhead.
@@ -0,0 +1,164 @@
+//! MCP over plain TCP connections.
+//!
+//! [`TcpServer`] accepts connections and runs a fresh service from a shared
+//! factory on each of them; [`connect`] opens a connection a client can
+//! [`serve`](crate::ServiceExt::serve) over.
+//!
+//! ```rust,ignore
+//! let ct = CancellationToken::new();
+//! let server = TcpServer::bind(
+//!     "127.0.0.1:8001",
+//!     || Ok(Calculator::new()),
+//!     TcpServerConfig {
+//!         cancellation_token: ct.child_token(),
+//!         ..Default::default()
+//!     },
+//! )
+//! .await?;
+//! tokio::spawn(server.serve());
+//!
+//! let client = ().serve(tcp::connect("127.0.0.1:8001").await?).await?;
+//! ```
+use std::io;
+
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio_util::sync::CancellationToken;
+
+use super::DEFAULT_MAX_MESSAGE_SIZE;
+
+/// Open a TCP connection to an MCP server.
+pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect(addr).await?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// Settings for a [`TcpServer`].
+///
+/// The default disables Nagle's algorithm and uses the transport's default
+/// message size limit.
+#[derive(Debug, Clone)]
+pub struct TcpServerConfig {
+    /// Disable Nagle's algorithm on accepted connections.
+    pub nodelay: bool,
+    /// Largest incoming message accepted on a connection, in bytes.
+    pub max_message_size: usize,
+    /// Cancellation token for the server.
+    ///
+    /// When this token is cancelled, the server stops accepting connections
+    /// and every session is terminated.
+    pub cancellation_token: CancellationToken,
+}
+
+impl Default for TcpServerConfig {
+    fn default() -> Self {
+        Self {
+            nodelay: true,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            cancellation_token: CancellationToken::new(),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub use server::TcpServer;
+
+#[cfg(feature = "server")]
+mod server {
+    use std::{io, net::SocketAddr};
+
+    use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+    use super::TcpServerConfig;
+    use crate::{
+        RoleServer, Service,
+        transport::listener::{Accept, serve_connections},
+    };
+
+    /// Serves MCP on every connection accepted from a TCP listener.
+    pub struct TcpServer<F> {
+        listener: TcpListener,
+        service_factory: F,
+        config: TcpServerConfig,
+    }
+
+    impl<F> std::fmt::Debug for TcpServer<F> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("TcpServer")
+                .field("listener", &self.listener)
+                .field("config", &self.config)
+                .finish()
+        }
+    }
+
+    impl<S, F> TcpServer<F>
+    where
+        S: Service<RoleServer>,
+        F: Fn() -> Result<S, io::Error> + Send + Sync + 'static,
+    {
+        /// Bind a listener to `addr`.
+        pub async fn bind(
+            addr: impl ToSocketAddrs,
+            service_factory: F,
+            config: TcpServerConfig,
+        ) -> io::Result<Self> {
+            let listener = TcpListener::bind(addr).await?;
+            Ok(Self::from_listener(listener, service_factory, config))
+        }
+
+        /// Serve connections from an already bound listener.
+        pub fn from_listener(
+            listener: TcpListener,
+            service_factory: F,
+            config: TcpServerConfig,
+        ) -> Self {
+            Self {
+                listener,
+                service_factory,
+                config,
+            }
+        }
+
+        pub fn local_addr(&self) -> io::Result<SocketAddr> {
+            self.listener.local_addr()
+        }
+
+        /// Accept connections until the configured cancellation token is
+        /// cancelled, then wait for every session to end.
+        pub async fn serve(self) {
+            let ct = self.config.cancellation_token.clone();
+            let listener = Listener {
+                listener: self.listener,
+                nodelay: self.config.nodelay,
+            };
+            serve_connections(
+                listener,
+                self.service_factory,
+                self.config.max_message_size,
+                ct,
+            )
+            .await
+        }
+    }
+
+    struct Listener {
+        listener: TcpListener,
+        nodelay: bool,
+    }
+
+    impl Accept for Listener {
+        type Stream = TcpStream;
+        type Addr = SocketAddr;
+
+        async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+            let (stream, addr) = self.listener.accept().await?;
+            if self.nodelay {
+                // the connection is usable either way, so don't fail the accept
+                if let Err(e) = stream.set_nodelay(true) {
+                    tracing::warn!(%addr, "failed to set TCP_NODELAY: {e}");
+                }
+            }
+            Ok((stream, addr))
+        }
+    }
+}
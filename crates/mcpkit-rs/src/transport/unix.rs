@@ -0,0 +1,231 @@
+//! MCP over Unix domain sockets.
+//!
+//! [`UnixServer`] binds a socket path and runs a fresh service from a shared
+//! factory on each connection; [`connect`] opens a connection a client can
+//! [`serve`](crate::ServiceExt::serve) over.
+//!
+//! A socket file left behind by a server that crashed is removed on bind, but
+//! only if nothing is listening on it any more. The server removes its own
+//! socket file again when it shuts down.
+//!
+//! ```rust,ignore
+//! let server = UnixServer::bind(
+//!     "/run/user/1000/calculator.sock",
+//!     || Ok(Calculator::new()),
+//!     UnixServerConfig {
+//!         permissions: Some(0o600),
+//!         ..Default::default()
+//!     },
+//! )
+//! .await?;
+//! tokio::spawn(server.serve());
+//!
+//! let client = ().serve(unix::connect("/run/user/1000/calculator.sock").await?).await?;
+//! ```
+use std::{io, path::Path};
+
+use tokio::net::UnixStream;
+use tokio_util::sync::CancellationToken;
+
+use super::DEFAULT_MAX_MESSAGE_SIZE;
+
+/// Open a connection to an MCP server listening on `path`.
+pub async fn connect(path: impl AsRef<Path>) -> io::Result<UnixStream> {
+    UnixStream::connect(path).await
+}
+
+/// Settings for a [`UnixServer`].
+///
+/// The default leaves the socket mode to the umask, removes stale sockets on
+/// bind and uses the transport's default message size limit.
+#[derive(Debug, Clone)]
+pub struct UnixServerConfig {
+    /// Mode of the socket file, e.g. `0o600` to only admit the owner.
+    ///
+    /// When unset the mode follows the process umask.
+    pub permissions: Option<u32>,
+    /// Remove a socket file at the bind path if no server is listening on it.
+    pub remove_stale_socket: bool,
+    /// Largest incoming message accepted on a connection, in bytes.
+    pub max_message_size: usize,
+    /// Cancellation token for the server.
+    ///
+    /// When this token is cancelled, the server stops accepting connections,
+    /// every session is terminated and the socket file is removed.
+    pub cancellation_token: CancellationToken,
+}
+
+impl Default for UnixServerConfig {
+    fn default() -> Self {
+        Self {
+            permissions: None,
+            remove_stale_socket: true,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            cancellation_token: CancellationToken::new(),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub use server::UnixServer;
+
+#[cfg(feature = "server")]
+mod server {
+    use std::{
+        io,
+        os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt},
+        path::{Path, PathBuf},
+    };
+
+    use tokio::net::{UnixListener, UnixStream, unix::SocketAddr};
+
+    use super::UnixServerConfig;
+    use crate::{
+        RoleServer, Service,
+        transport::listener::{Accept, serve_connections},
+    };
+
+    /// Serves MCP on every connection accepted from a Unix socket.
+    pub struct UnixServer<F> {
+        listener: Listener,
+        service_factory: F,
+        config: UnixServerConfig,
+    }
+
+    impl<F> std::fmt::Debug for UnixServer<F> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("UnixServer")
+                .field("path", &self.listener.socket.path)
+                .field("config", &self.config)
+                .finish()
+        }
+    }
+
+    impl<S, F> UnixServer<F>
+    where
+        S: Service<RoleServer>,
+        F: Fn() -> Result<S, io::Error> + Send + Sync + 'static,
+    {
+        /// Bind a listener to the socket at `path`.
+        ///
+        /// Fails with [`AddrInUse`](io::ErrorKind::AddrInUse) if another server
+        /// is listening there, and with
+        /// [`AlreadyExists`](io::ErrorKind::AlreadyExists) if the path is
+        /// taken by something other than a socket.
+        pub async fn bind(
+            path: impl AsRef<Path>,
+            service_factory: F,
+            config: UnixServerConfig,
+        ) -> io::Result<Self> {
+            let path = path.as_ref();
+            if config.remove_stale_socket {
+                remove_stale_socket(path).await?;
+            }
+            let listener = UnixListener::bind(path)?;
+            // from here on the socket file is removed again if bind fails
+            let socket = SocketFile::new(path).inspect_err(|_| {
+                let _ = std::fs::remove_file(path);
+            })?;
+            // the socket is briefly accessible under the umask until this
+            // runs; bind inside a private directory if that matters
+            if let Some(mode) = config.permissions {
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+            }
+            Ok(Self {
+                listener: Listener { listener, socket },
+                service_factory,
+                config,
+            })
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.listener.socket.path
+        }
+
+        /// Accept connections until the configured cancellation token is
+        /// cancelled, then wait for every session to end and remove the
+        /// socket file.
+        pub async fn serve(self) {
+            let ct = self.config.cancellation_token.clone();
+            serve_connections(
+                self.listener,
+                self.service_factory,
+                self.config.max_message_size,
+                ct,
+            )
+            .await
+        }
+    }
+
+    async fn remove_stale_socket(path: &Path) -> io::Result<()> {
+        let metadata = match std::fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if !metadata.file_type().is_socket() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+        match UnixStream::connect(path).await {
+            Ok(_) => Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("another server is listening on {}", path.display()),
+            )),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+                tracing::info!(path = %path.display(), "removing stale socket");
+                std::fs::remove_file(path)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Removes the socket file on drop, unless it has been replaced since.
+    struct SocketFile {
+        path: PathBuf,
+        inode: (u64, u64),
+    }
+
+    impl SocketFile {
+        fn new(path: &Path) -> io::Result<Self> {
+            let metadata = std::fs::metadata(path)?;
+            Ok(Self {
+                path: path.to_owned(),
+                inode: (metadata.dev(), metadata.ino()),
+            })
+        }
+    }
+
+    impl Drop for SocketFile {
+        fn drop(&mut self) {
+            let Ok(metadata) = std::fs::symlink_metadata(&self.path) else {
+                return;
+            };
+            if (metadata.dev(), metadata.ino()) != self.inode {
+                return;
+            }
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                tracing::warn!(path = %self.path.display(), "failed to remove socket: {e}");
+            }
+        }
+    }
+
+    struct Listener {
+        listener: UnixListener,
+        socket: SocketFile,
+    }
+
+    impl Accept for Listener {
+        type Stream = UnixStream;
+        type Addr = SocketAddr;
+
+        fn accept(
+            &self,
+        ) -> impl std::future::Future<Output = io::Result<(UnixStream, SocketAddr)>> + Send
+        {
+            self.listener.accept()
+        }
+    }
+}
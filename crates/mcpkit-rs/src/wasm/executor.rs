@@ -1,10 +1,14 @@
 //! WASM tool executor
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use super::{WasmContext, WasmError, WasmToolRegistry};
+use super::{FuelMetrics, WasmContext, WasmError, WasmToolRegistry};
 use crate::{
     ErrorData,
+    audit::{AuditLog, AuditOutcome, AuditRecord, PolicyDecision},
     model::{CallToolResult, Content, JsonObject},
 };
 
@@ -17,6 +21,13 @@ pub struct WasmToolExecutor {
     #[cfg(feature = "config")]
     /// Optional server configuration
     config: Option<Arc<crate::config::ServerConfig>>,
+
+    /// Optional audit log for executions
+    audit: Option<AuditLog>,
+
+    #[cfg(feature = "otel")]
+    /// Optional OpenTelemetry instruments for fuel and memory
+    metrics: Option<crate::telemetry::McpMetrics>,
 }
 
 impl WasmToolExecutor {
@@ -26,6 +37,9 @@ impl WasmToolExecutor {
             registry,
             #[cfg(feature = "config")]
             config: None,
+            audit: None,
+            #[cfg(feature = "otel")]
+            metrics: None,
         }
     }
 
@@ -38,24 +52,90 @@ impl WasmToolExecutor {
         Self {
             registry,
             config: Some(config),
+            audit: None,
+            #[cfg(feature = "otel")]
+            metrics: None,
         }
     }
 
+    /// Record every execution, including its fuel usage, to `audit`
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    #[cfg(feature = "otel")]
+    /// Record fuel and memory gauges for every execution
+    pub fn with_metrics(mut self, metrics: crate::telemetry::McpMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     /// Execute a WASM tool
     pub async fn execute(
         &self,
         tool_name: &str,
         arguments: JsonObject,
     ) -> Result<CallToolResult, ErrorData> {
-        // Check policy if configured
+        let Some(audit) = &self.audit else {
+            return self
+                .execute_metered(tool_name, arguments)
+                .await
+                .map(|(r, _)| r);
+        };
+
+        let started = Instant::now();
+        let record = AuditRecord::new("tools/call")
+            .with_target(tool_name)
+            .with_arguments_digest(audit.digest_arguments(&arguments))
+            .with_decision(self.policy_decision(tool_name));
+
+        let (result, record) = match self.execute_metered(tool_name, arguments).await {
+            Ok((result, metrics)) => {
+                let record = match metrics {
+                    Some(metrics) => record.with_fuel_used(metrics.compute_units.0),
+                    None => record,
+                };
+                let outcome = if result.is_error == Some(true) {
+                    AuditOutcome::ToolError
+                } else {
+                    AuditOutcome::Success
+                };
+                (Ok(result), record.with_outcome(outcome))
+            }
+            Err(error) => {
+                let outcome = AuditOutcome::from(&error);
+                (Err(error), record.with_outcome(outcome))
+            }
+        };
+        audit.record(&record.with_duration(started.elapsed()));
+        result
+    }
+
+    fn policy_decision(&self, _tool_name: &str) -> PolicyDecision {
         #[cfg(feature = "config")]
         if let Some(ref config) = self.config {
-            if !config.is_tool_allowed(tool_name) {
-                return Err(ErrorData::invalid_request(
-                    format!("Tool '{}' is not allowed by policy", tool_name),
-                    None,
-                ));
-            }
+            return if config.is_tool_allowed(_tool_name) {
+                PolicyDecision::Allowed
+            } else {
+                PolicyDecision::Denied
+            };
+        }
+        PolicyDecision::NotEvaluated
+    }
+
+    /// Execute a WASM tool, returning fuel metrics alongside the result
+    pub async fn execute_metered(
+        &self,
+        tool_name: &str,
+        arguments: JsonObject,
+    ) -> Result<(CallToolResult, Option<FuelMetrics>), ErrorData> {
+        // Check policy if configured
+        if self.policy_decision(tool_name) == PolicyDecision::Denied {
+            return Err(ErrorData::invalid_request(
+                format!("Tool '{}' is not allowed by policy", tool_name),
+                None,
+            ));
         }
 
         // Get the tool
@@ -142,10 +222,10 @@ impl WasmToolExecutor {
         }
 
         // Execute the WASM module
-        let output = self
+        let (output, metrics) = self
             .registry
             .runtime()
-            .execute(&tool.compiled_module, context)
+            .execute_with_metering(&tool.compiled_module, context)
             .await
             .map_err(|e| match e {
                 WasmError::Timeout => ErrorData::internal_error(
@@ -158,6 +238,11 @@ impl WasmToolExecutor {
                 ),
             })?;
 
+        #[cfg(feature = "otel")]
+        if let (Some(recorder), Some(metrics)) = (&self.metrics, &metrics) {
+            recorder.record_wasm_execution(tool_name, metrics);
+        }
+
         // Parse the output as JSON
         let output_json: serde_json::Value = serde_json::from_slice(&output).map_err(|e| {
             ErrorData::internal_error(
@@ -172,7 +257,10 @@ impl WasmToolExecutor {
             // Check if it's an error response
             if let Some(error) = obj.get("error") {
                 if let Some(error_str) = error.as_str() {
-                    return Ok(CallToolResult::error(vec![Content::text(error_str)]));
+                    return Ok((
+                        CallToolResult::error(vec![Content::text(error_str)]),
+                        metrics,
+                    ));
                 }
             }
 
@@ -185,17 +273,21 @@ impl WasmToolExecutor {
                 vec![Content::text(output_json.to_string())]
             };
 
-            Ok(CallToolResult {
-                content,
-                structured_content: None,
-                is_error: Some(false),
-                meta: None,
-            })
+            Ok((
+                CallToolResult {
+                    content,
+                    structured_content: None,
+                    is_error: Some(false),
+                    meta: None,
+                },
+                metrics,
+            ))
         } else {
             // Non-object output - treat as plain content
-            Ok(CallToolResult::success(vec![Content::text(
-                output_json.to_string(),
-            )]))
+            Ok((
+                CallToolResult::success(vec![Content::text(output_json.to_string())]),
+                metrics,
+            ))
         }
     }
 
@@ -240,6 +332,39 @@ mod tests {
         assert_eq!(executor.list_tools().len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_executor_audits_failed_execution() {
+        use std::sync::Mutex;
+
+        use crate::audit::AuditSink;
+
+        #[derive(Default)]
+        struct Records(Mutex<Vec<AuditRecord>>);
+        impl AuditSink for Records {
+            fn record(&self, record: &AuditRecord) {
+                self.0.lock().unwrap().push(record.clone());
+            }
+        }
+
+        let runtime = Arc::new(WasmRuntime::new().unwrap());
+        let provider = Arc::new(InMemoryCredentialProvider::new());
+        let registry = Arc::new(WasmToolRegistry::new(provider, runtime));
+        let records = Arc::new(Records::default());
+        let executor =
+            WasmToolExecutor::new(registry).with_audit(AuditLog::new().with_sink(records.clone()));
+
+        let result = executor.execute("nonexistent", JsonObject::new()).await;
+        assert!(result.is_err());
+
+        let records = records.0.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].method, "tools/call");
+        assert_eq!(records[0].target.as_deref(), Some("nonexistent"));
+        assert_eq!(records[0].decision, PolicyDecision::NotEvaluated);
+        assert!(matches!(records[0].outcome, AuditOutcome::Error { .. }));
+        assert!(records[0].fuel_used.is_none());
+    }
+
     // More comprehensive tests would require actual WASM modules
     // or mock implementations
 }
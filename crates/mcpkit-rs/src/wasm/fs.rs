@@ -94,6 +94,41 @@ impl FsPermissionMapper {
         preopen_dirs
     }
 
+    /// Narrow each of `dirs` to the operations the policy allows inside it,
+    /// dropping the directories it allows nothing in
+    pub fn restrict_preopen_dirs(
+        &self,
+        dirs: impl IntoIterator<Item = PreopenDir>,
+    ) -> Vec<PreopenDir> {
+        dirs.into_iter()
+            .filter_map(|dir| {
+                // storage rules such as `fs:///data/**` match the contents of
+                // a directory, not the directory itself
+                let contents = dir.host_path.join("");
+                let contents = contents.to_string_lossy();
+                let access: Vec<String> = ["read", "write", "execute"]
+                    .into_iter()
+                    .filter(|op| self.is_operation_allowed(&contents, op))
+                    .map(String::from)
+                    .collect();
+                let (dir_perms, file_perms) = Self::map_to_wasi(&access);
+                let dir_perms = dir.dir_perms & dir_perms;
+                if dir_perms.is_empty() {
+                    tracing::warn!(
+                        path = %dir.host_path.display(),
+                        "not preopening a directory the policy denies"
+                    );
+                    return None;
+                }
+                Some(PreopenDir {
+                    dir_perms,
+                    file_perms: dir.file_perms & file_perms,
+                    ..dir
+                })
+            })
+            .collect()
+    }
+
     /// Convert a glob pattern to the base directory that needs to be opened
     fn pattern_to_dir_path(pattern: &str) -> PathBuf {
         let pattern = pattern.strip_prefix("fs://").unwrap_or(pattern);
@@ -175,6 +210,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_restrict_preopen_dirs() {
+        let policy = mcpkit_rs_policy::Policy::from_yaml(
+            r#"
+            version: "1.0"
+            core:
+              storage:
+                allow:
+                  - uri: "fs:///data/**"
+                    access: ["read"]
+            "#,
+        )
+        .unwrap();
+        let mapper = FsPermissionMapper::new(Some(Arc::new(
+            mcpkit_rs_policy::CompiledPolicy::compile(&policy).unwrap(),
+        )));
+        let (dir_perms, file_perms) =
+            FsPermissionMapper::map_to_wasi(&["read".to_string(), "write".to_string()]);
+        let dir = |path: &str| PreopenDir {
+            host_path: PathBuf::from(path),
+            guest_path: PathBuf::from(path),
+            dir_perms,
+            file_perms,
+        };
+
+        let dirs = mapper.restrict_preopen_dirs([dir("/data"), dir("/home")]);
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].host_path, PathBuf::from("/data"));
+        assert_eq!(dirs[0].dir_perms, DirPerms::READ);
+        assert_eq!(dirs[0].file_perms, FilePerms::READ);
+    }
+
     #[test]
     fn test_no_policy_returns_empty_dirs() {
         let mapper = FsPermissionMapper::new(None);
@@ -74,6 +74,11 @@ pub struct FuelMetrics {
 
     /// Total instructions executed (if available)
     pub instruction_count: Option<u64>,
+
+    /// Linear memory size in bytes when execution finished (memory only
+    /// grows, so this is also the peak)
+    #[serde(default)]
+    pub memory_bytes: Option<u64>,
 }
 
 impl FuelMetrics {
@@ -117,6 +122,13 @@ impl FuelMetrics {
             report.push_str(&format!("Instructions: {}\n", format_with_commas(count)));
         }
 
+        if let Some(bytes) = self.memory_bytes {
+            report.push_str(&format!(
+                "Memory:      {} bytes\n",
+                format_with_commas(bytes)
+            ));
+        }
+
         report
     }
 
@@ -18,7 +18,7 @@ use wasmtime_wasi::{
 
 use super::{
     WasmError,
-    fs::FsPermissionMapper,
+    fs::{FsPermissionMapper, PreopenDir},
     metering::{
         ComputeUnits, DisplayFormat, EnforcementMode, FuelMetrics, FuelUpdate, MemoryLimits,
         MeteringConfig, MeteringMonitor, SamplingStrategy,
@@ -105,6 +105,10 @@ pub struct WasmContext {
 
     /// Policy for filesystem permissions
     pub policy: Option<Arc<mcpkit_rs_policy::CompiledPolicy>>,
+
+    /// Directories to preopen in addition to those granted by the policy,
+    /// restricted to the access it allows
+    pub preopen_dirs: Vec<PreopenDir>,
 }
 
 impl WasmContext {
@@ -131,6 +135,7 @@ impl WasmContext {
             metering: Some(default_metering),
             monitor: None,
             policy: None,
+            preopen_dirs: Vec::new(),
         }
     }
 
@@ -190,6 +195,14 @@ impl WasmContext {
         self
     }
 
+    /// Preopen directories, such as the client's roots from
+    /// [`RootsGuard::preopen_dirs`](crate::handler::server::roots::RootsGuard::preopen_dirs).
+    /// With a policy, they only get the access its storage rules allow.
+    pub fn with_preopen_dirs(mut self, dirs: impl IntoIterator<Item = PreopenDir>) -> Self {
+        self.preopen_dirs.extend(dirs);
+        self
+    }
+
     /// Build WASI context for preview1
     fn build_wasi(&mut self) -> Result<WasiWithPipes, WasmError> {
         let mut builder = WasiCtxBuilder::new();
@@ -211,23 +224,29 @@ impl WasmContext {
             builder.env(key, value);
         }
 
-        // Add preopened directories based on policy
-        if let Some(policy) = &self.policy {
-            let mapper = FsPermissionMapper::new(Some(policy.clone()));
-            let preopen_dirs = mapper.get_preopen_dirs();
-
-            for dir in preopen_dirs {
-                builder
-                    .preopened_dir(
-                        &dir.host_path,
-                        dir.guest_path.to_string_lossy(),
-                        dir.dir_perms,
-                        dir.file_perms,
-                    )
-                    .map_err(|e| {
-                        WasmError::RuntimeError(format!("Failed to preopen directory: {}", e))
-                    })?;
+        // Add preopened directories based on policy, then the explicit ones,
+        // limited to what the policy allows in them
+        let mut preopen_dirs = Vec::new();
+        match &self.policy {
+            Some(policy) => {
+                let mapper = FsPermissionMapper::new(Some(policy.clone()));
+                preopen_dirs.extend(mapper.get_preopen_dirs());
+                preopen_dirs
+                    .extend(mapper.restrict_preopen_dirs(self.preopen_dirs.iter().cloned()));
             }
+            None => preopen_dirs.extend(self.preopen_dirs.iter().cloned()),
+        }
+        for dir in preopen_dirs {
+            builder
+                .preopened_dir(
+                    &dir.host_path,
+                    dir.guest_path.to_string_lossy(),
+                    dir.dir_perms,
+                    dir.file_perms,
+                )
+                .map_err(|e| {
+                    WasmError::RuntimeError(format!("Failed to preopen directory: {}", e))
+                })?;
         }
 
         // Build the WASI preview1 context
@@ -401,6 +420,10 @@ impl WasmRuntime {
                     0
                 };
 
+                let memory_bytes = instance
+                    .get_memory(&mut store, "memory")
+                    .map(|memory| memory.data_size(&store) as u64);
+
                 // Get stdout from the pipe after execution regardless of result
                 let output_bytes = stdout_pipe.contents();
 
@@ -433,6 +456,7 @@ impl WasmRuntime {
                         units_per_second,
                         peak_rate: None, // Could be tracked with periodic sampling
                         instruction_count: None, // Wasmtime doesn't expose this directly
+                        memory_bytes,
                     })
                 } else {
                     None
@@ -0,0 +1,178 @@
+//cargo test --test test_cassette --features "client server"
+#![cfg(all(feature = "client", feature = "server"))]
+
+use std::{path::Path, time::Duration};
+
+use mcpkit_rs::{
+    ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{RequestContext, RunningService},
+    transport::{
+        MatchMode, RecordingTransport, ReplayTransport,
+        async_rw::AsyncRwTransport,
+        cassette::{Direction, read_cassette},
+    },
+};
+
+struct Echo;
+
+impl ServerHandler for Echo {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(ServerCapabilities::builder().enable_tools().build())
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(vec![Tool::new(
+            "echo",
+            "Echo the arguments",
+            JsonObject::new(),
+        )]))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let arguments = serde_json::to_string(&request.arguments).unwrap();
+        Ok(CallToolResult::success(vec![Content::text(arguments)]))
+    }
+}
+
+fn echo_args(text: &str) -> CallToolRequestParams {
+    CallToolRequestParams::new("echo").with_arguments(
+        serde_json::json!({ "text": text })
+            .as_object()
+            .unwrap()
+            .clone(),
+    )
+}
+
+/// List the tools and call `echo` with `text`, returning what came back.
+async fn exercise(
+    client: &RunningService<RoleClient, ()>,
+    text: &str,
+) -> Result<(Vec<Tool>, CallToolResult), mcpkit_rs::ServiceError> {
+    let tools = client.list_all_tools().await?;
+    let result = client.call_tool(echo_args(text)).await?;
+    Ok((tools, result))
+}
+
+async fn record(path: &Path) -> anyhow::Result<(Vec<Tool>, CallToolResult)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move {
+        Echo.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    let (read, write) = tokio::io::split(client_transport);
+    let transport = AsyncRwTransport::new_client(read, write);
+    let client = ().serve(RecordingTransport::create(transport, path)?).await?;
+    let outcome = exercise(&client, "hello").await?;
+    client.cancel().await?;
+    server.await??;
+    Ok(outcome)
+}
+
+#[tokio::test]
+async fn test_record_then_replay() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("echo.jsonl");
+    let recorded = record(&path).await?;
+
+    let entries = read_cassette(std::io::BufReader::new(std::fs::File::open(&path)?))?;
+    let methods: Vec<_> = entries
+        .iter()
+        .map(|entry| (entry.direction, entry.message["method"].as_str()))
+        .collect();
+    assert_eq!(
+        methods,
+        [
+            (Direction::Sent, Some("initialize")),
+            (Direction::Received, None),
+            (Direction::Sent, Some("notifications/initialized")),
+            (Direction::Sent, Some("tools/list")),
+            (Direction::Received, None),
+            (Direction::Sent, Some("tools/call")),
+            (Direction::Received, None),
+        ]
+    );
+    assert!(entries.windows(2).all(|pair| pair[0].time <= pair[1].time));
+
+    let replay = ReplayTransport::load(&path, MatchMode::Exact)?;
+    let handle = replay.handle();
+    let client = ().serve(replay).await?;
+    assert_eq!(exercise(&client, "hello").await?, recorded);
+    assert_eq!(handle.remaining(), 0);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_maps_recorded_ids() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("echo.jsonl");
+    let recorded = record(&path).await?;
+
+    // as if the recording client had numbered its requests differently
+    let mut entries = read_cassette(std::io::BufReader::new(std::fs::File::open(&path)?))?;
+    for entry in &mut entries {
+        if let Some(id) = entry.message.get("id").and_then(|id| id.as_i64()) {
+            entry.message["id"] = format!("recorded-{id}").into();
+        }
+    }
+
+    let exact = ().serve(ReplayTransport::new(entries.clone(), MatchMode::Exact));
+    assert!(
+        tokio::time::timeout(Duration::from_secs(5), exact)
+            .await?
+            .is_err()
+    );
+
+    for mode in [MatchMode::IgnoreIds, MatchMode::MethodAndParams] {
+        let client = ().serve(ReplayTransport::new(entries.clone(), mode)).await?;
+        assert_eq!(exercise(&client, "hello").await?, recorded);
+        client.cancel().await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_rejects_unexpected_messages() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("echo.jsonl");
+    record(&path).await?;
+
+    let replay = ReplayTransport::load(&path, MatchMode::MethodAndParams)?;
+    let handle = replay.handle();
+    let client = ().serve(replay).await?;
+    let outcome =
+        tokio::time::timeout(Duration::from_secs(5), exercise(&client, "goodbye")).await?;
+    assert!(outcome.is_err());
+    assert_eq!(handle.remaining(), 2);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_ends_on_unexpected_message() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("echo.jsonl");
+    record(&path).await?;
+
+    // a mismatch leaves the rest of the cassette unplayable, so the replay ends
+    // instead of waiting for a message that will never be sent
+    let client = ().serve(ReplayTransport::load(&path, MatchMode::Exact)?).await?;
+    assert!(exercise(&client, "goodbye").await.is_err());
+    let quit = tokio::time::timeout(Duration::from_secs(5), client.waiting()).await??;
+    match quit {
+        mcpkit_rs::service::QuitReason::Closed {
+            reason: Some(reason),
+        } => assert!(reason.contains("goodbye"), "{reason}"),
+        other => panic!("unexpected quit reason: {other:?}"),
+    }
+    Ok(())
+}
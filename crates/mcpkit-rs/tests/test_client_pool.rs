@@ -0,0 +1,307 @@
+//cargo test --test test_client_pool --features "client server"
+#![cfg(all(feature = "client", feature = "server"))]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use mcpkit_rs::{
+    ClientHandler, ErrorData, Peer, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::client::pool::{ClientPool, ClientPoolError},
+    model::*,
+    service::{NotificationContext, RequestContext},
+};
+
+/// A server whose tool list can change while it is running.
+#[derive(Clone)]
+struct Upstream {
+    name: &'static str,
+    tools: Arc<Mutex<Vec<Tool>>>,
+}
+
+impl Upstream {
+    fn new(name: &'static str, tools: &[&'static str]) -> Self {
+        Self {
+            name,
+            tools: Arc::new(Mutex::new(tools.iter().map(|t| tool(t)).collect())),
+        }
+    }
+
+    fn resource_uri(&self) -> String {
+        format!("memo://{}/readme", self.name)
+    }
+}
+
+fn tool(name: &'static str) -> Tool {
+    Tool::new(name, "test tool", JsonObject::new())
+}
+
+fn text_of(result: &CallToolResult) -> String {
+    result.content[0].as_text().expect("text").text.clone()
+}
+
+impl ServerHandler for Upstream {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_resources()
+                .build(),
+        )
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(
+            self.tools.lock().unwrap().clone(),
+        ))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !self
+            .tools
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|t| t.name == request.name)
+        {
+            return Err(ErrorData::invalid_params("no such tool", None));
+        }
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{}:{}",
+            self.name, request.name
+        ))]))
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        Ok(ListResourcesResult::with_all_items(vec![
+            RawResource::new(self.resource_uri(), "readme").no_annotation(),
+            RawResource::new("memo://shared", "shared").no_annotation(),
+        ]))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        Ok(ListResourceTemplatesResult::with_all_items(vec![
+            RawResourceTemplate::new(format!("memo://{}/notes/{{id}}", self.name), "note")
+                .no_annotation(),
+        ]))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        Ok(ReadResourceResult::new(vec![ResourceContents::text(
+            self.name,
+            request.uri,
+        )]))
+    }
+}
+
+async fn spawn_upstream(pool: &ClientPool, server: Upstream) -> anyhow::Result<Peer<RoleServer>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let name = server.name;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        let service = server.serve(server_transport).await?;
+        let _ = tx.send(service.peer().clone());
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+    pool.connect(name, client_transport).await?;
+    Ok(rx.await?)
+}
+
+#[tokio::test]
+async fn test_pool_namespaces_and_routes() -> anyhow::Result<()> {
+    let pool = ClientPool::new();
+    let github = Upstream::new("github", &["create_issue", "search"]);
+    let jira = Upstream::new("jira", &["search"]);
+    spawn_upstream(&pool, github.clone()).await?;
+    spawn_upstream(&pool, jira.clone()).await?;
+    assert_eq!(pool.names().await, ["github", "jira"]);
+
+    let mut names: Vec<_> = pool
+        .list_all_tools()
+        .await
+        .into_iter()
+        .map(|t| t.name.into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        ["github.create_issue", "github.search", "jira.search"]
+    );
+
+    let result = pool
+        .call_tool(CallToolRequestParams::new("jira.search"))
+        .await?;
+    assert_eq!(text_of(&result), "jira:search");
+    assert!(matches!(
+        pool.call_tool(CallToolRequestParams::new("search")).await,
+        Err(ClientPoolError::NotNamespaced(_))
+    ));
+    assert!(matches!(
+        pool.call_tool(CallToolRequestParams::new("gitlab.search"))
+            .await,
+        Err(ClientPoolError::UnknownServer(_))
+    ));
+
+    let resources = pool.list_all_resources().await;
+    assert!(resources.iter().any(|r| r.raw.name == "jira.readme"));
+    let read = pool
+        .read_resource(ReadResourceRequestParams::new(jira.resource_uri()))
+        .await?;
+    assert!(matches!(
+        &read.contents[0],
+        ResourceContents::TextResourceContents { text, .. } if text == "jira"
+    ));
+
+    let templates = pool.list_all_resource_templates().await;
+    assert!(templates.iter().any(|t| t.raw.name == "jira.note"));
+    let read = pool
+        .read_resource(ReadResourceRequestParams::new("memo://jira/notes/7"))
+        .await?;
+    assert!(matches!(
+        &read.contents[0],
+        ResourceContents::TextResourceContents { text, .. } if text == "jira"
+    ));
+
+    // both servers list it, so neither is picked
+    match pool
+        .read_resource(ReadResourceRequestParams::new("memo://shared"))
+        .await
+    {
+        Err(ClientPoolError::AmbiguousResource { servers, .. }) => {
+            assert_eq!(servers, ["github", "jira"])
+        }
+        other => panic!("expected an ambiguous resource, got {other:?}"),
+    }
+    assert!(matches!(
+        pool.read_resource(ReadResourceRequestParams::new("memo://nobody"))
+            .await,
+        Err(ClientPoolError::UnknownResource(_))
+    ));
+
+    let health = pool.health().await;
+    assert_eq!(health.len(), 2);
+    assert!(health.iter().all(|h| h.connected && h.latency.is_some()));
+
+    pool.disconnect("jira").await?;
+    assert_eq!(pool.names().await, ["github"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_refreshes_on_tool_list_changed() -> anyhow::Result<()> {
+    let pool = ClientPool::new().with_separator("__");
+    let upstream = Upstream::new("calc", &["sum"]);
+    let peer = spawn_upstream(&pool, upstream.clone()).await?;
+    assert_eq!(pool.list_all_tools().await.len(), 1);
+
+    upstream.tools.lock().unwrap().push(tool("sub"));
+    // the cached catalog is only replaced once the server announces the change
+    assert_eq!(pool.list_all_tools().await.len(), 1);
+    peer.notify_tool_list_changed().await?;
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !pool
+            .list_all_tools()
+            .await
+            .iter()
+            .any(|t| t.name == "calc__sub")
+        {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_serves_as_gateway() -> anyhow::Result<()> {
+    let pool = ClientPool::new();
+    spawn_upstream(&pool, Upstream::new("a", &["ping"])).await?;
+    spawn_upstream(&pool, Upstream::new("b", &["ping"])).await?;
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let service = pool.serve(server_transport).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    assert_eq!(client.list_all_tools().await?.len(), 2);
+    let result = client
+        .call_tool(CallToolRequestParams::new("b.ping"))
+        .await?;
+    assert_eq!(text_of(&result), "b:ping");
+    let error = client
+        .call_tool(CallToolRequestParams::new("c.ping"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        mcpkit_rs::ServiceError::McpError(ErrorData {
+            code: ErrorCode::INVALID_PARAMS,
+            ..
+        })
+    ));
+    client.cancel().await?;
+    Ok(())
+}
+
+/// A downstream client that reports each tool list change.
+struct ChangeWatcher(tokio::sync::mpsc::UnboundedSender<()>);
+
+impl ClientHandler for ChangeWatcher {
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        let _ = self.0.send(());
+    }
+}
+
+#[tokio::test]
+async fn test_gateway_forwards_list_changed() -> anyhow::Result<()> {
+    let pool = ClientPool::new();
+    let upstream = Upstream::new("calc", &["sum"]);
+    let peer = spawn_upstream(&pool, upstream.clone()).await?;
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let service = pool.serve(server_transport).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+    let (tx, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let client = ChangeWatcher(tx).serve(client_transport).await?;
+    assert_eq!(client.list_all_tools().await?.len(), 1);
+
+    upstream.tools.lock().unwrap().push(tool("sub"));
+    peer.notify_tool_list_changed().await?;
+    tokio::time::timeout(Duration::from_secs(5), changes.recv())
+        .await?
+        .expect("change forwarded");
+    // the announcement only goes out once the refreshed list is cached
+    assert_eq!(client.list_all_tools().await?.len(), 2);
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,322 @@
+//cargo test --test test_client_tasks --features "testing macros elicitation auth"
+#![cfg(all(feature = "testing", feature = "macros"))]
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use mcpkit_rs::{
+    ErrorData as McpError, RoleServer, ServerHandler, ServiceError,
+    model::*,
+    service::RequestContext,
+    task_handler,
+    task_manager::{OperationProcessor, TaskReporter},
+    testing::TestHarness,
+};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+/// Runs `sleep` for the requested number of milliseconds, as a task when
+/// `tasks` is set. `quick` never runs as a task.
+#[derive(Clone)]
+struct Jobs {
+    processor: Arc<Mutex<OperationProcessor>>,
+    tasks: bool,
+}
+
+impl Jobs {
+    fn new(tasks: bool) -> Self {
+        Self {
+            processor: Arc::new(Mutex::new(OperationProcessor::new())),
+            tasks,
+        }
+    }
+}
+
+#[task_handler]
+impl ServerHandler for Jobs {
+    fn get_info(&self) -> ServerInfo {
+        let capabilities = if self.tasks {
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tasks_with(TasksCapability::server_default())
+                .build()
+        } else {
+            ServerCapabilities::builder().enable_tools().build()
+        };
+        ServerInfo::new(capabilities)
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let as_task = ToolExecution::new().with_task_support(TaskSupport::Optional);
+        let mut tools: Vec<_> = ["sleep", "report", "confirm"]
+            .into_iter()
+            .map(|name| Tool::new(name, "", JsonObject::new()).with_execution(as_task.clone()))
+            .collect();
+        tools.push(Tool::new("quick", "", JsonObject::new()));
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let arguments = request.arguments.unwrap_or_default();
+        match request.name.as_ref() {
+            "sleep" => {
+                let ms = arguments.get("ms").and_then(|ms| ms.as_u64()).unwrap_or(0);
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                Ok(CallToolResult::success(vec![Content::text(format!(
+                    "slept {ms}ms"
+                ))]))
+            }
+            "quick" => Ok(CallToolResult::success(vec![Content::text("done")])),
+            "report" => {
+                let reporter = TaskReporter::current().expect("runs as a task");
+                reporter
+                    .report_progress(1.0, Some(2.0), Some("halfway".to_owned()))
+                    .await;
+                Ok(CallToolResult::success(vec![Content::text("reported")]))
+            }
+            #[cfg(feature = "elicitation")]
+            "confirm" => {
+                let reporter = TaskReporter::current().expect("runs as a task");
+                let answer = reporter
+                    .elicit(CreateElicitationRequestParams::FormElicitationParams {
+                        meta: None,
+                        message: "Proceed?".to_owned(),
+                        requested_schema: ElicitationSchema::builder().build().unwrap(),
+                    })
+                    .await
+                    .map_err(|error| McpError::internal_error(error.to_string(), None))?;
+                Ok(CallToolResult::success(vec![Content::text(format!(
+                    "{:?}",
+                    answer.action
+                ))]))
+            }
+            _ => Err(McpError::invalid_params("unknown tool", None)),
+        }
+    }
+}
+
+fn sleep(ms: u64) -> CallToolRequestParams {
+    CallToolRequestParams::new("sleep")
+        .with_arguments(json!({ "ms": ms }).as_object().unwrap().clone())
+}
+
+/// The status changes the client was notified of for `task_id`.
+fn status_notifications(
+    harness: &TestHarness<Jobs>,
+    task_id: &str,
+) -> Vec<(TaskStatus, Option<String>)> {
+    harness
+        .notifications()
+        .into_iter()
+        .filter_map(|notification| match notification {
+            ServerNotification::TaskStatusNotification(notification)
+                if notification.params.task.task_id == task_id =>
+            {
+                let task = notification.params.task;
+                Some((task.status, task.status_message))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+async fn wait_until_finished(harness: &TestHarness<Jobs>, task_id: &str) {
+    harness
+        .wait_for_notification(|notification| {
+            matches!(
+                notification,
+                ServerNotification::TaskStatusNotification(notification)
+                    if notification.params.task.task_id == task_id
+                        && notification.params.task.status.is_terminal()
+            )
+        })
+        .await
+        .expect("task finished");
+}
+
+fn text(result: &CallToolResult) -> &str {
+    &result.content[0].as_text().unwrap().text
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_call_tool_as_task() {
+    let harness = TestHarness::new(Jobs::new(true)).await.unwrap();
+    assert!(harness.supports_tool_tasks());
+
+    let handle = harness.call_tool_as_task(sleep(5_000)).await.unwrap();
+    assert!(handle.is_task());
+    assert_eq!(handle.task().unwrap().status, TaskStatus::Working);
+    assert!(!handle.is_finished());
+
+    let changes: Vec<_> = handle
+        .status_changes()
+        .map(|task| task.unwrap().status)
+        .collect()
+        .await;
+    assert_eq!(changes.last(), Some(&TaskStatus::Completed));
+    assert!(
+        changes[..changes.len() - 1]
+            .iter()
+            .all(|status| *status == TaskStatus::Working)
+    );
+
+    let task_id = handle.task_id().unwrap().to_owned();
+    let listed = harness.list_tasks(None).await.unwrap();
+    assert!(listed.tasks.iter().any(|task| task.task_id == task_id));
+
+    let result = handle.result().await.unwrap();
+    assert_eq!(text(&result), "slept 5000ms");
+    harness.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancel_task() {
+    let harness = TestHarness::new(Jobs::new(true)).await.unwrap();
+    let mut handle = harness.call_tool_as_task(sleep(600_000)).await.unwrap();
+
+    let task = handle.cancel().await.unwrap().unwrap().clone();
+    assert_eq!(task.status, TaskStatus::Cancelled);
+    assert!(handle.is_finished());
+
+    let info = harness
+        .get_task_info(GetTaskInfoParams {
+            meta: None,
+            task_id: task.task_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(info.task.status, TaskStatus::Cancelled);
+
+    let error = handle.result().await.unwrap_err();
+    assert!(matches!(error, ServiceError::McpError(_)), "{error}");
+    harness.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_falls_back_without_task_support() {
+    let harness = TestHarness::new(Jobs::new(false)).await.unwrap();
+    assert!(!harness.supports_tool_tasks());
+
+    let mut handle = harness.call_tool_as_task(sleep(10)).await.unwrap();
+    assert!(!handle.is_task());
+    assert!(handle.is_finished());
+    assert!(handle.refresh().await.unwrap().is_none());
+    assert_eq!(handle.status_changes().count().await, 0);
+
+    let result = handle.result().await.unwrap();
+    assert_eq!(text(&result), "slept 10ms");
+    harness.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tool_without_task_support_is_called_directly() {
+    let harness = TestHarness::new(Jobs::new(true)).await.unwrap();
+    assert_eq!(
+        harness.tool_task_support("quick").await.unwrap(),
+        TaskSupport::Forbidden
+    );
+
+    let handle = harness
+        .call_tool_as_task(CallToolRequestParams::new("quick"))
+        .await
+        .unwrap();
+    assert!(!handle.is_task());
+    assert_eq!(text(&handle.result().await.unwrap()), "done");
+    let processor = harness.handler().processor.clone();
+    assert!(
+        processor
+            .lock()
+            .await
+            .task_records()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    harness.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_task_status_notifications() {
+    let harness = TestHarness::new(Jobs::new(true)).await.unwrap();
+    let handle = harness
+        .call_tool_as_task(CallToolRequestParams::new("report"))
+        .await
+        .unwrap();
+    let task_id = handle.task_id().unwrap().to_owned();
+    wait_until_finished(&harness, &task_id).await;
+    assert_eq!(
+        status_notifications(&harness, &task_id),
+        [
+            (TaskStatus::Working, Some("halfway".to_owned())),
+            (TaskStatus::Completed, None),
+        ]
+    );
+    let progress = harness
+        .wait_for_notification(|notification| {
+            matches!(notification, ServerNotification::ProgressNotification(_))
+        })
+        .await
+        .unwrap();
+    let ServerNotification::ProgressNotification(progress) = progress else {
+        unreachable!()
+    };
+    assert_eq!(progress.params.total, Some(2.0));
+    assert_eq!(progress.params.message.as_deref(), Some("halfway"));
+
+    assert_eq!(text(&handle.result().await.unwrap()), "reported");
+    harness.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancelled_task_is_announced() {
+    let harness = TestHarness::new(Jobs::new(true)).await.unwrap();
+    let mut handle = harness.call_tool_as_task(sleep(600_000)).await.unwrap();
+    let task_id = handle.task_id().unwrap().to_owned();
+    handle.cancel().await.unwrap();
+    wait_until_finished(&harness, &task_id).await;
+    assert_eq!(
+        status_notifications(&harness, &task_id),
+        [(
+            TaskStatus::Cancelled,
+            Some("Operation cancelled".to_owned())
+        )]
+    );
+    harness.shutdown().await;
+}
+
+#[cfg(feature = "elicitation")]
+#[tokio::test(start_paused = true)]
+async fn test_elicitation_requires_input() {
+    let harness = TestHarness::new(Jobs::new(true)).await.unwrap();
+    harness.script_elicitation(Ok(CreateElicitationResult {
+        action: ElicitationAction::Accept,
+        content: Some(json!({})),
+    }));
+
+    let handle = harness
+        .call_tool_as_task(CallToolRequestParams::new("confirm"))
+        .await
+        .unwrap();
+    let task_id = handle.task_id().unwrap().to_owned();
+    wait_until_finished(&harness, &task_id).await;
+    assert_eq!(
+        status_notifications(&harness, &task_id),
+        [
+            (TaskStatus::InputRequired, Some("Proceed?".to_owned())),
+            (TaskStatus::Working, None),
+            (TaskStatus::Completed, None),
+        ]
+    );
+
+    assert_eq!(harness.elicitation_requests().len(), 1);
+    assert_eq!(text(&handle.result().await.unwrap()), "Accept");
+    harness.shutdown().await;
+}
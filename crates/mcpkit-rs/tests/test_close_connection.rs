@@ -35,7 +35,7 @@ async fn test_close_method() -> anyhow::Result<()> {
 
     // Calling close() again should return Closed immediately
     let result = client.close().await?;
-    assert!(matches!(result, QuitReason::Closed));
+    assert!(matches!(result, QuitReason::Closed { .. }));
 
     // Wait for server to finish
     server_handle.await??;
@@ -108,7 +108,10 @@ async fn test_drop_without_close() -> anyhow::Result<()> {
         // The server should close when the client drops
         let result = server.waiting().await?;
         // Server should detect closure
-        assert!(matches!(result, QuitReason::Closed | QuitReason::Cancelled));
+        assert!(matches!(
+            result,
+            QuitReason::Closed { .. } | QuitReason::Cancelled
+        ));
         anyhow::Ok(())
     });
 
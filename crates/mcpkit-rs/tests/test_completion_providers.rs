@@ -0,0 +1,298 @@
+//cargo test --test test_completion_providers --features "testing macros"
+#![cfg(all(feature = "testing", feature = "macros"))]
+
+use std::collections::HashMap;
+
+use mcpkit_rs::{
+    RoleServer, ServerHandler, ServiceExt,
+    handler::server::{
+        completion::{ArgumentCompletions, CompletionContext, fuzzy_match},
+        router::{
+            Router,
+            prompt::PromptRouter,
+            resource::{ResourceTemplateRoute, ResourceTemplateRouter},
+        },
+        wrapper::Parameters,
+    },
+    model::*,
+    prompt, prompt_handler, prompt_router,
+    service::RequestContext,
+    testing::TestHarness,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct ReviewArgs {
+    language: String,
+    framework: Option<String>,
+}
+
+#[derive(Clone)]
+struct Reviewer {
+    prompt_router: PromptRouter<Reviewer>,
+}
+
+#[prompt_router]
+impl Reviewer {
+    #[prompt(
+        complete(arg = "language", values = ["Rust", "Python", "TypeScript", "JavaScript"]),
+        complete(arg = "framework", with = Self::complete_framework)
+    )]
+    async fn review(&self, Parameters(args): Parameters<ReviewArgs>) -> Vec<PromptMessage> {
+        vec![PromptMessage::new_text(
+            PromptMessageRole::User,
+            format!("Review my {} code ({:?})", args.language, args.framework),
+        )]
+    }
+
+    async fn complete_framework(&self, context: &CompletionContext<'_, Self>) -> Vec<String> {
+        match context.get_argument("language") {
+            Some("Rust") => fuzzy_match(context.value(), ["axum", "actix-web", "rocket"]),
+            Some("Python") => fuzzy_match(context.value(), ["django", "flask", "fastapi"]),
+            _ => vec![],
+        }
+    }
+}
+
+#[prompt_handler]
+impl ServerHandler for Reviewer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_completions()
+                .build(),
+        )
+    }
+}
+
+async fn reviewer() -> TestHarness<Reviewer> {
+    TestHarness::new(Reviewer {
+        prompt_router: Reviewer::prompt_router(),
+    })
+    .await
+    .unwrap()
+}
+
+#[test]
+fn test_fuzzy_match_ranking() {
+    let candidates = ["JavaScript", "Java", "TypeScript", "Rust", "CoffeeScript"];
+
+    assert_eq!(fuzzy_match("java", candidates), ["Java", "JavaScript"]);
+    assert_eq!(
+        fuzzy_match("script", candidates),
+        ["JavaScript", "TypeScript", "CoffeeScript"]
+    );
+    assert_eq!(fuzzy_match("rs", candidates), ["Rust"]);
+    assert_eq!(fuzzy_match("", candidates), candidates);
+    assert!(fuzzy_match("go", candidates).is_empty());
+    // prefixes of words rank above substrings
+    assert_eq!(
+        fuzzy_match("call", ["recall", "on call", "callback"]),
+        ["callback", "on call", "recall"]
+    );
+}
+
+#[tokio::test]
+async fn test_prompt_static_values() {
+    let harness = reviewer().await;
+
+    let values = harness
+        .peer()
+        .complete_prompt_simple("review", "language", "ty")
+        .await
+        .unwrap();
+    assert_eq!(values, ["TypeScript"]);
+
+    let completion = harness
+        .peer()
+        .complete_prompt_argument("review", "language", "", None)
+        .await
+        .unwrap();
+    assert_eq!(completion.values.len(), 4);
+    assert_eq!(completion.total, Some(4));
+    assert_eq!(completion.has_more, Some(false));
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_prompt_provider_sees_resolved_arguments() {
+    let harness = reviewer().await;
+    let context = |language: &str| {
+        Some(mcpkit_rs::model::CompletionContext::with_arguments(
+            HashMap::from([("language".to_string(), language.to_string())]),
+        ))
+    };
+
+    let completion = harness
+        .peer()
+        .complete_prompt_argument("review", "framework", "a", context("Rust"))
+        .await
+        .unwrap();
+    assert_eq!(completion.values, ["axum", "actix-web"]);
+
+    let completion = harness
+        .peer()
+        .complete_prompt_argument("review", "framework", "f", context("Python"))
+        .await
+        .unwrap();
+    assert_eq!(completion.values, ["flask", "fastapi"]);
+
+    let completion = harness
+        .peer()
+        .complete_prompt_argument("review", "framework", "", None)
+        .await
+        .unwrap();
+    assert!(completion.values.is_empty());
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_unknown_prompt_and_argument() {
+    let harness = reviewer().await;
+
+    let values = harness
+        .peer()
+        .complete_prompt_simple("review", "style", "")
+        .await
+        .unwrap();
+    assert!(values.is_empty());
+
+    let error = harness
+        .peer()
+        .complete_prompt_simple("missing", "language", "")
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("not found"), "{error}");
+    harness.shutdown().await;
+}
+
+#[derive(Clone)]
+struct Repositories;
+
+impl ServerHandler for Repositories {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_resources()
+                .enable_completions()
+                .build(),
+        )
+    }
+}
+
+/// Completes `github://{owner}/{repo}` for any handler.
+fn repository_templates<S: Send + Sync + 'static>() -> ResourceTemplateRouter<S> {
+    let completions = ArgumentCompletions::new()
+        .with_values("owner", ["rust-lang", "tokio-rs"])
+        .with_provider("repo", |context| {
+            Box::pin(async move {
+                let repos: &[&str] = match context.get_argument("owner") {
+                    Some("rust-lang") => &["rust", "cargo", "rustfmt"],
+                    Some("tokio-rs") => &["tokio", "axum", "tracing"],
+                    _ => &[],
+                };
+                Ok(CompletionInfo::with_all_values(fuzzy_match(context.value(), repos)).unwrap())
+            })
+        });
+    ResourceTemplateRouter::new().with_route(
+        ResourceTemplateRoute::new(
+            RawResourceTemplate::new("github://{owner}/{repo}", "repository").no_annotation(),
+        )
+        .with_completions(completions),
+    )
+}
+
+#[tokio::test]
+async fn test_router_completes_resource_templates() {
+    let router = Router::new(Repositories).with_resource_templates(repository_templates());
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = router.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await.unwrap();
+
+    let values = client
+        .complete_resource_argument("github://{owner}/{repo}", "owner", "tok", None)
+        .await
+        .unwrap()
+        .values;
+    assert_eq!(values, ["tokio-rs"]);
+
+    let context = mcpkit_rs::model::CompletionContext::with_arguments(HashMap::from([(
+        "owner".to_string(),
+        "tokio-rs".to_string(),
+    )]));
+    let values = client
+        .complete_resource_argument("github://{owner}/{repo}", "repo", "t", Some(context))
+        .await
+        .unwrap()
+        .values;
+    assert_eq!(values, ["tokio", "tracing"]);
+
+    let templates = client.list_all_resource_templates().await.unwrap();
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].uri_template, "github://{owner}/{repo}");
+
+    // templates without a route fall through to the handler
+    let values = client
+        .complete_resource_argument("file:///{path}", "path", "", None)
+        .await
+        .unwrap()
+        .values;
+    assert!(values.is_empty());
+    client.cancel().await.unwrap();
+}
+
+/// Completes its prompts, and its resource templates through the macro.
+#[derive(Clone)]
+struct Catalog {
+    prompt_router: PromptRouter<Catalog>,
+    resource_templates: ResourceTemplateRouter<Catalog>,
+}
+
+#[prompt_handler(resource_router = self.resource_templates)]
+impl ServerHandler for Catalog {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_resources()
+                .enable_completions()
+                .build(),
+        )
+    }
+}
+
+#[tokio::test]
+async fn test_prompt_handler_resource_completions() {
+    // without a resource router, resource templates can not be completed
+    let harness = reviewer().await;
+    let error = harness
+        .peer()
+        .complete_resource_argument("github://{owner}/{repo}", "owner", "", None)
+        .await
+        .unwrap_err();
+    let mcpkit_rs::ServiceError::McpError(error) = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(error.code, ErrorCode::METHOD_NOT_FOUND);
+    harness.shutdown().await;
+
+    let harness = TestHarness::new(Catalog {
+        prompt_router: PromptRouter::new(),
+        resource_templates: repository_templates(),
+    })
+    .await
+    .unwrap();
+    let values = harness
+        .peer()
+        .complete_resource_argument("github://{owner}/{repo}", "owner", "rust", None)
+        .await
+        .unwrap()
+        .values;
+    assert_eq!(values, ["rust-lang"]);
+    harness.shutdown().await;
+}
@@ -0,0 +1,306 @@
+//cargo test --test test_elicit_derive --features "macros server client elicitation auth"
+#![cfg(all(
+    feature = "macros",
+    feature = "server",
+    feature = "client",
+    feature = "elicitation"
+))]
+
+use std::sync::{Arc, Mutex};
+
+use mcpkit_rs::{
+    ClientHandler, Elicit, ErrorData, RoleClient, ServerHandler, ServiceExt,
+    model::*,
+    service::{ElicitationError, RequestContext},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, PartialEq, Deserialize, Elicit)]
+#[serde(rename_all = "kebab-case")]
+enum Plan {
+    #[elicit(title = "Free tier")]
+    Free,
+    #[elicit(title = "Team plan")]
+    TeamPlan,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Elicit)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum Topic {
+    ReleaseNotes,
+    Security,
+}
+
+/// Create your account
+#[derive(Debug, PartialEq, Deserialize, Elicit)]
+#[serde(rename_all = "camelCase")]
+#[elicit(title = "Sign up")]
+struct SignUp {
+    /// Where to send the confirmation
+    #[elicit(format = "email", max_length = 64)]
+    email_address: String,
+    #[elicit(title = "Age", min = 13, max = 150)]
+    age: u8,
+    #[elicit(min = 0, max = 1, default = 0.5)]
+    ratio: Option<f64>,
+    #[serde(default)]
+    #[elicit(default = true)]
+    newsletter: bool,
+    #[elicit(default = "free")]
+    plan: Plan,
+    #[elicit(min_items = 1, default = ["SECURITY"])]
+    topics: Option<Vec<Topic>>,
+    r#type: Option<String>,
+    #[serde(rename = "referrer")]
+    referred_by: Option<String>,
+    #[serde(skip)]
+    internal: u32,
+}
+
+fn valid() -> Value {
+    json!({
+        "emailAddress": "a@b.io",
+        "age": 42,
+        "plan": "team-plan",
+        "topics": ["RELEASE_NOTES"],
+    })
+}
+
+#[test]
+fn test_schema() {
+    let schema = serde_json::to_value(SignUp::elicitation_schema()).unwrap();
+    assert_eq!(
+        schema,
+        json!({
+            "type": "object",
+            "title": "Sign up",
+            "description": "Create your account",
+            "properties": {
+                "emailAddress": {
+                    "type": "string",
+                    "description": "Where to send the confirmation",
+                    "format": "email",
+                    "maxLength": 64,
+                },
+                "age": { "type": "integer", "title": "Age", "minimum": 13, "maximum": 150 },
+                "ratio": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.5 },
+                "newsletter": { "type": "boolean", "default": true },
+                "plan": {
+                    "type": "string",
+                    "oneOf": [
+                        { "const": "free", "title": "Free tier" },
+                        { "const": "team-plan", "title": "Team plan" },
+                    ],
+                    "default": "free",
+                },
+                "topics": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["RELEASE_NOTES", "SECURITY"] },
+                    "minItems": 1,
+                    "default": ["SECURITY"],
+                },
+                "type": { "type": "string" },
+                "referrer": { "type": "string" },
+            },
+            "required": ["emailAddress", "age", "plan"],
+        })
+    );
+}
+
+#[test]
+fn test_from_content() {
+    let sign_up = SignUp::from_content(valid()).unwrap();
+    assert_eq!(
+        sign_up,
+        SignUp {
+            email_address: "a@b.io".to_owned(),
+            age: 42,
+            ratio: None,
+            newsletter: false,
+            plan: Plan::TeamPlan,
+            topics: Some(vec![Topic::ReleaseNotes]),
+            r#type: None,
+            referred_by: None,
+            internal: 0,
+        }
+    );
+
+    let invalid = |patch: Value| {
+        let mut content = valid();
+        content
+            .as_object_mut()
+            .unwrap()
+            .extend(patch.as_object().unwrap().clone());
+        SignUp::from_content(content).unwrap_err()
+    };
+    assert!(matches!(
+        invalid(json!({ "emailAddress": "nope" })),
+        ElicitationValidationError::InvalidValue { property, .. } if property == "emailAddress"
+    ));
+    assert!(matches!(
+        invalid(json!({ "age": 12 })),
+        ElicitationValidationError::InvalidValue { property, .. } if property == "age"
+    ));
+    assert!(matches!(
+        invalid(json!({ "plan": "enterprise" })),
+        ElicitationValidationError::InvalidValue { property, .. } if property == "plan"
+    ));
+    assert!(matches!(
+        invalid(json!({ "topics": [] })),
+        ElicitationValidationError::InvalidValue { property, .. } if property == "topics"
+    ));
+    assert_eq!(
+        SignUp::from_content(json!({ "age": 42, "plan": "free" })).unwrap_err(),
+        ElicitationValidationError::MissingProperty("emailAddress".to_owned())
+    );
+    assert_eq!(
+        SignUp::validate_content(&json!([])),
+        Err(ElicitationValidationError::NotAnObject)
+    );
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Elicit)]
+#[serde(rename_all = "lowercase")]
+enum Shade {
+    DarkBlue,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Elicit)]
+#[serde(rename_all = "UPPERCASE")]
+enum Size {
+    ExtraLarge,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Elicit)]
+#[serde(rename_all = "lowercase")]
+struct Lowercase {
+    first_name: String,
+    shade: Shade,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Elicit)]
+#[serde(rename_all = "UPPERCASE")]
+struct Uppercase {
+    first_name: String,
+    size: Size,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Elicit)]
+#[serde(rename_all = "PascalCase")]
+struct Pascal {
+    first_name: String,
+    plan_b: Option<bool>,
+}
+
+/// Serialize `value` and read it back through the derived schema, checking
+/// that serde and the schema agree on every name.
+fn round_trip<T>(value: T)
+where
+    T: Elicit + Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+{
+    let content = serde_json::to_value(&value).unwrap();
+    let schema = T::elicitation_schema();
+    let mut names: Vec<_> = content.as_object().unwrap().keys().cloned().collect();
+    names.sort();
+    let properties: Vec<_> = schema.properties.keys().cloned().collect();
+    assert_eq!(names, properties);
+    assert_eq!(T::from_content(content).unwrap(), value);
+}
+
+#[test]
+fn test_serde_round_trip() {
+    round_trip(Lowercase {
+        first_name: "Ada".to_owned(),
+        shade: Shade::DarkBlue,
+    });
+    round_trip(Uppercase {
+        first_name: "Ada".to_owned(),
+        size: Size::ExtraLarge,
+    });
+    round_trip(Pascal {
+        first_name: "Ada".to_owned(),
+        plan_b: Some(true),
+    });
+    assert_eq!(
+        Uppercase::elicitation_schema()
+            .properties
+            .keys()
+            .collect::<Vec<_>>(),
+        ["FIRST_NAME", "SIZE"]
+    );
+}
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+/// Answers every form with `content`, recording the requested schemas.
+#[derive(Clone)]
+struct Client {
+    content: Value,
+    schemas: Arc<Mutex<Vec<ElicitationSchema>>>,
+}
+
+impl ClientHandler for Client {
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, ErrorData> {
+        let CreateElicitationRequestParams::FormElicitationParams {
+            requested_schema, ..
+        } = request
+        else {
+            return Err(ErrorData::invalid_params("expected a form", None));
+        };
+        self.schemas.lock().unwrap().push(requested_schema);
+        Ok(CreateElicitationResult {
+            action: ElicitationAction::Accept,
+            content: Some(self.content.clone()),
+        })
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = ClientInfo::default();
+        info.capabilities.elicitation = Some(ElicitationCapability::default());
+        info
+    }
+}
+
+async fn elicit(content: Value) -> anyhow::Result<(Result<SignUp, ElicitationError>, Client)> {
+    let client = Client {
+        content,
+        schemas: Default::default(),
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let running = client.clone().serve(client_transport).await?;
+    let server = server.await??;
+    let result = server.peer().elicit_form::<SignUp>("Welcome").await;
+    running.cancel().await?;
+    server.cancel().await?;
+    Ok((result, client))
+}
+
+#[tokio::test]
+async fn test_elicit_form() -> anyhow::Result<()> {
+    let (result, client) = elicit(valid()).await?;
+    assert_eq!(result?.plan, Plan::TeamPlan);
+    assert_eq!(
+        client.schemas.lock().unwrap().as_slice(),
+        [SignUp::elicitation_schema()]
+    );
+
+    let (result, _) = elicit(json!({ "emailAddress": "a@b.io" })).await?;
+    let Err(ElicitationError::InvalidContent { error, data }) = result else {
+        panic!("unexpected result: {result:?}");
+    };
+    assert_eq!(
+        error,
+        ElicitationValidationError::MissingProperty("age".to_owned())
+    );
+    assert_eq!(data, json!({ "emailAddress": "a@b.io" }));
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//cargo test --test test_harness --features "testing elicitation auth wasm-tools"
+#![cfg(feature = "testing")]
+
+use std::time::Duration;
+
+use mcpkit_rs::{
+    ErrorData, RoleServer, ServerHandler, model::*, service::RequestContext, testing::TestHarness,
+};
+use serde_json::json;
+use tokio::time::Instant;
+
+/// Tools that exercise every part of the harness.
+struct Assistant {
+    started: Instant,
+}
+
+impl ServerHandler for Assistant {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_logging()
+                .build(),
+        )
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let arguments = request.arguments.unwrap_or_default();
+        let text = match request.name.as_ref() {
+            "echo" => {
+                let text = arguments
+                    .get("text")
+                    .and_then(|text| text.as_str())
+                    .unwrap_or_default()
+                    .to_owned();
+                // requests are handled inline, so notify from a separate task
+                let message = format!("echoing {text}");
+                tokio::spawn(async move {
+                    let _ = context
+                        .peer
+                        .notify_logging_message(LoggingMessageNotificationParam::new(
+                            LoggingLevel::Info,
+                            json!(message),
+                        ))
+                        .await;
+                });
+                text
+            }
+            "uptime" => self.started.elapsed().as_secs().to_string(),
+            _ => return Err(ErrorData::invalid_params("unknown tool", None)),
+        };
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+}
+
+async fn harness() -> TestHarness<Assistant> {
+    TestHarness::new(Assistant {
+        started: Instant::now(),
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_call_tool_and_capture_notifications() {
+    let harness = harness().await;
+    harness
+        .assert_tool_text("echo", json!({ "text": "hello" }), "hello")
+        .await;
+
+    let notification = harness
+        .wait_for_notification(|notification| {
+            matches!(
+                notification,
+                ServerNotification::LoggingMessageNotification(_)
+            )
+        })
+        .await
+        .expect("logging notification");
+    let ServerNotification::LoggingMessageNotification(notification) = notification else {
+        unreachable!()
+    };
+    assert_eq!(notification.params.data, json!("echoing hello"));
+    assert_eq!(harness.notifications().len(), 1);
+
+    let error = harness.call_tool("missing", json!(null)).await.unwrap_err();
+    assert!(error.to_string().contains("unknown tool"), "{error}");
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_scripted_sampling() {
+    let harness = harness().await;
+    harness.script_sampling(Ok(CreateMessageResult::new(
+        SamplingMessage::assistant_text("Paris"),
+        "scripted".to_owned(),
+    )));
+    let ask = |question: &str| {
+        harness
+            .server_peer()
+            .create_message(CreateMessageRequestParams::new(
+                vec![SamplingMessage::user_text(question)],
+                100,
+            ))
+    };
+
+    let result = ask("What is the capital of France?").await.unwrap();
+    assert_eq!(
+        result
+            .message
+            .content
+            .first()
+            .unwrap()
+            .as_text()
+            .unwrap()
+            .text,
+        "Paris"
+    );
+    let requests = harness.sampling_requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].messages[0]
+            .content
+            .first()
+            .unwrap()
+            .as_text()
+            .unwrap()
+            .text,
+        "What is the capital of France?"
+    );
+
+    // nothing scripted for the second request
+    assert!(ask("And Spain?").await.is_err());
+    harness.shutdown().await;
+}
+
+#[cfg(feature = "elicitation")]
+#[tokio::test]
+async fn test_scripted_elicitation() {
+    let harness = harness().await;
+    harness.script_elicitation(Ok(CreateElicitationResult {
+        action: ElicitationAction::Accept,
+        content: Some(json!({})),
+    }));
+    let result = harness
+        .server_peer()
+        .create_elicitation(CreateElicitationRequestParams::FormElicitationParams {
+            meta: None,
+            message: "Proceed?".to_owned(),
+            requested_schema: ElicitationSchema::builder().build().unwrap(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.action, ElicitationAction::Accept);
+    assert_eq!(harness.elicitation_requests().len(), 1);
+    harness.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_advance_time() {
+    let harness = harness().await;
+    harness.advance(Duration::from_secs(3600)).await;
+    let result = harness.call_tool("uptime", json!({})).await.unwrap();
+    let uptime: u64 = result.content[0].as_text().unwrap().text.parse().unwrap();
+    assert!(uptime >= 3600, "{uptime}");
+    harness.shutdown().await;
+}
+
+#[cfg(feature = "wasm-tools")]
+#[tokio::test]
+async fn test_wasm_tools_from_directory() {
+    // prints a fixed JSON object to stdout
+    const GREETER: &str = r#"
+        (module
+          (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 16) "{\"greeting\":\"hello\"}")
+          (func (export "_start")
+            (i32.store (i32.const 0) (i32.const 16))
+            (i32.store (i32.const 4) (i32.const 20))
+            (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))))
+    "#;
+
+    let dir = tempfile::tempdir().unwrap();
+    let tool_dir = dir.path().join("greeter");
+    std::fs::create_dir(&tool_dir).unwrap();
+    std::fs::write(
+        tool_dir.join("greeter.wasm"),
+        wat::parse_str(GREETER).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(
+        tool_dir.join("manifest.json"),
+        json!({
+            "name": "greeter",
+            "version": "1.0.0",
+            "description": "Say hello",
+            "wasm_module": "greeter.wasm",
+            "input_schema": { "type": "object" },
+            "timeout_seconds": 5,
+            "credentials": [],
+            "env_vars": []
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let harness = TestHarness::with_wasm_tools(dir.path()).await.unwrap();
+    let tools = harness.list_all_tools().await.unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "greeter");
+    harness
+        .assert_tool_text("greeter", json!({}), r#"{"greeting":"hello"}"#)
+        .await;
+    harness.shutdown().await;
+}
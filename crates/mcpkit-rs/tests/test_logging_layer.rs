@@ -0,0 +1,208 @@
+//cargo test --test test_logging_layer --features "logging-layer testing"
+#![cfg(all(feature = "logging-layer", feature = "testing"))]
+
+use std::time::Duration;
+
+use mcpkit_rs::{
+    ErrorData, RoleServer, ServerHandler,
+    handler::server::logging::{LoggingLevelHandle, McpLoggingLayer},
+    model::*,
+    service::RequestContext,
+    testing::TestHarness,
+};
+use serde_json::json;
+use tracing_subscriber::layer::SubscriberExt;
+
+struct Logger {
+    levels: LoggingLevelHandle,
+}
+
+impl ServerHandler for Logger {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(ServerCapabilities::builder().enable_logging().build())
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.levels.set(request.level);
+        Ok(())
+    }
+}
+
+async fn harness() -> TestHarness<Logger> {
+    TestHarness::new(Logger {
+        levels: LoggingLevelHandle::default(),
+    })
+    .await
+    .unwrap()
+}
+
+fn messages(harness: &TestHarness<Logger>) -> Vec<LoggingMessageNotificationParam> {
+    harness
+        .notifications()
+        .into_iter()
+        .filter_map(|notification| match notification {
+            ServerNotification::LoggingMessageNotification(notification) => {
+                Some(notification.params)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Wait for the logging message saying `text`, returning every logging
+/// message received up to then.
+async fn wait_for_message(
+    harness: &TestHarness<Logger>,
+    text: &str,
+) -> Vec<LoggingMessageNotificationParam> {
+    // the predicate runs under the harness's lock, so it must not call back
+    // into the harness
+    let found = harness
+        .wait_for_notification(|notification| {
+            matches!(
+                notification,
+                ServerNotification::LoggingMessageNotification(notification)
+                    if notification.params.data["message"] == text
+            )
+        })
+        .await;
+    assert!(
+        found.is_some(),
+        "{text:?} was not logged, got {:?}",
+        messages(harness)
+    );
+    messages(harness)
+}
+
+#[tokio::test]
+async fn test_levels_and_structured_data() {
+    let harness = harness().await;
+    let layer = McpLoggingLayer::new(harness.server_peer().clone())
+        .with_level_handle(harness.handler().levels.clone());
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+
+    harness
+        .set_level(SetLevelRequestParams::new(LoggingLevel::Warning))
+        .await
+        .unwrap();
+    let span = tracing::info_span!(target: "app", "fetch", url = "https://example.com");
+    span.in_scope(|| {
+        tracing::info!(target: "app", "not forwarded below the client's level");
+        tracing::warn!(target: "app", status = 503, "fetch failed");
+        tracing::error!(target: "app", mcp_level = "critical", "database unreachable");
+    });
+    tracing::error!(target: "mcpkit_rs::transport", "not forwarded from the transport");
+
+    let messages = wait_for_message(&harness, "database unreachable").await;
+    assert_eq!(messages.len(), 2, "{messages:?}");
+    assert_eq!(messages[0].level, LoggingLevel::Warning);
+    assert_eq!(messages[0].logger.as_deref(), Some("app"));
+    assert_eq!(
+        messages[0].data,
+        json!({
+            "message": "fetch failed",
+            "fields": { "status": 503 },
+            "spans": [{ "name": "fetch", "fields": { "url": "https://example.com" } }],
+        })
+    );
+    assert_eq!(messages[1].level, LoggingLevel::Critical);
+    assert_eq!(messages[1].data["message"], "database unreachable");
+    assert!(messages[1].data.get("fields").is_none());
+
+    harness
+        .set_level(SetLevelRequestParams::new(LoggingLevel::Debug))
+        .await
+        .unwrap();
+    tracing::trace!(target: "app", "trace maps onto debug");
+    let messages = wait_for_message(&harness, "trace maps onto debug").await;
+    assert_eq!(messages[2].level, LoggingLevel::Debug);
+    harness.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limit_reports_dropped_messages() {
+    let harness = harness().await;
+    let layer = McpLoggingLayer::new(harness.server_peer().clone()).with_rate_limit(1, 2);
+    let levels = layer.level_handle();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+
+    assert_eq!(levels.get(), LoggingLevel::Info);
+    tracing::info!(target: "app", "first");
+    tracing::info!(target: "app", "second");
+    for i in 0..3 {
+        tracing::info!(target: "app", i, "over the limit");
+    }
+    let messages = wait_for_message(&harness, "second").await;
+    assert_eq!(messages.len(), 2);
+
+    harness.advance(Duration::from_secs(1)).await;
+    tracing::info!(target: "app", "after the burst");
+    let messages = wait_for_message(&harness, "after the burst").await;
+    assert_eq!(messages[2].level, LoggingLevel::Warning);
+    assert_eq!(messages[2].data["message"], "dropped 3 log messages");
+    assert_eq!(messages[3].data["message"], "after the burst");
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_notifications_do_not_log_themselves() {
+    let harness = harness().await;
+    let layer = McpLoggingLayer::new(harness.server_peer().clone())
+        .with_level_handle(harness.handler().levels.clone())
+        .without_ignored_targets();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+
+    // the service traces every message it sends, at the lowest level
+    harness
+        .set_level(SetLevelRequestParams::new(LoggingLevel::Debug))
+        .await
+        .unwrap();
+    tracing::info!(target: "app", "first");
+    wait_for_message(&harness, "first").await;
+    // anything the first notification caused is queued before this one
+    tracing::info!(target: "app", "second");
+    let messages = wait_for_message(&harness, "second").await;
+    let loggers: Vec<_> = messages
+        .iter()
+        .map(|message| message.logger.as_deref())
+        .collect();
+    assert_eq!(loggers, [Some("app"), Some("app")]);
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_sessions_only_see_their_own_events() {
+    let first = harness().await;
+    let second = harness().await;
+    let subscriber = tracing_subscriber::registry()
+        .with(McpLoggingLayer::new(first.server_peer().clone()).with_session("first"))
+        .with(McpLoggingLayer::new(second.server_peer().clone()).with_session("second"));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    tracing::info!(target: "app", "outside any session");
+    tracing::info_span!("session", mcp_session = "first").in_scope(|| {
+        tracing::info!(target: "app", "for the first session");
+    });
+    tracing::info_span!("session", mcp_session = "second").in_scope(|| {
+        // the innermost session decides
+        tracing::info_span!("nested", mcp_session = "first").in_scope(|| {
+            tracing::info!(target: "app", "also for the first session");
+        });
+        tracing::info!(target: "app", "for the second session");
+    });
+
+    let messages = wait_for_message(&first, "also for the first session").await;
+    let texts: Vec<_> = messages.iter().map(|m| m.data["message"].clone()).collect();
+    assert_eq!(
+        texts,
+        ["for the first session", "also for the first session"]
+    );
+    let messages = wait_for_message(&second, "for the second session").await;
+    assert_eq!(messages.len(), 1, "{messages:?}");
+    first.shutdown().await;
+    second.shutdown().await;
+}
@@ -312,6 +312,7 @@ async fn test_metering_display_formats() {
         units_per_second: 12_345_670,
         peak_rate: Some(15_000_000),
         instruction_count: Some(1_000_000),
+        memory_bytes: None,
     };
 
     let minimal = metrics.display(DisplayFormat::Minimal);
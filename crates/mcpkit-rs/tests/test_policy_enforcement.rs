@@ -389,3 +389,163 @@ core: {}
     // Policy enforcement is transport-agnostic
     assert!(server1.has_policy());
 }
+
+#[cfg(feature = "policy")]
+#[tokio::test]
+async fn test_policy_decisions_are_audited() {
+    use mcpkit_rs::{
+        PolicyEnabledServer,
+        audit::{AuditLog, AuditOutcome, AuditRecord, AuditSink, PolicyDecision},
+    };
+    use mcpkit_rs_policy::Policy;
+
+    #[derive(Default)]
+    struct Records(std::sync::Mutex<Vec<AuditRecord>>);
+
+    impl AuditSink for Records {
+        fn record(&self, record: &AuditRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    let policy_yaml = r#"
+version: "1.0"
+extensions:
+  mcp:
+    tools:
+      allow:
+        - name: "read_file"
+      deny: []
+"#;
+    let policy = Policy::from_yaml(policy_yaml).unwrap();
+    let records = Arc::new(Records::default());
+    let audit = AuditLog::new()
+        .with_sink(records.clone())
+        .with_redacted_fields(["path"]);
+    let policy_server = PolicyEnabledServer::with_policy(TestToolServer::new(), policy)
+        .unwrap()
+        .with_audit(audit);
+
+    let (server_transport, client_transport) = tokio::io::duplex(65536);
+
+    let server_handle = tokio::spawn(async move {
+        let service = policy_server.serve(server_transport).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+
+    let client_handle = tokio::spawn(async move {
+        let mut client = TestClientHandler::new(true, true)
+            .serve(client_transport)
+            .await?;
+
+        client
+            .peer()
+            .call_tool(
+                CallToolRequestParams::new("read_file").with_arguments(
+                    serde_json::json!({"path": "/secret"})
+                        .as_object()
+                        .unwrap()
+                        .clone(),
+                ),
+            )
+            .await?;
+        client
+            .peer()
+            .call_tool(CallToolRequestParams::new("dangerous_tool"))
+            .await
+            .unwrap_err();
+
+        client.close().await?;
+        anyhow::Ok(())
+    });
+
+    let (server_result, client_result) = tokio::join!(server_handle, client_handle);
+    server_result.unwrap().unwrap();
+    client_result.unwrap().unwrap();
+
+    let records = records.0.lock().unwrap();
+    assert_eq!(records.len(), 2);
+
+    assert_eq!(records[0].method, "tools/call");
+    assert_eq!(records[0].target.as_deref(), Some("read_file"));
+    assert_eq!(records[0].decision, PolicyDecision::Allowed);
+    assert_eq!(records[0].outcome, AuditOutcome::Success);
+    assert!(records[0].arguments_digest.is_some());
+    assert!(records[0].client.is_some());
+
+    assert_eq!(records[1].target.as_deref(), Some("dangerous_tool"));
+    assert_eq!(records[1].decision, PolicyDecision::Denied);
+    assert!(matches!(
+        records[1].outcome,
+        AuditOutcome::Error { code: -32602, .. }
+    ));
+}
+
+#[cfg(feature = "policy")]
+#[tokio::test]
+async fn test_runtime_violations_are_audited() {
+    use mcpkit_rs::{
+        PolicyEnabledServer,
+        audit::{AuditLog, AuditRecord, AuditSink, PolicyDecision},
+    };
+    use mcpkit_rs_policy::{
+        CompiledPolicy, Policy,
+        core::{Metrics, PolicyState, Violation},
+    };
+
+    #[derive(Default)]
+    struct Records(std::sync::Mutex<Vec<AuditRecord>>);
+
+    impl AuditSink for Records {
+        fn record(&self, record: &AuditRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    let policy = Policy::from_yaml("version: \"1.0\"\ncore: {}\n").unwrap();
+    let state = Arc::new(PolicyState {
+        policy: Arc::new(CompiledPolicy::compile(&policy).unwrap()),
+        violations: Default::default(),
+        metrics: Arc::new(Metrics::default()),
+    });
+    state
+        .record_violation(Violation::FileDenied {
+            path: "/etc/passwd".into(),
+            operation: "read".into(),
+            timestamp: 1_700_000_000,
+        })
+        .await;
+
+    let records = Arc::new(Records::default());
+    let policy_server = PolicyEnabledServer::new(TestToolServer::new())
+        .with_audit(AuditLog::new().with_sink(records.clone()))
+        .with_policy_state(state.clone());
+
+    let (server_transport, client_transport) = tokio::io::duplex(65536);
+    let server_handle = tokio::spawn(async move {
+        let service = policy_server.serve(server_transport).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = TestClientHandler::new(true, true)
+        .serve(client_transport)
+        .await
+        .unwrap();
+    client
+        .peer()
+        .call_tool(CallToolRequestParams::new("read_file"))
+        .await
+        .unwrap();
+    client.cancel().await.unwrap();
+    server_handle.await.unwrap().unwrap();
+
+    assert!(state.violations.lock().await.is_empty());
+    let records = records.0.lock().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].target.as_deref(), Some("read_file"));
+    assert_eq!(records[1].method, "storage/read");
+    assert_eq!(records[1].target.as_deref(), Some("/etc/passwd"));
+    assert_eq!(records[1].decision, PolicyDecision::Denied);
+    assert_eq!(records[1].timestamp.timestamp(), 1_700_000_000);
+}
@@ -0,0 +1,184 @@
+//cargo test --test test_proxy --features "client server transport-streamable-http-server transport-streamable-http-client-reqwest"
+#![cfg(all(
+    feature = "client",
+    feature = "server",
+    feature = "transport-streamable-http-server",
+    feature = "transport-streamable-http-client-reqwest"
+))]
+
+use std::sync::{Arc, Mutex};
+
+use mcpkit_rs::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{NotificationContext, Proxy, ProxyQuitReason, RequestContext},
+    transport::{
+        StreamableHttpClientTransport,
+        streamable_http_server::{StreamableHttpService, session::local::LocalSessionManager},
+    },
+};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// The upstream server; records who connected to it.
+#[derive(Clone, Default)]
+struct Upstream {
+    clients: Arc<Mutex<Vec<String>>>,
+}
+
+impl ServerHandler for Upstream {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .build(),
+        )
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(vec![Tool::new(
+            "upstream_tool",
+            "a tool behind the proxy",
+            JsonObject::new(),
+        )]))
+    }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        if let Some(info) = context.peer.peer_info() {
+            self.clients
+                .lock()
+                .unwrap()
+                .push(info.client_info.name.clone());
+        }
+    }
+}
+
+/// The host; answers roots requests and waits for list_changed.
+#[derive(Clone)]
+struct Host {
+    tools_changed: Arc<Notify>,
+}
+
+impl ClientHandler for Host {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo::new(
+            ClientCapabilities::builder().enable_roots().build(),
+            Implementation::new("proxy-host", "1.0.0"),
+        )
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        Ok(ListRootsResult::new(vec![
+            Root::new("file:///workspace").with_name("workspace"),
+        ]))
+    }
+
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        self.tools_changed.notify_one();
+    }
+}
+
+#[tokio::test]
+async fn test_proxy_relays_both_directions() -> anyhow::Result<()> {
+    let (host_side, proxy_downstream) = tokio::io::duplex(4096);
+    let (proxy_upstream, server_side) = tokio::io::duplex(4096);
+    let upstream = Upstream::default();
+    let ct = CancellationToken::new();
+    let proxy = tokio::spawn(
+        Proxy::new(proxy_upstream)
+            .with_cancellation_token(ct.clone())
+            .serve(proxy_downstream),
+    );
+    let server = upstream.clone().serve(server_side);
+    let host = Host {
+        tools_changed: Arc::new(Notify::new()),
+    };
+    let client = host.clone().serve(host_side);
+    let (server, client) =
+        tokio::try_join!(async { server.await.map_err(anyhow::Error::from) }, async {
+            client.await.map_err(anyhow::Error::from)
+        })?;
+
+    // the upstream server was initialized by the host itself
+    assert_eq!(
+        client.peer_info().map(|i| i.capabilities.tools.is_some()),
+        Some(true)
+    );
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools[0].name, "upstream_tool");
+    assert_eq!(*upstream.clients.lock().unwrap(), ["proxy-host"]);
+
+    // server -> client requests and notifications reach the host
+    let roots = server.peer().list_roots().await?;
+    assert_eq!(roots.roots[0].uri, "file:///workspace");
+    server.peer().notify_tool_list_changed().await?;
+    tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        host.tools_changed.notified(),
+    )
+    .await?;
+
+    client.cancel().await?;
+    assert_eq!(proxy.await??, ProxyQuitReason::DownstreamClosed);
+    // closing the downstream closes the upstream too
+    server.waiting().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proxy_served_over_streamable_http() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let upstream = Upstream::default();
+    let service = StreamableHttpService::new(
+        {
+            let upstream = upstream.clone();
+            move || {
+                let (proxy_upstream, server_side) = tokio::io::duplex(4096);
+                let server = upstream.clone();
+                tokio::spawn(async move {
+                    let service = server.serve(server_side).await?;
+                    service.waiting().await?;
+                    anyhow::Ok(())
+                });
+                Ok(Proxy::new(proxy_upstream))
+            }
+        },
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+    let router = axum::Router::new().nest_service("/mcp", service);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn({
+        let ct = ct.clone();
+        async move {
+            let _ = axum::serve(listener, router)
+                .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+                .await;
+        }
+    });
+
+    let host = Host {
+        tools_changed: Arc::new(Notify::new()),
+    };
+    let client = host
+        .serve(StreamableHttpClientTransport::from_uri(format!(
+            "http://{addr}/mcp"
+        )))
+        .await?;
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools[0].name, "upstream_tool");
+    assert_eq!(*upstream.clients.lock().unwrap(), ["proxy-host"]);
+
+    client.cancel().await?;
+    ct.cancel();
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//cargo test --test test_reconnect --features "client server"
+#![cfg(all(feature = "client", feature = "server"))]
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use mcpkit_rs::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{ReconnectConfig, ReconnectEvent, ReconnectingClient, RequestContext},
+};
+use tokio::{io::DuplexStream, sync::broadcast};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Clone, Default)]
+struct Subscribable {
+    subscriptions: Arc<Mutex<Vec<String>>>,
+}
+
+impl ServerHandler for Subscribable {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
+        )
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(vec![Tool::new(
+            "echo",
+            "echo",
+            JsonObject::new(),
+        )]))
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        if request.uri.starts_with("memo://forbidden") {
+            return Err(ErrorData::invalid_params("not subscribable", None));
+        }
+        self.subscriptions.lock().unwrap().push(request.uri);
+        Ok(())
+    }
+}
+
+/// Hands out a fresh in-memory server per connection and lets the test kill
+/// the current one.
+#[derive(Clone, Default)]
+struct Servers {
+    server: Subscribable,
+    current: Arc<Mutex<Option<CancellationToken>>>,
+    connections: Arc<Mutex<usize>>,
+    refuse: Arc<Mutex<bool>>,
+}
+
+impl Servers {
+    async fn connect(self) -> io::Result<DuplexStream> {
+        if *self.refuse.lock().unwrap() {
+            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "down"));
+        }
+        *self.connections.lock().unwrap() += 1;
+        let (server_transport, client_transport) = tokio::io::duplex(4096);
+        let ct = CancellationToken::new();
+        *self.current.lock().unwrap() = Some(ct.clone());
+        let server = self.server.clone();
+        tokio::spawn(async move {
+            let service = server.serve_with_ct(server_transport, ct).await?;
+            service.waiting().await?;
+            anyhow::Ok(())
+        });
+        Ok(client_transport)
+    }
+
+    fn kill(&self) {
+        self.current.lock().unwrap().take().unwrap().cancel();
+    }
+}
+
+fn fast_config() -> ReconnectConfig {
+    ReconnectConfig::default()
+        .with_initial_delay(Duration::from_millis(10))
+        .with_max_delay(Duration::from_millis(50))
+}
+
+async fn next_matching(
+    events: &mut broadcast::Receiver<ReconnectEvent>,
+    mut matches: impl FnMut(&ReconnectEvent) -> bool,
+) -> anyhow::Result<ReconnectEvent> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let event = events.recv().await?;
+            if matches(&event) {
+                return anyhow::Ok(event);
+            }
+        }
+    })
+    .await?
+}
+
+#[tokio::test]
+async fn test_reconnect_restores_subscriptions() -> anyhow::Result<()> {
+    let servers = Servers::default();
+    let factory = servers.clone();
+    let client = ReconnectingClient::connect(ClientInfo::default(), fast_config(), move || {
+        factory.clone().connect()
+    })
+    .await?;
+    let mut events = client.events();
+    client.subscribe("memo://notes").await?;
+
+    servers.kill();
+    next_matching(&mut events, |e| {
+        matches!(e, ReconnectEvent::Disconnected { generation: 0, .. })
+    })
+    .await?;
+    next_matching(&mut events, |e| {
+        matches!(e, ReconnectEvent::Reconnected { generation: 1, .. })
+    })
+    .await?;
+
+    assert_eq!(*servers.connections.lock().unwrap(), 2);
+    assert_eq!(
+        *servers.server.subscriptions.lock().unwrap(),
+        ["memo://notes", "memo://notes"]
+    );
+    assert_eq!(client.list_all_tools().await?.len(), 1);
+    client.close().await;
+    assert!(!client.is_connected());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_subscription_is_not_restored() -> anyhow::Result<()> {
+    let servers = Servers::default();
+    let factory = servers.clone();
+    let client = ReconnectingClient::connect(ClientInfo::default(), fast_config(), move || {
+        factory.clone().connect()
+    })
+    .await?;
+    let mut events = client.events();
+    assert!(client.subscribe("memo://forbidden").await.is_err());
+    client.subscribe("memo://notes").await?;
+
+    servers.kill();
+    next_matching(&mut events, |e| {
+        matches!(e, ReconnectEvent::Reconnected { generation: 1, .. })
+    })
+    .await?;
+
+    assert_eq!(
+        *servers.server.subscriptions.lock().unwrap(),
+        ["memo://notes", "memo://notes"]
+    );
+    client.close().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_idempotent_request_survives_disconnect() -> anyhow::Result<()> {
+    let servers = Servers::default();
+    let factory = servers.clone();
+    let client = ReconnectingClient::connect(ClientInfo::default(), fast_config(), move || {
+        factory.clone().connect()
+    })
+    .await?;
+
+    servers.kill();
+    // issued while the old connection is going away; answered by the new one
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools.len(), 1);
+    assert!(*servers.connections.lock().unwrap() >= 2);
+    client.close().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() -> anyhow::Result<()> {
+    let servers = Servers::default();
+    let factory = servers.clone();
+    let client = ReconnectingClient::connect(
+        ClientInfo::default(),
+        fast_config().with_max_attempts(2),
+        move || factory.clone().connect(),
+    )
+    .await?;
+    let mut events = client.events();
+
+    *servers.refuse.lock().unwrap() = true;
+    servers.kill();
+    let failures = Arc::new(Mutex::new(0));
+    let counted = failures.clone();
+    let event = next_matching(&mut events, move |e| {
+        if matches!(e, ReconnectEvent::ReconnectFailed { .. }) {
+            *counted.lock().unwrap() += 1;
+        }
+        matches!(e, ReconnectEvent::GaveUp { .. })
+    })
+    .await?;
+    assert!(matches!(event, ReconnectEvent::GaveUp { attempts: 2 }));
+    assert_eq!(*failures.lock().unwrap(), 2);
+    assert!(client.peer().is_none());
+    assert!(client.list_all_tools().await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reconnect_request_is_not_lost() -> anyhow::Result<()> {
+    let servers = Servers::default();
+    let factory = servers.clone();
+    let client = ReconnectingClient::connect(ClientInfo::default(), fast_config(), move || {
+        factory.clone().connect()
+    })
+    .await?;
+    let mut events = client.events();
+
+    // made before the supervisor task first waits for a request
+    client.reconnect();
+    next_matching(&mut events, |e| {
+        matches!(e, ReconnectEvent::Reconnected { generation: 1, .. })
+    })
+    .await?;
+    assert_eq!(*servers.connections.lock().unwrap(), 2);
+    client.close().await;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//cargo test --test test_response_cache --features "client server"
+#![cfg(all(feature = "client", feature = "server"))]
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use mcpkit_rs::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    handler::client::cache::{CachedPeer, CachingHandler, ResponseCache},
+    model::*,
+    service::RequestContext,
+};
+
+/// Counts how often each request reaches the server.
+#[derive(Clone, Default)]
+struct Counting {
+    list_tools: Arc<AtomicUsize>,
+    read_resource: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for Counting {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_resources()
+                .build(),
+        )
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let n = self.list_tools.fetch_add(1, Ordering::SeqCst);
+        Ok(ListToolsResult::with_all_items(vec![Tool::new(
+            format!("tool_{n}"),
+            "test tool",
+            JsonObject::new(),
+        )]))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let n = self.read_resource.fetch_add(1, Ordering::SeqCst);
+        Ok(ReadResourceResult::new(vec![ResourceContents::text(
+            format!("version {n}"),
+            request.uri,
+        )]))
+    }
+}
+
+fn text_of(result: &ReadResourceResult) -> &str {
+    match &result.contents[0] {
+        ResourceContents::TextResourceContents { text, .. } => text,
+        _ => panic!("expected text contents"),
+    }
+}
+
+#[tokio::test]
+async fn test_cache_invalidated_by_notifications() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let counting = Counting::default();
+    let server = counting.clone().serve(server_transport);
+    let cache = ResponseCache::new();
+    let client = CachingHandler::new(ClientInfo::default(), cache.clone()).serve(client_transport);
+    let (server, client) =
+        tokio::try_join!(async { server.await.map_err(anyhow::Error::from) }, async {
+            client.await.map_err(anyhow::Error::from)
+        })?;
+    let peer = CachedPeer::new(client.peer().clone(), cache.clone());
+
+    assert_eq!(peer.list_all_tools().await?[0].name, "tool_0");
+    assert_eq!(peer.list_all_tools().await?[0].name, "tool_0");
+    assert_eq!(counting.list_tools.load(Ordering::SeqCst), 1);
+
+    server.peer().notify_tool_list_changed().await?;
+    // the notification is handled before the response to a later request
+    client
+        .peer()
+        .send_request(ClientRequest::PingRequest(Default::default()))
+        .await?;
+    assert_eq!(peer.list_all_tools().await?[0].name, "tool_1");
+    assert_eq!(counting.list_tools.load(Ordering::SeqCst), 2);
+
+    let read = || peer.read_resource(ReadResourceRequestParams::new("memo://a"));
+    assert_eq!(text_of(&read().await?), "version 0");
+    assert_eq!(text_of(&read().await?), "version 0");
+    server
+        .peer()
+        .notify_resource_updated(ResourceUpdatedNotificationParam::new("memo://a"))
+        .await?;
+    client
+        .peer()
+        .send_request(ClientRequest::PingRequest(Default::default()))
+        .await?;
+    assert_eq!(text_of(&read().await?), "version 1");
+
+    cache.invalidate_tools();
+    assert_eq!(peer.list_all_tools().await?[0].name, "tool_2");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cache_entries_expire_after_ttl() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let counting = Counting::default();
+    let server = counting.clone().serve(server_transport);
+    let cache = ResponseCache::new().with_ttl(Duration::from_millis(50));
+    let client = CachingHandler::new(ClientInfo::default(), cache.clone()).serve(client_transport);
+    let (server, client) =
+        tokio::try_join!(async { server.await.map_err(anyhow::Error::from) }, async {
+            client.await.map_err(anyhow::Error::from)
+        })?;
+    let peer = CachedPeer::new(client.peer().clone(), cache);
+
+    peer.list_all_tools().await?;
+    peer.list_all_tools().await?;
+    assert_eq!(counting.list_tools.load(Ordering::SeqCst), 1);
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    peer.list_all_tools().await?;
+    assert_eq!(counting.list_tools.load(Ordering::SeqCst), 2);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
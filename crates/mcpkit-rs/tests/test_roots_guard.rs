@@ -0,0 +1,234 @@
+//cargo test --test test_roots_guard --features "server client"
+#![cfg(all(feature = "server", feature = "client"))]
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use mcpkit_rs::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::server::roots::{RootsError, RootsGuard},
+    model::*,
+    service::{NotificationContext, Peer, RequestContext, RunningService},
+};
+use tempfile::TempDir;
+
+/// Prefetches the roots and keeps them up to date.
+#[derive(Clone, Default)]
+struct Server {
+    roots: RootsGuard,
+}
+
+impl ServerHandler for Server {
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        self.roots.refresh_in_background(context.peer);
+    }
+
+    async fn on_roots_list_changed(&self, context: NotificationContext<RoleServer>) {
+        self.roots.on_roots_list_changed(context.peer);
+    }
+}
+
+/// Shares the directories in `roots` as `file://` roots.
+#[derive(Clone, Default)]
+struct Client {
+    roots: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl ClientHandler for Client {
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        let roots = self
+            .roots
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|path| Root::new(format!("file://{}", path.display())))
+            .chain([Root::new("https://example.com/not-a-directory")])
+            .collect();
+        Ok(ListRootsResult::new(roots))
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = ClientInfo::default();
+        info.capabilities = ClientCapabilities::builder()
+            .enable_roots()
+            .enable_roots_list_changed()
+            .build();
+        info
+    }
+}
+
+async fn connect(
+    client: Client,
+) -> anyhow::Result<(Server, Peer<RoleServer>, RunningService<RoleClient, Client>)> {
+    let server = Server::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let running = tokio::spawn(server.clone().serve(server_transport));
+    let client = client.serve(client_transport).await?;
+    let running = running.await??;
+    let peer = running.peer().clone();
+    tokio::spawn(running.waiting());
+    Ok((server, peer, client))
+}
+
+/// Wait until the cached roots are exactly `expected`.
+async fn roots_become(guard: &RootsGuard, expected: &[&PathBuf]) {
+    let wait = async {
+        loop {
+            let cached = guard
+                .cached_roots()
+                .map(|roots| roots.into_iter().map(|root| root.path).collect::<Vec<_>>());
+            if cached.is_some_and(|cached| cached.iter().eq(expected.iter().copied())) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), wait)
+        .await
+        .unwrap_or_else(|_| panic!("roots did not become {expected:?}"));
+}
+
+fn directory() -> anyhow::Result<(TempDir, PathBuf)> {
+    let dir = TempDir::new()?;
+    let path = dir.path().canonicalize()?;
+    Ok((dir, path))
+}
+
+#[tokio::test]
+async fn test_resolve() -> anyhow::Result<()> {
+    let (_root, root) = directory()?;
+    let (_outside, outside) = directory()?;
+    std::fs::create_dir(root.join("docs"))?;
+    std::fs::write(root.join("docs/readme.md"), "hello")?;
+    std::fs::write(outside.join("secret.txt"), "secret")?;
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&outside, root.join("escape"))?;
+        std::os::unix::fs::symlink(outside.join("new.txt"), root.join("dangling"))?;
+    }
+
+    let client = Client::default();
+    client.roots.lock().unwrap().push(root.clone());
+    let (server, peer, running) = connect(client).await?;
+    roots_become(&server.roots, &[&root]).await;
+    let guard = &server.roots;
+
+    assert_eq!(
+        guard.resolve_cached(root.join("docs/readme.md"))?,
+        root.join("docs/readme.md")
+    );
+    assert_eq!(
+        guard.resolve_cached("docs/../docs/readme.md")?,
+        root.join("docs/readme.md")
+    );
+    // files that do not exist yet can be created
+    assert_eq!(
+        guard.resolve_cached("docs/new.md")?,
+        root.join("docs/new.md")
+    );
+    assert!(matches!(
+        guard.resolve_cached("missing/new.md"),
+        Err(RootsError::Io { .. })
+    ));
+    assert!(matches!(
+        guard.resolve_cached(outside.join("secret.txt")),
+        Err(RootsError::OutsideRoots(_))
+    ));
+    assert!(matches!(
+        guard.resolve_cached("../"),
+        Err(RootsError::OutsideRoots(_))
+    ));
+    #[cfg(unix)]
+    assert!(matches!(
+        guard.resolve_cached("escape/secret.txt"),
+        Err(RootsError::OutsideRoots(_))
+    ));
+    // a dangling symlink would create its target outside the roots
+    #[cfg(unix)]
+    assert!(matches!(
+        guard.resolve_cached("dangling"),
+        Err(RootsError::Io { .. })
+    ));
+
+    let error = ErrorData::from(guard.resolve_cached("../").unwrap_err());
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+    // a fresh guard lists the roots itself outside the service loop
+    let fresh = RootsGuard::new();
+    assert!(matches!(
+        fresh.resolve_cached("docs"),
+        Err(RootsError::NotListed)
+    ));
+    assert_eq!(fresh.resolve(&peer, "docs").await?, root.join("docs"));
+    running.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_roots_list_changed() -> anyhow::Result<()> {
+    let (_first, first) = directory()?;
+    let (_second, second) = directory()?;
+    let client = Client::default();
+    client.roots.lock().unwrap().push(first.clone());
+    let (server, _peer, running) = connect(client.clone()).await?;
+    roots_become(&server.roots, &[&first]).await;
+
+    *client.roots.lock().unwrap() = vec![second.clone()];
+    running.peer().notify_roots_list_changed().await?;
+    roots_become(&server.roots, &[&second]).await;
+    assert!(matches!(
+        server.roots.resolve_cached(&first),
+        Err(RootsError::OutsideRoots(_))
+    ));
+    running.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_without_roots() -> anyhow::Result<()> {
+    let server = Server::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let running = tokio::spawn(server.clone().serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let running = running.await??;
+
+    assert!(server.roots.roots(running.peer()).await?.is_empty());
+    assert!(matches!(
+        server.roots.resolve_cached("anything"),
+        Err(RootsError::NoRoots)
+    ));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[cfg(feature = "wasm-tools")]
+#[tokio::test]
+async fn test_preopen_dirs() -> anyhow::Result<()> {
+    use wasmtime_wasi::{DirPerms, FilePerms};
+
+    let (_root, root) = directory()?;
+    let client = Client::default();
+    client.roots.lock().unwrap().push(root.clone());
+    let (server, _peer, running) = connect(client).await?;
+    roots_become(&server.roots, &[&root]).await;
+
+    let dirs = server.roots.preopen_dirs(&["read".to_owned()])?;
+    assert_eq!(dirs.len(), 1);
+    assert_eq!(dirs[0].host_path, root);
+    assert_eq!(dirs[0].guest_path, root);
+    assert_eq!(dirs[0].dir_perms, DirPerms::READ);
+    assert_eq!(dirs[0].file_perms, FilePerms::READ);
+
+    let dirs = server.roots.preopen_dirs(&["write".to_owned()])?;
+    assert_eq!(dirs[0].dir_perms, DirPerms::READ | DirPerms::MUTATE);
+    assert_eq!(dirs[0].file_perms, FilePerms::READ | FilePerms::WRITE);
+    let _context = mcpkit_rs::wasm::WasmContext::new().with_preopen_dirs(dirs);
+    running.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,348 @@
+//cargo test --test test_sampling_provider --features "server client sampling-openai"
+#![cfg(all(feature = "server", feature = "client", feature = "sampling-openai"))]
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    Router, extract::State, http::header::CONTENT_TYPE, response::IntoResponse, routing::post,
+};
+use mcpkit_rs::{
+    RoleServer, ServerHandler, ServiceError, ServiceExt,
+    handler::client::sampling::{
+        ApprovalDecision, SamplingHandler, SamplingLimits, SamplingModel, SamplingReview,
+        USER_REJECTED, openai::OpenAiProvider,
+    },
+    model::*,
+    service::{Peer, RunningService},
+};
+use serde_json::{Value, json};
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::default().with_server_info(Implementation::new("test-server", "1.0.0"))
+    }
+}
+
+type Requests = Arc<Mutex<Vec<Value>>>;
+
+/// Answers chat completions with the requested model's name, or with a tool
+/// call when tools are offered.
+async fn chat_completions(State(requests): State<Requests>, body: String) -> impl IntoResponse {
+    let body: Value = serde_json::from_str(&body).unwrap();
+    requests.lock().unwrap().push(body.clone());
+    let model = body["model"].as_str().unwrap_or_default();
+    let choice = if body.get("tools").is_some() {
+        json!({
+            "message": {
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": { "name": "add", "arguments": "{\"a\":1,\"b\":2}" },
+                }],
+            },
+            "finish_reason": "tool_calls",
+        })
+    } else {
+        json!({
+            "message": { "role": "assistant", "content": format!("answered by {model}") },
+            "finish_reason": "stop",
+        })
+    };
+    let response = json!({ "model": model, "choices": [choice] });
+    ([(CONTENT_TYPE, "application/json")], response.to_string())
+}
+
+async fn backend() -> anyhow::Result<(String, Requests)> {
+    let requests = Requests::default();
+    let router = Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .with_state(requests.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    Ok((format!("http://{addr}/v1"), requests))
+}
+
+fn models() -> Vec<SamplingModel> {
+    vec![
+        SamplingModel::new("mini")
+            .with_cost(0.9)
+            .with_speed(0.9)
+            .with_intelligence(0.3),
+        SamplingModel::new("large")
+            .with_cost(0.2)
+            .with_speed(0.4)
+            .with_intelligence(0.9),
+    ]
+}
+
+/// Connect [`Server`] to `client`, returning the server's view of the
+/// client.
+async fn connect(
+    client: SamplingHandler<ClientInfo>,
+) -> anyhow::Result<(
+    Peer<RoleServer>,
+    RunningService<mcpkit_rs::RoleClient, SamplingHandler<ClientInfo>>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let client = client.serve(client_transport).await?;
+    let server = server.await??;
+    let peer = server.peer().clone();
+    tokio::spawn(server.waiting());
+    Ok((peer, client))
+}
+
+fn ask(text: &str) -> CreateMessageRequestParams {
+    CreateMessageRequestParams::new(vec![SamplingMessage::user_text(text)], 100)
+}
+
+fn text(result: &CreateMessageResult) -> &str {
+    &result
+        .message
+        .content
+        .first()
+        .unwrap()
+        .as_text()
+        .unwrap()
+        .text
+}
+
+#[tokio::test]
+async fn test_model_selection() -> anyhow::Result<()> {
+    let (url, requests) = backend().await?;
+    let handler =
+        SamplingHandler::new(ClientInfo::default(), OpenAiProvider::new(url)).with_models(models());
+    let (peer, client) = connect(handler).await?;
+    assert!(peer.supports_sampling_tools());
+
+    let result = peer.create_message(ask("hi")).await?;
+    assert_eq!(result.model, "mini");
+    assert_eq!(text(&result), "answered by mini");
+    assert_eq!(
+        result.stop_reason.as_deref(),
+        Some(CreateMessageResult::STOP_REASON_END_TURN)
+    );
+
+    let smart = ask("hi").with_model_preferences(
+        ModelPreferences::new()
+            .with_intelligence_priority(1.0)
+            .with_cost_priority(0.1),
+    );
+    assert_eq!(peer.create_message(smart).await?.model, "large");
+
+    let hinted = ask("hi")
+        .with_system_prompt("be brief")
+        .with_model_preferences(
+            ModelPreferences::new()
+                .with_hints(vec![ModelHint::new("min")])
+                .with_intelligence_priority(1.0),
+        );
+    assert_eq!(peer.create_message(hinted).await?.model, "mini");
+
+    let sent = requests.lock().unwrap().last().cloned().unwrap();
+    assert_eq!(sent["max_tokens"], 100);
+    assert_eq!(
+        sent["messages"],
+        json!([
+            { "role": "system", "content": "be brief" },
+            { "role": "user", "content": [{ "type": "text", "text": "hi" }] },
+        ])
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_approval() -> anyhow::Result<()> {
+    let (url, requests) = backend().await?;
+    let reviews = Arc::new(Mutex::new(Vec::new()));
+    let handler = SamplingHandler::new(ClientInfo::default(), OpenAiProvider::new(url))
+        .with_models(models())
+        .with_approval({
+            let reviews = reviews.clone();
+            move |mut review: SamplingReview| {
+                reviews.lock().unwrap().push(review.clone());
+                async move {
+                    match text_of(&review.params).as_str() {
+                        "reject" => ApprovalDecision::Reject {
+                            reason: Some("not today".to_owned()),
+                        },
+                        _ => {
+                            review.model = "large".to_owned();
+                            review.params.max_tokens = 10;
+                            ApprovalDecision::Approve(review)
+                        }
+                    }
+                }
+            }
+        });
+    let (peer, client) = connect(handler).await?;
+
+    let result = peer.create_message(ask("edit")).await?;
+    assert_eq!(result.model, "large");
+    assert_eq!(requests.lock().unwrap()[0]["max_tokens"], 10);
+
+    let error = peer.create_message(ask("reject")).await.unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(error.code, USER_REJECTED);
+    assert_eq!(error.message, "not today");
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    client.cancel().await?;
+    let reviews = reviews.lock().unwrap();
+    assert_eq!(reviews.len(), 2);
+    assert_eq!(reviews[0].server.as_deref(), Some("test-server"));
+    assert_eq!(reviews[0].model, "mini");
+    Ok(())
+}
+
+fn text_of(params: &CreateMessageRequestParams) -> String {
+    params.messages[0]
+        .content
+        .first()
+        .unwrap()
+        .as_text()
+        .unwrap()
+        .text
+        .clone()
+}
+
+#[tokio::test]
+async fn test_limits() -> anyhow::Result<()> {
+    let (url, requests) = backend().await?;
+    let reviews = Arc::new(Mutex::new(0));
+    let handler = SamplingHandler::new(ClientInfo::default(), OpenAiProvider::new(url))
+        .with_models(models())
+        .with_limits(
+            SamplingLimits::default()
+                .with_max_requests(2)
+                .with_max_tokens_per_request(50),
+        )
+        .with_approval({
+            let reviews = reviews.clone();
+            move |review: SamplingReview| {
+                *reviews.lock().unwrap() += 1;
+                async move { ApprovalDecision::Approve(review) }
+            }
+        });
+    let (peer, client) = connect(handler.clone()).await?;
+
+    peer.create_message(ask("one")).await?;
+    peer.create_message(ask("two")).await?;
+    let error = peer.create_message(ask("three")).await.unwrap_err();
+    assert!(error.to_string().contains("limit"), "{error}");
+    // requests over the limits never reach the user
+    assert_eq!(*reviews.lock().unwrap(), 2);
+
+    // another connection with the same server name has its own limits
+    let (other, other_client) = connect(handler).await?;
+    other.create_message(ask("four")).await?;
+    assert_eq!(*reviews.lock().unwrap(), 3);
+
+    client.cancel().await?;
+    other_client.cancel().await?;
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0]["max_tokens"], 50);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tool_calls() -> anyhow::Result<()> {
+    let (url, requests) = backend().await?;
+    let handler =
+        SamplingHandler::new(ClientInfo::default(), OpenAiProvider::new(url)).with_models(models());
+    let (peer, client) = connect(handler).await?;
+
+    let tool = Tool::new(
+        "add",
+        "Add two numbers",
+        json!({ "type": "object" }).as_object().unwrap().clone(),
+    );
+    let params = ask("1 + 2?").with_tools(vec![tool]);
+    let result = peer.create_message(params.clone()).await?;
+    assert_eq!(
+        result.stop_reason.as_deref(),
+        Some(CreateMessageResult::STOP_REASON_TOOL_USE)
+    );
+    let tool_use = result
+        .message
+        .content
+        .first()
+        .unwrap()
+        .as_tool_use()
+        .unwrap();
+    assert_eq!(tool_use.id, "call_1");
+    assert_eq!(tool_use.name, "add");
+    assert_eq!(
+        tool_use.input,
+        *json!({ "a": 1, "b": 2 }).as_object().unwrap()
+    );
+
+    // send the result back, as a sampling loop would
+    let mut follow_up = params;
+    follow_up.messages.push(result.message);
+    follow_up.messages.push(SamplingMessage::new_multiple(
+        Role::User,
+        vec![SamplingMessageContent::ToolResult(ToolResultContent::new(
+            "call_1",
+            vec![Content::text("3")],
+        ))],
+    ));
+    peer.create_message(follow_up.clone()).await?;
+
+    // a failed call is marked for the model, content it cannot read is refused
+    let mut failed = follow_up.clone();
+    failed.messages[2] = SamplingMessage::new_multiple(
+        Role::User,
+        vec![SamplingMessageContent::ToolResult(
+            ToolResultContent::error("call_1", vec![Content::text("overflow")]),
+        )],
+    );
+    peer.create_message(failed).await?;
+    let mut image = follow_up;
+    image.messages[2] = SamplingMessage::new_multiple(
+        Role::User,
+        vec![SamplingMessageContent::ToolResult(ToolResultContent::new(
+            "call_1",
+            vec![Content::image("aGVsbG8=", "image/png")],
+        ))],
+    );
+    let error = peer.create_message(image).await.unwrap_err();
+    assert!(
+        matches!(&error, ServiceError::McpError(error) if error.code == ErrorCode::INVALID_PARAMS),
+        "{error}"
+    );
+
+    client.cancel().await?;
+    let requests = requests.lock().unwrap();
+    assert_eq!(
+        requests[0]["tools"],
+        json!([{
+            "type": "function",
+            "function": {
+                "name": "add",
+                "description": "Add two numbers",
+                "parameters": { "type": "object" },
+            },
+        }])
+    );
+    let messages = &requests[1]["messages"];
+    assert_eq!(messages[1]["content"], Value::Null);
+    assert_eq!(messages[1]["tool_calls"][0]["function"]["name"], "add");
+    assert_eq!(
+        messages[2],
+        json!({ "role": "tool", "tool_call_id": "call_1", "content": "3" })
+    );
+    assert_eq!(requests[2]["messages"][2]["content"], "Error: overflow");
+    assert_eq!(requests.len(), 3);
+    Ok(())
+}
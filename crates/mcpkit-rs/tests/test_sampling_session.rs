@@ -0,0 +1,188 @@
+//cargo test --test test_sampling_session --features "testing macros"
+#![cfg(all(feature = "testing", feature = "macros"))]
+
+use std::sync::{Arc, Mutex};
+
+use mcpkit_rs::{
+    ServerHandler,
+    handler::server::{
+        router::tool::ToolRouter,
+        sampling::{SamplingSession, SamplingStop},
+        wrapper::Parameters,
+    },
+    model::*,
+    service::RequestContext,
+    testing::TestHarness,
+    tool, tool_handler, tool_router,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, JsonSchema)]
+struct AddRequest {
+    a: i64,
+    b: i64,
+}
+
+#[derive(Clone)]
+struct Calculator {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Calculator {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Add two numbers")]
+    fn add(&self, Parameters(AddRequest { a, b }): Parameters<AddRequest>) -> String {
+        (a + b).to_string()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {}
+
+fn assistant(message: SamplingMessage) -> Result<CreateMessageResult, mcpkit_rs::ErrorData> {
+    Ok(CreateMessageResult::new(message, "test-model".to_owned())
+        .with_stop_reason(CreateMessageResult::STOP_REASON_END_TURN))
+}
+
+fn add(id: &str, a: i64, b: i64) -> SamplingMessage {
+    SamplingMessage::assistant_tool_use(
+        id,
+        "add",
+        json!({ "a": a, "b": b }).as_object().unwrap().clone(),
+    )
+}
+
+fn session<'a>(
+    harness: &'a TestHarness<Calculator>,
+    max_tokens: u32,
+) -> SamplingSession<'a, Calculator> {
+    let calculator = harness.handler();
+    let context = RequestContext::new(NumberOrString::Number(1), harness.server_peer().clone());
+    let params = CreateMessageRequestParams::new(
+        vec![SamplingMessage::user_text("What is 1 + 2 + 3?")],
+        max_tokens,
+    );
+    SamplingSession::new(&calculator.tool_router, calculator, context, params)
+}
+
+#[tokio::test]
+async fn test_runs_tools_until_the_model_answers() {
+    let harness = TestHarness::new(Calculator::new()).await.unwrap();
+    harness.script_sampling(assistant(add("1", 1, 2)));
+    harness.script_sampling(assistant(add("2", 3, 3)));
+    harness.script_sampling(assistant(SamplingMessage::assistant_text("6")));
+
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let sink = streamed.clone();
+    let transcript = session(&harness, 100)
+        .on_message(move |message| sink.lock().unwrap().push(message.role.clone()))
+        .run()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        transcript.stop,
+        SamplingStop::Finished {
+            stop_reason: Some("endTurn".to_owned())
+        }
+    );
+    assert_eq!(transcript.iterations, 3);
+    assert_eq!(transcript.tokens_requested, 300);
+    assert_eq!(transcript.model.as_deref(), Some("test-model"));
+    assert_eq!(transcript.final_text().as_deref(), Some("6"));
+    assert_eq!(transcript.messages.len(), 6);
+    assert_eq!(
+        *streamed.lock().unwrap(),
+        [
+            Role::Assistant,
+            Role::User,
+            Role::Assistant,
+            Role::User,
+            Role::Assistant
+        ]
+    );
+
+    let requests = harness.sampling_requests();
+    assert_eq!(requests.len(), 3);
+    let tools = requests[0].tools.as_ref().unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "add");
+    let result = requests[1].messages[2].content.first().unwrap();
+    let result = result.as_tool_result().unwrap();
+    assert_eq!(result.tool_use_id, "1");
+    assert_eq!(result.content[0].as_text().unwrap().text, "3");
+    assert_ne!(result.is_error, Some(true));
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_token_count_saturates() {
+    let harness = TestHarness::new(Calculator::new()).await.unwrap();
+    harness.script_sampling(assistant(add("1", 1, 2)));
+    harness.script_sampling(assistant(SamplingMessage::assistant_text("3")));
+
+    let transcript = session(&harness, u32::MAX).run().await.unwrap();
+    assert_eq!(transcript.iterations, 2);
+    assert_eq!(transcript.tokens_requested, u32::MAX);
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_tool_errors_are_returned_to_the_model() {
+    let harness = TestHarness::new(Calculator::new()).await.unwrap();
+    harness.script_sampling(assistant(SamplingMessage::assistant_tool_use(
+        "1",
+        "divide",
+        JsonObject::new(),
+    )));
+    harness.script_sampling(assistant(SamplingMessage::assistant_text(
+        "I cannot divide",
+    )));
+
+    let transcript = session(&harness, 100).run().await.unwrap();
+    let result = transcript.messages[2].content.first().unwrap();
+    let result = result.as_tool_result().unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(transcript.final_text().as_deref(), Some("I cannot divide"));
+    harness.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_guards_stop_the_loop() {
+    let harness = TestHarness::new(Calculator::new()).await.unwrap();
+    for id in 0..4 {
+        harness.script_sampling(assistant(add(&id.to_string(), id, id)));
+    }
+
+    let transcript = session(&harness, 100)
+        .with_max_iterations(2)
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(transcript.stop, SamplingStop::MaxIterations);
+    assert_eq!(transcript.iterations, 2);
+
+    let transcript = session(&harness, 100)
+        .with_token_budget(150)
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(transcript.stop, SamplingStop::TokenBudget);
+    assert_eq!(transcript.iterations, 2);
+    assert_eq!(transcript.tokens_requested, 150);
+    let max_tokens: Vec<_> = harness
+        .sampling_requests()
+        .iter()
+        .map(|request| request.max_tokens)
+        .collect();
+    assert_eq!(max_tokens, [100, 100, 100, 50]);
+    harness.shutdown().await;
+}
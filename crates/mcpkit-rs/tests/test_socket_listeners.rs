@@ -0,0 +1,148 @@
+//cargo test --test test_socket_listeners --features "client server transport-tcp transport-unix"
+#![cfg(all(
+    feature = "client",
+    feature = "server",
+    feature = "transport-tcp",
+    feature = "transport-unix"
+))]
+
+mod common;
+use std::time::Duration;
+
+use common::calculator::Calculator;
+use mcpkit_rs::{
+    RoleClient, ServiceExt,
+    service::RunningService,
+    transport::{TcpServer, TcpServerConfig, tcp},
+};
+use tokio_util::sync::CancellationToken;
+
+/// Check that `client` reached a live calculator.
+async fn assert_served(client: &RunningService<RoleClient, ()>) -> anyhow::Result<()> {
+    let instructions = client
+        .peer_info()
+        .and_then(|info| info.instructions.clone());
+    assert_eq!(instructions.as_deref(), Some("A simple calculator"));
+    client.list_all_tools().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tcp_server_serves_each_connection() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let server = TcpServer::bind(
+        "127.0.0.1:0",
+        || Ok(Calculator::new()),
+        TcpServerConfig {
+            cancellation_token: ct.clone(),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let addr = server.local_addr()?;
+    let server = tokio::spawn(server.serve());
+
+    let first = ().serve(tcp::connect(addr).await?).await?;
+    let second = ().serve(tcp::connect(addr).await?).await?;
+    assert_served(&first).await?;
+    assert_served(&second).await?;
+
+    // cancelling the server ends the open sessions too
+    ct.cancel();
+    tokio::time::timeout(Duration::from_secs(5), server).await??;
+    tokio::time::timeout(Duration::from_secs(5), first.waiting()).await??;
+    tokio::time::timeout(Duration::from_secs(5), second.waiting()).await??;
+    assert!(tcp::connect(addr).await.is_err());
+    Ok(())
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{io, os::unix::fs::PermissionsExt, time::Duration};
+
+    use mcpkit_rs::{
+        ServiceExt,
+        transport::{UnixServer, UnixServerConfig, unix},
+    };
+    use tokio_util::sync::CancellationToken;
+
+    use super::{assert_served, common::calculator::Calculator};
+
+    async fn bind(
+        path: &std::path::Path,
+        config: UnixServerConfig,
+    ) -> io::Result<UnixServer<impl Fn() -> io::Result<Calculator> + Send + Sync + 'static>> {
+        UnixServer::bind(path, || Ok(Calculator::new()), config).await
+    }
+
+    #[tokio::test]
+    async fn test_unix_server_lifecycle() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("calculator.sock");
+        let ct = CancellationToken::new();
+        let server = bind(
+            &path,
+            UnixServerConfig {
+                permissions: Some(0o600),
+                cancellation_token: ct.clone(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let server = tokio::spawn(server.serve());
+
+        let client = ().serve(unix::connect(&path).await?).await?;
+        assert_served(&client).await?;
+
+        // a second server must not steal a live socket
+        let error = bind(&path, UnixServerConfig::default()).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+
+        ct.cancel();
+        tokio::time::timeout(Duration::from_secs(5), server).await??;
+        tokio::time::timeout(Duration::from_secs(5), client.waiting()).await??;
+        assert!(!path.exists(), "socket file should be removed on shutdown");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unix_server_replaces_stale_socket() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("stale.sock");
+        // a socket nobody listens on any more, as left by a crashed server
+        drop(std::os::unix::net::UnixListener::bind(&path)?);
+        assert!(path.exists());
+
+        let error = bind(
+            &path,
+            UnixServerConfig {
+                remove_stale_socket: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+
+        let server = bind(&path, UnixServerConfig::default()).await?;
+        let server = tokio::spawn(server.serve());
+        let client = ().serve(unix::connect(&path).await?).await?;
+        assert_served(&client).await?;
+        client.cancel().await?;
+        server.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unix_server_keeps_other_files() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("not-a-socket");
+        std::fs::write(&path, "precious")?;
+        let error = bind(&path, UnixServerConfig::default()).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path)?, "precious");
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "schemars")]
+
+use std::time::Duration;
+
+use mcpkit_rs::{
+    ServiceExt,
+    transport::{SseClientTransport, SseServerConfig, SseService},
+};
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::{calculator::Calculator, handlers::TestClientHandler};
+
+async fn spawn_server(
+    ct: CancellationToken,
+) -> anyhow::Result<(std::net::SocketAddr, SseService<Calculator>)> {
+    let service = SseService::new(
+        || Ok(Calculator::new()),
+        SseServerConfig {
+            sse_keep_alive: None,
+            cancellation_token: ct.child_token(),
+            ..Default::default()
+        },
+    );
+    let router = axum::Router::new()
+        .route_service("/sse", service.clone())
+        .route_service("/messages", service.clone());
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = tcp_listener.local_addr()?;
+    tokio::spawn(async move {
+        let _ = axum::serve(tcp_listener, router)
+            .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+            .await;
+    });
+    Ok((addr, service))
+}
+
+#[tokio::test]
+async fn test_legacy_sse_round_trip() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let (addr, service) = spawn_server(ct.clone()).await?;
+
+    let transport = SseClientTransport::from_uri(format!("http://{addr}/sse"));
+    let client = TestClientHandler::new(true, true).serve(transport).await?;
+    let info = client.peer_info().expect("server info");
+    assert_eq!(info.instructions.as_deref(), Some("A simple calculator"));
+
+    // responses travel back over the event stream, not the POST body
+    client.list_all_tools().await?;
+    assert_eq!(service.session_count().await, 1);
+
+    client.cancel().await?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while service.session_count().await != 0 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+    ct.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_legacy_sse_post_requires_known_session() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let (addr, _service) = spawn_server(ct.clone()).await?;
+    let client = reqwest::Client::new();
+    let body = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+
+    let response = client
+        .post(format!("http://{addr}/messages"))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post(format!("http://{addr}/messages?sessionId=unknown"))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let response = client.get(format!("http://{addr}/sse")).send().await?;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()?
+            .starts_with("text/event-stream")
+    );
+    ct.cancel();
+    Ok(())
+}
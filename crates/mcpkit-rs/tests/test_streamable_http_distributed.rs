@@ -0,0 +1,208 @@
+#![cfg(feature = "schemars")]
+
+use std::sync::{Arc, Mutex};
+
+use mcpkit_rs::transport::streamable_http_server::{
+    StreamableHttpServerConfig, StreamableHttpService,
+    session::distributed::{
+        DistributedSessionManager, FileSessionRegistry, NodeInfo, SessionRegistry,
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::calculator::Calculator;
+
+type SeenHeaders = Arc<Mutex<Vec<axum::http::HeaderMap>>>;
+
+async fn spawn_node(
+    id: &str,
+    registry: Arc<dyn SessionRegistry>,
+    ct: CancellationToken,
+) -> anyhow::Result<(String, tokio::task::JoinHandle<()>)> {
+    spawn_recording_node(id, registry, ct, SeenHeaders::default()).await
+}
+
+/// A node recording the headers of every request it receives in `seen`.
+async fn spawn_recording_node(
+    id: &str,
+    registry: Arc<dyn SessionRegistry>,
+    ct: CancellationToken,
+    seen: SeenHeaders,
+) -> anyhow::Result<(String, tokio::task::JoinHandle<()>)> {
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let endpoint = format!("http://{}/mcp", tcp_listener.local_addr()?);
+    let session_manager = DistributedSessionManager::with_shared_registry(
+        NodeInfo::new(id, endpoint.clone()),
+        registry,
+    );
+    let service = StreamableHttpService::new(
+        || Ok(Calculator::new()),
+        Arc::new(session_manager),
+        StreamableHttpServerConfig {
+            stateful_mode: true,
+            sse_keep_alive: None,
+            cancellation_token: ct.child_token(),
+            ..Default::default()
+        },
+    );
+    let router =
+        axum::Router::new()
+            .nest_service("/mcp", service)
+            .layer(axum::middleware::from_fn(
+                move |request: axum::extract::Request, next: axum::middleware::Next| {
+                    seen.lock().unwrap().push(request.headers().clone());
+                    next.run(request)
+                },
+            ));
+    let handle = tokio::spawn(async move {
+        let _ = axum::serve(tcp_listener, router)
+            .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+            .await;
+    });
+    Ok((endpoint, handle))
+}
+
+fn post(
+    client: &reqwest::Client,
+    endpoint: &str,
+    session_id: Option<&str>,
+    body: &'static str,
+) -> reqwest::RequestBuilder {
+    let request = client
+        .post(endpoint)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json, text/event-stream")
+        .body(body);
+    match session_id {
+        Some(session_id) => request.header("mcp-session-id", session_id),
+        None => request,
+    }
+}
+
+#[tokio::test]
+async fn test_requests_are_forwarded_to_owning_node() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let registry: Arc<dyn SessionRegistry> = Arc::new(FileSessionRegistry::open(dir.path())?);
+    let ct = CancellationToken::new();
+    let (node_a, handle_a) = spawn_node("a", registry.clone(), ct.clone()).await?;
+    let (node_b, handle_b) = spawn_node("b", registry.clone(), ct.clone()).await?;
+    let client = reqwest::Client::new();
+
+    let response = post(
+        &client,
+        &node_a,
+        None,
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-11-25","capabilities":{},"clientInfo":{"name":"test","version":"1.0"}}}"#,
+    )
+    .send()
+    .await?;
+    let session_id = response.headers()["mcp-session-id"].to_str()?.to_owned();
+    response.text().await?;
+
+    let record = registry.get(&session_id.as_str().into()).await?.unwrap();
+    assert_eq!(record.owner.id, "a");
+
+    // node b does not run the session, but forwards to node a
+    let response = post(
+        &client,
+        &node_b,
+        Some(&session_id),
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+    )
+    .send()
+    .await?;
+    assert_eq!(response.status(), 202);
+    let body = post(
+        &client,
+        &node_b,
+        Some(&session_id),
+        r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#,
+    )
+    .send()
+    .await?
+    .text()
+    .await?;
+    assert!(body.contains(r#""id":2"#), "{body}");
+
+    // closing through node b ends the session everywhere
+    let response = client
+        .delete(&node_b)
+        .header("mcp-session-id", &session_id)
+        .send()
+        .await?;
+    assert!(response.status().is_success());
+    assert!(registry.get(&session_id.as_str().into()).await?.is_none());
+    let response = post(
+        &client,
+        &node_a,
+        Some(&session_id),
+        r#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#,
+    )
+    .send()
+    .await?;
+    assert_eq!(response.status(), 404);
+
+    ct.cancel();
+    handle_a.await?;
+    handle_b.await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forwarded_requests_keep_auth_and_protocol_headers() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let registry: Arc<dyn SessionRegistry> = Arc::new(FileSessionRegistry::open(dir.path())?);
+    let ct = CancellationToken::new();
+    let seen = SeenHeaders::default();
+    let (node_a, handle_a) =
+        spawn_recording_node("a", registry.clone(), ct.clone(), seen.clone()).await?;
+    let (node_b, handle_b) = spawn_node("b", registry.clone(), ct.clone()).await?;
+    let client = reqwest::Client::new();
+
+    let response = post(
+        &client,
+        &node_a,
+        None,
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-11-25","capabilities":{},"clientInfo":{"name":"test","version":"1.0"}}}"#,
+    )
+    .send()
+    .await?;
+    let session_id = response.headers()["mcp-session-id"].to_str()?.to_owned();
+    response.text().await?;
+    post(
+        &client,
+        &node_a,
+        Some(&session_id),
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+    )
+    .send()
+    .await?;
+    seen.lock().unwrap().clear();
+
+    let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    let body = post(
+        &client,
+        &node_b,
+        Some(&session_id),
+        r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#,
+    )
+    .header("Authorization", "Bearer secret")
+    .header("MCP-Protocol-Version", "2025-06-18")
+    .header("traceparent", traceparent)
+    .send()
+    .await?
+    .text()
+    .await?;
+    assert!(body.contains(r#""id":2"#), "{body}");
+
+    let forwarded = seen.lock().unwrap().pop().expect("node a saw the ping");
+    assert_eq!(forwarded["authorization"], "Bearer secret");
+    assert_eq!(forwarded["mcp-protocol-version"], "2025-06-18");
+    assert_eq!(forwarded["traceparent"], traceparent);
+
+    ct.cancel();
+    handle_a.await?;
+    handle_b.await?;
+    Ok(())
+}
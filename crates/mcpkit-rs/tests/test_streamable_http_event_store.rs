@@ -0,0 +1,137 @@
+#![cfg(feature = "schemars")]
+
+use std::sync::Arc;
+
+use mcpkit_rs::transport::streamable_http_server::{
+    StreamableHttpServerConfig, StreamableHttpService,
+    session::{
+        event_store::{EventStore, InMemoryEventStore},
+        local::{LocalSessionManager, SessionConfig},
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::calculator::Calculator;
+
+async fn spawn_server(
+    event_store: Arc<dyn EventStore>,
+    ct: CancellationToken,
+) -> anyhow::Result<(std::net::SocketAddr, tokio::task::JoinHandle<()>)> {
+    let session_manager = LocalSessionManager {
+        sessions: Default::default(),
+        session_config: SessionConfig {
+            event_store: Some(event_store),
+            ..Default::default()
+        },
+    };
+    let service = StreamableHttpService::new(
+        || Ok(Calculator::new()),
+        Arc::new(session_manager),
+        StreamableHttpServerConfig {
+            stateful_mode: true,
+            sse_keep_alive: None,
+            cancellation_token: ct.child_token(),
+            ..Default::default()
+        },
+    );
+    let router = axum::Router::new().nest_service("/mcp", service);
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = tcp_listener.local_addr()?;
+    let handle = tokio::spawn(async move {
+        let _ = axum::serve(tcp_listener, router)
+            .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+            .await;
+    });
+    Ok((addr, handle))
+}
+
+async fn post(
+    client: &reqwest::Client,
+    addr: std::net::SocketAddr,
+    session_id: Option<&str>,
+    body: &'static str,
+) -> anyhow::Result<reqwest::Response> {
+    let mut request = client
+        .post(format!("http://{addr}/mcp"))
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json, text/event-stream");
+    if let Some(session_id) = session_id {
+        request = request.header("mcp-session-id", session_id);
+    }
+    Ok(request.body(body).send().await?)
+}
+
+#[tokio::test]
+async fn test_resume_replays_from_store_after_restart() -> anyhow::Result<()> {
+    let event_store: Arc<dyn EventStore> = Arc::new(InMemoryEventStore::default());
+    let client = reqwest::Client::new();
+
+    let ct = CancellationToken::new();
+    let (addr, handle) = spawn_server(event_store.clone(), ct.clone()).await?;
+    let response = post(
+        &client,
+        addr,
+        None,
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-11-25","capabilities":{},"clientInfo":{"name":"test","version":"1.0"}}}"#,
+    )
+    .await?;
+    let session_id = response.headers()["mcp-session-id"].to_str()?.to_owned();
+    response.text().await?;
+    post(
+        &client,
+        addr,
+        Some(&session_id),
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+    )
+    .await?;
+    let body = post(
+        &client,
+        addr,
+        Some(&session_id),
+        r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#,
+    )
+    .await?
+    .text()
+    .await?;
+    let response_event = body
+        .split("\n\n")
+        .find(|event| event.contains(r#""id":2"#))
+        .expect("ping response");
+    let event_id = response_event
+        .lines()
+        .find_map(|line| line.strip_prefix("id: "))
+        .expect("event id")
+        .to_owned();
+
+    // the first server goes away together with its in-memory sessions
+    ct.cancel();
+    handle.await?;
+
+    let ct = CancellationToken::new();
+    let (addr, handle) = spawn_server(event_store, ct.clone()).await?;
+    let response = client
+        .get(format!("http://{addr}/mcp"))
+        .header("Accept", "text/event-stream")
+        .header("mcp-session-id", &session_id)
+        .header("last-event-id", &event_id)
+        .send()
+        .await?;
+    assert_eq!(response.status(), 200);
+    let body = response.text().await?;
+    assert!(body.contains(&format!("id: {event_id}")));
+    assert!(body.contains(r#""id":2"#));
+
+    let response = client
+        .get(format!("http://{addr}/mcp"))
+        .header("Accept", "text/event-stream")
+        .header("mcp-session-id", "unknown")
+        .header("last-event-id", &event_id)
+        .send()
+        .await?;
+    assert_eq!(response.status(), 404);
+
+    ct.cancel();
+    handle.await?;
+    Ok(())
+}
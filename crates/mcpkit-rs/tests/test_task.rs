@@ -1,7 +1,13 @@
-use std::{any::Any, time::Duration};
+use std::{any::Any, sync::Arc, time::Duration};
 
-use mcpkit_rs::task_manager::{
-    OperationDescriptor, OperationMessage, OperationProcessor, OperationResultTransport,
+use futures::{FutureExt, future::BoxFuture};
+use mcpkit_rs::{
+    model::{CallToolResult, Content, TaskStatus},
+    task_manager::{
+        FileTaskStore, InMemoryTaskStore, OperationDescriptor, OperationMessage,
+        OperationProcessor, OperationResultTransport, TaskOutcome, TaskRecord, TaskStore,
+        TaskStoreError, ToolCallTaskResult,
+    },
 };
 
 struct DummyTransport {
@@ -75,3 +81,299 @@ async fn rejects_duplicate_operation_ids() {
         .expect_err("duplicate should fail");
     assert!(format!("{err}").contains("already running"));
 }
+
+fn tool_call(id: &str, text: &str) -> OperationMessage {
+    let result =
+        ToolCallTaskResult::new(id, Ok(CallToolResult::success(vec![Content::text(text)])));
+    OperationMessage::new(
+        OperationDescriptor::new(id, "echo"),
+        Box::pin(async move { Ok(Box::new(result) as Box<dyn OperationResultTransport>) }),
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn task_records_survive_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(FileTaskStore::open(dir.path()).unwrap());
+
+    let mut processor = OperationProcessor::with_store(store.clone());
+    processor
+        .submit_operation(tool_call("done", "hello"))
+        .unwrap();
+    let working = processor.task_record("done").await.unwrap().unwrap();
+    assert_eq!(working.task.status, TaskStatus::Working);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(processor.take_completed_result("done").is_some());
+
+    // a task left working by a process that stopped
+    let interrupted = TaskRecord::new(&OperationDescriptor::new("interrupted", "slow"));
+    store.save(&interrupted).await.unwrap();
+    drop(processor);
+
+    let mut processor =
+        OperationProcessor::with_store(Arc::new(FileTaskStore::open(dir.path()).unwrap()));
+    let done = processor.task_record("done").await.unwrap().unwrap();
+    assert_eq!(done.task.status, TaskStatus::Completed);
+    assert_eq!(done.task.created_at, working.task.created_at);
+    assert_eq!(
+        done.outcome,
+        Some(TaskOutcome::Result(CallToolResult::success(vec![
+            Content::text("hello")
+        ])))
+    );
+    let interrupted = processor.task_record("interrupted").await.unwrap().unwrap();
+    assert_eq!(interrupted.task.status, TaskStatus::Failed);
+
+    let ids: Vec<_> = processor
+        .task_records()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|record| record.task.task_id)
+        .collect();
+    assert_eq!(ids, ["done", "interrupted"]);
+}
+
+#[tokio::test]
+async fn cancelled_tasks_are_recorded() {
+    let mut processor = OperationProcessor::new();
+    let future = Box::pin(async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(Box::new(DummyTransport {
+            id: "slow".to_string(),
+            value: 0,
+        }) as Box<dyn OperationResultTransport>)
+    });
+    processor
+        .submit_operation(OperationMessage::new(
+            OperationDescriptor::new("slow", "dummy"),
+            future,
+        ))
+        .unwrap();
+    assert!(processor.cancel_task("slow"));
+    let record = processor.task_record("slow").await.unwrap().unwrap();
+    assert_eq!(record.task.status, TaskStatus::Cancelled);
+    assert!(record.outcome.is_none());
+}
+
+/// A store that notes the status of every save and holds saves back by
+/// `delay(n, record)` for the `n`th save, like writes queued on the blocking
+/// pool: a held back save still lands after the task that started it is
+/// aborted.
+#[derive(Debug)]
+struct DelayedStore {
+    inner: Arc<InMemoryTaskStore>,
+    saved: Arc<std::sync::Mutex<Vec<TaskStatus>>>,
+    saves: std::sync::atomic::AtomicUsize,
+    delay: fn(usize, &TaskRecord) -> Duration,
+}
+
+impl DelayedStore {
+    fn new(delay: fn(usize, &TaskRecord) -> Duration) -> Self {
+        Self {
+            inner: Arc::default(),
+            saved: Arc::default(),
+            saves: Default::default(),
+            delay,
+        }
+    }
+
+    /// The terminal statuses saved so far.
+    fn terminal_saves(&self) -> Vec<TaskStatus> {
+        let saved = self.saved.lock().unwrap();
+        saved
+            .iter()
+            .filter(|status| status.is_terminal())
+            .cloned()
+            .collect()
+    }
+}
+
+impl TaskStore for DelayedStore {
+    fn save<'a>(&'a self, record: &'a TaskRecord) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        let n = self.saves.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let delay = (self.delay)(n, record);
+        let inner = self.inner.clone();
+        let saved = self.saved.clone();
+        let record = record.clone();
+        let write = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            saved.lock().unwrap().push(record.task.status.clone());
+            inner.save(&record).await
+        });
+        async move { write.await.unwrap() }.boxed()
+    }
+
+    fn load<'a>(
+        &'a self,
+        task_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<TaskRecord>, TaskStoreError>> {
+        self.inner.load(task_id)
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<TaskRecord>, TaskStoreError>> {
+        self.inner.list()
+    }
+
+    fn remove<'a>(&'a self, task_id: &'a str) -> BoxFuture<'a, Result<(), TaskStoreError>> {
+        self.inner.remove(task_id)
+    }
+
+    fn remove_expired(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> BoxFuture<'_, Result<usize, TaskStoreError>> {
+        self.inner.remove_expired(now)
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancellation_is_not_overwritten_by_the_initial_save() {
+    let store = Arc::new(DelayedStore::new(|n, _| match n {
+        0 => Duration::from_millis(50),
+        _ => Duration::ZERO,
+    }));
+    let mut processor = OperationProcessor::with_store(store.clone());
+    let future = Box::pin(async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(Box::new(DummyTransport {
+            id: "slow".to_string(),
+            value: 0,
+        }) as Box<dyn OperationResultTransport>)
+    });
+    processor
+        .submit_operation(OperationMessage::new(
+            OperationDescriptor::new("slow", "dummy"),
+            future,
+        ))
+        .unwrap();
+    // let the task start saving its working record before cancelling it
+    tokio::task::yield_now().await;
+    assert!(processor.cancel_task("slow"));
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let stored = store.load("slow").await.unwrap().unwrap();
+    assert_eq!(stored.task.status, TaskStatus::Cancelled);
+}
+
+/// Terminal saves take a while, so the processor can act on a task that
+/// already finished but has not handed over its result.
+fn slow_terminal_saves(_: usize, record: &TaskRecord) -> Duration {
+    if record.is_terminal() {
+        Duration::from_millis(1500)
+    } else {
+        Duration::ZERO
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn timeout_is_announced_once() {
+    let store = Arc::new(DelayedStore::new(slow_terminal_saves));
+    let mut processor = OperationProcessor::with_store(store.clone());
+    let descriptor = OperationDescriptor::new("stuck", "dummy").with_timeout(1);
+    let future = Box::pin(async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(Box::new(DummyTransport {
+            id: "stuck".to_string(),
+            value: 0,
+        }) as Box<dyn OperationResultTransport>)
+    });
+    processor
+        .submit_operation(OperationMessage::new(descriptor, future))
+        .unwrap();
+
+    // the operation timed out by itself and is still saving that
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+    processor.check_timeouts();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    assert_eq!(store.terminal_saves(), [TaskStatus::Failed]);
+    let result = processor.take_completed_result("stuck").unwrap();
+    assert_eq!(result.record.task.status, TaskStatus::Failed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn finished_task_is_not_cancelled() {
+    let store = Arc::new(DelayedStore::new(slow_terminal_saves));
+    let mut processor = OperationProcessor::with_store(store.clone());
+    processor
+        .submit_operation(tool_call("done", "hello"))
+        .unwrap();
+
+    // the call returned and its result is being saved
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!processor.cancel_task("done"));
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    assert_eq!(store.terminal_saves(), [TaskStatus::Completed]);
+    let record = processor.task_record("done").await.unwrap().unwrap();
+    assert_eq!(record.task.status, TaskStatus::Completed);
+    assert!(record.outcome.is_some());
+    assert_eq!(store.load("done").await.unwrap().unwrap(), record);
+}
+
+#[tokio::test(start_paused = true)]
+async fn expired_tasks_are_collected() {
+    let mut processor = OperationProcessor::new();
+    let mut message = tool_call("short-lived", "bye");
+    message.descriptor.retention_ttl = Some(1000);
+    processor.submit_operation(message).unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(processor.task_records().await.unwrap().len(), 1);
+
+    assert!(processor.take_completed_result("short-lived").is_some());
+
+    // expiry follows the wall clock, so backdate the record instead of waiting
+    let mut record = processor.task_record("short-lived").await.unwrap().unwrap();
+    record.task.created_at = (chrono::Utc::now() - chrono::Duration::seconds(2)).to_rfc3339();
+    processor.store().save(&record).await.unwrap();
+    assert!(processor.task_records().await.unwrap().is_empty());
+    assert!(
+        processor
+            .task_record("short-lived")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn retention_ttl_does_not_limit_run_time() {
+    let mut processor = OperationProcessor::new();
+    let descriptor = OperationDescriptor::new("long", "dummy").with_retention_ttl(1000);
+    let future = Box::pin(async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok(Box::new(DummyTransport {
+            id: "long".to_string(),
+            value: 7,
+        }) as Box<dyn OperationResultTransport>)
+    });
+    processor
+        .submit_operation(OperationMessage::new(descriptor, future))
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_secs(6)).await;
+    let result = processor.take_completed_result("long").unwrap();
+    assert!(result.result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn timeout_aborts_the_operation() {
+    let mut processor = OperationProcessor::new();
+    let descriptor = OperationDescriptor::new("stuck", "dummy").with_timeout(1);
+    let future = Box::pin(async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(Box::new(DummyTransport {
+            id: "stuck".to_string(),
+            value: 0,
+        }) as Box<dyn OperationResultTransport>)
+    });
+    processor
+        .submit_operation(OperationMessage::new(descriptor, future))
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let result = processor.take_completed_result("stuck").unwrap();
+    assert!(result.result.is_err());
+    assert_eq!(result.record.task.status, TaskStatus::Failed);
+}
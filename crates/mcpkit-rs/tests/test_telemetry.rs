@@ -0,0 +1,123 @@
+//cargo test --test test_telemetry --features "client server otel-testing"
+#![cfg(all(feature = "server", feature = "client", feature = "otel-testing"))]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::handlers::TestClientHandler;
+use mcpkit_rs::{
+    ErrorData, ServerHandler, ServiceExt,
+    model::*,
+    service::{RequestContext, RoleServer},
+    telemetry::{TelemetryService, testing::InMemoryTelemetry},
+};
+use opentelemetry::{
+    Context,
+    context::FutureExt as _,
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+#[derive(Clone, Default)]
+struct TraceRecorder {
+    seen: Arc<Mutex<Option<TraceId>>>,
+}
+
+impl ServerHandler for TraceRecorder {
+    async fn call_tool(
+        &self,
+        params: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let trace_id = Context::current().span().span_context().trace_id();
+        *self.seen.lock().unwrap() = Some(trace_id);
+        if params.name == "missing" {
+            return Err(ErrorData::invalid_params("tool not found", None));
+        }
+        Ok(CallToolResult::success(vec![]))
+    }
+}
+
+#[tokio::test]
+async fn test_request_metrics_by_method_and_tool() -> anyhow::Result<()> {
+    let telemetry = InMemoryTelemetry::new();
+    let server = TelemetryService::with_metrics(TraceRecorder::default(), telemetry.metrics());
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let service = server.serve(server_transport).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+
+    let client = TestClientHandler::new(true, true)
+        .serve(client_transport)
+        .await?;
+    for _ in 0..2 {
+        client.call_tool(CallToolRequestParams::new("sum")).await?;
+    }
+    client.list_tools(None).await?;
+    let error = client
+        .call_tool(CallToolRequestParams::new("missing"))
+        .await;
+    assert!(error.is_err());
+    client.cancel().await?;
+
+    let call_sum = [
+        ("mcp.method.name", "tools/call"),
+        ("gen_ai.tool.name", "sum"),
+    ];
+    assert_eq!(telemetry.counter("mcp.requests", &call_sum), 2);
+    assert_eq!(
+        telemetry.histogram_count("mcp.request.duration", &call_sum),
+        2
+    );
+    assert_eq!(telemetry.counter("mcp.request.errors", &call_sum), 0);
+    assert_eq!(
+        telemetry.counter("mcp.requests", &[("mcp.method.name", "tools/list")]),
+        1
+    );
+    assert_eq!(
+        telemetry.counter("mcp.request.errors", &[("gen_ai.tool.name", "missing")]),
+        1
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_trace_context_propagates_through_meta() -> anyhow::Result<()> {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let recorder = TraceRecorder::default();
+    let server =
+        TelemetryService::with_metrics(recorder.clone(), InMemoryTelemetry::new().metrics());
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let service = server.serve(server_transport).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+
+    let client = TestClientHandler::new(true, true)
+        .serve(client_transport)
+        .await?;
+
+    let trace_id = TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736")?;
+    let parent = Context::new().with_remote_span_context(SpanContext::new(
+        trace_id,
+        SpanId::from_hex("00f067aa0ba902b7")?,
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    ));
+    client
+        .call_tool(CallToolRequestParams::new("anything"))
+        .with_context(parent)
+        .await?;
+    client.cancel().await?;
+
+    assert_eq!(*recorder.seen.lock().unwrap(), Some(trace_id));
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//cargo test --test test_terminal_elicitation --features "server client elicitation elicitation-terminal auth"
+#![cfg(all(
+    feature = "server",
+    feature = "client",
+    feature = "elicitation",
+    feature = "elicitation-terminal"
+))]
+
+use std::{
+    io::Cursor,
+    sync::{Arc, Mutex},
+};
+
+use mcpkit_rs::{
+    RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::client::elicitation::TerminalElicitationHandler,
+    model::*,
+    service::{ElicitationMode, Peer, RunningService},
+};
+use serde_json::json;
+use tokio::io::AsyncReadExt;
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+type Client = RunningService<RoleClient, TerminalElicitationHandler<ClientInfo>>;
+
+/// Connect [`Server`] to a terminal handler reading `input`, returning the
+/// server's view of the client and what the handler printed so far.
+async fn connect(input: &str) -> anyhow::Result<(Peer<RoleServer>, Client, Arc<Mutex<String>>)> {
+    let (mut printed, output) = tokio::io::duplex(64 * 1024);
+    let transcript = Arc::new(Mutex::new(String::new()));
+    tokio::spawn({
+        let transcript = transcript.clone();
+        async move {
+            let mut buffer = [0; 1024];
+            while let Ok(read @ 1..) = printed.read(&mut buffer).await {
+                transcript
+                    .lock()
+                    .unwrap()
+                    .push_str(&String::from_utf8_lossy(&buffer[..read]));
+            }
+        }
+    });
+    let handler = TerminalElicitationHandler::with_io(
+        ClientInfo::default(),
+        Cursor::new(input.as_bytes().to_vec()),
+        output,
+    );
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let client = handler.serve(client_transport).await?;
+    let server = server.await??;
+    let peer = server.peer().clone();
+    tokio::spawn(server.waiting());
+    Ok((peer, client, transcript))
+}
+
+/// What the handler printed, once it includes `text`.
+async fn printed(transcript: &Mutex<String>, text: &str) -> String {
+    let wait = async {
+        loop {
+            let printed = transcript.lock().unwrap().clone();
+            if printed.contains(text) {
+                return printed;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(std::time::Duration::from_secs(5), wait)
+        .await
+        .unwrap_or_else(|_| panic!("{text:?} was not printed"))
+}
+
+fn form(schema: ElicitationSchema) -> CreateElicitationRequestParams {
+    CreateElicitationRequestParams::FormElicitationParams {
+        meta: None,
+        message: "Tell us about yourself".to_owned(),
+        requested_schema: schema,
+    }
+}
+
+fn profile() -> ElicitationSchema {
+    ElicitationSchema::builder()
+        .required_email("email")
+        .required_integer("age", 0, 150)
+        .optional_bool("newsletter", false)
+        .required_enum_schema(
+            "color",
+            EnumSchema::builder(vec!["red".into(), "green".into()])
+                .enum_titles(vec!["Red".into(), "Green".into()])
+                .unwrap()
+                .build(),
+        )
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_form() -> anyhow::Result<()> {
+    // properties are asked for in name order: age, color, email, newsletter
+    let (peer, client, transcript) = connect("200\n42\nGreen\nnope\na@b.io\n\n").await?;
+    assert!(
+        peer.supported_elicitation_modes()
+            .contains(&ElicitationMode::Form)
+    );
+
+    let result = peer.create_elicitation(form(profile())).await?;
+    assert_eq!(result.action, ElicitationAction::Accept);
+    assert_eq!(
+        result.content,
+        Some(json!({ "age": 42, "color": "green", "email": "a@b.io", "newsletter": false }))
+    );
+
+    let transcript = printed(&transcript, "newsletter [false]: ").await;
+    assert!(
+        transcript.contains("Tell us about yourself"),
+        "{transcript}"
+    );
+    assert!(transcript.contains("1) Red [red]"), "{transcript}");
+    assert!(
+        transcript.contains("Enter a whole number from 0 to 150"),
+        "{transcript}"
+    );
+    assert!(
+        transcript.contains("Enter an email address"),
+        "{transcript}"
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_decline_and_cancel() -> anyhow::Result<()> {
+    let (peer, client, _) = connect("30\n:decline\n").await?;
+    let result = peer.create_elicitation(form(profile())).await?;
+    assert_eq!(result.action, ElicitationAction::Decline);
+    assert_eq!(result.content, None);
+
+    // the input is exhausted now
+    let result = peer.create_elicitation(form(profile())).await?;
+    assert_eq!(result.action, ElicitationAction::Cancel);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_url() -> anyhow::Result<()> {
+    let (peer, client, transcript) = connect("maybe\ny\nn\n").await?;
+    assert!(
+        peer.supported_elicitation_modes()
+            .contains(&ElicitationMode::Url)
+    );
+    let url = |id: &str| CreateElicitationRequestParams::UrlElicitationParams {
+        meta: None,
+        message: "Sign in to continue".to_owned(),
+        url: "https://example.com/login".to_owned(),
+        elicitation_id: id.to_owned(),
+    };
+
+    let result = peer.create_elicitation(url("login")).await?;
+    assert_eq!(result.action, ElicitationAction::Accept);
+    let result = peer.create_elicitation(url("other")).await?;
+    assert_eq!(result.action, ElicitationAction::Decline);
+    printed(
+        &transcript,
+        "Open this URL to continue: https://example.com/login",
+    )
+    .await;
+
+    assert!(!client.service().wait_for_url_completion("other").await);
+    let completion = client.service().wait_for_url_completion("login");
+    peer.notify_url_elicitation_completed(ElicitationResponseNotificationParam::new("login"))
+        .await?;
+    assert!(completion.await);
+    // completed elicitations are forgotten
+    assert!(!client.service().wait_for_url_completion("login").await);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_url_wait_ends_when_connection_closes() -> anyhow::Result<()> {
+    let (peer, client, _) = connect("y\n").await?;
+    let result = peer
+        .create_elicitation(CreateElicitationRequestParams::UrlElicitationParams {
+            meta: None,
+            message: "Sign in to continue".to_owned(),
+            url: "https://example.com/login".to_owned(),
+            elicitation_id: "login".to_owned(),
+        })
+        .await?;
+    assert_eq!(result.action, ElicitationAction::Accept);
+
+    let handler = client.service().clone();
+    let completion = tokio::spawn(handler.wait_for_url_completion("login"));
+    client.cancel().await?;
+    let completed = tokio::time::timeout(std::time::Duration::from_secs(5), completion).await??;
+    assert!(!completed);
+    assert!(!handler.wait_for_url_completion("login").await);
+    Ok(())
+}
@@ -1,7 +1,7 @@
 //! MCP Server demonstrating code review completion functionality
 //!
-//! This example shows how to implement completion support for MCP prompts
-//! with intelligent fuzzy matching for code review parameters.
+//! This example shows how to declare completion providers for the arguments
+//! of MCP prompts, with fuzzy matching over the suggestions.
 //!
 //! Run with MCP Inspector:
 //! ```bash
@@ -11,7 +11,11 @@
 use anyhow::Result;
 use mcpkit_rs::{
     ErrorData as McpError, RoleServer, ServerHandler, ServiceExt,
-    handler::server::{router::prompt::PromptRouter, wrapper::Parameters},
+    handler::server::{
+        completion::{CompletionContext, fuzzy_match},
+        router::prompt::PromptRouter,
+        wrapper::Parameters,
+    },
     model::*,
     prompt, prompt_handler, prompt_router,
     schemars::JsonSchema,
@@ -57,145 +61,20 @@ impl Default for SqlQueryServer {
     }
 }
 
-impl SqlQueryServer {
-    /// Fuzzy matching with scoring for completion suggestions
-    fn fuzzy_match(&self, query: &str, candidates: &[&str]) -> Vec<String> {
-        if query.is_empty() {
-            return candidates.iter().take(10).map(|s| s.to_string()).collect();
-        }
-
-        let query_lower = query.to_lowercase();
-        let mut scored_matches = Vec::new();
-
-        for candidate in candidates {
-            let candidate_lower = candidate.to_lowercase();
-
-            let score = if candidate_lower == query_lower {
-                1000 // Exact match
-            } else if candidate_lower.starts_with(&query_lower) {
-                900 // Prefix match  
-            } else if candidate_lower.contains(&query_lower) {
-                800 // Contains substring
-            } else if self.is_acronym_match(&query_lower, candidate) {
-                700 // Acronym match (e.g., "js" → "JavaScript")
-            } else if self.is_subsequence_match(&query_lower, &candidate_lower) {
-                680 // Subsequence match (e.g., "rs" → "Rust")
-            } else if self.is_single_letter_match(&query_lower, candidate) {
-                650 // Single letter match (e.g., "j" → "Java")
-            } else {
-                continue; // No match
-            };
-
-            scored_matches.push((candidate.to_string(), score));
-        }
-
-        // Sort by score (desc) then alphabetically
-        scored_matches.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
-        scored_matches
-            .into_iter()
-            .take(10)
-            .map(|(name, _)| name)
-            .collect()
-    }
-
-    /// Check if query matches as acronym (first letters of words or camelCase)
-    fn is_acronym_match(&self, query: &str, candidate: &str) -> bool {
-        let query_chars: Vec<char> = query.chars().collect();
-
-        // Extract first letters from words (split by whitespace) or uppercase letters (camelCase)
-        let mut first_chars: Vec<char>;
-
-        // Split by whitespace first
-        let words: Vec<&str> = candidate.split_whitespace().collect();
-        if words.len() > 1 {
-            // Multi-word case (e.g., "Memory Safety" -> "MS")
-            first_chars = words
-                .into_iter()
-                .filter_map(|word| word.chars().next())
-                .map(|c| c.to_lowercase().next().unwrap_or('\0'))
-                .collect();
-        } else {
-            // Single word case - extract uppercase letters for camelCase (e.g., "JavaScript" -> "JS")
-            first_chars = candidate
-                .chars()
-                .filter(|c| c.is_uppercase())
-                .map(|c| c.to_lowercase().next().unwrap_or('\0'))
-                .collect();
-
-            // If no uppercase letters found, just use first letter
-            if first_chars.is_empty() && !candidate.is_empty() {
-                if let Some(first) = candidate.chars().next() {
-                    first_chars.push(first.to_lowercase().next().unwrap_or('\0'));
-                }
-            }
-        }
-
-        // Special case: if query is 2 chars and we only got 1 char, try matching first 2 letters
-        if query_chars.len() == 2 && first_chars.len() == 1 {
-            if let Some(first) = candidate.chars().nth(0) {
-                if let Some(second) = candidate.chars().nth(1) {
-                    first_chars = vec![
-                        first.to_lowercase().next().unwrap_or('\0'),
-                        second.to_lowercase().next().unwrap_or('\0'),
-                    ];
-                }
-            }
-        }
-
-        if query_chars.len() != first_chars.len() {
-            return false;
-        }
-
-        query_chars
-            .iter()
-            .zip(first_chars.iter())
-            .all(|(q, c)| q.to_lowercase().next().unwrap_or('\0') == *c)
-    }
-
-    /// Check if query is a subsequence of candidate (e.g., "rs" in "rust")
-    fn is_subsequence_match(&self, query: &str, candidate_lower: &str) -> bool {
-        let query_chars: Vec<char> = query.chars().collect();
-        let candidate_chars: Vec<char> = candidate_lower.chars().collect();
-
-        let mut query_idx = 0;
-
-        for &candidate_char in &candidate_chars {
-            if query_idx < query_chars.len() && query_chars[query_idx] == candidate_char {
-                query_idx += 1;
-            }
-        }
-
-        query_idx == query_chars.len()
-    }
-
-    /// Check if query matches first letter of single word
-    fn is_single_letter_match(&self, query: &str, candidate: &str) -> bool {
-        if query.len() != 1 {
-            return false;
-        }
-
-        let query_char = query
-            .chars()
-            .next()
-            .unwrap()
-            .to_lowercase()
-            .next()
-            .unwrap_or('\0');
-        let first_char = candidate
-            .chars()
-            .next()
-            .unwrap_or('\0')
-            .to_lowercase()
-            .next()
-            .unwrap_or('\0');
-
-        query_char == first_char
-    }
-}
-
 #[prompt_router]
 impl SqlQueryServer {
-    #[prompt(name = "sql_query", description = "Smart SQL query builder")]
+    #[prompt(
+        name = "sql_query",
+        description = "Smart SQL query builder",
+        complete(arg = "operation", values = ["SELECT", "INSERT", "UPDATE", "DELETE"]),
+        complete(
+            arg = "table",
+            values = ["users", "orders", "products", "categories", "reviews"]
+        ),
+        complete(arg = "columns", with = Self::complete_columns),
+        complete(arg = "values", with = Self::complete_values),
+        complete(arg = "where_clause", with = Self::complete_where_clause)
+    )]
     async fn sql_query(
         &self,
         Parameters(args): Parameters<SqlQueryArgs>,
@@ -306,6 +185,48 @@ impl SqlQueryServer {
             }
         )))
     }
+
+    /// Only show columns completion if operation is SELECT or UPDATE
+    async fn complete_columns(&self, context: &CompletionContext<'_, Self>) -> Vec<String> {
+        let candidates: &[&str] = match context.get_argument("operation") {
+            Some(operation) => match operation.to_uppercase().as_str() {
+                "SELECT" | "UPDATE" => &["id", "name", "email", "created_at", "updated_at", "*"],
+                _ => &["Not applicable for this operation"],
+            },
+            None => &["Choose operation first"],
+        };
+        fuzzy_match(context.value(), candidates)
+    }
+
+    /// Only show values completion for INSERT
+    async fn complete_values(&self, context: &CompletionContext<'_, Self>) -> Vec<String> {
+        let candidates: &[&str] = match context.get_argument("operation") {
+            Some(operation) => match operation.to_uppercase().as_str() {
+                "INSERT" => &["'John Doe'", "'jane@example.com'", "123", "NOW()"],
+                _ => &["Not applicable for this operation"],
+            },
+            None => &["Choose operation first"],
+        };
+        fuzzy_match(context.value(), candidates)
+    }
+
+    /// WHERE clause suggestions based on filled fields count
+    async fn complete_where_clause(&self, context: &CompletionContext<'_, Self>) -> Vec<String> {
+        tracing::debug!(
+            "SQL completion - filled fields: {:?}",
+            context.arguments.keys().collect::<Vec<_>>()
+        );
+        let candidates: &[&str] = match context.arguments.len() {
+            0..=1 => &["Complete operation and table first"],
+            _ => &[
+                "id = 1",
+                "name = 'example'",
+                "created_at > '2023-01-01'",
+                "status = 'active'",
+            ],
+        };
+        fuzzy_match(context.value(), candidates)
+    }
 }
 
 #[prompt_handler]
@@ -328,93 +249,6 @@ impl ServerHandler for SqlQueryServer {
              The completion adapts - only relevant fields appear based on your SQL operation!",
         )
     }
-
-    async fn complete(
-        &self,
-        request: CompleteRequestParams,
-        _context: RequestContext<RoleServer>,
-    ) -> Result<CompleteResult, McpError> {
-        let candidates = match &request.r#ref {
-            Reference::Prompt(prompt_ref) if prompt_ref.name == "sql_query" => {
-                let filled_fields: Vec<&str> = request
-                    .context
-                    .as_ref()
-                    .map(|ctx| ctx.argument_names().collect())
-                    .unwrap_or_default();
-
-                tracing::debug!(
-                    "SQL completion - filled fields: {:?}, completing: {}",
-                    filled_fields,
-                    request.argument.name
-                );
-
-                match request.argument.name.as_str() {
-                    "operation" => vec!["SELECT", "INSERT", "UPDATE", "DELETE"],
-                    "table" => {
-                        vec!["users", "orders", "products", "categories", "reviews"]
-                    }
-                    "columns" => {
-                        // Only show columns completion if operation is SELECT or UPDATE
-                        if let Some(context) = &request.context {
-                            if let Some(operation) = context.get_argument("operation") {
-                                match operation.to_uppercase().as_str() {
-                                    "SELECT" | "UPDATE" => {
-                                        vec!["id", "name", "email", "created_at", "updated_at", "*"]
-                                    }
-                                    _ => vec!["Not applicable for this operation"],
-                                }
-                            } else {
-                                vec!["Choose operation first"]
-                            }
-                        } else {
-                            vec!["Choose operation first"]
-                        }
-                    }
-                    "values" => {
-                        // Only show values completion for INSERT
-                        if let Some(context) = &request.context {
-                            if let Some(operation) = context.get_argument("operation") {
-                                match operation.to_uppercase().as_str() {
-                                    "INSERT" => {
-                                        vec!["'John Doe'", "'jane@example.com'", "123", "NOW()"]
-                                    }
-                                    _ => vec!["Not applicable for this operation"],
-                                }
-                            } else {
-                                vec!["Choose operation first"]
-                            }
-                        } else {
-                            vec!["Choose operation first"]
-                        }
-                    }
-                    "where_clause" => {
-                        // WHERE clause suggestions based on filled fields count
-                        match filled_fields.len() {
-                            0..=1 => vec!["Complete operation and table first"],
-                            _ => vec![
-                                "id = 1",
-                                "name = 'example'",
-                                "created_at > '2023-01-01'",
-                                "status = 'active'",
-                            ],
-                        }
-                    }
-                    _ => vec![],
-                }
-            }
-            _ => vec![],
-        };
-
-        let suggestions = self.fuzzy_match(&request.argument.value, &candidates);
-
-        let completion = CompletionInfo {
-            values: suggestions,
-            total: None,
-            has_more: Some(false),
-        };
-
-        Ok(CompleteResult::new(completion))
-    }
 }
 
 #[tokio::main]
@@ -1,30 +1,25 @@
 use common::calculator::Calculator;
-use mcpkit_rs::{serve_client, serve_server};
+use mcpkit_rs::{
+    serve_client,
+    transport::{TcpServer, TcpServerConfig, tcp},
+};
 
 mod common;
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tokio::spawn(server());
+    let server = TcpServer::bind(
+        "127.0.0.1:8001",
+        || Ok(Calculator::new()),
+        TcpServerConfig::default(),
+    )
+    .await?;
+    tokio::spawn(server.serve());
     client().await?;
     Ok(())
 }
 
-async fn server() -> anyhow::Result<()> {
-    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:8001").await?;
-    while let Ok((stream, _)) = tcp_listener.accept().await {
-        tokio::spawn(async move {
-            let server = serve_server(Calculator::new(), stream).await?;
-            server.waiting().await?;
-            anyhow::Ok(())
-        });
-    }
-    Ok(())
-}
-
 async fn client() -> anyhow::Result<()> {
-    let stream = tokio::net::TcpSocket::new_v4()?
-        .connect("127.0.0.1:8001".parse()?)
-        .await?;
+    let stream = tcp::connect("127.0.0.1:8001").await?;
     let client = serve_client((), stream).await?;
     let tools = client.peer().list_tools(Default::default()).await?;
     println!("{:?}", tools);
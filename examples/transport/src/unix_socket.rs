@@ -3,36 +3,18 @@ mod common;
 #[cfg(target_family = "unix")]
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    use std::fs;
-
     use common::calculator::Calculator;
-    use mcpkit_rs::{serve_client, serve_server};
-    use tokio::net::{UnixListener, UnixStream};
+    use mcpkit_rs::{
+        serve_client,
+        transport::{UnixServer, UnixServerConfig, unix},
+    };
+    use tokio_util::sync::CancellationToken;
 
     const SOCKET_PATH: &str = "/tmp/rmcp_example.sock";
-    async fn server(unix_listener: UnixListener) -> anyhow::Result<()> {
-        while let Ok((stream, addr)) = unix_listener.accept().await {
-            println!("Client connected: {:?}", addr);
-            tokio::spawn(async move {
-                match serve_server(Calculator::new(), stream).await {
-                    Ok(server) => {
-                        println!("Server initialized successfully");
-                        if let Err(e) = server.waiting().await {
-                            println!("Error while server waiting: {}", e);
-                        }
-                    }
-                    Err(e) => println!("Server initialization failed: {}", e),
-                }
-
-                anyhow::Ok(())
-            });
-        }
-        Ok(())
-    }
 
     async fn client() -> anyhow::Result<()> {
         println!("Client connecting to {}", SOCKET_PATH);
-        let stream = UnixStream::connect(SOCKET_PATH).await?;
+        let stream = unix::connect(SOCKET_PATH).await?;
 
         let client = serve_client((), stream).await?;
         println!("Client connected and initialized successfully");
@@ -61,22 +43,26 @@ async fn main() -> anyhow::Result<()> {
         Ok(())
     }
 
-    // Remove any existing socket file
-    let _ = fs::remove_file(SOCKET_PATH);
-    match UnixListener::bind(SOCKET_PATH) {
-        Ok(unix_listener) => {
-            println!("Server successfully listening on {}", SOCKET_PATH);
-            tokio::spawn(server(unix_listener));
-        }
-        Err(e) => {
-            println!("Unable to bind to {}: {}", SOCKET_PATH, e);
-        }
-    }
+    // a stale socket from an earlier run is replaced, and the file is
+    // removed again on shutdown
+    let ct = CancellationToken::new();
+    let server = UnixServer::bind(
+        SOCKET_PATH,
+        || Ok(Calculator::new()),
+        UnixServerConfig {
+            permissions: Some(0o600),
+            cancellation_token: ct.clone(),
+            ..Default::default()
+        },
+    )
+    .await?;
+    println!("Server successfully listening on {}", SOCKET_PATH);
+    let server = tokio::spawn(server.serve());
 
     client().await?;
 
-    // Clean up socket file
-    let _ = fs::remove_file(SOCKET_PATH);
+    ct.cancel();
+    server.await?;
 
     Ok(())
 }